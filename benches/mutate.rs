@@ -16,6 +16,7 @@ fn bench_mutate(bench: &mut Criterion) {
             genome
                 .clone()
                 .new_connection(&mut rng, &mut InnoGen::new(300))
+                .unwrap()
         })
     });
 
@@ -24,6 +25,7 @@ fn bench_mutate(bench: &mut Criterion) {
             genome
                 .clone()
                 .bisect_connection(&mut rng, &mut InnoGen::new(300))
+                .unwrap()
         })
     });
 }
@@ -2,15 +2,25 @@ use criterion::Criterion;
 use eevee::{
     genome::{Connection, Genome, InnoGen, Recurrent, WConnection},
     random::default_rng,
-    reproduce::reproduce,
+    reproduce::{reproduce, Reproduction},
+    serialize::upgrade_legacy,
+    Fit,
 };
 
 type C = WConnection;
 type G = Recurrent<C>;
 
 fn bench_reproduce(bench: &mut Criterion) {
-    let genomes =
-        serde_json::from_str::<Vec<(G, _)>>(include_str!("data/ctr-genome-xor-100.json")).unwrap();
+    let genomes = serde_json::from_str::<Vec<(serde_json::Value, Fit)>>(include_str!(
+        "data/ctr-genome-xor-100.json"
+    ))
+    .unwrap()
+    .into_iter()
+    .map(|(mut genome, fit)| {
+        upgrade_legacy(&mut genome);
+        (serde_json::from_value::<G>(genome).unwrap(), fit)
+    })
+    .collect::<Vec<_>>();
     let inno_head = genomes
         .iter()
         .map(|(genome, _)| {
@@ -26,7 +36,16 @@ fn bench_reproduce(bench: &mut Criterion) {
 
     let mut rng = default_rng();
     bench.bench_function("reproduce", |b| {
-        b.iter(|| reproduce(genomes.clone(), 100, &mut InnoGen::new(inno_head), &mut rng))
+        b.iter(|| {
+            reproduce(
+                genomes.clone(),
+                100,
+                G::PROBABILITIES,
+                &Reproduction::default(),
+                &mut InnoGen::new(inno_head),
+                &mut rng,
+            )
+        })
     });
 }
 
@@ -4,6 +4,8 @@ use eevee::{
     crossover::{avg_param_diff, disjoint_excess_count},
     genome::{Recurrent, WConnection},
     population::speciate,
+    serialize::upgrade_legacy,
+    Fit,
 };
 
 type C = WConnection;
@@ -25,8 +27,16 @@ fn bench_distance(bench: &mut Criterion) {
 }
 
 fn bench_speciate(bench: &mut Criterion) {
-    let genomes =
-        serde_json::from_str::<Vec<(G, _)>>(include_str!("data/ctr-genome-xor-100.json")).unwrap();
+    let genomes = serde_json::from_str::<Vec<(serde_json::Value, Fit)>>(include_str!(
+        "data/ctr-genome-xor-100.json"
+    ))
+    .unwrap()
+    .into_iter()
+    .map(|(mut genome, fit)| {
+        upgrade_legacy(&mut genome);
+        (serde_json::from_value::<G>(genome).unwrap(), fit)
+    })
+    .collect::<Vec<_>>();
     bench.bench_function("speciate", |b| {
         b.iter(|| speciate(genomes.iter().cloned(), empty()))
     });
@@ -0,0 +1,621 @@
+//! Structures for evaluating genomes against one another, as in self-play and co-evolution.
+
+use crate::{
+    crossover::genome_delta,
+    genome::Genome,
+    scenario::{Hook, Scenario},
+    Connection, Fit, Stats,
+};
+use core::{cmp::Ordering, ops::ControlFlow};
+use rand::{
+    seq::{index, SliceRandom},
+    RngCore,
+};
+use std::{
+    cmp::Reverse,
+    collections::VecDeque,
+    sync::{Arc, RwLock, RwLockReadGuard},
+};
+
+/// An archive of champions from past generations. Evaluating against a sample of them, rather
+/// than only against the current generation, keeps adversarial scenarios from cycling through
+/// strategies that beat only whatever is currently popular.
+///
+/// Opponents are re-sampled every time a champion is recorded, so that every genome in a
+/// generation faces the same ones.
+pub struct HallOfFame<G> {
+    capacity: usize,
+    sample: usize,
+    champions: RwLock<VecDeque<G>>,
+    opponents: RwLock<Vec<G>>,
+}
+
+impl<G: Clone> HallOfFame<G> {
+    /// An empty hall of fame retaining at most `capacity` champions, from whom `sample` are
+    /// picked as opponents.
+    pub fn new(capacity: usize, sample: usize) -> Self {
+        Self {
+            capacity,
+            sample,
+            champions: RwLock::new(VecDeque::with_capacity(capacity)),
+            opponents: RwLock::new(Vec::with_capacity(sample)),
+        }
+    }
+
+    /// Record a new champion, evicting the oldest if the archive is full, and re-sample
+    /// opponents. Only the champions sampled are cloned.
+    pub fn record(&self, champion: G, rng: &mut impl RngCore) {
+        let mut champions = self.champions.write().unwrap();
+        champions.push_back(champion);
+        while champions.len() > self.capacity {
+            champions.pop_front();
+        }
+
+        let sample = usize::min(self.sample, champions.len());
+        *self.opponents.write().unwrap() = index::sample(rng, champions.len(), sample)
+            .into_iter()
+            .map(|idx| champions[idx].clone())
+            .collect();
+    }
+
+    /// Opponents sampled at the most recent [record](HallOfFame::record).
+    pub fn opponents(&self) -> RwLockReadGuard<'_, Vec<G>> {
+        self.opponents.read().unwrap()
+    }
+
+    pub fn len(&self) -> usize {
+        self.champions.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A [Hook] recording the fittest genome of every generation into `hall`, sampling opponents
+/// with the run's [rng](Stats::rng).
+pub fn hall_of_fame_hook<C: Connection + 'static, G: Genome<C> + 'static>(
+    hall: Arc<HallOfFame<G>>,
+) -> Hook<C, G> {
+    Box::new(move |stats: &mut Stats<'_, C, G>| {
+        if let Some(champion) = stats.fittest().map(|(champion, _)| champion.clone()) {
+            hall.record(champion, &mut stats.rng);
+        }
+        ControlFlow::Continue(())
+    })
+}
+
+/// A setting where a genome's fitness is decided by playing against an opponent. Where a
+/// [Scenario] scores a genome alone, an adversarial scenario scores it relative to another.
+pub trait Adversarial<C: Connection, G: Genome<C>, A: Fn(f64) -> f64> {
+    fn io(&self) -> (usize, usize);
+
+    /// Fitness of `genome` from a single game against `opponent`
+    fn play(&self, genome: &G, opponent: &G, σ: &A) -> f64;
+}
+
+/// A [Scenario] scoring genomes by their average fitness over games against opponents from a
+/// [HallOfFame]. Before any champion is recorded, genomes play against themselves.
+pub struct SelfPlay<S, G> {
+    pub scenario: S,
+    pub hall: Arc<HallOfFame<G>>,
+}
+
+impl<C: Connection, G: Genome<C>, A: Fn(f64) -> f64, S: Adversarial<C, G, A>> Scenario<C, G, A>
+    for SelfPlay<S, G>
+{
+    fn io(&self) -> (usize, usize) {
+        self.scenario.io()
+    }
+
+    fn eval(&self, genome: &G, σ: &A) -> f64 {
+        let opponents = self.hall.opponents();
+        if opponents.is_empty() {
+            return self.scenario.play(genome, genome, σ);
+        }
+
+        opponents
+            .iter()
+            .map(|opponent| self.scenario.play(genome, opponent, σ))
+            .sum::<f64>()
+            / opponents.len() as f64
+    }
+}
+
+/// How a [Tournament] decides who plays whom
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pairing {
+    /// Every genome plays every other once, n(n-1)/2 games in all. Fine for small populations
+    RoundRobin,
+    /// Genomes are paired with whoever has a similar score so far, over some rounds of n/2 games
+    /// each. The first round is paired at random. Given an odd number of genomes, the lowest
+    /// standing who hasn't yet sat out a round sits out each round.
+    Swiss { rounds: usize },
+}
+
+/// How a [Tournament] turns game results into fitness
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Standing {
+    /// The fraction of games won, where a draw is half a win
+    WinRate,
+    /// An Elo rating, starting at [ELO_INITIAL] and updated by a factor of `k` after every game
+    Elo { k: f64 },
+}
+
+/// Elo rating held by every genome before its first game
+pub const ELO_INITIAL: f64 = 1500.;
+
+/// Expected result of a game between ratings `rating` and `opponent`, from 0 for a sure loss to
+/// 1 for a sure win
+pub fn elo_expected(rating: f64, opponent: f64) -> f64 {
+    1. / (1. + 10f64.powf((opponent - rating) / 400.))
+}
+
+/// A [Scenario] who scores a whole generation by playing its genomes against one another in an
+/// [Adversarial] scenario. A game is won by whoever [plays](Adversarial::play) to the greater
+/// fitness against the other. Fitness is the [Standing] of each genome after every game.
+pub struct Tournament<S> {
+    pub scenario: S,
+    pub pairing: Pairing,
+    pub standing: Standing,
+}
+
+/// Result of a game between `genome` and `opponent` for `genome`: 1 for a win, 0.5 for a draw, 0
+/// for a loss
+fn game<C: Connection, G: Genome<C>, A: Fn(f64) -> f64, S: Adversarial<C, G, A>>(
+    scenario: &S,
+    genome: &G,
+    opponent: &G,
+    σ: &A,
+) -> f64 {
+    match Fit(scenario.play(genome, opponent, σ)).cmp(&Fit(scenario.play(opponent, genome, σ))) {
+        Ordering::Greater => 1.,
+        Ordering::Equal => 0.5,
+        Ordering::Less => 0.,
+    }
+}
+
+/// Pair off `n` players by `pairing`, calling `game` with the indices of each pair and expecting
+/// the result for the first of them
+fn play_out(
+    pairing: Pairing,
+    n: usize,
+    rng: &mut impl RngCore,
+    mut game: impl FnMut(usize, usize) -> f64,
+) {
+    match pairing {
+        Pairing::RoundRobin => {
+            for l in 0..n {
+                for r in l + 1..n {
+                    game(l, r);
+                }
+            }
+        }
+        Pairing::Swiss { rounds } => {
+            let mut order = (0..n).collect::<Vec<_>>();
+            order.shuffle(rng);
+            let mut standings = vec![0.; n];
+            let mut sat_out = vec![false; n];
+            for _ in 0..rounds {
+                let mut pairs = order.clone();
+                if pairs.len() % 2 == 1 {
+                    let bye = pairs
+                        .iter()
+                        .rposition(|idx| !sat_out[*idx])
+                        .unwrap_or(pairs.len() - 1);
+                    sat_out[pairs.remove(bye)] = true;
+                }
+                for pair in pairs.chunks_exact(2) {
+                    let result = game(pair[0], pair[1]);
+                    standings[pair[0]] += result;
+                    standings[pair[1]] += 1. - result;
+                }
+                // stable, so that ties stay in the random order of the first round
+                order.sort_by_key(|idx| Reverse(Fit(standings[*idx])));
+            }
+        }
+    }
+}
+
+impl<S> Tournament<S> {
+    /// The standing of each of `genomes` after the tournament
+    pub fn run<C: Connection, G: Genome<C>, A: Fn(f64) -> f64>(
+        &self,
+        genomes: &[G],
+        σ: &A,
+        rng: &mut impl RngCore,
+    ) -> Vec<f64>
+    where
+        S: Adversarial<C, G, A>,
+    {
+        let mut points = vec![0.; genomes.len()];
+        let mut played = vec![0usize; genomes.len()];
+        let mut elo = vec![ELO_INITIAL; genomes.len()];
+        play_out(self.pairing, genomes.len(), rng, |l, r| {
+            let result = game(&self.scenario, &genomes[l], &genomes[r], σ);
+            points[l] += result;
+            points[r] += 1. - result;
+            played[l] += 1;
+            played[r] += 1;
+            if let Standing::Elo { k } = self.standing {
+                let expected = elo_expected(elo[l], elo[r]);
+                elo[l] += k * (result - expected);
+                elo[r] -= k * (result - expected);
+            }
+            result
+        });
+
+        match self.standing {
+            Standing::WinRate => points
+                .iter()
+                .zip(played)
+                .map(|(points, played)| match played {
+                    0 => 0.,
+                    played => points / played as f64,
+                })
+                .collect(),
+            Standing::Elo { .. } => elo,
+        }
+    }
+}
+
+/// Genomes are scored by [Tournament::run] over the whole generation, so [Scenario::eval] is only
+/// ever used outside of [evolve](crate::scenario::evolve), where a genome plays against itself.
+impl<C: Connection, G: Genome<C>, A: Fn(f64) -> f64, S: Adversarial<C, G, A>> Scenario<C, G, A>
+    for Tournament<S>
+{
+    fn io(&self) -> (usize, usize) {
+        self.scenario.io()
+    }
+
+    fn eval(&self, genome: &G, σ: &A) -> f64 {
+        self.scenario.play(genome, genome, σ)
+    }
+
+    fn eval_population(&self, genomes: &[G], σ: &A, rng: &mut dyn RngCore) -> Option<Vec<f64>> {
+        let mut rng = rng;
+        Some(self.run(genomes, σ, &mut rng))
+    }
+}
+
+/// An Elo rating, along with how uncertain it is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rating {
+    pub elo: f64,
+    /// Uncertainty in `elo`, from [Rating::MIN_DEVIATION] to 1. Ratings change by
+    /// `k * deviation` after a game, so uncertain ratings move quickly and settled ratings slowly.
+    pub deviation: f64,
+}
+
+impl Rating {
+    pub const INITIAL: Self = Self {
+        elo: ELO_INITIAL,
+        deviation: 1.,
+    };
+    /// Factor by which deviation shrinks after every game
+    pub const SETTLE: f64 = 0.9;
+    pub const MIN_DEVIATION: f64 = 0.1;
+}
+
+/// Elo ratings kept across generations, so that a lineage's skill is measured over many
+/// tournaments rather than only against its current peers, which is a steadier signal in
+/// open-ended competitive evolution.
+///
+/// Genomes don't know their parents, so an unrated genome inherits the rating of the most
+/// [compatible](crate::crossover::genome_delta) genome rated in the previous generation. Unless
+/// it's an unchanged copy, its inherited deviation is inflated by `inflation`, as offspring may
+/// not play like their parent does.
+pub struct Ratings<G> {
+    pub k: f64,
+    pub inflation: f64,
+    rated: RwLock<Vec<(G, Rating)>>,
+}
+
+impl<G> Ratings<G> {
+    pub fn new(k: f64, inflation: f64) -> Self {
+        Self {
+            k,
+            inflation,
+            rated: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Every genome rated in the most recent generation, and its rating
+    pub fn rated(&self) -> RwLockReadGuard<'_, Vec<(G, Rating)>> {
+        self.rated.read().unwrap()
+    }
+
+    pub fn len(&self) -> usize {
+        self.rated().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The rating that `genome` starts its next tournament with
+    pub fn inherit<C: Connection>(&self, genome: &G) -> Rating
+    where
+        G: Genome<C>,
+    {
+        let rated = self.rated();
+        let Some((delta, parent)) = rated
+            .iter()
+            .map(|(rated, rating)| (genome_delta(genome, rated), rating))
+            .min_by_key(|(delta, _)| Fit(*delta))
+        else {
+            return Rating::INITIAL;
+        };
+
+        Rating {
+            elo: parent.elo,
+            deviation: if delta == 0. {
+                parent.deviation
+            } else {
+                f64::min(1., parent.deviation + self.inflation)
+            },
+        }
+    }
+}
+
+/// A [Scenario] who scores a whole generation by its [Ratings] after playing it out with some
+/// [Pairing], such that ratings carry on from one generation into the next.
+pub struct Rated<S, G> {
+    pub scenario: S,
+    pub pairing: Pairing,
+    pub ratings: Arc<Ratings<G>>,
+}
+
+impl<S, G> Rated<S, G> {
+    /// The rating of each of `genomes` after the tournament, which replace every rating held
+    /// by [ratings](Rated::ratings)
+    pub fn run<C: Connection, A: Fn(f64) -> f64>(
+        &self,
+        genomes: &[G],
+        σ: &A,
+        rng: &mut impl RngCore,
+    ) -> Vec<Rating>
+    where
+        G: Genome<C>,
+        S: Adversarial<C, G, A>,
+    {
+        let k = self.ratings.k;
+        let mut ratings = genomes
+            .iter()
+            .map(|genome| self.ratings.inherit(genome))
+            .collect::<Vec<_>>();
+        play_out(self.pairing, genomes.len(), rng, |l, r| {
+            let result = game(&self.scenario, &genomes[l], &genomes[r], σ);
+            let expected = elo_expected(ratings[l].elo, ratings[r].elo);
+            ratings[l].elo += k * ratings[l].deviation * (result - expected);
+            ratings[r].elo -= k * ratings[r].deviation * (result - expected);
+            for idx in [l, r] {
+                ratings[idx].deviation = f64::max(
+                    Rating::MIN_DEVIATION,
+                    ratings[idx].deviation * Rating::SETTLE,
+                );
+            }
+            result
+        });
+
+        *self.ratings.rated.write().unwrap() = genomes
+            .iter()
+            .cloned()
+            .zip(ratings.iter().copied())
+            .collect();
+        ratings
+    }
+}
+
+impl<C: Connection, G: Genome<C>, A: Fn(f64) -> f64, S: Adversarial<C, G, A>> Scenario<C, G, A>
+    for Rated<S, G>
+{
+    fn io(&self) -> (usize, usize) {
+        self.scenario.io()
+    }
+
+    /// The rating that `genome` would inherit
+    fn eval(&self, genome: &G, _: &A) -> f64 {
+        self.ratings.inherit(genome).elo
+    }
+
+    fn eval_population(&self, genomes: &[G], σ: &A, rng: &mut dyn RngCore) -> Option<Vec<f64>> {
+        let mut rng = rng;
+        Some(
+            self.run(genomes, σ, &mut rng)
+                .into_iter()
+                .map(|rating| rating.elo)
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        assert_f64_approx,
+        genome::{InnoGen, Recurrent, WConnection},
+        random::{default_rng, WyRng},
+    };
+    use std::cell::Cell;
+
+    type C = WConnection;
+    type G = Recurrent<C>;
+    type A = fn(f64) -> f64;
+
+    fn sized(connections: usize) -> G {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = G::new(connections, 1);
+        for from in 0..connections {
+            genome.push_connection(C::new(from, connections, &mut inno));
+        }
+        genome
+    }
+
+    struct Bigger;
+
+    impl Adversarial<C, G, A> for Bigger {
+        fn io(&self) -> (usize, usize) {
+            (0, 1)
+        }
+
+        fn play(&self, genome: &G, opponent: &G, _: &A) -> f64 {
+            genome.connections().len() as f64 - opponent.connections().len() as f64
+        }
+    }
+
+    #[test]
+    fn test_hall_of_fame_capacity() {
+        let hall = HallOfFame::new(3, 2);
+        let mut rng = default_rng();
+        assert!(hall.is_empty());
+        assert!(hall.opponents().is_empty());
+
+        for n in 0..5 {
+            hall.record(sized(n), &mut rng);
+        }
+        assert_eq!(hall.len(), 3);
+        assert_eq!(hall.opponents().len(), 2);
+        assert!(hall
+            .opponents()
+            .iter()
+            .all(|g| (2..5).contains(&g.connections().len())));
+    }
+
+    #[test]
+    fn test_hall_of_fame_clones_sampled() {
+        struct Tally<'a>(&'a Cell<usize>);
+        impl Clone for Tally<'_> {
+            fn clone(&self) -> Self {
+                self.0.set(self.0.get() + 1);
+                Self(self.0)
+            }
+        }
+
+        let clones = Cell::new(0);
+        let hall = HallOfFame::new(10, 2);
+        let mut rng = default_rng();
+        for _ in 0..10 {
+            hall.record(Tally(&clones), &mut rng);
+        }
+        // only those sampled as opponents are cloned, not the whole archive
+        assert_eq!(clones.get(), 1 + 2 * 9);
+    }
+
+    #[test]
+    fn test_self_play() {
+        let σ: A = |x| x;
+        let hall = Arc::new(HallOfFame::new(2, 2));
+        let scenario = SelfPlay {
+            scenario: Bigger,
+            hall: hall.clone(),
+        };
+        assert_f64_approx!(scenario.eval(&sized(4), &σ), 0.);
+
+        hall.record(sized(1), &mut default_rng());
+        hall.record(sized(3), &mut default_rng());
+        assert_f64_approx!(scenario.eval(&sized(4), &σ), 2.);
+    }
+
+    #[test]
+    fn test_tournament_round_robin() {
+        let σ: A = |x| x;
+        let genomes = [sized(2), sized(0), sized(1), sized(1)];
+        let win_rate = Tournament {
+            scenario: Bigger,
+            pairing: Pairing::RoundRobin,
+            standing: Standing::WinRate,
+        };
+        let fits = win_rate.run(&genomes, &σ, &mut default_rng());
+        for (have, want) in fits.iter().zip([1., 0., 0.5, 0.5]) {
+            assert_f64_approx!(have, want);
+        }
+
+        let elo = Tournament {
+            standing: Standing::Elo { k: 32. },
+            ..win_rate
+        };
+        let fits = elo.run(&genomes, &σ, &mut default_rng());
+        assert!(fits[0] > fits[2] && fits[2] > fits[1]);
+        assert_f64_approx!(fits.iter().sum::<f64>(), 4. * ELO_INITIAL);
+    }
+
+    #[test]
+    fn test_tournament_swiss() {
+        let σ: A = |x| x;
+        let genomes = (0..9).map(sized).collect::<Vec<_>>();
+        let swiss = Tournament {
+            scenario: Bigger,
+            pairing: Pairing::Swiss { rounds: 4 },
+            standing: Standing::WinRate,
+        };
+        let fits = swiss
+            .eval_population(&genomes, &σ, &mut default_rng())
+            .unwrap();
+        assert_eq!(fits.len(), 9);
+        assert_f64_approx!(fits[8], 1.);
+        assert_f64_approx!(fits[0], 0.);
+    }
+
+    #[test]
+    fn test_tournament_seeded() {
+        let σ: A = |x| x;
+        let genomes = (0..9).map(sized).collect::<Vec<_>>();
+        let swiss = Tournament {
+            scenario: Bigger,
+            pairing: Pairing::Swiss { rounds: 2 },
+            standing: Standing::WinRate,
+        };
+        // pairings are drawn from the rng given, so the same seed plays the same games
+        let fits = |seed| {
+            swiss
+                .eval_population(&genomes, &σ, &mut WyRng::seeded(seed))
+                .unwrap()
+        };
+        assert_eq!(fits(7), fits(7));
+    }
+
+    #[test]
+    fn test_ratings() {
+        let σ: A = |x| x;
+        let rated = Rated {
+            scenario: Bigger,
+            pairing: Pairing::RoundRobin,
+            ratings: Arc::new(Ratings::new(32., 0.3)),
+        };
+        assert_eq!(rated.ratings.inherit(&sized(2)), Rating::INITIAL);
+
+        let first = rated.run(&[sized(1), sized(3)], &σ, &mut default_rng());
+        assert!(first[1].elo > first[0].elo);
+        assert_eq!(first[1].deviation, Rating::SETTLE);
+        assert_eq!(rated.ratings.len(), 2);
+
+        // an unchanged copy keeps its rating, and offspring of it are less certain
+        assert_eq!(rated.ratings.inherit(&sized(3)), first[1]);
+        let offspring = rated.ratings.inherit(&sized(4));
+        assert_eq!(offspring.elo, first[1].elo);
+        assert_eq!(offspring.deviation, 1.);
+
+        let second = rated.run(&[sized(3), sized(1)], &σ, &mut default_rng());
+        assert!(second[0].elo > first[1].elo);
+        assert!(second[0].deviation < first[1].deviation);
+    }
+
+    #[test]
+    fn test_ratings_seeded() {
+        let σ: A = |x| x;
+        let genomes = (0..9).map(sized).collect::<Vec<_>>();
+        let elos = |seed| {
+            let rated = Rated {
+                scenario: Bigger,
+                pairing: Pairing::Swiss { rounds: 2 },
+                ratings: Arc::new(Ratings::new(32., 0.3)),
+            };
+            rated
+                .eval_population(&genomes, &σ, &mut WyRng::seeded(seed))
+                .unwrap()
+        };
+        assert_eq!(elos(7), elos(7));
+    }
+}
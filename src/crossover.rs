@@ -1,9 +1,13 @@
 //! Functions related to performing measuring compatability for and performing crossover
 //! reproduction.
 
-use crate::genome::Connection;
+use crate::{
+    genome::{Connection, Genome, NodeGenes},
+    random::{CrossoverEvent, CrossoverProbabilities, EventKind},
+};
 use core::cmp::Ordering;
 use rand::RngCore;
+use serde::{Deserialize, Serialize};
 
 /// Count misaligned [Connection]s between 2 slices. Where `l` is more fit ( TODO really? ), we
 /// consider disjoint genes to be misalignments of innovation ids < `r`s max, and excess are
@@ -59,6 +63,49 @@ pub fn disjoint_excess_count<C: Connection>(l: &[C], r: &[C]) -> (f64, f64) {
     )
 }
 
+/// Like [disjoint_excess_count], but by the definition of the original NEAT paper: a gene that
+/// the other side lacks is excess if its innovation id is past every id of the other side, and
+/// disjoint otherwise. This doesn't depend on `l` and `r` being sorted by innovation id, or
+/// holding each id once, which [disjoint_excess_count] does. Where they are, both agree.
+///
+/// To do so, it collects and sorts the innovation ids of either side on every call, so it costs an
+/// allocation and `O(n log n)` per pair where [disjoint_excess_count] costs neither.
+pub fn disjoint_excess_count_strict<C: Connection>(l: &[C], r: &[C]) -> (f64, f64) {
+    let sorted = |genes: &[C]| {
+        let mut ids = genes.iter().map(|c| c.inno()).collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids
+    };
+    let (l_ids, r_ids) = (sorted(l), sorted(r));
+
+    let (mut disjoint, mut excess) = (0., 0.);
+    for (genes, other) in [(l, &r_ids), (r, &l_ids)] {
+        let other_max = other.last();
+        for c in genes
+            .iter()
+            .filter(|c| other.binary_search(&c.inno()).is_err())
+        {
+            if other_max.is_none_or(|max| c.inno() > *max) {
+                excess += 1.;
+            } else {
+                disjoint += 1.;
+            }
+        }
+    }
+
+    (disjoint, excess)
+}
+
+/// How [delta] counts disjoint and excess genes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DisjointExcess {
+    /// A single merge over both genomes, see [disjoint_excess_count]
+    #[default]
+    Merge,
+    /// As defined by the original NEAT paper, see [disjoint_excess_count_strict]
+    Strict,
+}
+
 /// Average param difference between aligned genes from `l` and `r`. Misaligned genes are not
 /// considered
 pub fn avg_param_diff<C: Connection>(l: &[C], r: &[C]) -> f64 {
@@ -115,41 +162,150 @@ pub fn avg_param_diff<C: Connection>(l: &[C], r: &[C]) -> f64 {
     }
 }
 
+/// Counts of disjoint and excess genes between `l` and `r`, as in [disjoint_excess_count], along
+/// with their [avg_param_diff], taken in a single merge over both. This is what [delta] uses
+/// unless counting [DisjointExcess::Strict], as for long genomes the matching of genes dominates
+/// speciation.
+pub fn alignment<C: Connection>(l: &[C], r: &[C]) -> (f64, f64, f64) {
+    let (mut l_idx, mut r_idx) = (0, 0);
+    let (mut disjoint, mut matched, mut diff) = (0, 0, 0.);
+    while l_idx < l.len() && r_idx < r.len() {
+        let (l_inno, r_inno) = (l[l_idx].inno(), r[r_idx].inno());
+        if l_inno == r_inno {
+            diff += l[l_idx].param_diff(&r[r_idx]);
+            matched += 1;
+            l_idx += 1;
+            r_idx += 1;
+        } else {
+            disjoint += 1;
+            l_idx += (l_inno < r_inno) as usize;
+            r_idx += (l_inno > r_inno) as usize;
+        }
+    }
+
+    let excess = (l.len() - l_idx) + (r.len() - r_idx);
+    let avg = if matched == 0 {
+        0.
+    } else {
+        diff / matched as f64
+    };
+    (disjoint as f64, excess as f64, avg)
+}
+
+/// How the disjoint and excess terms of [delta] are normalized by N, the gene count of the
+/// longer of 2 genomes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Normalization {
+    /// Never normalize, as is common for small genomes
+    One,
+    /// Always normalize by N, as in the original NEAT paper
+    Longest,
+    /// Normalize by N only once N is at least this many genes
+    Threshold(usize),
+}
+
+impl Normalization {
+    pub const DEFAULT: Self = Self::Threshold(20);
+
+    /// Factor to divide by, where the longer genome has `longest` genes
+    pub fn factor(&self, longest: usize) -> f64 {
+        match self {
+            Self::Longest => longest.max(1) as f64,
+            Self::Threshold(min) if longest >= *min => longest.max(1) as f64,
+            _ => 1.,
+        }
+    }
+}
+
+impl Default for Normalization {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
 /// difference between [Connection]s in terms of crossover compatability. Higher deltas tend to
 /// yield more destructive crossover.
 pub fn delta<C: Connection>(l: &[C], r: &[C]) -> f64 {
+    delta_with(l, r, Normalization::DEFAULT, DisjointExcess::Merge)
+}
+
+/// Like [delta], but normalizing with `normalization` and counting disjoint and excess genes as
+/// `disjoint_excess` says, such as for a [Compatibility](crate::population::Compatibility)
+pub fn delta_with<C: Connection>(
+    l: &[C],
+    r: &[C],
+    normalization: Normalization,
+    disjoint_excess: DisjointExcess,
+) -> f64 {
     let l_size = l.len() as f64;
     let r_size = r.len() as f64;
-    let fac = {
-        let longest = f64::max(l_size, r_size);
-        if longest < 20. {
-            1.
-        } else {
-            longest
-        }
-    };
+    let fac = normalization.factor(usize::max(l.len(), r.len()));
 
     if l_size == 0. || r_size == 0. {
         (C::EXCESS_COEFFICIENT * f64::max(l_size, r_size)) / fac
     } else {
-        let (disjoint, excess) = disjoint_excess_count(l, r);
+        let (disjoint, excess, param_diff) = match disjoint_excess {
+            DisjointExcess::Merge => alignment(l, r),
+            DisjointExcess::Strict => {
+                let (disjoint, excess) = disjoint_excess_count_strict(l, r);
+                (disjoint, excess, avg_param_diff(l, r))
+            }
+        };
         (C::DISJOINT_COEFFICIENT * disjoint + C::EXCESS_COEFFICIENT * excess) / fac
-            + C::PARAM_COEFFICIENT * avg_param_diff(l, r)
+            + C::PARAM_COEFFICIENT * param_diff
     }
 }
 
+/// Difference between the [NodeGenes] of two genomes, which [delta] doesn't see. Speciation adds
+/// it to the delta of their connections, scaled by
+/// [Compatibility::nodes](crate::population::Compatibility::nodes).
+pub fn node_delta(l: &NodeGenes, r: &NodeGenes) -> f64 {
+    l.activation_diff(r)
+        + l.time_constant_diff(r)
+        + l.bias_diff(r)
+        + l.sensory_gain_diff(r)
+        + l.module_diff(r)
+}
+
 #[inline]
-fn pick_gene<C: Connection>(base_conn: &C, opt_conn: Option<&C>, rng: &mut impl RngCore) -> C {
-    let mut conn = if let Some(r_conn) = opt_conn {
-        // TODO be able to differentiate PickLEQ and PickLNE
-        if rng.next_u64() < C::PROBABILITY_PICK_RL {
-            r_conn
-        } else {
-            base_conn
+/// Like [delta], but between whole genomes, so that mismatched node activations, time
+/// constants, biases, and modules count as well, each weighted by its coefficient on the genome.
+pub fn genome_delta<C: Connection, G: Genome<C>>(l: &G, r: &G) -> f64 {
+    delta(l.connections(), r.connections())
+        + G::ACTIVATION_COEFFICIENT * l.activation_diff(r)
+        + G::TIME_CONSTANT_COEFFICIENT * l.time_constant_diff(r)
+        + G::BIAS_COEFFICIENT * l.bias_diff(r)
+        + G::MODULE_COEFFICIENT * l.module_diff(r)
+}
+
+fn pick_gene<C: Connection>(
+    base_conn: &C,
+    opt_conn: Option<&C>,
+    probabilities: CrossoverProbabilities,
+    rng: &mut impl RngCore,
+) -> C {
+    // matched genes only roll for a blend if they may be blended at all
+    let blend = |rng: &mut _| {
+        probabilities[CrossoverEvent::Blend.idx()] > 0
+            && matches!(
+                CrossoverEvent::pick(rng, probabilities),
+                Some(CrossoverEvent::Blend)
+            )
+    };
+    let mut conn = match opt_conn {
+        Some(r_conn) if blend(rng) => {
+            let mut conn = base_conn.to_owned();
+            let blend = base_conn
+                .params()
+                .zip(r_conn.params())
+                .map(|(l, r)| (l + r) / 2.)
+                .collect::<Vec<_>>();
+            conn.set_params(&blend);
+            conn
         }
-        .to_owned()
-    } else {
-        base_conn.to_owned()
+        // TODO be able to differentiate PickLEQ and PickLNE
+        Some(r_conn) if rng.next_u64() < C::PROBABILITY_PICK_RL => r_conn.to_owned(),
+        _ => base_conn.to_owned(),
     };
 
     // TODO It seems like it will always check RAND_DISABLED, and sometimes
@@ -164,11 +320,32 @@ fn pick_gene<C: Connection>(base_conn: &C, opt_conn: Option<&C>, rng: &mut impl
     conn
 }
 
+/// Number of distinct innovation ids between `l` and `r`, which is how many genes crossover of
+/// equally fit parents yields
+fn union_count<C: Connection>(l: &[C], r: &[C]) -> usize {
+    let (mut l_idx, mut r_idx, mut count) = (0, 0, 0);
+    while let (Some(l_conn), Some(r_conn)) = (l.get(l_idx), r.get(r_idx)) {
+        match l_conn.inno().cmp(&r_conn.inno()) {
+            Ordering::Equal => {
+                l_idx += 1;
+                r_idx += 1;
+            }
+            Ordering::Less => l_idx += 1,
+            Ordering::Greater => r_idx += 1,
+        }
+        count += 1;
+    }
+    count + (l.len() - l_idx) + (r.len() - r_idx)
+}
+
 /// crossover connections where l and r are equally fit
-fn crossover_eq<C: Connection>(l: &[C], r: &[C], rng: &mut impl RngCore) -> Vec<C> {
-    // TODO I wonder what the actual average case overlap between genomes is?
-    // probably pretty close, could we measure this?
-    let mut cross = Vec::with_capacity(l.len() + r.len());
+fn crossover_eq<C: Connection>(
+    l: &[C],
+    r: &[C],
+    probabilities: CrossoverProbabilities,
+    rng: &mut impl RngCore,
+    cross: &mut Vec<C>,
+) {
     let mut l_idx = 0;
     let mut r_idx = 0;
     loop {
@@ -176,39 +353,49 @@ fn crossover_eq<C: Connection>(l: &[C], r: &[C], rng: &mut impl RngCore) -> Vec<
             (None, None) => break,
             (None, Some(_)) => {
                 // TODO is it faster to extend, or to loop-push?
-                cross.extend(r[r_idx..].iter().map(|conn| pick_gene(conn, None, rng)));
+                cross.extend(
+                    r[r_idx..]
+                        .iter()
+                        .map(|conn| pick_gene(conn, None, probabilities, rng)),
+                );
                 break;
             }
             (Some(_), None) => {
-                cross.extend(l[l_idx..].iter().map(|conn| pick_gene(conn, None, rng)));
+                cross.extend(
+                    l[l_idx..]
+                        .iter()
+                        .map(|conn| pick_gene(conn, None, probabilities, rng)),
+                );
                 break;
             }
             (Some(l_conn), Some(r_conn)) => match l_conn.inno().cmp(&r_conn.inno()) {
                 Ordering::Equal => {
-                    cross.push(pick_gene(l_conn, Some(r_conn), rng));
+                    cross.push(pick_gene(l_conn, Some(r_conn), probabilities, rng));
                     l_idx += 1;
                     r_idx += 1;
                 }
                 Ordering::Less => {
-                    cross.push(pick_gene(l_conn, None, rng));
+                    cross.push(pick_gene(l_conn, None, probabilities, rng));
                     l_idx += 1;
                 }
                 Ordering::Greater => {
-                    cross.push(pick_gene(r_conn, None, rng));
+                    cross.push(pick_gene(r_conn, None, probabilities, rng));
                     r_idx += 1;
                 }
             },
         }
     }
-
-    cross.shrink_to_fit(); // TODO what happens if I remove this
-    cross
 }
 
 /// crossover connections where l is more fit than r
-fn crossover_ne<C: Connection>(l: &[C], r: &[C], rng: &mut impl RngCore) -> Vec<C> {
+fn crossover_ne<C: Connection>(
+    l: &[C],
+    r: &[C],
+    probabilities: CrossoverProbabilities,
+    rng: &mut impl RngCore,
+    cross: &mut Vec<C>,
+) {
     // copy l, pick_gene where l.inno() == r.inno()
-    let mut cross = Vec::with_capacity(l.len());
     let mut r_idx = 0;
     for l_conn in l {
         // TODO is r_idx < r.len() && r[r_idx] or maybe even get_unchecked
@@ -225,11 +412,10 @@ fn crossover_ne<C: Connection>(l: &[C], r: &[C], rng: &mut impl RngCore) -> Vec<
             r.get(r_idx)
                 .is_some_and(|r_conn| r_conn.inno() == l_conn.inno())
                 .then(|| &r[r_idx]),
+            probabilities,
             rng,
         ))
     }
-
-    cross
 }
 
 /// Perform crossover reproduction across 2 [Connection] slices `l` and `r`. `l_fit` describes
@@ -240,14 +426,69 @@ pub fn crossover<C: Connection>(
     l_fit: Ordering,
     rng: &mut impl RngCore,
 ) -> Vec<C> {
-    let mut usort = match l_fit {
-        Ordering::Equal => crossover_eq(l, r, rng),
-        Ordering::Less => crossover_ne(r, l, rng),
-        Ordering::Greater => crossover_ne(l, r, rng),
+    crossover_with(l, r, l_fit, C::CROSSOVER_PROBABILITIES, rng)
+}
+
+/// Like [crossover], but inheriting matched genes with `probabilities` rather than
+/// [CROSSOVER_PROBABILITIES](Connection::CROSSOVER_PROBABILITIES)
+pub fn crossover_with<C: Connection>(
+    l: &[C],
+    r: &[C],
+    l_fit: Ordering,
+    probabilities: CrossoverProbabilities,
+    rng: &mut impl RngCore,
+) -> Vec<C> {
+    let mut cross = Vec::new();
+    crossover_with_into(l, r, l_fit, probabilities, rng, &mut cross);
+    cross
+}
+
+/// Like [crossover], but writing the child's connections into `out`, which is cleared first. `out`
+/// grows at most once, to exactly the child's size, so a buffer reused between calls needn't
+/// allocate at all.
+///
+/// [reproduce_into](crate::reproduce::reproduce_into) crosses each offspring over into a buffer
+/// made ahead for it, see [reproduce_buffered](Genome::reproduce_buffered).
+pub fn crossover_into<C: Connection>(
+    l: &[C],
+    r: &[C],
+    l_fit: Ordering,
+    rng: &mut impl RngCore,
+    out: &mut Vec<C>,
+) {
+    crossover_with_into(l, r, l_fit, C::CROSSOVER_PROBABILITIES, rng, out)
+}
+
+/// Like [crossover_into], but inheriting matched genes with `probabilities` as in
+/// [crossover_with]
+pub fn crossover_with_into<C: Connection>(
+    l: &[C],
+    r: &[C],
+    l_fit: Ordering,
+    probabilities: CrossoverProbabilities,
+    rng: &mut impl RngCore,
+    out: &mut Vec<C>,
+) {
+    out.clear();
+    match l_fit {
+        Ordering::Equal => {
+            out.reserve_exact(union_count(l, r));
+            crossover_eq(l, r, probabilities, rng, out)
+        }
+        Ordering::Less => {
+            out.reserve_exact(r.len());
+            crossover_ne(r, l, probabilities, rng, out)
+        }
+        Ordering::Greater => {
+            out.reserve_exact(l.len());
+            crossover_ne(l, r, probabilities, rng, out)
+        }
     };
 
-    usort.sort_by_key(|c| c.inno());
-    usort
+    // parents sorted by innovation id, as they almost always are, give a sorted child
+    if !out.is_sorted_by_key(|c| c.inno()) {
+        out.sort_by_key(|c| c.inno());
+    }
 }
 
 #[cfg(test)]
@@ -255,13 +496,36 @@ mod test {
     use super::*;
     use crate::{
         assert_f64_approx, assert_some_normalized,
-        genome::{connection::BWConnection, WConnection},
+        genome::{connection::BWConnection, HConnection, MConnection, WConnection},
         new_t,
-        random::default_rng,
+        random::{default_rng, Counted},
         test_t,
     };
+    use rand::Rng;
     use std::collections::{HashMap, HashSet};
 
+    fn crossover_eq<C: Connection>(
+        l: &[C],
+        r: &[C],
+        probabilities: CrossoverProbabilities,
+        rng: &mut impl RngCore,
+    ) -> Vec<C> {
+        let mut cross = Vec::new();
+        super::crossover_eq(l, r, probabilities, rng, &mut cross);
+        cross
+    }
+
+    fn crossover_ne<C: Connection>(
+        l: &[C],
+        r: &[C],
+        probabilities: CrossoverProbabilities,
+        rng: &mut impl RngCore,
+    ) -> Vec<C> {
+        let mut cross = Vec::new();
+        super::crossover_ne(l, r, probabilities, rng, &mut cross);
+        cross
+    }
+
     test_t!(
     test_avg_param_diff[T: WConnection]() {
         let diff = avg_param_diff(
@@ -299,7 +563,7 @@ mod test {
     });
 
     test_t!(
-    test_avg_param_diff_empty[T: WConnection | BWConnection]() {
+    test_avg_param_diff_empty[T: WConnection | BWConnection | HConnection | MConnection]() {
         let full = vec![
             new_t!(inno = 1, weight = 0.0,),
             new_t!(inno = 2, weight = -1.0,),
@@ -317,7 +581,7 @@ mod test {
     });
 
     test_t!(
-    test_avg_param_diff_no_overlap[T: WConnection | BWConnection]() {
+    test_avg_param_diff_no_overlap[T: WConnection | BWConnection | HConnection | MConnection]() {
         let diff = avg_param_diff(
             &[
                 new_t!(inno = 1, weight = 0.5,),
@@ -333,7 +597,7 @@ mod test {
     });
 
     test_t!(
-    test_avg_param_diff_no_diff[T: WConnection | BWConnection]() {
+    test_avg_param_diff_no_diff[T: WConnection | BWConnection | HConnection | MConnection]() {
         let diff = avg_param_diff(
             &[
                 new_t!(inno = 1, weight = 0.5,),
@@ -350,7 +614,7 @@ mod test {
     });
 
     test_t!(
-    test_disjoint_excess_count[T: WConnection | BWConnection]() {
+    test_disjoint_excess_count[T: WConnection | BWConnection | HConnection | MConnection]() {
         assert_eq!(
             (4.0, 2.0),
             disjoint_excess_count(
@@ -371,7 +635,55 @@ mod test {
     });
 
     test_t!(
-    test_disjoint_excess_count_symmetrical[T: WConnection | BWConnection]() {
+    test_disjoint_excess_count_strict[T: WConnection | BWConnection | HConnection | MConnection]() {
+        let genes = |innos: &[usize]| {
+            innos.iter().map(|inno| new_t!(inno = *inno)).collect::<Vec<T>>()
+        };
+        // both agree on sorted genomes, at every boundary
+        for (l, r) in [
+            (vec![1, 2, 6], vec![1, 3, 4, 8, 10]),
+            (vec![1, 2], vec![]),
+            (vec![], vec![]),
+            (vec![0, 1, 2], vec![0, 1]),
+            (vec![1, 2], vec![3, 4]),
+            (vec![10], vec![1, 2, 3]),
+            (vec![1, 5], vec![5]),
+            (vec![3], vec![1, 3, 5]),
+        ] {
+            let (l, r) = (genes(&l), genes(&r));
+            assert_eq!(disjoint_excess_count_strict(&l, &r), disjoint_excess_count(&l, &r));
+            assert_eq!(disjoint_excess_count_strict(&r, &l), disjoint_excess_count(&r, &l));
+        }
+
+        // a gene appended out of order is still disjoint, as it's below the other's max
+        let (l, r) = (genes(&[1, 4, 2]), genes(&[1, 3, 4]));
+        assert_eq!(disjoint_excess_count_strict(&l, &r), (2., 0.));
+        assert_ne!(disjoint_excess_count(&l, &r), (2., 0.));
+    });
+
+    test_t!(
+    test_alignment[T: WConnection | BWConnection | HConnection | MConnection]() {
+        let mut rng = default_rng();
+        for _ in 0..200 {
+            let mut genes = || {
+                let mut genes = (0..rng.random_range(0..12))
+                    .map(|_| new_t!(inno = rng.random_range(0..16), weight = rng.random()))
+                    .collect::<Vec<T>>();
+                if rng.random_bool(0.75) {
+                    genes.sort_by_key(|c| c.inno());
+                    genes.dedup_by_key(|c| c.inno());
+                }
+                genes
+            };
+            let (l, r) = (genes(), genes());
+            let (disjoint, excess, diff) = alignment(&l, &r);
+            assert_eq!((disjoint, excess), disjoint_excess_count(&l, &r));
+            assert_f64_approx!(diff, avg_param_diff(&l, &r));
+        }
+    });
+
+    test_t!(
+    test_disjoint_excess_count_symmetrical[T: WConnection | BWConnection | HConnection | MConnection]() {
         let l = vec![
             new_t!(inno = 1),
             new_t!(inno = 2),
@@ -388,7 +700,7 @@ mod test {
     });
 
     test_t!(
-    test_disjoint_excess_count_empty[T: WConnection | BWConnection]() {
+    test_disjoint_excess_count_empty[T: WConnection | BWConnection | HConnection | MConnection]() {
         let full = vec![new_t!(inno = 1), new_t!(inno = 2)];
         assert_eq!((0.0, 2.0), disjoint_excess_count(&full, &[]));
         assert_eq!((0.0, 2.0), disjoint_excess_count(&[], &full));
@@ -396,7 +708,7 @@ mod test {
     });
 
     test_t!(
-    test_disjoint_excess_count_hanging_l[T: WConnection | BWConnection]() {
+    test_disjoint_excess_count_hanging_l[T: WConnection | BWConnection | HConnection | MConnection]() {
         assert_eq!(
             (0.0, 1.0),
             disjoint_excess_count(
@@ -411,7 +723,7 @@ mod test {
     });
 
     test_t!(
-    test_disjoint_excess_count_no_overlap[T: WConnection | BWConnection]() {
+    test_disjoint_excess_count_no_overlap[T: WConnection | BWConnection | HConnection | MConnection]() {
         assert_eq!(
             (2.0, 2.0),
             disjoint_excess_count(
@@ -422,7 +734,7 @@ mod test {
     });
 
     test_t!(
-    test_disjoint_excess_count_short_larger_inno[T: WConnection | BWConnection]() {
+    test_disjoint_excess_count_short_larger_inno[T: WConnection | BWConnection | HConnection | MConnection]() {
         assert_eq!(
             (3.0, 1.0),
             disjoint_excess_count(
@@ -450,7 +762,7 @@ mod test {
 
             let mut rng = default_rng();
             for _ in 0..1000 {
-                let lr = crossover_eq(l, r, &mut rng);
+                let lr = crossover_eq(l, r, C::CROSSOVER_PROBABILITIES, &mut rng);
                 assert_eq!(inno.len(), lr.len());
 
                 let lr_inno = lr.iter().map(|c| c.inno()).collect::<HashSet<_>>();
@@ -473,7 +785,7 @@ mod test {
     }
 
     test_t!(
-    test_crossover_eq[T: WConnection | BWConnection]() {
+    test_crossover_eq[T: WConnection | BWConnection | HConnection | MConnection]() {
         let l = [
             new_t!(inno = 0, from = 1_1),
             new_t!(inno = 1, from = 1_2),
@@ -489,7 +801,7 @@ mod test {
     });
 
     test_t!(
-    test_crossover_eq_empty[T: WConnection | BWConnection]() {
+    test_crossover_eq_empty[T: WConnection | BWConnection | HConnection | MConnection]() {
         let l = [new_t!(inno = 2, from = 1)];
 
         assert_crossover_eq(&l, &[]);
@@ -497,7 +809,7 @@ mod test {
     });
 
     test_t!(
-    test_crossover_eq_overflow[T: WConnection | BWConnection]() {
+    test_crossover_eq_overflow[T: WConnection | BWConnection | HConnection | MConnection]() {
         let l = [new_t!(inno = 0, from = 1_1)];
         let r = [new_t!(inno = 1, from = 2_1)];
 
@@ -511,7 +823,7 @@ mod test {
 
     test_t!(
     #[should_panic(expected = "not from r_0")]
-    test_crossover_eq_catchup_l[T: WConnection | BWConnection]() {
+    test_crossover_eq_catchup_l[T: WConnection | BWConnection | HConnection | MConnection]() {
         let l = [
             new_t!(inno = 0, from = 1_1),
             new_t!(inno = 1, from = 1_2),
@@ -519,7 +831,7 @@ mod test {
         let r = [new_t!(inno = 1, from = 2_1)];
         let mut rng = default_rng();
         for _ in 0..1000 {
-            let lr = crossover_eq(&l, &r, &mut rng);
+            let lr = crossover_eq(&l, &r, T::CROSSOVER_PROBABILITIES, &mut rng);
             assert_eq!(lr.len(), 2);
             assert_some_normalized!(&lr[0], [&l[0]]; {.enable()});
             assert_some_normalized!(&lr[1], [&r[0]]; {.enable()}, "not from r_0");
@@ -528,7 +840,7 @@ mod test {
 
     test_t!(
     #[should_panic(expected = "not from l_0")]
-    test_crossover_eq_catchup_r[T: WConnection | BWConnection]() {
+    test_crossover_eq_catchup_r[T: WConnection | BWConnection | HConnection | MConnection]() {
         let l = [new_t!(inno = 1, from = 2_1)];
         let r = [
             new_t!(inno = 0, from = 1_1),
@@ -536,7 +848,7 @@ mod test {
         ];
         let mut rng = default_rng();
         for _ in 0..1000 {
-            let lr = crossover_eq(&l, &r, &mut rng);
+            let lr = crossover_eq(&l, &r, T::CROSSOVER_PROBABILITIES, &mut rng);
             assert_eq!(lr.len(), 2);
             assert_some_normalized!(&lr[0], [&r[0]]; {.enable()});
             assert_some_normalized!(&lr[1], [&l[0]]; {.enable()}, "not from l_0");
@@ -545,7 +857,7 @@ mod test {
 
     test_t!(
     #[should_panic(expected = "not from l_1")]
-    test_crossover_eq_both_step_l[T: WConnection | BWConnection]() {
+    test_crossover_eq_both_step_l[T: WConnection | BWConnection | HConnection | MConnection]() {
         let l = [
             new_t!(inno = 0, from = 1_1),
             new_t!(inno = 1, from = 1_2),
@@ -556,7 +868,7 @@ mod test {
         ];
         let mut rng = default_rng();
         for _ in 0..1000 {
-            let lr = crossover_eq(&l, &r, &mut rng);
+            let lr = crossover_eq(&l, &r, T::CROSSOVER_PROBABILITIES, &mut rng);
             assert_eq!(lr.len(), 2);
             assert_some_normalized!(&lr[0], [&l[0], &r[0]]; {.enable()});
             assert_some_normalized!(&lr[1], [&l[1]]; {.enable()}, "not from l_1");
@@ -565,7 +877,7 @@ mod test {
 
     test_t!(
     #[should_panic(expected = "not from r_1")]
-    test_crossover_eq_both_step_r[T: WConnection | BWConnection]() {
+    test_crossover_eq_both_step_r[T: WConnection | BWConnection | HConnection | MConnection]() {
         let l = [
             new_t!(inno = 0, from = 1_1),
             new_t!(inno = 1, from = 1_2),
@@ -576,7 +888,7 @@ mod test {
         ];
         let mut rng = default_rng();
         for _ in 0..1000 {
-            let lr = crossover_eq(&l, &r, &mut rng);
+            let lr = crossover_eq(&l, &r, T::CROSSOVER_PROBABILITIES, &mut rng);
             assert_eq!(lr.len(), 2);
             assert_some_normalized!(&lr[0], [&l[0], &r[0]]; {.enable()});
             assert_some_normalized!(&lr[1], [&r[1]]; {.enable()}, "not from r_1");
@@ -595,7 +907,7 @@ mod test {
 
             let mut rng = default_rng();
             for _ in 0..1000 {
-                let lr = crossover_ne(l, r, &mut rng);
+                let lr = crossover_ne(l, r, C::CROSSOVER_PROBABILITIES, &mut rng);
                 assert_eq!(lr.len(), l.len());
 
                 let lr_inno = lr.iter().map(|c| c.inno()).collect::<HashSet<_>>();
@@ -620,7 +932,7 @@ mod test {
     }
 
     test_t!(
-    test_crossover_ne[T: WConnection | BWConnection]() {
+    test_crossover_ne[T: WConnection | BWConnection | HConnection | MConnection]() {
         let l = [
             new_t!(inno = 0, from = 1_1),
             new_t!(inno = 1, from = 1_2),
@@ -639,7 +951,7 @@ mod test {
     });
 
     test_t!(
-    test_crossover_ne_empty[T: WConnection | BWConnection]() {
+    test_crossover_ne_empty[T: WConnection | BWConnection | HConnection | MConnection]() {
         let l = [new_t!(inno = 0, from = 1_1)];
 
         assert_crossover_ne(&l, &[]);
@@ -647,7 +959,7 @@ mod test {
     });
 
     test_t!(
-    test_crossover_ne_no_overlap[T: WConnection | BWConnection]() {
+    test_crossover_ne_no_overlap[T: WConnection | BWConnection | HConnection | MConnection]() {
         let l = [
             new_t!(inno = 1, from = 1_1),
             new_t!(inno = 3, from = 1_2),
@@ -663,7 +975,7 @@ mod test {
     });
 
     test_t!(
-    test_crossover_ne_full_overlap[T: WConnection | BWConnection]() {
+    test_crossover_ne_full_overlap[T: WConnection | BWConnection | HConnection | MConnection]() {
         let l = [
             new_t!(inno = 1, from = 1_1),
             new_t!(inno = 2, from = 1_2),
@@ -679,7 +991,7 @@ mod test {
     });
 
     test_t!(
-    test_crossover_ne_overflow[T: WConnection | BWConnection]() {
+    test_crossover_ne_overflow[T: WConnection | BWConnection | HConnection | MConnection]() {
         let l = [new_t!(inno = 10, from = 1_1)];
         let r = [
             new_t!(inno = 1, from = 2_1),
@@ -690,7 +1002,7 @@ mod test {
     });
 
     test_t!(
-    test_crossover_ne_no_lt[T: WConnection | BWConnection]() {
+    test_crossover_ne_no_lt[T: WConnection | BWConnection | HConnection | MConnection]() {
         let l = [new_t!(inno = 0, from = 1_1)];
         let r = [new_t!(inno = 10, from = 2_1)];
 
@@ -698,7 +1010,7 @@ mod test {
     });
 
     test_t!(
-    test_crossover_lt[T: WConnection | BWConnection]() {
+    test_crossover_lt[T: WConnection | BWConnection | HConnection | MConnection]() {
         let l = [
             new_t!(inno = 0, from = 1_1),
             new_t!(inno = 1, from = 1_2),
@@ -715,9 +1027,110 @@ mod test {
         assert_crossover_ne(&l, &r);
         for (le, ge) in crossover(&l, &r, Ordering::Less, &mut rng)
             .iter()
-            .zip(crossover_ne(&r, &l, &mut rng))
+            .zip(crossover_ne(&r, &l, T::CROSSOVER_PROBABILITIES, &mut rng))
         {
             assert_eq!(le.inno(), ge.inno());
         }
     });
+
+    test_t!(
+    test_crossover_blend[T: WConnection | BWConnection | HConnection | MConnection]() {
+        let l = [
+            new_t!(inno = 0, from = 1_1),
+            new_t!(inno = 1, from = 1_2, weight = 1.),
+        ];
+        let r = [
+            new_t!(inno = 1, from = 2_1, weight = -0.5),
+            new_t!(inno = 2, from = 2_2),
+        ];
+
+        let mut rng = default_rng();
+        let blend = [0, u64::MAX];
+        for l_fit in [Ordering::Equal, Ordering::Greater, Ordering::Less] {
+            let lr = crossover_with(&l, &r, l_fit, blend, &mut rng);
+            let matched = lr.iter().find(|c| c.inno() == 1).unwrap();
+            assert_f64_approx!(matched.weight(), 0.25);
+            assert!(matched
+                .params()
+                .zip(l[1].params().zip(r[0].params()))
+                .all(|(b, (l, r))| b == (l + r) / 2.));
+        }
+
+        // unmatched genes are left as they are
+        let lr = crossover_with(&l, &r, Ordering::Equal, blend, &mut rng);
+        assert_eq!(lr[0].weight(), l[0].weight());
+        assert_eq!(lr[2].weight(), r[1].weight());
+
+        // without any chance of blending, only picking the matched gene draws from the rng
+        let mut counted = Counted::new(default_rng());
+        crossover_with(&l, &r, Ordering::Greater, [u64::MAX, 0], &mut counted);
+        assert_eq!(counted.draws(), 1);
+    });
+
+    test_t!(
+    test_delta_normalized[T: WConnection]() {
+        let l = (0..30).map(|inno| new_t!(inno = inno)).collect::<Vec<_>>();
+        let r = (0..10).map(|inno| new_t!(inno = inno)).collect::<Vec<_>>();
+        let raw = T::EXCESS_COEFFICIENT * 20.;
+        let delta_normalized = |l: &[T], r: &[T], normalization| {
+            delta_with(l, r, normalization, DisjointExcess::Merge)
+        };
+
+        assert_f64_approx!(delta_normalized(&l, &r, Normalization::One), raw);
+        assert_f64_approx!(delta_normalized(&l, &r, Normalization::Longest), raw / 30.);
+        assert_f64_approx!(delta_normalized(&l, &r, Normalization::Threshold(30)), raw / 30.);
+        assert_f64_approx!(delta_normalized(&l, &r, Normalization::Threshold(31)), raw);
+        assert_f64_approx!(delta(&l, &r), raw / 30.);
+        assert_f64_approx!(delta(&l[..15], &r), T::EXCESS_COEFFICIENT * 5.);
+
+        // empty genomes are never divided by 0
+        assert_eq!(delta_normalized(&[], &[], Normalization::Longest), 0.);
+        assert_f64_approx!(
+            delta_normalized(&l, &[], Normalization::Longest),
+            T::EXCESS_COEFFICIENT
+        );
+    });
+
+    test_t!(
+    test_crossover_into[T: WConnection | BWConnection | HConnection | MConnection]() {
+        let l = [
+            new_t!(inno = 0, from = 1_1),
+            new_t!(inno = 1, from = 1_2),
+            new_t!(inno = 3, from = 1_3),
+        ];
+        let r = [
+            new_t!(inno = 1, from = 2_1),
+            new_t!(inno = 2, from = 2_2),
+            new_t!(inno = 3, from = 2_3),
+            new_t!(inno = 4, from = 2_4),
+        ];
+
+        let mut rng = default_rng();
+        let mut out = vec![new_t!(inno = 9)];
+        for (l_fit, size) in [
+            (Ordering::Equal, 5),
+            (Ordering::Greater, 3),
+            (Ordering::Less, 4),
+        ] {
+            crossover_into(&l, &r, l_fit, &mut rng, &mut out);
+            assert_eq!(out.len(), size);
+            assert!(out.is_sorted_by_key(|c| c.inno()));
+        }
+
+        // a buffer already as large as the child isn't grown
+        let capacity = out.capacity();
+        let ptr = out.as_ptr();
+        crossover_into(&l, &r, Ordering::Equal, &mut rng, &mut out);
+        assert_eq!((out.capacity(), out.as_ptr()), (capacity, ptr));
+
+        let cross = crossover(&l, &r, Ordering::Equal, &mut rng);
+        assert_eq!(cross.capacity(), cross.len());
+
+        // matched genes may be blended into the buffer too
+        let l = [new_t!(inno = 1, from = 1_2, weight = 1.)];
+        let r = [new_t!(inno = 1, from = 2_1, weight = -0.5)];
+        crossover_with_into(&l, &r, Ordering::Equal, [0, u64::MAX], &mut rng, &mut out);
+        assert_eq!(out.len(), 1);
+        assert_f64_approx!(out[0].weight(), 0.25);
+    });
 }
@@ -0,0 +1,139 @@
+//! A totally ordered fitness.
+//!
+//! Fitness is an f64, which is only [PartialOrd], so sorting or taking the max of a population
+//! would otherwise need `partial_cmp().unwrap()` at every site. [Fit] orders by
+//! [f64::total_cmp] instead, which agrees with `<` for every non-NaN value, except that NaN is
+//! ordered below every other fitness so that a genome scoring NaN is never the fittest.
+use core::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    iter::Sum,
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+use serde::{Deserialize, Serialize};
+
+/// The fitness of some [Genome](crate::Genome), as scored by a [Scenario](crate::Scenario)
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Fit(pub f64);
+
+impl Fit {
+    /// Least possible fitness, held by genomes who are yet to be evaluated
+    pub const MIN: Self = Self(f64::MIN);
+    pub const MAX: Self = Self(f64::MAX);
+
+    /// The fitness of a genome scored `fit` by some scenario. Scores that aren't finite, such as
+    /// NaN from a diverging network, are taken as [MIN](Fit::MIN), so that they can't win a specie
+    /// or poison its [adjusted fitness](crate::Specie::fit_adjusted).
+    pub fn scored(fit: f64) -> Self {
+        if fit.is_finite() {
+            Self(fit)
+        } else {
+            Self::MIN
+        }
+    }
+}
+
+impl PartialEq for Fit {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Fit {}
+
+impl PartialOrd for Fit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Fit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.0.is_nan(), other.0.is_nan()) {
+            (false, false) => self.0.total_cmp(&other.0),
+            (l, r) => r.cmp(&l),
+        }
+    }
+}
+
+impl From<f64> for Fit {
+    fn from(fit: f64) -> Self {
+        Self(fit)
+    }
+}
+
+impl From<Fit> for f64 {
+    fn from(fit: Fit) -> Self {
+        fit.0
+    }
+}
+
+impl Display for Fit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+macro_rules! fit_op {
+    ($trait:ident, $fn:ident) => {
+        impl $trait for Fit {
+            type Output = Fit;
+
+            fn $fn(self, rhs: Fit) -> Fit {
+                Fit(self.0.$fn(rhs.0))
+            }
+        }
+
+        impl $trait<f64> for Fit {
+            type Output = Fit;
+
+            fn $fn(self, rhs: f64) -> Fit {
+                Fit(self.0.$fn(rhs))
+            }
+        }
+    };
+}
+
+fit_op!(Add, add);
+fit_op!(Sub, sub);
+fit_op!(Mul, mul);
+fit_op!(Div, div);
+
+impl Neg for Fit {
+    type Output = Fit;
+
+    fn neg(self) -> Fit {
+        Fit(-self.0)
+    }
+}
+
+impl Sum for Fit {
+    fn sum<I: Iterator<Item = Fit>>(iter: I) -> Fit {
+        Fit(iter.map(|fit| fit.0).sum())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fit_ord() {
+        let mut fits = [Fit(1.), Fit::MIN, Fit(-0.5), Fit(f64::NAN), Fit(3.)];
+        fits.sort();
+        assert!(fits[0].0.is_nan());
+        assert_eq!(fits[1..], [Fit::MIN, Fit(-0.5), Fit(1.), Fit(3.)]);
+        assert_eq!(fits.iter().max(), Some(&Fit(3.)));
+        assert!(Fit(-f64::NAN) < Fit::MIN);
+        assert_eq!(Fit(f64::NAN), Fit(f64::NAN));
+
+        assert_eq!(Fit::scored(2.), Fit(2.));
+        for score in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert_eq!(Fit::scored(score), Fit::MIN);
+        }
+        assert_eq!(Fit(1.) + Fit(2.) * 2., Fit(5.));
+        assert_eq!([Fit(1.), Fit(2.)].into_iter().sum::<Fit>(), Fit(3.));
+        assert_eq!(serde_json::to_string(&Fit(1.5)).unwrap(), "1.5");
+    }
+}
@@ -0,0 +1,49 @@
+use crate::random::percent;
+use rand::{distr::Uniform, Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+/// An evolvable transform `gain * x + offset` over a single network output, applied after
+/// activation so that evolution may reach output ranges that σ alone can't produce.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Affine {
+    pub gain: f64,
+    pub offset: f64,
+}
+
+impl Affine {
+    const PARAM_REPLACE_PROBABILITY: u64 = percent(10);
+    const PARAM_PERTURB_FAC: f64 = 0.05;
+
+    #[inline]
+    pub fn apply(&self, x: f64) -> f64 {
+        self.gain * x + self.offset
+    }
+
+    /// Perturb or replace either the gain or the offset, in the same way that connection params
+    /// are mutated.
+    pub fn mutate(&mut self, rng: &mut impl RngCore) {
+        let replace = rng.next_u64() < Self::PARAM_REPLACE_PROBABILITY;
+        let v: f64 =
+            rng.sample(Uniform::new_inclusive(-3., 3.).expect("distribution of -3. ..= 3. failed"));
+        let param = if rng.next_u64() < percent(50) {
+            &mut self.gain
+        } else {
+            &mut self.offset
+        };
+
+        *param = if replace {
+            v
+        } else {
+            *param + Self::PARAM_PERTURB_FAC * v
+        };
+    }
+}
+
+impl Default for Affine {
+    fn default() -> Self {
+        Self {
+            gain: 1.,
+            offset: 0.,
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Handcraft genomes of a known topology, such as for tests, examples, or importing an
+//! architecture from elsewhere.
+//!
+//! Nodes are numbered as [Genome::new] lays them out: sensory nodes first, then action nodes,
+//! then the bias, followed by every hidden node in the order that they were added.
+//!
+//! ```
+//! use eevee::genome::{Genome, GenomeBuilder, Recurrent, WConnection};
+//!
+//! let (genome, _) = GenomeBuilder::<WConnection, Recurrent<_>>::new(2, 1)
+//!     .hidden(1)
+//!     .connect(0, 4, 0.5)
+//!     .connect(1, 4, -0.5)
+//!     .connect(4, 2, 1.)
+//!     .build();
+//!
+//! assert_eq!(genome.nodes().len(), 5);
+//! assert_eq!(genome.connections().len(), 3);
+//! ```
+use super::{Connection, Genome, InnoGen, NodeKind, Recurrence};
+use core::marker::PhantomData;
+
+/// A builder of genomes whose nodes and connections are given by hand. Connections are given
+/// innovation ids by path, so that genomes built with the same [InnoGen] agree on them.
+#[derive(Debug, Clone)]
+pub struct GenomeBuilder<C: Connection, G: Genome<C>> {
+    sensory: usize,
+    action: usize,
+    hidden: usize,
+    connections: Vec<(usize, usize, f64, bool)>,
+    _genome: PhantomData<(C, G)>,
+}
+
+impl<C: Connection, G: Genome<C>> GenomeBuilder<C, G> {
+    pub fn new(sensory: usize, action: usize) -> Self {
+        Self {
+            sensory,
+            action,
+            hidden: 0,
+            connections: Vec::new(),
+            _genome: PhantomData,
+        }
+    }
+
+    /// Add `count` hidden nodes, numbered after every node added so far
+    pub fn hidden(mut self, count: usize) -> Self {
+        self.hidden += count;
+        self
+    }
+
+    /// Connect node `from` to node `to` with `weight`. Panics if either node doesn't exist yet,
+    /// or if `to` is a sensory or bias node, which can't have incoming connections.
+    pub fn connect(self, from: usize, to: usize, weight: f64) -> Self {
+        self.push(from, to, weight, false)
+    }
+
+    /// Like [connect](GenomeBuilder::connect), but the connection is
+    /// [frozen](Connection::frozen), so that evolution leaves it as it's given
+    pub fn connect_frozen(self, from: usize, to: usize, weight: f64) -> Self {
+        self.push(from, to, weight, true)
+    }
+
+    fn push(mut self, from: usize, to: usize, weight: f64, frozen: bool) -> Self {
+        let nodes = self.sensory + self.action + 1 + self.hidden;
+        assert!(
+            from < nodes && to < nodes,
+            "path ({from}, {to}) leaves the {nodes} nodes built so far"
+        );
+        assert!(
+            to >= self.sensory && to != self.sensory + self.action,
+            "path ({from}, {to}) enters a sensory or bias node"
+        );
+        self.connections.push((from, to, weight, frozen));
+        self
+    }
+
+    /// Build the genome, with innovation ids given out past [Genome::new]'s head. Returns the
+    /// genome and the next innovation id. Panics as [build_with](GenomeBuilder::build_with) does.
+    pub fn build(self) -> (G, usize) {
+        let (_, inno_head) = G::new(self.sensory, self.action);
+        let mut innogen = InnoGen::new(inno_head);
+        let genome = self.build_with(&mut innogen);
+        (genome, innogen.head)
+    }
+
+    /// Build the genome, with innovation ids from `innogen`. Building several genomes with the
+    /// same `innogen` gives connections along the same path the same id in each. Panics if the
+    /// genome is [Recurrence::Acyclic] and its connections close a cycle.
+    pub fn build_with(self, innogen: &mut InnoGen) -> G {
+        let (mut genome, _) = G::new(self.sensory, self.action);
+        for _ in 0..self.hidden {
+            genome.push_node(NodeKind::Internal);
+        }
+        for (from, to, weight, frozen) in self.connections {
+            let mut connection = C::new(from, to, innogen);
+            connection.set_weight(weight);
+            connection.set_frozen(frozen);
+            genome.push_connection(connection);
+        }
+        if genome.recurrence() == Recurrence::Acyclic {
+            let recurrent = genome.recurrent();
+            if let Some(idx) = recurrent.iter().position(|recurrent| *recurrent) {
+                let (from, to) = genome.connections()[idx].path();
+                panic!("path ({from}, {to}) closes a cycle in an acyclic genome");
+            }
+        }
+        genome
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::genome::{FeedForward, Recurrent, WConnection};
+
+    type C = WConnection;
+
+    #[test]
+    fn test_genome_builder() {
+        let (genome, inno_head) = GenomeBuilder::<C, Recurrent<C>>::new(2, 1)
+            .hidden(2)
+            .connect(0, 4, 0.5)
+            .connect(1, 5, -0.5)
+            .connect(4, 2, 1.)
+            .connect(5, 2, 2.)
+            .build();
+
+        assert_eq!(genome.sensory(), 0..2);
+        assert_eq!(genome.action(), 2..3);
+        assert_eq!(
+            &genome.nodes()[4..],
+            &[NodeKind::Internal, NodeKind::Internal]
+        );
+        assert_eq!(
+            genome
+                .connections()
+                .iter()
+                .map(|c| (c.path(), c.weight()))
+                .collect::<Vec<_>>(),
+            vec![((0, 4), 0.5), ((1, 5), -0.5), ((4, 2), 1.), ((5, 2), 2.)]
+        );
+
+        let base = Recurrent::<C>::new(2, 1).1;
+        assert_eq!(inno_head, base + 4);
+        let innos = genome.connections().iter().map(|c| c.inno());
+        assert!(innos.eq(base..base + 4));
+    }
+
+    #[test]
+    fn test_genome_builder_shared_innogen() {
+        let mut innogen = InnoGen::new(0);
+        let a = GenomeBuilder::<C, FeedForward<C>>::new(1, 1)
+            .hidden(1)
+            .connect(0, 3, 1.)
+            .connect(3, 1, 1.)
+            .build_with(&mut innogen);
+        let b = GenomeBuilder::<C, FeedForward<C>>::new(1, 1)
+            .hidden(1)
+            .connect(3, 1, -1.)
+            .connect(0, 1, 1.)
+            .build_with(&mut innogen);
+
+        assert_eq!(a.connections()[1].inno(), b.connections()[0].inno());
+        assert_eq!(b.connections()[1].inno(), 2);
+    }
+
+    #[test]
+    fn test_genome_builder_frozen() {
+        let (genome, _) = GenomeBuilder::<C, Recurrent<C>>::new(1, 1)
+            .connect_frozen(0, 1, 0.5)
+            .connect(2, 1, 1.)
+            .build();
+        let frozen = genome.connections().iter().map(|c| c.frozen);
+        assert!(frozen.eq([true, false]));
+    }
+
+    #[test]
+    #[should_panic(expected = "path (4, 3) closes a cycle")]
+    fn test_genome_builder_acyclic() {
+        GenomeBuilder::<C, FeedForward<C>>::new(1, 1)
+            .hidden(2)
+            .connect(0, 3, 1.)
+            .connect(3, 4, 1.)
+            .connect(4, 3, 1.)
+            .connect(4, 1, 1.)
+            .build();
+    }
+
+    #[test]
+    #[should_panic(expected = "enters a sensory or bias node")]
+    fn test_genome_builder_into_bias() {
+        GenomeBuilder::<C, Recurrent<C>>::new(2, 1).connect(0, 3, 1.);
+    }
+}
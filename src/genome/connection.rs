@@ -1,4 +1,4 @@
-use super::{Connection, InnoGen};
+use super::{Connection, InnoGen, MutationParams};
 use crate::{mutate_param, random::percent};
 use core::hash::Hash;
 use serde::{Deserialize, Serialize};
@@ -10,6 +10,8 @@ pub struct WConnection {
     pub to: usize,
     pub weight: f64,
     pub enabled: bool,
+    #[serde(default, skip_serializing_if = "core::ops::Not::not")]
+    pub frozen: bool,
 }
 
 /// A basic connection, with a single weighted path
@@ -27,6 +29,7 @@ impl Connection for WConnection {
             to,
             weight: 1.,
             enabled: true,
+            frozen: false,
         }
     }
 
@@ -34,6 +37,15 @@ impl Connection for WConnection {
         self.inno
     }
 
+    fn set_inno(&mut self, inno: usize) {
+        self.inno = inno;
+    }
+
+    fn set_path(&mut self, (from, to): (usize, usize)) {
+        self.from = from;
+        self.to = to;
+    }
+
     fn enable(&mut self) {
         self.enabled = true;
     }
@@ -46,6 +58,14 @@ impl Connection for WConnection {
         self.enabled
     }
 
+    fn frozen(&self) -> bool {
+        self.frozen
+    }
+
+    fn set_frozen(&mut self, frozen: bool) {
+        self.frozen = frozen;
+    }
+
     fn path(&self) -> (usize, usize) {
         (self.from, self.to)
     }
@@ -54,6 +74,10 @@ impl Connection for WConnection {
         self.weight
     }
 
+    fn set_weight(&mut self, weight: f64) {
+        self.weight = weight;
+    }
+
     fn bisect(&mut self, center: usize, inno: &mut InnoGen) -> (Self, Self) {
         <Self as Connection>::disable(self);
         (
@@ -64,6 +88,7 @@ impl Connection for WConnection {
                 to: center,
                 weight: 1.,
                 enabled: true,
+                frozen: false,
             },
             // bisect-node -{w}> to
             Self {
@@ -72,6 +97,7 @@ impl Connection for WConnection {
                 to: self.to,
                 weight: self.weight,
                 enabled: true,
+                frozen: false,
             },
         )
     }
@@ -85,6 +111,7 @@ impl Default for WConnection {
             to: 0,
             weight: 0.,
             enabled: true,
+            frozen: false,
         }
     }
 }
@@ -107,6 +134,8 @@ pub struct BWConnection {
     pub bias: f64,
     pub weight: f64,
     pub enabled: bool,
+    #[serde(default, skip_serializing_if = "core::ops::Not::not")]
+    pub frozen: bool,
 }
 
 impl Connection for BWConnection {
@@ -124,6 +153,7 @@ impl Connection for BWConnection {
             bias: 0.,
             weight: 1.,
             enabled: true,
+            frozen: false,
         }
     }
 
@@ -131,6 +161,15 @@ impl Connection for BWConnection {
         self.inno
     }
 
+    fn set_inno(&mut self, inno: usize) {
+        self.inno = inno;
+    }
+
+    fn set_path(&mut self, (from, to): (usize, usize)) {
+        self.from = from;
+        self.to = to;
+    }
+
     fn enable(&mut self) {
         self.enabled = true;
     }
@@ -143,6 +182,14 @@ impl Connection for BWConnection {
         self.enabled
     }
 
+    fn frozen(&self) -> bool {
+        self.frozen
+    }
+
+    fn set_frozen(&mut self, frozen: bool) {
+        self.frozen = frozen;
+    }
+
     fn path(&self) -> (usize, usize) {
         (self.from, self.to)
     }
@@ -151,6 +198,10 @@ impl Connection for BWConnection {
         self.weight
     }
 
+    fn set_weight(&mut self, weight: f64) {
+        self.weight = weight;
+    }
+
     fn bisect(&mut self, center: usize, inno: &mut InnoGen) -> (Self, Self) {
         <Self as Connection>::disable(self);
         (
@@ -162,6 +213,7 @@ impl Connection for BWConnection {
                 bias: 0.,
                 weight: 1.,
                 enabled: true,
+                frozen: false,
             },
             // bisect-node -{w}> to
             Self {
@@ -171,6 +223,7 @@ impl Connection for BWConnection {
                 bias: self.bias,
                 weight: self.weight,
                 enabled: true,
+                frozen: false,
             },
         )
     }
@@ -185,6 +238,7 @@ impl Default for BWConnection {
             bias: 0.,
             weight: 0.,
             enabled: true,
+            frozen: false,
         }
     }
 }
@@ -198,3 +252,356 @@ impl Hash for BWConnection {
         ((1000. * self.weight) as usize).hash(state);
     }
 }
+
+/// A connection whose params may change over the lifetime of a network, rather than only over
+/// evolution.
+pub trait Plastic: Connection {
+    /// hebbian learning rate of this connection's weight
+    fn rate(&self) -> f64;
+}
+
+/// A connection whose weight adapts while its network is stepped, at an evolved hebbian
+/// learning rate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HConnection {
+    pub inno: usize,
+    pub from: usize,
+    pub to: usize,
+    pub weight: f64,
+    pub rate: f64,
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "core::ops::Not::not")]
+    pub frozen: bool,
+}
+
+impl Connection for HConnection {
+    const EXCESS_COEFFICIENT: f64 = 1.0;
+    const DISJOINT_COEFFICIENT: f64 = 1.0;
+    const PARAM_COEFFICIENT: f64 = 0.4;
+
+    mutate_param!([Weight, Rate]: [percent(70), percent(30)]);
+
+    fn new(from: usize, to: usize, inno: &mut InnoGen) -> Self {
+        Self {
+            inno: inno.path((from, to)),
+            from,
+            to,
+            weight: 1.,
+            rate: 0.,
+            enabled: true,
+            frozen: false,
+        }
+    }
+
+    fn inno(&self) -> usize {
+        self.inno
+    }
+
+    fn set_inno(&mut self, inno: usize) {
+        self.inno = inno;
+    }
+
+    fn set_path(&mut self, (from, to): (usize, usize)) {
+        self.from = from;
+        self.to = to;
+    }
+
+    fn enable(&mut self) {
+        self.enabled = true;
+    }
+
+    fn disable(&mut self) {
+        self.enabled = false;
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn frozen(&self) -> bool {
+        self.frozen
+    }
+
+    fn set_frozen(&mut self, frozen: bool) {
+        self.frozen = frozen;
+    }
+
+    fn path(&self) -> (usize, usize) {
+        (self.from, self.to)
+    }
+
+    fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    fn set_weight(&mut self, weight: f64) {
+        self.weight = weight;
+    }
+
+    fn bisect(&mut self, center: usize, inno: &mut InnoGen) -> (Self, Self) {
+        <Self as Connection>::disable(self);
+        (
+            // from -{1.}> bisect-node
+            Self {
+                inno: inno.path((self.from, center)),
+                from: self.from,
+                to: center,
+                weight: 1.,
+                rate: 0.,
+                enabled: true,
+                frozen: false,
+            },
+            // bisect-node -{w}> to
+            Self {
+                inno: inno.path((center, self.to)),
+                from: center,
+                to: self.to,
+                weight: self.weight,
+                rate: self.rate,
+                enabled: true,
+                frozen: false,
+            },
+        )
+    }
+}
+
+impl Plastic for HConnection {
+    fn rate(&self) -> f64 {
+        self.rate
+    }
+}
+
+impl Default for HConnection {
+    fn default() -> Self {
+        Self {
+            inno: 0,
+            from: 0,
+            to: 0,
+            weight: 0.,
+            rate: 0.,
+            enabled: true,
+            frozen: false,
+        }
+    }
+}
+
+impl Hash for HConnection {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.inno.hash(state);
+        self.from.hash(state);
+        self.to.hash(state);
+        ((1000. * self.weight) as usize).hash(state);
+        ((1000. * self.rate) as usize).hash(state);
+    }
+}
+
+/// A plastic connection who may be modulatory, see [modulatory](Modulatory::modulatory)
+pub trait Modulatory: Plastic {
+    /// whether this connection modulates the plasticity of its target node, rather than feeding it
+    /// activation. Modulation into a node scales the hebbian updates of every weight into it.
+    fn modulatory(&self) -> bool;
+}
+
+/// A [plastic](Plastic) connection who either feeds activation like any other, or instead
+/// modulates the plasticity of its target, for neuromodulated networks like
+/// [Modulated](crate::network::Modulated). Mutation may flip which of the two it is.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MConnection {
+    pub inno: usize,
+    pub from: usize,
+    pub to: usize,
+    pub weight: f64,
+    pub rate: f64,
+    #[serde(default, skip_serializing_if = "core::ops::Not::not")]
+    pub modulatory: bool,
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "core::ops::Not::not")]
+    pub frozen: bool,
+}
+
+impl MConnection {
+    /// Probability that a param mutation instead flips whether this connection is modulatory
+    pub const FLIP_PROBABILITY: u64 = percent(5);
+    /// Probability that a param mutation that doesn't flip mutates the weight, rather than the rate
+    pub const WEIGHT_PROBABILITY: u64 = percent(70);
+    /// Difference in [param_diff](Connection::param_diff) between connections of differing kind
+    pub const KIND_DIFF: f64 = 1.;
+}
+
+impl Connection for MConnection {
+    const EXCESS_COEFFICIENT: f64 = 1.0;
+    const DISJOINT_COEFFICIENT: f64 = 1.0;
+    const PARAM_COEFFICIENT: f64 = 0.4;
+    const PARAMS: usize = 2;
+
+    fn mutate_param(&mut self, rng: &mut impl rand::RngCore) {
+        self.mutate_param_with(rng, &Self::MUTATION_PARAMS)
+    }
+
+    fn mutate_param_with(&mut self, rng: &mut impl rand::RngCore, params: &MutationParams) {
+        if rng.next_u64() < Self::FLIP_PROBABILITY {
+            self.modulatory = !self.modulatory;
+        } else if rng.next_u64() < Self::WEIGHT_PROBABILITY {
+            self.weight = params.apply(self.weight, rng);
+        } else {
+            self.rate = params.apply(self.rate, rng);
+        }
+    }
+
+    /// 1 if this connection is modulatory, and 0 if it feeds activation
+    fn kind(&self) -> u8 {
+        self.modulatory as u8
+    }
+
+    /// Differing weights and rates, and [KIND_DIFF](MConnection::KIND_DIFF) if only one of the
+    /// two is modulatory
+    fn param_diff(&self, other: &Self) -> f64 {
+        let kind = if self.modulatory == other.modulatory {
+            0.
+        } else {
+            Self::KIND_DIFF
+        };
+        (self.weight - other.weight).abs() + (self.rate - other.rate).abs() + kind
+    }
+
+    fn params(&self) -> impl Iterator<Item = f64> {
+        [self.weight, self.rate].into_iter()
+    }
+
+    fn set_params(&mut self, params: &[f64]) {
+        assert_eq!(
+            params.len(),
+            Self::PARAMS,
+            "expected {} params",
+            Self::PARAMS
+        );
+        self.weight = params[0];
+        self.rate = params[1];
+    }
+
+    fn new(from: usize, to: usize, inno: &mut InnoGen) -> Self {
+        Self {
+            inno: inno.path((from, to)),
+            from,
+            to,
+            weight: 1.,
+            rate: 0.,
+            modulatory: false,
+            enabled: true,
+            frozen: false,
+        }
+    }
+
+    fn inno(&self) -> usize {
+        self.inno
+    }
+
+    fn set_inno(&mut self, inno: usize) {
+        self.inno = inno;
+    }
+
+    fn set_path(&mut self, (from, to): (usize, usize)) {
+        self.from = from;
+        self.to = to;
+    }
+
+    fn enable(&mut self) {
+        self.enabled = true;
+    }
+
+    fn disable(&mut self) {
+        self.enabled = false;
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn frozen(&self) -> bool {
+        self.frozen
+    }
+
+    fn set_frozen(&mut self, frozen: bool) {
+        self.frozen = frozen;
+    }
+
+    fn path(&self) -> (usize, usize) {
+        (self.from, self.to)
+    }
+
+    fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    fn set_weight(&mut self, weight: f64) {
+        self.weight = weight;
+    }
+
+    /// The lower half always feeds activation, and the upper half keeps the kind of the original,
+    /// so that a bisected modulatory connection still modulates its target
+    fn bisect(&mut self, center: usize, inno: &mut InnoGen) -> (Self, Self) {
+        <Self as Connection>::disable(self);
+        (
+            // from -{1.}> bisect-node
+            Self {
+                inno: inno.path((self.from, center)),
+                from: self.from,
+                to: center,
+                weight: 1.,
+                rate: 0.,
+                modulatory: false,
+                enabled: true,
+                frozen: false,
+            },
+            // bisect-node -{w}> to
+            Self {
+                inno: inno.path((center, self.to)),
+                from: center,
+                to: self.to,
+                weight: self.weight,
+                rate: self.rate,
+                modulatory: self.modulatory,
+                enabled: true,
+                frozen: false,
+            },
+        )
+    }
+}
+
+impl Plastic for MConnection {
+    fn rate(&self) -> f64 {
+        self.rate
+    }
+}
+
+impl Modulatory for MConnection {
+    fn modulatory(&self) -> bool {
+        self.modulatory
+    }
+}
+
+impl Default for MConnection {
+    fn default() -> Self {
+        Self {
+            inno: 0,
+            from: 0,
+            to: 0,
+            weight: 0.,
+            rate: 0.,
+            modulatory: false,
+            enabled: true,
+            frozen: false,
+        }
+    }
+}
+
+impl Hash for MConnection {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.inno.hash(state);
+        self.from.hash(state);
+        self.to.hash(state);
+        ((1000. * self.weight) as usize).hash(state);
+        ((1000. * self.rate) as usize).hash(state);
+        self.modulatory.hash(state);
+    }
+}
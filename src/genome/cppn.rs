@@ -0,0 +1,297 @@
+use super::{
+    feedforward::open_acyclic_path, Affine, Connection, FeedForward, Genome, IoLabels, Module,
+    NodeKind, Recurrence,
+};
+use crate::{
+    activate::id, network::Continuous, network::NetworkView, random::CrossoverProbabilities,
+    substrate::Substrate,
+};
+use core::{cmp::Ordering, error::Error, ops::Range};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+/// A compositional pattern producing network: a genome whose network is a function over
+/// coordinates rather than a controller. Every node evolves its own activation, picked from a
+/// few whose composition produces regular patterns ( symmetry from gauss and abs, repetition
+/// from sin ), and connections never form a cycle, like those of a [FeedForward] genome, so that
+/// a query is a single feedforward pass. A Cppn of io `(4, 1)` may [decode](Cppn::decode) a
+/// HyperNEAT [Substrate].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(transparent, bound = "")]
+pub struct Cppn<C: Connection> {
+    inner: FeedForward<C>,
+}
+
+impl<C: Connection> Cppn<C> {
+    /// A view over this genome's connections, for querying it many times over
+    pub fn view(&self) -> NetworkView<'_, C> {
+        NetworkView::new(self)
+    }
+
+    /// The output of this genome's network at some `coordinates`, one for each sensory node
+    pub fn query(&self, coordinates: &[f64]) -> Vec<f64> {
+        self.view().eval(coordinates, |x| x)
+    }
+
+    /// Query the weight of every connection of `substrate`, and build a network of it
+    pub fn decode(&self, substrate: &Substrate) -> Continuous {
+        assert_eq!(
+            (self.inputs(), self.outputs()),
+            (4, 1),
+            "a CPPN genome must have io (4, 1)"
+        );
+        let view = self.view();
+        substrate.query_with(|coordinates| view.eval(coordinates, |x| x)[0])
+    }
+}
+
+impl<C: Connection> Genome<C> for Cppn<C> {
+    const ACTIVATIONS: &'static [usize] = &[id::SIN, id::GAUSS, id::SIGMOID, id::ABS];
+
+    fn new(sensory: usize, action: usize) -> (Self, usize) {
+        let (inner, inno_head) = FeedForward::new(sensory, action);
+        let mut cppn = Self {
+            inner: inner.with_node_activations(),
+        };
+        cppn.node_activations_mut()
+            .unwrap()
+            .fill(Self::ACTIVATIONS[0]);
+        (cppn, inno_head)
+    }
+
+    fn sensory(&self) -> Range<usize> {
+        self.inner.sensory()
+    }
+
+    fn action(&self) -> Range<usize> {
+        self.inner.action()
+    }
+
+    fn io_appended(&self) -> (&[usize], &[usize]) {
+        self.inner.io_appended()
+    }
+
+    fn nodes(&self) -> &[NodeKind] {
+        self.inner.nodes()
+    }
+
+    fn nodes_mut(&mut self) -> &mut [NodeKind] {
+        self.inner.nodes_mut()
+    }
+
+    fn push_node(&mut self, node: NodeKind) {
+        self.inner.push_node(node);
+        if let Some(last) = self.node_activations_mut().unwrap().last_mut() {
+            *last = Self::ACTIVATIONS[0];
+        }
+    }
+
+    fn connections(&self) -> &[C] {
+        self.inner.connections()
+    }
+
+    fn connections_mut(&mut self) -> &mut [C] {
+        self.inner.connections_mut()
+    }
+
+    fn push_connection(&mut self, connection: C) {
+        self.inner.push_connection(connection);
+    }
+
+    fn retain_nodes(&mut self, keep: &[bool]) {
+        self.inner.retain_nodes(keep);
+    }
+
+    fn retain_connections(&mut self, keep: &[bool]) {
+        self.inner.retain_connections(keep);
+    }
+
+    fn set_io_boundary(&mut self, sensory: usize) -> Result<(), Box<dyn Error>> {
+        self.inner.set_io_boundary(sensory)
+    }
+
+    fn append_io(&mut self, sensory: usize, action: usize) -> Result<(), Box<dyn Error>> {
+        self.inner.append_io(sensory, action)?;
+        let activation = self.node_activations_mut().unwrap();
+        let held = activation.len();
+        activation[held - sensory - action..].fill(Self::ACTIVATIONS[0]);
+        Ok(())
+    }
+
+    fn mutation_sigma(&self) -> Option<f64> {
+        self.inner.mutation_sigma()
+    }
+
+    fn mutation_sigma_mut(&mut self) -> Option<&mut f64> {
+        self.inner.mutation_sigma_mut()
+    }
+
+    fn io_labels(&self) -> Option<&IoLabels> {
+        self.inner.io_labels()
+    }
+
+    fn io_labels_mut(&mut self) -> Option<&mut IoLabels> {
+        self.inner.io_labels_mut()
+    }
+
+    fn modules(&self) -> &[Module] {
+        self.inner.modules()
+    }
+
+    fn modules_mut(&mut self) -> Option<&mut Vec<Module>> {
+        self.inner.modules_mut()
+    }
+
+    fn output_affine(&self) -> Option<&[Affine]> {
+        self.inner.output_affine()
+    }
+
+    fn output_affine_mut(&mut self) -> Option<&mut [Affine]> {
+        self.inner.output_affine_mut()
+    }
+
+    fn node_activations(&self) -> Option<&[usize]> {
+        self.inner.node_activations()
+    }
+
+    fn node_activations_mut(&mut self) -> Option<&mut [usize]> {
+        self.inner.node_activations_mut()
+    }
+
+    fn node_time_constants(&self) -> Option<&[f64]> {
+        self.inner.node_time_constants()
+    }
+
+    fn node_time_constants_mut(&mut self) -> Option<&mut [f64]> {
+        self.inner.node_time_constants_mut()
+    }
+
+    fn node_biases(&self) -> Option<&[f64]> {
+        self.inner.node_biases()
+    }
+
+    fn node_biases_mut(&mut self) -> Option<&mut [f64]> {
+        self.inner.node_biases_mut()
+    }
+
+    fn sensory_gains(&self) -> Option<&[f64]> {
+        self.inner.sensory_gains()
+    }
+
+    fn sensory_gains_mut(&mut self) -> Option<&mut [f64]> {
+        self.inner.sensory_gains_mut()
+    }
+
+    fn recurrence(&self) -> Recurrence {
+        Recurrence::Acyclic
+    }
+
+    fn open_path(&self, rng: &mut impl RngCore) -> Option<(usize, usize)> {
+        open_acyclic_path(self, rng)
+    }
+
+    fn reproduce_with(&self, other: &Self, self_fit: Ordering, rng: &mut impl RngCore) -> Self {
+        self.reproduce_configured(other, self_fit, C::CROSSOVER_PROBABILITIES, rng)
+    }
+
+    fn reproduce_configured(
+        &self,
+        other: &Self,
+        self_fit: Ordering,
+        probabilities: CrossoverProbabilities,
+        rng: &mut impl RngCore,
+    ) -> Self {
+        self.reproduce_buffered(other, self_fit, probabilities, rng, &mut Vec::new())
+    }
+
+    fn reproduce_buffered(
+        &self,
+        other: &Self,
+        self_fit: Ordering,
+        probabilities: CrossoverProbabilities,
+        rng: &mut impl RngCore,
+        scratch: &mut Vec<C>,
+    ) -> Self {
+        Self {
+            inner: self.inner.reproduce_buffered(
+                &other.inner,
+                self_fit,
+                probabilities,
+                rng,
+                scratch,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        activate,
+        genome::{InnoGen, WConnection},
+        random::{default_rng, percent},
+    };
+    use rulinalg::matrix::BaseMatrix;
+
+    type C = WConnection;
+
+    #[test]
+    fn test_cppn_acyclic() {
+        let mut rng = default_rng();
+        let (mut genome, inno_head) = Cppn::<C>::new(2, 2);
+        let mut inno = InnoGen::new(inno_head);
+        for _ in 0..200 {
+            // new_connection panics once saturated, which small genomes may well be
+            match genome.open_path(&mut rng) {
+                Some((from, to)) if rng.next_u64() < percent(50) => {
+                    genome.push_connection(C::new(from, to, &mut inno))
+                }
+                _ => genome.mutate_with(&mut rng, &mut inno, [0, percent(50), 0, 0, percent(50)]),
+            }
+            assert!(genome.recurrent().iter().all(|recurrent| !recurrent));
+        }
+        assert!(genome
+            .node_activations()
+            .unwrap()
+            .iter()
+            .all(|id| Cppn::<C>::ACTIVATIONS.contains(id)));
+    }
+
+    #[test]
+    fn test_cppn_query() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = Cppn::<C>::new(2, 1);
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.node_activations_mut().unwrap()[2] = id::GAUSS;
+        assert_eq!(genome.query(&[0.5, 3.]), vec![activate::gauss(0.5)]);
+
+        genome
+            .bisect_connection(&mut default_rng(), &mut inno)
+            .unwrap();
+        assert_eq!(genome.node_activations().unwrap()[4], id::SIN);
+        assert_eq!(
+            genome.query(&[0.5, 3.]),
+            vec![activate::gauss(activate::sin(0.5))]
+        );
+
+        let decoded = Cppn::<C>::from_str(&genome.to_string().unwrap()).unwrap();
+        assert_eq!(decoded.node_activations(), genome.node_activations());
+    }
+
+    #[test]
+    fn test_cppn_decode() {
+        // weight is sin of the target's x
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = Cppn::<C>::new(4, 1);
+        genome.push_connection(C::new(2, 4, &mut inno));
+
+        let nn = genome.decode(&Substrate::layered(3, 2));
+        let cols = nn.w.cols();
+        assert_eq!(cols, 5);
+        let w = nn.w.data();
+        assert_eq!(w[3], activate::sin(-1.));
+        assert_eq!(w[4], activate::sin(1.));
+        assert_eq!(w[cols + 3], activate::sin(-1.));
+    }
+}
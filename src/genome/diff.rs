@@ -0,0 +1,201 @@
+//! A structured account of how a child genome differs from its parent, so that what mutation
+//! and crossover did to a lineage may be logged or inspected, rather than only looked at as in
+//! [dot::diff](super::dot::diff).
+use super::{Connection, Genome};
+use core::fmt::{self, Display, Formatter};
+use fxhash::FxHashMap;
+use serde::Serialize;
+
+/// The params of one connection held by both parent and child, before and after
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParamChange {
+    pub inno: usize,
+    pub before: Vec<f64>,
+    pub after: Vec<f64>,
+}
+
+/// Every gene that differs between a parent and its child, see [diff]. Connections are matched
+/// by innovation id.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(bound = "")]
+pub struct GenomeDiff<C: Connection> {
+    /// Connections that the child has and the parent doesn't
+    pub added: Vec<C>,
+    /// Connections that the parent has and the child doesn't
+    pub removed: Vec<C>,
+    /// Innovation ids of connections disabled in the parent and enabled in the child
+    pub enabled: Vec<usize>,
+    /// Innovation ids of connections enabled in the parent and disabled in the child
+    pub disabled: Vec<usize>,
+    /// Connections whose [params](Connection::params) changed
+    pub changed: Vec<ParamChange>,
+    pub nodes_added: usize,
+    pub nodes_removed: usize,
+    /// Whether any per-node gene ( activations, time constants, biases, sensory gains, or output
+    /// genes ) changed on a node that both parent and child have. New nodes' genes don't count,
+    /// as they're part of adding the node.
+    pub node_genes_changed: bool,
+}
+
+impl<C: Connection> GenomeDiff<C> {
+    /// Whether the child is genetically the same as its parent
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.enabled.is_empty()
+            && self.disabled.is_empty()
+            && self.changed.is_empty()
+            && self.nodes_added == 0
+            && self.nodes_removed == 0
+            && !self.node_genes_changed
+    }
+}
+
+impl<C: Connection> Display for GenomeDiff<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "connections +{} -{}, {} enabled, {} disabled, {} changed; nodes +{} -{}",
+            self.added.len(),
+            self.removed.len(),
+            self.enabled.len(),
+            self.disabled.len(),
+            self.changed.len(),
+            self.nodes_added,
+            self.nodes_removed,
+        )?;
+        if self.node_genes_changed {
+            write!(f, ", node genes changed")?;
+        }
+        Ok(())
+    }
+}
+
+/// How `child` differs from `parent`
+pub fn diff<C: Connection, G: Genome<C>>(parent: &G, child: &G) -> GenomeDiff<C> {
+    let inherited = parent
+        .connections()
+        .iter()
+        .map(|c| (c.inno(), c))
+        .collect::<FxHashMap<_, _>>();
+    let kept = child
+        .connections()
+        .iter()
+        .map(|c| (c.inno(), c))
+        .collect::<FxHashMap<_, _>>();
+
+    let mut diff = GenomeDiff {
+        added: Vec::new(),
+        removed: parent
+            .connections()
+            .iter()
+            .filter(|c| !kept.contains_key(&c.inno()))
+            .cloned()
+            .collect(),
+        enabled: Vec::new(),
+        disabled: Vec::new(),
+        changed: Vec::new(),
+        nodes_added: child.nodes().len().saturating_sub(parent.nodes().len()),
+        nodes_removed: parent.nodes().len().saturating_sub(child.nodes().len()),
+        node_genes_changed: false,
+    };
+
+    for c in child.connections() {
+        let Some(p) = inherited.get(&c.inno()) else {
+            diff.added.push(c.clone());
+            continue;
+        };
+        match (p.enabled(), c.enabled()) {
+            (false, true) => diff.enabled.push(c.inno()),
+            (true, false) => diff.disabled.push(c.inno()),
+            _ => {}
+        }
+        if !p.params().eq(c.params()) {
+            diff.changed.push(ParamChange {
+                inno: c.inno(),
+                before: p.params().collect(),
+                after: c.params().collect(),
+            });
+        }
+    }
+
+    diff.node_genes_changed = shared_changed(parent.node_activations(), child.node_activations())
+        || shared_changed(parent.node_time_constants(), child.node_time_constants())
+        || shared_changed(parent.node_biases(), child.node_biases())
+        || shared_changed(parent.sensory_gains(), child.sensory_gains())
+        || shared_changed(parent.output_affine(), child.output_affine());
+    diff
+}
+
+/// Whether genes held by both `parent` and `child` differ, or only one of them holds any
+fn shared_changed<T: PartialEq>(parent: Option<&[T]>, child: Option<&[T]>) -> bool {
+    match (parent, child) {
+        (Some(p), Some(c)) => p.iter().zip(c).any(|(p, c)| p != c),
+        (p, c) => p.is_some() != c.is_some(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        genome::{InnoGen, Recurrent, WConnection},
+        random::default_rng,
+    };
+
+    type C = WConnection;
+
+    #[test]
+    fn test_diff() {
+        let mut inno = InnoGen::new(0);
+        let (mut parent, _) = Recurrent::<C>::new(2, 1);
+        parent.push_connection(C::new(0, 2, &mut inno));
+        parent.push_connection(C::new(1, 2, &mut inno));
+        assert!(diff(&parent, &parent).is_empty());
+
+        let mut child = parent.clone();
+        child.connections_mut()[0].weight = 0.5;
+        child.connections_mut()[1].disable();
+        child
+            .bisect_connection(&mut default_rng(), &mut inno)
+            .unwrap();
+
+        let d = diff(&parent, &child);
+        assert!(!d.is_empty());
+        assert_eq!(d.added.len(), 2);
+        assert!(d.removed.is_empty());
+        assert!(d.enabled.is_empty());
+        assert_eq!(d.nodes_added, 1);
+        assert_eq!(d.nodes_removed, 0);
+        assert!(!d.node_genes_changed);
+        // bisection disables what it splits, which may already be disabled
+        assert!(d.disabled.contains(&parent.connections()[1].inno()));
+        assert_eq!(
+            d.changed,
+            vec![ParamChange {
+                inno: parent.connections()[0].inno(),
+                before: vec![parent.connections()[0].weight],
+                after: vec![0.5],
+            }]
+        );
+
+        let back = diff(&child, &parent);
+        assert_eq!(back.removed.len(), 2);
+        assert_eq!(back.nodes_removed, 1);
+        assert!(back.enabled.contains(&parent.connections()[1].inno()));
+        assert!(back.to_string().starts_with("connections +0 -2"));
+
+        // a bisection alone grows node genes, but changes none that the parent had
+        let parent = parent.with_node_biases();
+        let mut child = parent.clone();
+        child
+            .bisect_connection(&mut default_rng(), &mut inno)
+            .unwrap();
+        assert_eq!(child.node_biases().unwrap().len(), 5);
+        let d = diff(&parent, &child);
+        assert_eq!((d.added.len(), d.nodes_added), (2, 1));
+        assert!(!d.node_genes_changed);
+        child.node_biases_mut().unwrap()[0] = 1.;
+        assert!(diff(&parent, &child).node_genes_changed);
+    }
+}
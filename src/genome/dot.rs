@@ -0,0 +1,139 @@
+//! Render genomes as [graphviz](https://graphviz.org) DOT, for looking at evolved structure.
+//!
+//! [diff] renders the changes between a parent and its child, which makes it easy to see what
+//! a mutation or crossover operator actually did. Pipe the output through `dot -Tsvg` to view it.
+use super::{Connection, Genome, NodeKind};
+use std::{collections::HashMap, fmt::Write};
+
+fn node_attrs(kind: &NodeKind) -> &'static str {
+    match kind {
+        NodeKind::Sensory => "shape=box",
+        NodeKind::Action => "shape=doublecircle",
+        NodeKind::Static => "shape=diamond",
+        NodeKind::Internal => "shape=circle",
+    }
+}
+
+/// A DOT digraph of `genome`. Disabled connections are dashed.
+pub fn genome<C: Connection, G: Genome<C>>(genome: &G) -> String {
+    let mut dot = String::from("digraph genome {\n");
+    for (idx, kind) in genome.nodes().iter().enumerate() {
+        writeln!(dot, "  {idx} [{}];", node_attrs(kind)).unwrap();
+    }
+    for c in genome.connections() {
+        let (from, to) = c.path();
+        let style = if c.enabled() { "solid" } else { "dashed" };
+        writeln!(
+            dot,
+            "  {from} -> {to} [label=\"{:.3}\", style={style}];",
+            c.weight()
+        )
+        .unwrap();
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// A DOT digraph of `child`, marking how it differs from `parent`. Connections are matched by
+/// innovation id:
+///
+/// - nodes and connections that `parent` doesn't have are green
+/// - connections that `child` has disabled are gray and dashed
+/// - connections whose weight changed are orange, labelled with the old weight and the new one
+/// - connections that `child` lost are red and dotted
+pub fn diff<C: Connection, G: Genome<C>>(parent: &G, child: &G) -> String {
+    let inherited = parent
+        .connections()
+        .iter()
+        .map(|c| (c.inno(), c))
+        .collect::<HashMap<_, _>>();
+
+    let mut dot = String::from("digraph diff {\n");
+    for (idx, kind) in child.nodes().iter().enumerate() {
+        let added = if idx < parent.nodes().len() {
+            ""
+        } else {
+            ", color=green"
+        };
+        writeln!(dot, "  {idx} [{}{added}];", node_attrs(kind)).unwrap();
+    }
+
+    for c in child.connections() {
+        let (from, to) = c.path();
+        let (label, attrs) = match inherited.get(&c.inno()) {
+            None => (format!("{:.3}", c.weight()), "color=green"),
+            Some(p) if p.weight() != c.weight() => (
+                format!("{:.3} → {:.3}", p.weight(), c.weight()),
+                "color=orange",
+            ),
+            Some(_) => (format!("{:.3}", c.weight()), "color=black"),
+        };
+        let attrs = if c.enabled() {
+            attrs
+        } else {
+            "color=gray, style=dashed"
+        };
+        writeln!(dot, "  {from} -> {to} [label=\"{label}\", {attrs}];").unwrap();
+    }
+
+    let kept = child
+        .connections()
+        .iter()
+        .map(|c| c.inno())
+        .collect::<Vec<_>>();
+    for c in parent
+        .connections()
+        .iter()
+        .filter(|c| !kept.contains(&c.inno()))
+    {
+        let (from, to) = c.path();
+        writeln!(dot, "  {from} -> {to} [color=red, style=dotted];").unwrap();
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        genome::{InnoGen, Recurrent, WConnection},
+        random::default_rng,
+    };
+
+    type C = WConnection;
+
+    #[test]
+    fn test_diff() {
+        let mut inno = InnoGen::new(0);
+        let (mut parent, _) = Recurrent::<C>::new(2, 1);
+        parent.push_connection(C::new(0, 2, &mut inno));
+        parent.push_connection(C::new(1, 2, &mut inno));
+        parent.connections_mut()[1].weight = 0.5;
+
+        // only 0 -> 2 may be bisected while 1 -> 2 is frozen, so that it's the one that's split
+        let mut child = parent.clone();
+        child.connections_mut()[1].weight = -0.25;
+        child.connections_mut()[1].set_frozen(true);
+        child
+            .bisect_connection(&mut default_rng(), &mut inno)
+            .unwrap();
+        child.connections_mut()[1].set_frozen(false);
+        assert!(!child.connections()[0].enabled());
+
+        let dot = diff(&parent, &child);
+        assert!(dot.starts_with("digraph diff {\n"));
+        assert!(dot.contains("  4 [shape=circle, color=green];"));
+        assert!(dot.contains("color=gray, style=dashed"));
+        // the bisecting node, and the connections to and from it
+        assert_eq!(dot.matches("color=green];").count(), 3);
+        assert!(dot.contains("1 -> 2 [label=\"0.500 → -0.250\", color=orange];"));
+
+        let same = diff(&parent, &parent);
+        for color in ["green", "orange", "gray", "red"] {
+            assert!(!same.contains(color), "{same}");
+        }
+        assert!(genome(&child).contains("style=dashed"));
+    }
+}
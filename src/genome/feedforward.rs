@@ -0,0 +1,397 @@
+use super::{Affine, Connection, Genome, IoLabels, Module, NodeKind, Recurrence, Recurrent};
+use crate::random::CrossoverProbabilities;
+use core::{cmp::Ordering, error::Error, ops::Range};
+use rand::{seq::IteratorRandom, RngCore};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// A genome whose connections never form a cycle, for classic feedforward NEAT. New connections
+/// only ever run to nodes that don't already reach their source, and connections that crossover
+/// would close a cycle with are disabled, so that every node has a place in a
+/// [topological order](FeedForward::order). Its [recurrence](Genome::recurrence) is always
+/// [Recurrence::Acyclic].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(transparent, bound = "")]
+pub struct FeedForward<C: Connection> {
+    inner: Recurrent<C>,
+}
+
+impl<C: Connection> FeedForward<C> {
+    /// See [Recurrent::with_output_affine]
+    pub fn with_output_affine(self) -> Self {
+        Self {
+            inner: self.inner.with_output_affine(),
+        }
+    }
+
+    /// See [Recurrent::with_node_activations]
+    pub fn with_node_activations(self) -> Self {
+        Self {
+            inner: self.inner.with_node_activations(),
+        }
+    }
+
+    /// See [Recurrent::with_node_time_constants]
+    pub fn with_node_time_constants(self) -> Self {
+        Self {
+            inner: self.inner.with_node_time_constants(),
+        }
+    }
+
+    /// See [Recurrent::with_node_biases]
+    pub fn with_node_biases(self) -> Self {
+        Self {
+            inner: self.inner.with_node_biases(),
+        }
+    }
+
+    /// See [Recurrent::with_sensory_gains]
+    pub fn with_sensory_gains(self) -> Self {
+        Self {
+            inner: self.inner.with_sensory_gains(),
+        }
+    }
+
+    /// See [Recurrent::with_io_labels]
+    pub fn with_io_labels(self, labels: IoLabels) -> Self {
+        Self {
+            inner: self.inner.with_io_labels(labels),
+        }
+    }
+
+    /// See [Recurrent::with_mutation_sigma]
+    pub fn with_mutation_sigma(self, sigma: f64) -> Self {
+        Self {
+            inner: self.inner.with_mutation_sigma(sigma),
+        }
+    }
+
+    /// Every node, each after all of those who connect to it
+    pub fn order(&self) -> Vec<usize> {
+        let mut incoming = vec![0usize; self.nodes().len()];
+        for c in self.connections().iter().filter(|c| c.enabled()) {
+            incoming[c.to()] += 1;
+        }
+
+        let mut order = (0..self.nodes().len())
+            .filter(|node| incoming[*node] == 0)
+            .collect::<Vec<_>>();
+        let mut next = 0;
+        while let Some(&from) = order.get(next) {
+            for c in self
+                .connections()
+                .iter()
+                .filter(|c| c.enabled() && c.from() == from)
+            {
+                incoming[c.to()] -= 1;
+                if incoming[c.to()] == 0 {
+                    order.push(c.to());
+                }
+            }
+            next += 1;
+        }
+
+        debug_assert_eq!(
+            order.len(),
+            self.nodes().len(),
+            "cycle in a feedforward genome"
+        );
+        order
+    }
+}
+
+/// Find some open path in `genome` whose connection wouldn't close a cycle, that is whose
+/// destination doesn't already reach its source through any connection, enabled or not.
+pub fn open_acyclic_path<C: Connection, G: Genome<C>>(
+    genome: &G,
+    rng: &mut impl RngCore,
+) -> Option<(usize, usize)> {
+    let mut saturated = HashSet::new();
+    loop {
+        let (from, _) = genome
+            .nodes()
+            .iter()
+            .enumerate()
+            .filter(|(from, node)| !matches!(node, NodeKind::Action) && !saturated.contains(from))
+            .choose(rng)?;
+
+        let mut upstream = HashSet::from([from]);
+        let mut frontier = vec![from];
+        while let Some(to) = frontier.pop() {
+            for c in genome.connections().iter().filter(|c| c.to() == to) {
+                if upstream.insert(c.from()) {
+                    frontier.push(c.from());
+                }
+            }
+        }
+
+        let exclude = genome
+            .connections()
+            .iter()
+            .filter_map(|c| (c.from() == from).then_some(c.to()))
+            .collect::<HashSet<_>>();
+
+        if let Some((to, _)) = genome
+            .nodes()
+            .iter()
+            .enumerate()
+            .filter(|(to, node)| {
+                !matches!(node, NodeKind::Static | NodeKind::Sensory)
+                    && !exclude.contains(to)
+                    && !upstream.contains(to)
+            })
+            .choose(rng)
+        {
+            break Some((from, to));
+        }
+
+        saturated.insert(from);
+    }
+}
+
+impl<C: Connection> Genome<C> for FeedForward<C> {
+    fn new(sensory: usize, action: usize) -> (Self, usize) {
+        let (inner, inno_head) = Recurrent::new(sensory, action);
+        (Self { inner }, inno_head)
+    }
+
+    fn sensory(&self) -> Range<usize> {
+        self.inner.sensory()
+    }
+
+    fn action(&self) -> Range<usize> {
+        self.inner.action()
+    }
+
+    fn io_appended(&self) -> (&[usize], &[usize]) {
+        self.inner.io_appended()
+    }
+
+    fn nodes(&self) -> &[NodeKind] {
+        self.inner.nodes()
+    }
+
+    fn nodes_mut(&mut self) -> &mut [NodeKind] {
+        self.inner.nodes_mut()
+    }
+
+    fn push_node(&mut self, node: NodeKind) {
+        self.inner.push_node(node);
+    }
+
+    fn connections(&self) -> &[C] {
+        self.inner.connections()
+    }
+
+    fn connections_mut(&mut self) -> &mut [C] {
+        self.inner.connections_mut()
+    }
+
+    fn push_connection(&mut self, connection: C) {
+        self.inner.push_connection(connection);
+    }
+
+    fn retain_nodes(&mut self, keep: &[bool]) {
+        self.inner.retain_nodes(keep);
+    }
+
+    fn retain_connections(&mut self, keep: &[bool]) {
+        self.inner.retain_connections(keep);
+    }
+
+    fn set_io_boundary(&mut self, sensory: usize) -> Result<(), Box<dyn Error>> {
+        self.inner.set_io_boundary(sensory)
+    }
+
+    fn append_io(&mut self, sensory: usize, action: usize) -> Result<(), Box<dyn Error>> {
+        self.inner.append_io(sensory, action)
+    }
+
+    fn mutation_sigma(&self) -> Option<f64> {
+        self.inner.mutation_sigma()
+    }
+
+    fn mutation_sigma_mut(&mut self) -> Option<&mut f64> {
+        self.inner.mutation_sigma_mut()
+    }
+
+    fn io_labels(&self) -> Option<&IoLabels> {
+        self.inner.io_labels()
+    }
+
+    fn io_labels_mut(&mut self) -> Option<&mut IoLabels> {
+        self.inner.io_labels_mut()
+    }
+
+    fn modules(&self) -> &[Module] {
+        self.inner.modules()
+    }
+
+    fn modules_mut(&mut self) -> Option<&mut Vec<Module>> {
+        self.inner.modules_mut()
+    }
+
+    fn output_affine(&self) -> Option<&[Affine]> {
+        self.inner.output_affine()
+    }
+
+    fn output_affine_mut(&mut self) -> Option<&mut [Affine]> {
+        self.inner.output_affine_mut()
+    }
+
+    fn node_activations(&self) -> Option<&[usize]> {
+        self.inner.node_activations()
+    }
+
+    fn node_activations_mut(&mut self) -> Option<&mut [usize]> {
+        self.inner.node_activations_mut()
+    }
+
+    fn node_time_constants(&self) -> Option<&[f64]> {
+        self.inner.node_time_constants()
+    }
+
+    fn node_time_constants_mut(&mut self) -> Option<&mut [f64]> {
+        self.inner.node_time_constants_mut()
+    }
+
+    fn node_biases(&self) -> Option<&[f64]> {
+        self.inner.node_biases()
+    }
+
+    fn node_biases_mut(&mut self) -> Option<&mut [f64]> {
+        self.inner.node_biases_mut()
+    }
+
+    fn sensory_gains(&self) -> Option<&[f64]> {
+        self.inner.sensory_gains()
+    }
+
+    fn sensory_gains_mut(&mut self) -> Option<&mut [f64]> {
+        self.inner.sensory_gains_mut()
+    }
+
+    fn recurrence(&self) -> Recurrence {
+        Recurrence::Acyclic
+    }
+
+    fn open_path(&self, rng: &mut impl RngCore) -> Option<(usize, usize)> {
+        open_acyclic_path(self, rng)
+    }
+
+    fn reproduce_with(&self, other: &Self, self_fit: Ordering, rng: &mut impl RngCore) -> Self {
+        self.reproduce_configured(other, self_fit, C::CROSSOVER_PROBABILITIES, rng)
+    }
+
+    fn reproduce_configured(
+        &self,
+        other: &Self,
+        self_fit: Ordering,
+        probabilities: CrossoverProbabilities,
+        rng: &mut impl RngCore,
+    ) -> Self {
+        self.reproduce_buffered(other, self_fit, probabilities, rng, &mut Vec::new())
+    }
+
+    fn reproduce_buffered(
+        &self,
+        other: &Self,
+        self_fit: Ordering,
+        probabilities: CrossoverProbabilities,
+        rng: &mut impl RngCore,
+        scratch: &mut Vec<C>,
+    ) -> Self {
+        let mut child = Self {
+            inner: self.inner.reproduce_buffered(
+                &other.inner,
+                self_fit,
+                probabilities,
+                rng,
+                scratch,
+            ),
+        };
+        for (idx, recurrent) in child.recurrent().into_iter().enumerate() {
+            if recurrent {
+                child.connections_mut()[idx].disable();
+            }
+        }
+        child
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        genome::{InnoGen, WConnection},
+        random::{default_rng, percent},
+    };
+
+    type C = WConnection;
+    type G = FeedForward<C>;
+
+    fn assert_order(genome: &G) {
+        let order = genome.order();
+        let mut rank = vec![0; order.len()];
+        for (position, node) in order.iter().enumerate() {
+            rank[*node] = position;
+        }
+        for c in genome.connections().iter().filter(|c| c.enabled()) {
+            assert!(rank[c.from()] < rank[c.to()], "{:?} out of order", c.path());
+        }
+    }
+
+    #[test]
+    fn test_feedforward_acyclic() {
+        let mut rng = default_rng();
+        let (genome, inno_head) = G::new(2, 2);
+        let mut inno = InnoGen::new(inno_head);
+        let mut pop = vec![genome; 8];
+        for _ in 0..30 {
+            for genome in pop.iter_mut() {
+                // small genomes may well be saturated, and mutate otherwise when they are
+                let grown = rng.next_u64() < percent(50)
+                    && genome.new_connection(&mut rng, &mut inno).is_ok();
+                if !grown {
+                    genome.mutate_with(&mut rng, &mut inno, [0, percent(40), percent(60), 0, 0])
+                }
+                assert!(genome.recurrent().iter().all(|recurrent| !recurrent));
+                assert_order(genome);
+            }
+
+            let children = pop
+                .iter()
+                .zip(pop.iter().rev())
+                .map(|(l, r)| l.reproduce_with(r, Ordering::Greater, &mut rng))
+                .collect::<Vec<_>>();
+            for child in children.iter() {
+                assert!(child.recurrent().iter().all(|recurrent| !recurrent));
+                assert_order(child);
+            }
+            pop = children;
+        }
+    }
+
+    #[test]
+    fn test_feedforward_open_path() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = G::new(1, 1);
+        genome.push_node(NodeKind::Internal);
+        genome.push_node(NodeKind::Internal);
+        // 0 -> 3 -> 4 -> 1, so the only open paths that are acyclic are 0 -> 4 and 3 -> 1,
+        // besides those from the bias node
+        for path in [(0, 3), (3, 4), (4, 1), (0, 1)] {
+            genome.push_connection(C::new(path.0, path.1, &mut inno));
+        }
+
+        let mut rng = default_rng();
+        for _ in 0..50 {
+            let (from, to) = genome.open_path(&mut rng).unwrap();
+            assert!(
+                from == 2 || [(0, 4), (3, 1)].contains(&(from, to)),
+                "{:?}",
+                (from, to)
+            );
+        }
+    }
+}
@@ -8,17 +8,41 @@
 //! that describes some discrete behavior. In aggregate, connections may describe arbitrarially
 //! complex behavior. Through evolution, that complex behavior is refined towards increasing
 //! some one-dimensional fitness.
+pub mod affine;
+pub mod builder;
 pub mod connection;
+pub mod cppn;
+pub mod diff;
+pub mod dot;
+pub mod feedforward;
 pub mod recurrent;
 
-pub use connection::WConnection;
+pub use affine::Affine;
+pub use builder::GenomeBuilder;
+pub use connection::{HConnection, MConnection, Modulatory, Plastic, WConnection};
+pub use cppn::Cppn;
+pub use diff::{diff, GenomeDiff};
+pub use feedforward::FeedForward;
 pub use recurrent::Recurrent;
 
-use crate::random::{percent, ConnectionEvent, EventKind, GenomeEvent};
-use core::{cmp::Ordering, error::Error, fmt::Debug, hash::Hash, ops::Range};
+use crate::network::{Footprint, Network};
+use crate::random::{
+    percent, ConnectionEvent, CrossoverProbabilities, EventKind, GenomeEvent, GenomeProbabilities,
+};
+use core::{
+    cmp::Ordering,
+    error::Error,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    mem::discriminant,
+    ops::Range,
+};
 use fxhash::FxHashMap;
-use rand::{Rng, RngCore};
+use rand::{distr::Uniform, seq::IteratorRandom, Rng, RngCore};
+use rand_distr::{Cauchy, StandardNormal};
 use serde::{Deserialize, Serialize};
+use std::hash::DefaultHasher;
+#[cfg(feature = "fs")]
 use std::{fs, path::Path};
 
 /// InnoGen is a structure who's job is to associate an innovation ID uniquely with some
@@ -26,9 +50,17 @@ use std::{fs, path::Path};
 /// connection through some path formed in a single generation should have the same innovation
 /// id as every other connection through the same path formed that generation so that they can
 /// later be used in crossover reproduction.
+///
+/// An InnoGen kept alive across many generations will grow with every path ever seen, so one
+/// may be given a capacity with [with_capacity](InnoGen::with_capacity). Once full, the least
+/// recently used half of its paths are forgotten, and will be given a fresh id if seen again.
 pub struct InnoGen {
     pub head: usize,
-    seen: FxHashMap<(usize, usize), usize>,
+    /// path -> (innovation id, tick of last use)
+    seen: FxHashMap<(usize, usize), (usize, usize)>,
+    tick: usize,
+    capacity: Option<usize>,
+    evicted: usize,
 }
 
 impl InnoGen {
@@ -36,19 +68,496 @@ impl InnoGen {
         Self {
             head,
             seen: FxHashMap::default(),
+            tick: 0,
+            capacity: None,
+            evicted: 0,
         }
     }
 
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be non-zero");
+        self.capacity = Some(capacity);
+        self
+    }
+
     pub fn path(&mut self, v: (usize, usize)) -> usize {
-        match self.seen.get(&v) {
-            Some(n) => *n,
-            None => {
-                let n = self.head;
-                self.head += 1;
-                self.seen.insert(v, n);
-                n
+        self.tick += 1;
+        if let Some((n, used)) = self.seen.get_mut(&v) {
+            *used = self.tick;
+            return *n;
+        }
+
+        if self.capacity.is_some_and(|cap| self.seen.len() >= cap) {
+            self.compact();
+        }
+
+        let n = self.head;
+        self.head += 1;
+        self.seen.insert(v, (n, self.tick));
+        n
+    }
+
+    /// Forget the least recently used half of known paths
+    pub fn compact(&mut self) {
+        let mut ticks = self.seen.values().map(|(_, t)| *t).collect::<Vec<_>>();
+        let mid = ticks.len() / 2;
+        if mid == 0 {
+            return;
+        }
+
+        let (_, cutoff, _) = ticks.select_nth_unstable(mid);
+        let cutoff = *cutoff;
+        let before = self.seen.len();
+        self.seen.retain(|_, (_, t)| *t >= cutoff);
+        self.evicted += before - self.seen.len();
+    }
+
+    /// Number of paths currently remembered
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Number of paths forgotten through compaction over this InnoGen's lifetime
+    pub fn evicted(&self) -> usize {
+        self.evicted
+    }
+}
+
+/// Hard limits on how large a genome may grow through mutation. A structural mutation that would
+/// take a genome past either limit is redirected to a parameter mutation instead, so that genomes
+/// at their budget keep refining what they have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Constraints {
+    pub max_connections: usize,
+    pub max_nodes: usize,
+}
+
+impl Constraints {
+    pub const NONE: Self = Self {
+        max_connections: usize::MAX,
+        max_nodes: usize::MAX,
+    };
+
+    /// These constraints, with genomes holding at most `max` nodes
+    pub fn with_max_nodes(self, max: usize) -> Self {
+        Self {
+            max_nodes: max,
+            ..self
+        }
+    }
+
+    /// These constraints, with genomes holding at most `max` connections, enabled or not
+    pub fn with_max_connections(self, max: usize) -> Self {
+        Self {
+            max_connections: max,
+            ..self
+        }
+    }
+
+    /// Whether a genome may grow to hold `connections` connections and `nodes` nodes
+    pub fn allows(&self, connections: usize, nodes: usize) -> bool {
+        connections <= self.max_connections && nodes <= self.max_nodes
+    }
+}
+
+impl Default for Constraints {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// Knobs for how a genome mutates that may be set at runtime, see
+/// [mutate_configured](Genome::mutate_configured), and
+/// [Reproduction](crate::reproduce::Reproduction) for setting them over a whole evolution run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MutationConfig {
+    pub constraints: Constraints,
+    /// Connection weights are clamped to within `[weight_min, weight_max]` after every mutation,
+    /// so that they don't drift without bound under repeated perturbation, and so that new and
+    /// bisected connections start out within them.
+    pub weight_min: f64,
+    pub weight_max: f64,
+    /// How connection params are mutated, in place of each connection's own
+    /// [MUTATION_PARAMS](Connection::MUTATION_PARAMS)
+    pub params: Option<MutationParams>,
+    /// The weight of new connections, in place of each connection's own
+    /// [WEIGHT_INIT](Connection::WEIGHT_INIT)
+    pub weight_init: Option<WeightInit>,
+}
+
+impl MutationConfig {
+    pub const NONE: Self = Self {
+        constraints: Constraints::NONE,
+        weight_min: f64::NEG_INFINITY,
+        weight_max: f64::INFINITY,
+        params: None,
+        weight_init: None,
+    };
+
+    /// This config, with genome growth bounded by `constraints`
+    pub fn with_constraints(self, constraints: Constraints) -> Self {
+        Self {
+            constraints,
+            ..self
+        }
+    }
+
+    /// This config, with connection params mutated by `params`
+    pub fn with_params(self, params: MutationParams) -> Self {
+        Self {
+            params: Some(params),
+            ..self
+        }
+    }
+
+    /// This config, with new connections' weights drawn from `init`
+    pub fn with_weight_init(self, init: WeightInit) -> Self {
+        Self {
+            weight_init: Some(init),
+            ..self
+        }
+    }
+
+    /// This config, with weights bounded to `[min, max]`
+    pub fn with_weight_bounds(self, min: f64, max: f64) -> Self {
+        assert!(min <= max, "weight bounds [{min}, {max}] are empty");
+        Self {
+            weight_min: min,
+            weight_max: max,
+            ..self
+        }
+    }
+
+    /// Whether weights are bounded at all
+    pub fn bounded(&self) -> bool {
+        self.weight_min.is_finite() || self.weight_max.is_finite()
+    }
+}
+
+impl Default for MutationConfig {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// The distribution that connection params are perturbed by, see [MutationParams]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Perturbation {
+    /// Uniform over `[-3, 3]`
+    #[default]
+    Uniform,
+    /// The standard normal
+    Gaussian,
+    /// The standard Cauchy, whose heavy tails make for the occasional large jump
+    Cauchy,
+}
+
+/// How a single connection param is mutated, see
+/// [mutate_param_with](Connection::mutate_param_with). A param is either replaced outright with
+/// a draw from `distribution`, with `replace_probability`, or perturbed by that draw scaled by
+/// `sigma`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MutationParams {
+    pub sigma: f64,
+    pub replace_probability: u64,
+    pub distribution: Perturbation,
+}
+
+impl MutationParams {
+    pub const DEFAULT: Self = Self {
+        sigma: 0.05,
+        replace_probability: percent(10),
+        distribution: Perturbation::Uniform,
+    };
+
+    /// An unscaled draw from [distribution](MutationParams::distribution)
+    pub fn sample(&self, rng: &mut impl RngCore) -> f64 {
+        match self.distribution {
+            Perturbation::Uniform => rng.sample(
+                Uniform::new_inclusive(-3., 3.).expect("distribution of -3. ..= 3. failed"),
+            ),
+            Perturbation::Gaussian => rng.sample(StandardNormal),
+            Perturbation::Cauchy => {
+                rng.sample(Cauchy::new(0., 1.).expect("standard cauchy failed"))
+            }
+        }
+    }
+
+    /// `param`, either replaced or perturbed
+    pub fn apply(&self, param: f64, rng: &mut impl RngCore) -> f64 {
+        let replace = rng.next_u64() < self.replace_probability;
+        let v = self.sample(rng);
+        if replace {
+            v
+        } else {
+            param + self.sigma * v
+        }
+    }
+}
+
+impl Default for MutationParams {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// The weight that new connections start out with, see
+/// [new_connection_with](Genome::new_connection_with)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WeightInit {
+    /// Always the same weight
+    Constant(f64),
+    /// Uniform over `[min, max]`
+    Uniform { min: f64, max: f64 },
+    /// Normal with `mean` and standard deviation `sigma`
+    Gaussian { mean: f64, sigma: f64 },
+}
+
+impl WeightInit {
+    /// A weight drawn from this distribution. Constant weights draw nothing from `rng`.
+    pub fn sample(&self, rng: &mut impl RngCore) -> f64 {
+        match *self {
+            Self::Constant(weight) => weight,
+            Self::Uniform { min, max } => rng.sample(
+                Uniform::new_inclusive(min, max)
+                    .unwrap_or_else(|_| panic!("distribution of {min} ..= {max} failed")),
+            ),
+            Self::Gaussian { mean, sigma } => mean + sigma * rng.sample::<f64, _>(StandardNormal),
+        }
+    }
+}
+
+impl Default for WeightInit {
+    fn default() -> Self {
+        Self::Constant(1.)
+    }
+}
+
+/// How complex a genome's structure is, see [complexity](Genome::complexity)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Complexity {
+    pub nodes: usize,
+    /// Connections that are enabled
+    pub connections: usize,
+    /// Internal nodes, those that are neither sensory, action, nor static
+    pub hidden: usize,
+    /// The most enabled connections along any path without a cycle, 0 for a genome without any
+    pub depth: usize,
+}
+
+/// What [simplify](Genome::simplify) removed from a genome
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Removed {
+    /// Innovation ids of the disabled connections removed
+    pub connections: Vec<usize>,
+    /// Internal nodes removed, as they were numbered before
+    pub nodes: Vec<NodeId>,
+}
+
+/// A named group of internal nodes, for modular genomes. A module may be duplicated as a whole by
+/// [duplicate_module](Genome::duplicate_module), and is inherited by name through crossover.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Module {
+    pub name: String,
+    pub nodes: Vec<NodeId>,
+}
+
+/// The genes that a genome holds for its nodes rather than its connections, as held by a
+/// [SpecieRepr](crate::population::SpecieRepr) so that speciation may compare them, see
+/// [node_delta](crate::crossover::node_delta). Genes that the genome doesn't evolve are `None`,
+/// and aside from activations, genes that only one of two genomes evolves aren't compared.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeGenes {
+    /// How many nodes the genome has
+    pub nodes: usize,
+    /// [Activation id](crate::activate::PALETTE) of every node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activations: Option<Vec<usize>>,
+    /// Time constant of every node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_constants: Option<Vec<f64>>,
+    /// What every node holds before activation when nothing flows into it, see
+    /// [biases](Genome::biases)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub biases: Option<Vec<f64>>,
+    /// Gain of every sensory node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sensory_gains: Option<Vec<f64>>,
+    /// Name of every [Module]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modules: Vec<String>,
+}
+
+/// Mean difference between the values shared by `l` and `r`, or 0 if either is missing
+fn mean_diff(l: Option<&[f64]>, r: Option<&[f64]>) -> f64 {
+    let (Some(l), Some(r)) = (l, r) else {
+        return 0.;
+    };
+    let shared = usize::min(l.len(), r.len());
+    if shared == 0 {
+        return 0.;
+    }
+    l.iter()
+        .zip(r.iter())
+        .map(|(l, r)| (l - r).abs())
+        .sum::<f64>()
+        / shared as f64
+}
+
+impl NodeGenes {
+    /// The node genes of `genome`
+    pub fn of<C: Connection, G: Genome<C>>(genome: &G) -> Self {
+        Self {
+            nodes: genome.nodes().len(),
+            activations: genome.node_activations().map(<[usize]>::to_vec),
+            time_constants: genome.node_time_constants().map(<[f64]>::to_vec),
+            biases: genome.node_biases().map(|_| genome.biases()),
+            sensory_gains: genome.sensory_gains().map(<[f64]>::to_vec),
+            modules: genome.modules().iter().map(|m| m.name.clone()).collect(),
+        }
+    }
+
+    /// Whether the genome evolves none of these genes
+    pub fn is_empty(&self) -> bool {
+        self.activations.is_none()
+            && self.time_constants.is_none()
+            && self.biases.is_none()
+            && self.sensory_gains.is_none()
+            && self.modules.is_empty()
+    }
+
+    /// Fraction of the nodes shared with `other` whose activations differ, like
+    /// [activation_diff](Genome::activation_diff)
+    pub fn activation_diff(&self, other: &Self) -> f64 {
+        let (l, r) = (self.activations.as_deref(), other.activations.as_deref());
+        let shared = usize::min(self.nodes, other.nodes);
+        if shared == 0 || (l.is_none() && r.is_none()) {
+            return 0.;
+        }
+
+        let id = |activations: Option<&[usize]>, idx: usize| activations.map_or(0, |a| a[idx]);
+        (0..shared).filter(|idx| id(l, *idx) != id(r, *idx)).count() as f64 / shared as f64
+    }
+
+    /// Mean difference between the time constants of the nodes shared with `other`
+    pub fn time_constant_diff(&self, other: &Self) -> f64 {
+        mean_diff(
+            self.time_constants.as_deref(),
+            other.time_constants.as_deref(),
+        )
+    }
+
+    /// Mean difference between the biases of the nodes shared with `other`
+    pub fn bias_diff(&self, other: &Self) -> f64 {
+        mean_diff(self.biases.as_deref(), other.biases.as_deref())
+    }
+
+    /// Mean difference between the gains of the sensory nodes shared with `other`
+    pub fn sensory_gain_diff(&self, other: &Self) -> f64 {
+        mean_diff(
+            self.sensory_gains.as_deref(),
+            other.sensory_gains.as_deref(),
+        )
+    }
+
+    /// Share of modules, by name, that only one of this and `other` has, like
+    /// [module_diff](Genome::module_diff)
+    pub fn module_diff(&self, other: &Self) -> f64 {
+        let (l, r) = (&self.modules, &other.modules);
+        let shared = l.iter().filter(|name| r.contains(name)).count();
+        let union = l.len() + r.len() - shared;
+        if union == 0 {
+            return 0.;
+        }
+        (union - shared) as f64 / union as f64
+    }
+
+    /// Approximate bytes held by these genes
+    pub fn memory(&self) -> usize {
+        size_of::<Self>()
+            + self.activations.as_deref().map_or(0, size_of_val)
+            + [&self.time_constants, &self.biases, &self.sensory_gains]
+                .iter()
+                .map(|genes| genes.as_deref().map_or(0, size_of_val))
+                .sum::<usize>()
+            + self
+                .modules
+                .iter()
+                .map(|name| size_of::<String>() + name.len())
+                .sum::<usize>()
+    }
+}
+
+impl Hash for NodeGenes {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.nodes.hash(state);
+        self.activations.hash(state);
+        for genes in [&self.time_constants, &self.biases, &self.sensory_gains] {
+            genes.as_ref().map(Vec::len).hash(state);
+            for gene in genes.iter().flatten() {
+                state.write_u64(gene.to_bits());
             }
         }
+        self.modules.hash(state);
+    }
+}
+
+/// Names of a genome's sensory and action nodes, each in order, so that saved genomes and the
+/// networks decoded from them may be read by name. See [io_labels](Genome::io_labels).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IoLabels {
+    pub sensory: Vec<String>,
+    pub action: Vec<String>,
+}
+
+impl IoLabels {
+    pub fn new<S: Into<String>>(
+        sensory: impl IntoIterator<Item = S>,
+        action: impl IntoIterator<Item = S>,
+    ) -> Self {
+        Self {
+            sensory: sensory.into_iter().map(Into::into).collect(),
+            action: action.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Position in the input of the sensory node labelled `name`
+    pub fn sensory_index(&self, name: &str) -> Option<usize> {
+        self.sensory.iter().position(|label| label == name)
+    }
+
+    /// Position in the output of the action node labelled `name`
+    pub fn action_index(&self, name: &str) -> Option<usize> {
+        self.action.iter().position(|label| label == name)
+    }
+}
+
+/// Which paths [open_path](Genome::open_path) may propose for new connections. Set on a genome,
+/// it's inherited by its offspring, so that setting it on an initial population sets it for a
+/// whole evolution.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Recurrence {
+    /// Any path, including from a node to itself and back to a node that reaches the source
+    #[default]
+    Any,
+    /// Any path but from a node to itself
+    NoSelfLoops,
+    /// Only paths that wouldn't close a cycle. Connections that crossover would close a cycle
+    /// with are disabled.
+    Acyclic,
+}
+
+impl Recurrence {
+    pub fn is_any(&self) -> bool {
+        *self == Self::Any
     }
 }
 
@@ -62,9 +571,42 @@ pub enum NodeKind {
     Static,
 }
 
+/// Position of a node in its genome's [nodes](Genome::nodes), and so in its network
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct NodeId(pub usize);
+
+/// Position of a connection in its genome's [connections](Genome::connections). This is not its
+/// innovation id, and changes as connections are removed or reordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ConnId(pub usize);
+
+impl From<usize> for NodeId {
+    fn from(idx: usize) -> Self {
+        Self(idx)
+    }
+}
+
+impl From<NodeId> for usize {
+    fn from(id: NodeId) -> Self {
+        id.0
+    }
+}
+
+impl From<usize> for ConnId {
+    fn from(idx: usize) -> Self {
+        Self(idx)
+    }
+}
+
+impl From<ConnId> for usize {
+    fn from(id: ConnId) -> Self {
+        id.0
+    }
+}
+
 /// A connection between 2 points. Connections may be arbitrarially parameterized, and those
-/// parameters mutated inside [mutate_param](Connection::mutate_param). For those params to
-/// actually be _used_, a connection should expose them with a trait, and a
+/// parameters mutated inside [mutate_param](Connection::mutate_param). For those params to actually
+/// be _used_, a connection should expose them with a trait, and a
 /// [Network](crate::network::Network) implementer should know about them. Any connection must
 /// have a path, weight, and innovation_id ( which should be supplied from InnoGen ).
 pub trait Connection:
@@ -73,6 +615,15 @@ pub trait Connection:
     const PROBABILITIES: [u64; ConnectionEvent::COUNT] = [percent(1), percent(99)];
     const PARAM_REPLACE_PROBABILITY: u64 = percent(10);
     const PARAM_PERTURB_FAC: f64 = 0.05;
+    /// How params are mutated when not given [MutationParams] at runtime
+    const MUTATION_PARAMS: MutationParams = MutationParams {
+        sigma: Self::PARAM_PERTURB_FAC,
+        replace_probability: Self::PARAM_REPLACE_PROBABILITY,
+        distribution: Perturbation::Uniform,
+    };
+    /// Weight of connections formed by [new_connection](Genome::new_connection) when not given a
+    /// [WeightInit] at runtime
+    const WEIGHT_INIT: WeightInit = WeightInit::Constant(1.);
 
     const EXCESS_COEFFICIENT: f64;
     const DISJOINT_COEFFICIENT: f64;
@@ -80,12 +631,22 @@ pub trait Connection:
 
     const PROBABILITY_PICK_RL: u64 = percent(50);
     const PROBABILITY_KEEP_DISABLED: u64 = percent(75);
+    /// How genes matched in crossover are inherited, either picked wholesale from one parent or
+    /// with their params blended as the average of both parents'
+    const CROSSOVER_PROBABILITIES: CrossoverProbabilities = [percent(100), percent(0)];
 
     fn new(from: usize, to: usize, inno: &mut InnoGen) -> Self;
 
     /// gene innovation id
     fn inno(&self) -> usize;
 
+    /// relabel this connection's innovation id, such as when moving it between runs whose ids
+    /// don't agree. Connections who don't override this can't be relabelled, and panic.
+    fn set_inno(&mut self, inno: usize) {
+        let _ = inno;
+        panic!("{} can't be relabelled", core::any::type_name::<Self>())
+    }
+
     /// whether or not this connection is active, and therefore affects its genomes behavior
     fn enabled(&self) -> bool;
 
@@ -95,9 +656,25 @@ pub trait Connection:
     /// unconditionally disable this connection
     fn disable(&mut self);
 
+    /// whether this connection is locked in, such as part of a hand-designed circuit. Frozen
+    /// connections are never mutated, bisected, or disabled by mutation, though they're still
+    /// inherited through crossover like any other.
+    fn frozen(&self) -> bool;
+
+    /// freeze or thaw this connection, see [frozen](Connection::frozen)
+    fn set_frozen(&mut self, frozen: bool);
+
     /// (from, to) path of this connection
     fn path(&self) -> (usize, usize);
 
+    /// move this connection onto some other path, such as when the nodes it joins are
+    /// renumbered. Its innovation id is untouched, so it no longer lines up with genes of the same
+    /// id in other genomes. Connections who don't override this can't be moved, and panic.
+    fn set_path(&mut self, path: (usize, usize)) {
+        let _ = path;
+        panic!("{} can't be moved", core::any::type_name::<Self>())
+    }
+
     /// path source
     fn from(&self) -> usize {
         self.path().0
@@ -108,8 +685,55 @@ pub trait Connection:
         self.path().1
     }
 
+    /// path source, as a node id
+    fn source(&self) -> NodeId {
+        NodeId(self.from())
+    }
+
+    /// path destination, as a node id
+    fn target(&self) -> NodeId {
+        NodeId(self.to())
+    }
+
     fn weight(&self) -> f64;
 
+    /// overwrite this connection's weight, such as when [optimizing](crate::optimize::Es) it.
+    /// Connections who don't override this can't be given weights, and panic.
+    fn set_weight(&mut self, weight: f64) {
+        let _ = weight;
+        panic!("{} can't be given a weight", core::any::type_name::<Self>())
+    }
+
+    /// number of evolvable params held by this connection, see [params](Connection::params). By
+    /// default just one, its weight
+    const PARAMS: usize = 1;
+
+    /// every evolvable param of this connection ( for example, weight ), always in the same order.
+    /// By default just its [weight](Connection::weight)
+    fn params(&self) -> impl Iterator<Item = f64> {
+        core::iter::once(self.weight())
+    }
+
+    /// overwrite every evolvable param, laid out as in [params](Connection::params). By default
+    /// the [weight](Connection::set_weight) alone
+    fn set_params(&mut self, params: &[f64]) {
+        assert_eq!(
+            params.len(),
+            Self::PARAMS,
+            "expected {} params",
+            Self::PARAMS
+        );
+        self.set_weight(params[0]);
+    }
+
+    /// what sort of connection this is, for connections who may act on their target in more
+    /// than one way ( for example, a [modulatory](connection::Modulatory) one ). Connections of
+    /// differing kind decode differently, even with equal [params](Connection::params).
+    /// Connections who only act one way are all of kind 0.
+    fn kind(&self) -> u8 {
+        0
+    }
+
     /// difference of connection parameters ( for example, weight )
     /// between this and another connection with the same innovation id
     fn param_diff(&self, other: &Self) -> f64;
@@ -117,16 +741,28 @@ pub trait Connection:
     /// possibly mutate a single param
     fn mutate_param(&mut self, rng: &mut impl RngCore);
 
-    /// mutate a connection
-    fn mutate(&mut self, rng: &mut impl RngCore) {
+    /// possibly mutate a single param, as described by `params`. Connections who don't override
+    /// this ignore `params`, and mutate as [mutate_param](Connection::mutate_param) does
+    fn mutate_param_with(&mut self, rng: &mut impl RngCore, params: &MutationParams) {
+        let _ = params;
+        self.mutate_param(rng)
+    }
+
+    /// mutate a connection, its params as described by `params`
+    fn mutate_with(&mut self, rng: &mut impl RngCore, params: &MutationParams) {
         if let Some(evt) = ConnectionEvent::pick(rng, Self::PROBABILITIES) {
             match evt {
                 ConnectionEvent::Disable => self.disable(),
-                ConnectionEvent::MutateParam => self.mutate_param(rng),
+                ConnectionEvent::MutateParam => self.mutate_param_with(rng, params),
             }
         }
     }
 
+    /// mutate a connection
+    fn mutate(&mut self, rng: &mut impl RngCore) {
+        self.mutate_with(rng, &Self::MUTATION_PARAMS)
+    }
+
     /// bisect this connection; disabling it, and returning the (upper, lower) bisection pair
     fn bisect(&mut self, center: usize, inno: &mut InnoGen) -> (Self, Self);
 }
@@ -139,15 +775,105 @@ pub trait Genome<C: Connection>: Serialize + for<'de> Deserialize<'de> + Clone {
     const MUTATE_NODE_PROBABILITY: u64 = percent(20);
     const MUTATE_CONNECTION_PROBABILITY: u64 = percent(20);
     const PROBABILITIES: [u64; GenomeEvent::COUNT] =
-        [percent(5), percent(15), percent(80), percent(0)];
+        [percent(5), percent(15), percent(70), percent(5), percent(5)];
+    /// How this genome mutates when not given a [MutationConfig] at runtime, such as through
+    /// [Reproduction](crate::reproduce::Reproduction)
+    const MUTATION: MutationConfig = MutationConfig::NONE;
+    /// Time constant of nodes of genomes who don't evolve them, and that new nodes of those who
+    /// do start out with. This is a rate, as in [Continuous::τ](crate::network::Continuous::τ),
+    /// scaling how quickly a node's state moves.
+    const TIME_CONSTANT: f64 = 0.1;
+    /// Learning rate of a [self-adaptive](Genome::mutation_sigma) sigma, which is scaled by
+    /// `exp(SIGMA_LEARNING_RATE * N(0, 1))` each time that it's used
+    const SIGMA_LEARNING_RATE: f64 = 0.2;
+    /// Least that a self-adaptive sigma may shrink to, so that it can't collapse to nothing
+    const SIGMA_MIN: f64 = 1e-4;
+    /// Weight of differing node time constants in [genome_delta](crate::crossover::genome_delta)
+    const TIME_CONSTANT_COEFFICIENT: f64 = 1.;
+    /// Weight of differing node biases in [genome_delta](crate::crossover::genome_delta)
+    const BIAS_COEFFICIENT: f64 = 1.;
+    /// Weight of mismatched node activations in [genome_delta](crate::crossover::genome_delta)
+    const ACTIVATION_COEFFICIENT: f64 = 1.;
+    /// Weight of modules held by only one genome in [genome_delta](crate::crossover::genome_delta)
+    const MODULE_COEFFICIENT: f64 = 1.;
+    /// Probability that a bisection mutation instead [duplicates](Genome::duplicate_module) some
+    /// module, for genomes that have any
+    const DUPLICATE_MODULE_PROBABILITY: u64 = percent(10);
+    /// Probability that a bisection mutation instead [duplicates](Genome::duplicate_node) some
+    /// internal node. Off by default.
+    const DUPLICATE_NODE_PROBABILITY: u64 = percent(0);
+    /// Step that params are rounded to before they're hashed into a
+    /// [fingerprint](Genome::fingerprint)
+    const FINGERPRINT_RESOLUTION: f64 = 1e-9;
+    /// [Activation ids](crate::activate::id) that nodes may evolve to use, if this genome evolves
+    /// them. New nodes start out with the first.
+    const ACTIVATIONS: &'static [usize] = &crate::activate::id::ALL;
 
     /// A new genome of this type, with a known input and output size.
     fn new(sensory: usize, action: usize) -> (Self, usize);
 
+    /// A genome of the network described by the square matrix `weights`, indexed as
+    /// `weights[from][to]`, such as one imported from another tool or a paper. Nodes are laid out
+    /// as by [new](Genome::new), with any rows past the bias being internal nodes, and every
+    /// nonzero weight becomes a connection with an innovation id from `inno`, so that genomes
+    /// imported with the same `inno` agree on them. Panics if `weights` isn't square, has too few
+    /// rows for `sensory` and `action`, has weights into a sensory or bias node, or has a cycle
+    /// while this genome is [Recurrence::Acyclic].
+    fn from_dense(weights: &[Vec<f64>], sensory: usize, action: usize, inno: &mut InnoGen) -> Self {
+        let nodes = weights.len();
+        assert!(
+            nodes > sensory + action,
+            "{nodes} rows can't hold {sensory} sensory, {action} action, and a bias"
+        );
+        let mut builder =
+            GenomeBuilder::<C, Self>::new(sensory, action).hidden(nodes - sensory - action - 1);
+        for (from, row) in weights.iter().enumerate() {
+            assert_eq!(row.len(), nodes, "row {from} of weights isn't {nodes} long");
+            for (to, weight) in row.iter().enumerate().filter(|(_, w)| **w != 0.) {
+                builder = builder.connect(from, to, *weight);
+            }
+        }
+        builder.build_with(inno)
+    }
+
     fn sensory(&self) -> Range<usize>;
 
     fn action(&self) -> Range<usize>;
 
+    /// Sensory and action nodes grown by [extend_io](Genome::extend_io), which follow the nodes
+    /// held before rather than joining [sensory](Genome::sensory) or [action](Genome::action).
+    /// Networks give input past the sensory range to these in order, and read output past the
+    /// action range from these in order. Genomes who can't grow their io have none.
+    fn io_appended(&self) -> (&[usize], &[usize]) {
+        (&[], &[])
+    }
+
+    /// Every sensory node, in the order that input is given to them
+    fn sensory_nodes(&self) -> Vec<usize> {
+        self.sensory()
+            .chain(self.io_appended().0.iter().copied())
+            .collect()
+    }
+
+    /// Every action node, in the order that output is read from them
+    fn action_nodes(&self) -> Vec<usize> {
+        self.action()
+            .chain(self.io_appended().1.iter().copied())
+            .collect()
+    }
+
+    /// How many inputs networks of this genome take, one for each of its
+    /// [sensory nodes](Genome::sensory_nodes)
+    fn inputs(&self) -> usize {
+        self.sensory().len() + self.io_appended().0.len()
+    }
+
+    /// How many outputs networks of this genome give, one for each of its
+    /// [action nodes](Genome::action_nodes)
+    fn outputs(&self) -> usize {
+        self.action().len() + self.io_appended().1.len()
+    }
+
     fn nodes(&self) -> &[NodeKind];
 
     #[deprecated]
@@ -165,6 +891,406 @@ pub trait Genome<C: Connection>: Serialize + for<'de> Deserialize<'de> + Clone {
     /// Push a connection onto the genome.
     fn push_connection(&mut self, connection: C);
 
+    /// The node at `id`, or None if this genome has no such node
+    fn node(&self, id: NodeId) -> Option<&NodeKind> {
+        self.nodes().get(id.0)
+    }
+
+    /// The connection at `id`, or None if this genome has no such connection
+    fn connection(&self, id: ConnId) -> Option<&C> {
+        self.connections().get(id.0)
+    }
+
+    /// Every node alongside its id
+    fn node_ids(&self) -> impl Iterator<Item = (NodeId, &NodeKind)> {
+        self.nodes()
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| (NodeId(idx), node))
+    }
+
+    /// Every connection alongside its id
+    fn connection_ids<'a>(&'a self) -> impl Iterator<Item = (ConnId, &'a C)>
+    where
+        C: 'a,
+    {
+        self.connections()
+            .iter()
+            .enumerate()
+            .map(|(idx, c)| (ConnId(idx), c))
+    }
+
+    /// Ids of every connection whose path leaves or enters a node that this genome doesn't have.
+    /// Such connections can't be expressed, and would panic when building a network.
+    fn dangling(&self) -> Vec<ConnId> {
+        self.connection_ids()
+            .filter(|(_, c)| self.node(c.source()).is_none() || self.node(c.target()).is_none())
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Remove every internal node for whom `keep` is false, along with every connection to or
+    /// from them, and renumber the rest to stay contiguous. Genomes who don't override this can't
+    /// remove nodes, and instead disable every connection to or from them, which leaves them
+    /// without any effect on the network.
+    fn retain_nodes(&mut self, keep: &[bool]) {
+        debug_assert_eq!(keep.len(), self.nodes().len());
+        for c in self.connections_mut() {
+            if !keep[c.from()] || !keep[c.to()] {
+                c.disable();
+            }
+        }
+    }
+
+    /// Remove every connection for whom `keep` is false, leaving nodes as they are. Genomes who
+    /// don't override this can't remove connections, and instead disable them, which leaves them
+    /// without any effect on the network.
+    fn retain_connections(&mut self, keep: &[bool]) {
+        debug_assert_eq!(keep.len(), self.connections().len());
+        for (c, keep) in self.connections_mut().iter_mut().zip(keep) {
+            if !keep {
+                c.disable();
+            }
+        }
+    }
+
+    /// Move the boundary between sensory and action nodes, such that the first `sensory` io nodes
+    /// are sensory and the rest are action, along with any genes kept for each sensory or action
+    /// node, new ones taking their identity. Meant to be called through
+    /// [convert_node](Genome::convert_node), which checks that it's sensible. Genomes who can't
+    /// move their boundary give an error, and are left as they were.
+    fn set_io_boundary(&mut self, _sensory: usize) -> Result<(), Box<dyn Error>> {
+        Err("this genome can't move its io boundary".into())
+    }
+
+    /// Push `sensory` sensory nodes and then `action` action nodes after every other node,
+    /// [appended](Genome::io_appended) to this genome's io, along with any genes kept for each
+    /// node, sensory node or action. Meant to be called through [extend_io](Genome::extend_io).
+    /// Genomes who can't grow their io give an error, and are left as they were.
+    fn append_io(&mut self, _sensory: usize, _action: usize) -> Result<(), Box<dyn Error>> {
+        Err("this genome can't grow its io".into())
+    }
+
+    /// Grow this genome's io by `extra_sensory` sensory and `extra_action` action nodes, such as
+    /// to re-evolve a controller for a task with more sensors or actuators. The new nodes are
+    /// [appended](Genome::io_appended) after every node this genome held, so that no node is
+    /// renumbered and every connection keeps its path. Every new sensory node is connected to
+    /// every action node, and every old sensory node to every new action node, each with
+    /// innovation ids from `inno` and a weight of 0 so that old actions behave exactly as they
+    /// did until mutation finds a use for them.
+    ///
+    /// It's an error if `inno` gives a new connection an innovation id that this genome already
+    /// holds, which leaves the genome as it was.
+    fn extend_io(
+        &mut self,
+        extra_sensory: usize,
+        extra_action: usize,
+        inno: &mut InnoGen,
+    ) -> Result<(), Box<dyn Error>> {
+        let head = self.nodes().len();
+        let (sensory_new, action_new) = (
+            head..head + extra_sensory,
+            head + extra_sensory..head + extra_sensory + extra_action,
+        );
+        let (sensory, action) = (self.sensory_nodes(), self.action_nodes());
+        let connections = sensory_new
+            .flat_map(|from| {
+                action
+                    .iter()
+                    .copied()
+                    .chain(action_new.clone())
+                    .map(move |to| (from, to))
+            })
+            .chain(
+                sensory
+                    .iter()
+                    .flat_map(|&from| action_new.clone().map(move |to| (from, to))),
+            )
+            .map(|(from, to)| C::new(from, to, inno))
+            .collect::<Vec<_>>();
+        if let Some(c) = connections.iter().find(|c| {
+            self.connections()
+                .iter()
+                .any(|held| held.inno() == c.inno())
+        }) {
+            return Err(format!(
+                "innovation id {} of new path {:?} is already held",
+                c.inno(),
+                c.path()
+            )
+            .into());
+        }
+
+        self.append_io(extra_sensory, extra_action)?;
+        for mut connection in connections {
+            connection.set_weight(0.);
+            self.push_connection(connection);
+        }
+        Ok(())
+    }
+
+    /// Convert the node `id` into a node of `kind`. Internal and static nodes may be converted
+    /// into one another, the last sensory node into an action node, and the first action node into
+    /// a sensory node, the last two moving the boundary between them. Nodes may not become
+    /// sensory or static while any connection leads into them, as it would be ignored. The
+    /// boundary can't be moved once io has been [appended](Genome::io_appended). Any other
+    /// conversion is an error, and leaves the genome as it was.
+    fn convert_node(&mut self, id: NodeId, kind: NodeKind) -> Result<(), Box<dyn Error>> {
+        let Some(from) = self.node(id).copied() else {
+            return Err(format!("no node {}", id.0).into());
+        };
+        let (sensory_appended, action_appended) = self.io_appended();
+        if matches!(from, NodeKind::Sensory | NodeKind::Action)
+            && from != kind
+            && !(sensory_appended.is_empty() && action_appended.is_empty())
+        {
+            return Err(format!(
+                "node {} can't be converted from {from:?}, as this genome has appended io",
+                id.0
+            )
+            .into());
+        }
+        if matches!(kind, NodeKind::Sensory | NodeKind::Static)
+            && self.connections().iter().any(|c| c.target() == id)
+        {
+            return Err(format!(
+                "node {} has incoming connections, can't become {kind:?}",
+                id.0
+            )
+            .into());
+        }
+
+        let (sensory, action) = (self.sensory(), self.action());
+        match (from, kind) {
+            (from, kind) if from == kind => {}
+            (NodeKind::Internal, NodeKind::Static) | (NodeKind::Static, NodeKind::Internal) => {
+                self.nodes_mut()[id.0] = kind
+            }
+            (NodeKind::Sensory, NodeKind::Action) if id.0 + 1 == sensory.end => {
+                self.set_io_boundary(sensory.end - 1)?
+            }
+            (NodeKind::Action, NodeKind::Sensory) if id.0 == action.start => {
+                self.set_io_boundary(action.start + 1)?
+            }
+            (from, kind) => {
+                return Err(
+                    format!("node {} can't be converted from {from:?} to {kind:?}", id.0).into(),
+                )
+            }
+        }
+        Ok(())
+    }
+
+    /// Promote some static node to an internal node, so that its connections carry what it
+    /// computes rather than a constant, and push a new static node so that this genome keeps a
+    /// bias. Returns the promoted node, or None if there's no static node to promote.
+    fn promote_static(&mut self, rng: &mut impl RngCore) -> Option<NodeId> {
+        let id = self
+            .node_ids()
+            .filter(|(_, node)| matches!(node, NodeKind::Static))
+            .map(|(id, _)| id)
+            .choose(rng)?;
+        self.nodes_mut()[id.0] = NodeKind::Internal;
+        self.push_node(NodeKind::Static);
+        Some(id)
+    }
+
+    /// Copy some internal node, along with every connection into or out of it under innovation
+    /// ids from `innogen`, each [mutated](Connection::mutate_param_with) as described by `params`
+    /// so that the copy isn't just a clone of the original. A connection from the node to itself
+    /// is copied as one from the copy to itself. Returns the copy, or None if there's no internal
+    /// node to copy.
+    fn duplicate_node(
+        &mut self,
+        rng: &mut impl RngCore,
+        innogen: &mut InnoGen,
+        params: &MutationParams,
+    ) -> Option<NodeId> {
+        let original = self
+            .node_ids()
+            .filter(|(_, node)| matches!(node, NodeKind::Internal))
+            .map(|(id, _)| id.0)
+            .choose(rng)?;
+        self.push_node(NodeKind::Internal);
+        let copy = self.nodes().len() - 1;
+        if let Some(activation) = self.node_activations_mut() {
+            activation[copy] = activation[original];
+        }
+        if let Some(time_constant) = self.node_time_constants_mut() {
+            time_constant[copy] = time_constant[original];
+        }
+        if let Some(bias) = self.node_biases_mut() {
+            bias[copy] = bias[original];
+        }
+
+        let relabel = |idx: usize| if idx == original { copy } else { idx };
+        let incident = self
+            .connections()
+            .iter()
+            .filter(|c| c.from() == original || c.to() == original)
+            .cloned()
+            .collect::<Vec<_>>();
+        for original in incident {
+            let mut c = C::new(relabel(original.from()), relabel(original.to()), innogen);
+            c.set_params(&original.params().collect::<Vec<_>>());
+            if !original.enabled() {
+                c.disable();
+            }
+            c.mutate_param_with(rng, params);
+            self.push_connection(c);
+        }
+        Some(NodeId(copy))
+    }
+
+    /// [Freeze](Connection::frozen) every connection that this genome has now, such as after
+    /// seeding it from a hand-designed template, so that only what evolves on top of them changes
+    fn freeze_all(&mut self) {
+        for c in self.connections_mut() {
+            c.set_frozen(true);
+        }
+    }
+
+    /// A copy of this genome with each enabled connection disabled with probability `rate`, for
+    /// evaluating under structural dropout. Genomes who only do well with every connection in
+    /// place are penalized, which rewards redundancy and guards against overfitting to small
+    /// samples. The copy is for evaluation alone, and shouldn't be evolved further.
+    fn dropped_out(&self, rate: f64, rng: &mut impl RngCore) -> Self {
+        assert!(
+            (0. ..=1.).contains(&rate),
+            "dropout rate must be within [0, 1], got {rate}"
+        );
+        let mut dropped = self.clone();
+        for c in dropped.connections_mut().iter_mut().filter(|c| c.enabled()) {
+            if rng.random_bool(rate) {
+                c.disable();
+            }
+        }
+        dropped
+    }
+
+    /// For every node, whether it can affect any action node through enabled connections. Nodes
+    /// that can't reach an action have no effect on the network's output.
+    ///
+    /// Nodes unreachable from sensory input are not dead, as they still reach actions with a
+    /// resting activation ( `σ(0)` is 0.5 for a sigmoid ).
+    fn reaches_action(&self) -> Vec<bool> {
+        let mut reaches = vec![false; self.nodes().len()];
+        let mut frontier = self.action_nodes();
+        frontier.iter().for_each(|&idx| reaches[idx] = true);
+        while let Some(to) = frontier.pop() {
+            for c in self
+                .connections()
+                .iter()
+                .filter(|c| c.enabled() && c.to() == to)
+            {
+                if !reaches[c.from()] {
+                    reaches[c.from()] = true;
+                    frontier.push(c.from());
+                }
+            }
+        }
+        reaches
+    }
+
+    /// For every connection, whether it's recurrent, that is enabled and closing a cycle through
+    /// enabled connections. Nodes are searched depth first in order, sensory nodes first, and a
+    /// connection is recurrent when it leads back to a node whose search isn't finished, such as
+    /// when a node connects to itself. Every other enabled connection feeds forward.
+    fn recurrent(&self) -> Vec<bool> {
+        let mut outgoing = vec![Vec::new(); self.nodes().len()];
+        for (idx, c) in self.connections().iter().enumerate() {
+            if c.enabled() {
+                outgoing[c.from()].push(idx);
+            }
+        }
+
+        // 0 unsearched, 1 being searched, 2 searched
+        let mut searched = vec![0u8; self.nodes().len()];
+        let mut recurrent = vec![false; self.connections().len()];
+        for root in 0..self.nodes().len() {
+            if searched[root] != 0 {
+                continue;
+            }
+
+            searched[root] = 1;
+            let mut stack = vec![(root, 0)];
+            while let Some((node, next)) = stack.last_mut() {
+                let Some(&idx) = outgoing[*node].get(*next) else {
+                    searched[*node] = 2;
+                    stack.pop();
+                    continue;
+                };
+
+                *next += 1;
+                let to = self.connections()[idx].to();
+                match searched[to] {
+                    0 => {
+                        searched[to] = 1;
+                        stack.push((to, 0));
+                    }
+                    1 => recurrent[idx] = true,
+                    _ => {}
+                }
+            }
+        }
+        recurrent
+    }
+
+    /// A copy of this genome without any internal node that can't
+    /// [reach an action](Genome::reaches_action), which decodes into a smaller network with the
+    /// same output, or None if there's no such node. Pruned genomes no longer line up with their
+    /// relatives, and are for expressing as a network rather than for evolving further.
+    fn pruned(&self) -> Option<Self> {
+        let keep = self
+            .nodes()
+            .iter()
+            .zip(self.reaches_action())
+            .map(|(node, reaches)| reaches || !matches!(node, NodeKind::Internal))
+            .collect::<Vec<_>>();
+        keep.iter().any(|keep| !keep).then(|| {
+            let mut pruned = self.clone();
+            pruned.retain_nodes(&keep);
+            pruned
+        })
+    }
+
+    /// Remove every disabled connection, then every internal node left without any connection,
+    /// renumbering the rest. This leaves a compact genome that decodes into the same network, for
+    /// deploying rather than for evolving further, as it no longer lines up with its relatives.
+    fn simplify(&mut self) -> Removed {
+        let enabled = self
+            .connections()
+            .iter()
+            .map(|c| c.enabled())
+            .collect::<Vec<_>>();
+        let connections = self
+            .connections()
+            .iter()
+            .filter(|c| !c.enabled())
+            .map(|c| c.inno())
+            .collect();
+        self.retain_connections(&enabled);
+
+        let mut keep = self
+            .nodes()
+            .iter()
+            .map(|node| !matches!(node, NodeKind::Internal))
+            .collect::<Vec<_>>();
+        for c in self.connections() {
+            keep[c.from()] = true;
+            keep[c.to()] = true;
+        }
+        let nodes = (0..keep.len())
+            .filter(|idx| !keep[*idx])
+            .map(NodeId)
+            .collect::<Vec<_>>();
+        if !nodes.is_empty() {
+            self.retain_nodes(&keep);
+        }
+
+        Removed { connections, nodes }
+    }
+
     /// Push 2 connections onto the genome, first then second.
     /// The idea with this is that we'll often do so as a result of bisection, so this gives us
     /// a chance to grow the connections just once if we want.
@@ -177,38 +1303,481 @@ pub trait Genome<C: Connection>: Serialize + for<'de> Deserialize<'de> + Clone {
     /// [MUTATE_CONNECTION_PROBABILITY](Genome::MUTATE_CONNECTION_PROBABILITY) / [u64::MAX]
     /// connection.
     fn mutate_connection(&mut self, rng: &mut impl RngCore) {
-        for c in self.connections_mut() {
+        self.mutate_connection_with(rng, &C::MUTATION_PARAMS)
+    }
+
+    /// Like [mutate_connection](Genome::mutate_connection), mutating params as described by
+    /// `params`
+    fn mutate_connection_with(&mut self, rng: &mut impl RngCore, params: &MutationParams) {
+        for c in self.connections_mut().iter_mut().filter(|c| !c.frozen()) {
             if rng.next_u64() < Self::MUTATE_CONNECTION_PROBABILITY {
-                c.mutate(rng);
+                c.mutate_with(rng, params);
             }
         }
     }
 
-    /// Find some open path ( that is, a path between nodes from -> to ) that no connection is
-    /// occupying if any exist. Whatever path is returned will be considered valid, and may be
-    /// used when generating a new connection.
-    fn open_path(&self, rng: &mut impl RngCore) -> Option<(usize, usize)>;
+    /// Per-action [Affine] genes applied to network outputs after activation, if this genome
+    /// evolves them.
+    fn output_affine(&self) -> Option<&[Affine]> {
+        None
+    }
 
-    /// Generate a new connection between unconnected nodes. Panics if all possible connections
-    /// between nodes are saturated ( TODO: is that a good idea? )
-    fn new_connection(&mut self, rng: &mut impl RngCore, inno: &mut InnoGen) {
-        if let Some((from, to)) = self.open_path(rng) {
-            self.push_connection(C::new(from, to, inno));
-        } else {
-            panic!("connections on genome are fully saturated")
+    /// Mutable reference to this genome's output [Affine] genes, if it evolves them.
+    fn output_affine_mut(&mut self) -> Option<&mut [Affine]> {
+        None
+    }
+
+    /// Possibly mutate output [Affine] genes, if this genome has any. Each is mutated with the
+    /// same probability that a connection is in [mutate_connection](Genome::mutate_connection).
+    fn mutate_output(&mut self, rng: &mut impl RngCore) {
+        if let Some(affine) = self.output_affine_mut() {
+            for a in affine {
+                if rng.next_u64() < Self::MUTATE_CONNECTION_PROBABILITY {
+                    a.mutate(rng);
+                }
+            }
+        }
+    }
+
+    /// The [activation id](crate::activate::PALETTE) of every node, if this genome evolves them
+    fn node_activations(&self) -> Option<&[usize]> {
+        None
+    }
+
+    /// Mutable reference to this genome's node activation ids, if it evolves them.
+    fn node_activations_mut(&mut self) -> Option<&mut [usize]> {
+        None
+    }
+
+    /// Give some internal or action node a different activation, picked at random from
+    /// [ACTIVATIONS](Genome::ACTIVATIONS). Does nothing if this genome doesn't evolve them.
+    fn mutate_activation(&mut self, rng: &mut impl RngCore) {
+        let Some(node) = self
+            .node_ids()
+            .filter(|(_, node)| matches!(node, NodeKind::Internal | NodeKind::Action))
+            .map(|(id, _)| id.0)
+            .choose(rng)
+        else {
+            return;
+        };
+        let Some(activations) = self.node_activations_mut() else {
+            return;
+        };
+
+        if let Some(id) = Self::ACTIVATIONS
+            .iter()
+            .filter(|id| **id != activations[node])
+            .choose(rng)
+        {
+            activations[node] = *id;
+        }
+    }
+
+    /// Fraction of the nodes shared with `other` whose activations differ. Genomes who don't
+    /// evolve activations use id 0 for every node.
+    fn activation_diff(&self, other: &Self) -> f64 {
+        let (l, r) = (self.node_activations(), other.node_activations());
+        let shared = usize::min(self.nodes().len(), other.nodes().len());
+        if shared == 0 || (l.is_none() && r.is_none()) {
+            return 0.;
+        }
+
+        let id = |activations: Option<&[usize]>, idx: usize| activations.map_or(0, |a| a[idx]);
+        (0..shared).filter(|idx| id(l, *idx) != id(r, *idx)).count() as f64 / shared as f64
+    }
+
+    /// The time constant of every node, if this genome evolves them
+    fn node_time_constants(&self) -> Option<&[f64]> {
+        None
+    }
+
+    /// Mutable reference to this genome's node time constants, if it evolves them.
+    fn node_time_constants_mut(&mut self) -> Option<&mut [f64]> {
+        None
+    }
+
+    /// The time constant of every node, whether or not this genome evolves them
+    fn time_constants(&self) -> Vec<f64> {
+        self.node_time_constants().map_or_else(
+            || vec![Self::TIME_CONSTANT; self.nodes().len()],
+            <[f64]>::to_vec,
+        )
+    }
+
+    /// Possibly perturb node time constants, if this genome has any. Each is mutated with
+    /// [MUTATE_NODE_PROBABILITY](Genome::MUTATE_NODE_PROBABILITY) by a draw from `params`,
+    /// scaling it so that it stays positive.
+    fn mutate_time_constants(&mut self, rng: &mut impl RngCore, params: &MutationParams) {
+        if let Some(time_constants) = self.node_time_constants_mut() {
+            for τ in time_constants {
+                if rng.next_u64() < Self::MUTATE_NODE_PROBABILITY {
+                    *τ *= f64::exp(params.sigma * params.sample(rng));
+                }
+            }
+        }
+    }
+
+    /// The bias of every node, if this genome evolves them. A node's bias is added to its state
+    /// before activation, so that it needn't be connected to a static node for one. Biases of
+    /// static nodes are ignored, as those always hold 1.
+    fn node_biases(&self) -> Option<&[f64]> {
+        None
+    }
+
+    /// Mutable reference to this genome's node biases, if it evolves them.
+    fn node_biases_mut(&mut self) -> Option<&mut [f64]> {
+        None
+    }
+
+    /// What every node holds before activation when nothing flows into it: 1 for static nodes,
+    /// otherwise its bias if this genome evolves them, or 0.
+    fn biases(&self) -> Vec<f64> {
+        let biases = self.node_biases();
+        self.nodes()
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| match node {
+                NodeKind::Static => 1.,
+                _ => biases.map_or(0., |biases| biases[idx]),
+            })
+            .collect()
+    }
+
+    /// Possibly perturb or reset node biases, if this genome has any, independently of its
+    /// connections. Each is mutated by `params` with
+    /// [MUTATE_NODE_PROBABILITY](Genome::MUTATE_NODE_PROBABILITY).
+    fn mutate_biases(&mut self, rng: &mut impl RngCore, params: &MutationParams) {
+        if let Some(biases) = self.node_biases_mut() {
+            for bias in biases {
+                if rng.next_u64() < Self::MUTATE_NODE_PROBABILITY {
+                    *bias = params.apply(*bias, rng);
+                }
+            }
+        }
+    }
+
+    /// The gain of every sensory node, if this genome evolves them. Raw input to each sensory
+    /// node is multiplied by its gain before it propagates, so that evolution may adapt to
+    /// sensors of any range rather than their input needing to be normalized first. Applied by
+    /// [Scaled](crate::network::Scaled) networks.
+    fn sensory_gains(&self) -> Option<&[f64]> {
+        None
+    }
+
+    /// Mutable reference to this genome's sensory gains, if it evolves them.
+    fn sensory_gains_mut(&mut self) -> Option<&mut [f64]> {
+        None
+    }
+
+    /// Possibly perturb or reset sensory gains, if this genome has any, like
+    /// [mutate_biases](Genome::mutate_biases).
+    fn mutate_sensory_gains(&mut self, rng: &mut impl RngCore, params: &MutationParams) {
+        if let Some(gains) = self.sensory_gains_mut() {
+            for gain in gains {
+                if rng.next_u64() < Self::MUTATE_NODE_PROBABILITY {
+                    *gain = params.apply(*gain, rng);
+                }
+            }
+        }
+    }
+
+    /// Mean difference between the biases of the nodes shared with `other`
+    fn bias_diff(&self, other: &Self) -> f64 {
+        if self.node_biases().is_none() && other.node_biases().is_none() {
+            return 0.;
+        }
+
+        let (l, r) = (self.biases(), other.biases());
+        let shared = usize::min(l.len(), r.len());
+        if shared == 0 {
+            return 0.;
+        }
+        l.iter()
+            .zip(r.iter())
+            .map(|(l, r)| (l - r).abs())
+            .sum::<f64>()
+            / shared as f64
+    }
+
+    /// This genome's own weight-perturbation sigma, if it self-adapts one. When it does, every
+    /// mutation of its connections first mutates the sigma, then perturbs params by it in place of
+    /// [MutationParams::sigma], so that step sizes adapt along with the genome as in evolution
+    /// strategies.
+    fn mutation_sigma(&self) -> Option<f64> {
+        None
+    }
+
+    /// Mutable reference to this genome's own sigma, if it self-adapts one
+    fn mutation_sigma_mut(&mut self) -> Option<&mut f64> {
+        None
+    }
+
+    /// The params that this genome mutates with as configured by `config`, perturbing by its
+    /// own [sigma](Genome::mutation_sigma) if it has one
+    fn mutation_params(&self, config: &MutationConfig) -> MutationParams {
+        let params = config.params.unwrap_or(C::MUTATION_PARAMS);
+        match self.mutation_sigma() {
+            Some(sigma) => MutationParams { sigma, ..params },
+            None => params,
+        }
+    }
+
+    /// Scale this genome's own sigma, if it has one, by a log-normal draw of
+    /// [SIGMA_LEARNING_RATE](Genome::SIGMA_LEARNING_RATE), and return the params to mutate
+    /// connections with: `params` with the new sigma, or `params` as they are if there's none.
+    fn adapt_sigma(&mut self, rng: &mut impl RngCore, params: &MutationParams) -> MutationParams {
+        match self.mutation_sigma_mut() {
+            Some(sigma) => {
+                let draw: f64 = rng.sample(StandardNormal);
+                *sigma = f64::max(
+                    Self::SIGMA_MIN,
+                    *sigma * f64::exp(Self::SIGMA_LEARNING_RATE * draw),
+                );
+                MutationParams {
+                    sigma: *sigma,
+                    ..*params
+                }
+            }
+            None => *params,
+        }
+    }
+
+    /// Mean difference between the time constants of the nodes shared with `other`
+    fn time_constant_diff(&self, other: &Self) -> f64 {
+        if self.node_time_constants().is_none() && other.node_time_constants().is_none() {
+            return 0.;
+        }
+
+        let (l, r) = (self.time_constants(), other.time_constants());
+        let shared = usize::min(l.len(), r.len());
+        if shared == 0 {
+            return 0.;
+        }
+        l.iter()
+            .zip(r.iter())
+            .map(|(l, r)| (l - r).abs())
+            .sum::<f64>()
+            / shared as f64
+    }
+
+    /// Labels of this genome's sensory and action nodes, if it has any. Labels don't affect the
+    /// network a genome decodes into, but are kept through serialization and crossover, and are
+    /// read by [Labelled](crate::network::Labelled) networks.
+    fn io_labels(&self) -> Option<&IoLabels> {
+        None
+    }
+
+    /// Mutable reference to this genome's io labels, if it has any
+    fn io_labels_mut(&mut self) -> Option<&mut IoLabels> {
+        None
+    }
+
+    /// The modules that this genome's internal nodes are grouped into
+    fn modules(&self) -> &[Module] {
+        &[]
+    }
+
+    /// Mutable reference to this genome's modules, if it may hold any
+    fn modules_mut(&mut self) -> Option<&mut Vec<Module>> {
+        None
+    }
+
+    /// Group the internal `nodes` into a new module called `name`. It's an error if this genome
+    /// can't hold modules, if the name is taken, or if any node isn't internal or already belongs
+    /// to some module, and the genome is left as it was.
+    fn define_module(&mut self, name: &str, nodes: &[NodeId]) -> Result<(), Box<dyn Error>> {
+        if nodes.is_empty() {
+            return Err(format!("module {name} has no nodes").into());
+        }
+        if self.modules().iter().any(|module| module.name == name) {
+            return Err(format!("module {name} already exists").into());
+        }
+        for id in nodes {
+            if self.node(*id) != Some(&NodeKind::Internal) {
+                return Err(format!("node {} isn't an internal node", id.0).into());
+            }
+            if self
+                .modules()
+                .iter()
+                .any(|module| module.nodes.contains(id))
+                || nodes.iter().filter(|other| *other == id).count() > 1
+            {
+                return Err(format!("node {} is already in a module", id.0).into());
+            }
         }
+
+        self.modules_mut()
+            .ok_or("genome can't hold modules")?
+            .push(Module {
+                name: name.to_owned(),
+                nodes: nodes.to_vec(),
+            });
+        Ok(())
+    }
+
+    /// Connections and nodes that [duplicate_module](Genome::duplicate_module) would add for the
+    /// module at `module`
+    fn module_growth(&self, module: usize) -> (usize, usize) {
+        let nodes = &self.modules()[module].nodes;
+        let touches = self
+            .connections()
+            .iter()
+            .filter(|c| nodes.contains(&c.source()) || nodes.contains(&c.target()))
+            .count();
+        (touches, nodes.len())
     }
 
-    /// Bisect an existing connection. Panics if there are no connections to bisect. This is the
-    /// mechanism by which the internal / "hidden" layer of nodes grows on a genome, the new
-    /// node being at the center of the bisection.
-    fn bisect_connection(&mut self, rng: &mut impl RngCore, inno: &mut InnoGen) {
-        if self.connections().is_empty() {
-            panic!("no connections available to bisect");
+    /// Duplicate the module at `module`, as a macro-mutation. Its nodes are copied, along with
+    /// every connection within, into, or out of it, with innovation ids from `innogen`. The copy
+    /// sees the same inputs as the original, and the weight of every connection leaving either is
+    /// halved, so that the genome behaves exactly as it did until the two diverge. Returns the
+    /// index of the new module, named after the original, or an error if a
+    /// [frozen](Connection::frozen) connection leaves it, whose weight can't be halved.
+    fn duplicate_module(
+        &mut self,
+        module: usize,
+        innogen: &mut InnoGen,
+    ) -> Result<usize, Box<dyn Error>> {
+        let Module { name, nodes } = self.modules()[module].clone();
+        if let Some(c) = self
+            .connections()
+            .iter()
+            .find(|c| c.frozen() && nodes.contains(&c.source()) && !nodes.contains(&c.target()))
+        {
+            return Err(format!("frozen connection {} leaves module {name}", c.inno()).into());
+        }
+        let head = self.nodes().len();
+        let copy = |id: usize| {
+            nodes
+                .iter()
+                .position(|node| node.0 == id)
+                .map(|pos| head + pos)
+        };
+
+        for node in nodes.iter() {
+            self.push_node(NodeKind::Internal);
+            let copied = self.nodes().len() - 1;
+            if let Some(activation) = self.node_activations_mut() {
+                activation[copied] = activation[node.0];
+            }
+            if let Some(time_constant) = self.node_time_constants_mut() {
+                time_constant[copied] = time_constant[node.0];
+            }
+            if let Some(bias) = self.node_biases_mut() {
+                bias[copied] = bias[node.0];
+            }
+        }
+
+        let mut copies = Vec::new();
+        for c in self.connections_mut() {
+            let (from, to) = c.path();
+            let path = match (copy(from), copy(to)) {
+                (None, None) => continue,
+                (Some(from), Some(to)) => (from, to),
+                (None, Some(to)) => (from, to),
+                (Some(from), None) => {
+                    c.set_weight(c.weight() / 2.);
+                    (from, to)
+                }
+            };
+            copies.push((path, c.clone()));
+        }
+        for ((from, to), original) in copies {
+            let mut c = C::new(from, to, innogen);
+            c.set_params(&original.params().collect::<Vec<_>>());
+            if !original.enabled() {
+                c.disable();
+            }
+            self.push_connection(c);
         }
 
+        let mut copied_name = format!("{name}'");
+        while self
+            .modules()
+            .iter()
+            .any(|module| module.name == copied_name)
+        {
+            copied_name.push('\'');
+        }
+        let modules = self
+            .modules_mut()
+            .expect("genome with a module can't hold modules");
+        modules.push(Module {
+            name: copied_name,
+            nodes: (head..head + nodes.len()).map(NodeId).collect(),
+        });
+        Ok(modules.len() - 1)
+    }
+
+    /// Share of modules, by name, that only one of this and `other` has, or 0 if neither has any
+    fn module_diff(&self, other: &Self) -> f64 {
+        let (l, r) = (self.modules(), other.modules());
+        let has = |modules: &[Module], name: &str| modules.iter().any(|m| m.name == name);
+        let shared = l.iter().filter(|m| has(r, &m.name)).count();
+        let union = l.len() + r.len() - shared;
+        if union == 0 {
+            return 0.;
+        }
+        (union - shared) as f64 / union as f64
+    }
+
+    /// Which paths [open_path](Genome::open_path) may propose
+    fn recurrence(&self) -> Recurrence {
+        Recurrence::Any
+    }
+
+    /// Find some open path ( that is, a path between nodes from -> to ) that no connection is
+    /// occupying if any exist, within this genome's [recurrence](Genome::recurrence). Whatever
+    /// path is returned will be considered valid, and may be used when generating a new
+    /// connection.
+    fn open_path(&self, rng: &mut impl RngCore) -> Option<(usize, usize)>;
+
+    /// Generate a new connection between unconnected nodes. It's an error if all possible
+    /// connections between nodes are saturated, and the genome is left as it was.
+    fn new_connection(
+        &mut self,
+        rng: &mut impl RngCore,
+        inno: &mut InnoGen,
+    ) -> Result<(), Box<dyn Error>> {
+        self.new_connection_with(rng, inno, &C::WEIGHT_INIT)
+    }
+
+    /// Like [new_connection](Genome::new_connection), but with the new connection's weight drawn
+    /// from `init` rather than [WEIGHT_INIT](Connection::WEIGHT_INIT).
+    fn new_connection_with(
+        &mut self,
+        rng: &mut impl RngCore,
+        inno: &mut InnoGen,
+        init: &WeightInit,
+    ) -> Result<(), Box<dyn Error>> {
+        let (from, to) = self
+            .open_path(rng)
+            .ok_or("connections on genome are fully saturated")?;
+        let mut connection = C::new(from, to, inno);
+        connection.set_weight(init.sample(rng));
+        self.push_connection(connection);
+        Ok(())
+    }
+
+    /// Bisect an existing connection that isn't [frozen](Connection::frozen). It's an error if
+    /// there are no such connections to bisect. This is the mechanism by which the internal /
+    /// "hidden" layer of nodes grows on a genome, the new node being at the center of the
+    /// bisection.
+    fn bisect_connection(
+        &mut self,
+        rng: &mut impl RngCore,
+        inno: &mut InnoGen,
+    ) -> Result<(), Box<dyn Error>> {
+        let source = self
+            .connections()
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| !c.frozen())
+            .map(|(idx, _)| idx)
+            .choose(rng)
+            .ok_or("no connections available to bisect")?;
+
         let center = self.nodes().len();
-        let source = rng.random_range(0..self.connections().len());
         let (lower, upper) = self
             .connections_mut()
             .get_mut(source)
@@ -217,49 +1786,520 @@ pub trait Genome<C: Connection>: Serialize + for<'de> Deserialize<'de> + Clone {
 
         self.push_node(NodeKind::Internal);
         self.push_2_connections(lower, upper);
+        Ok(())
     }
 
     /// Perform 0 or more mutations on this genome. If [PROBABILITIES](Genome::PROBABILITIES)
     /// add up to [u64::MAX], some event will always be picked. Otherwise, it's possible that
-    /// no mutation actually ocurrs.
+    /// no mutation actually ocurrs. Mutations that are impossible for this genome, such as new
+    /// connections on a saturated genome or bisections on an empty one, leave it as it was
+    /// rather than panicking, so that a single genome can't end an evolution run.
     fn mutate(&mut self, rng: &mut impl RngCore, innogen: &mut InnoGen) {
-        if let Some(evt) = GenomeEvent::pick(rng, Self::PROBABILITIES) {
-            match evt {
-                GenomeEvent::NewConnection => self.new_connection(rng, innogen),
-                GenomeEvent::BisectConnection => {
-                    if !self.connections().is_empty() {
-                        self.bisect_connection(rng, innogen)
+        self.mutate_with(rng, innogen, Self::PROBABILITIES)
+    }
+
+    /// Like [mutate](Genome::mutate), but picking events with `probabilities` rather than
+    /// [PROBABILITIES](Genome::PROBABILITIES), for when they should change over the course of
+    /// evolution.
+    fn mutate_with(
+        &mut self,
+        rng: &mut impl RngCore,
+        innogen: &mut InnoGen,
+        probabilities: GenomeProbabilities,
+    ) {
+        self.mutate_configured(rng, innogen, probabilities, &Self::MUTATION)
+    }
+
+    /// Like [mutate_with](Genome::mutate_with), but mutating as configured by `config` rather
+    /// than [MUTATION](Genome::MUTATION).
+    fn mutate_configured(
+        &mut self,
+        rng: &mut impl RngCore,
+        innogen: &mut InnoGen,
+        probabilities: GenomeProbabilities,
+        config: &MutationConfig,
+    ) {
+        if let Some(evt) = GenomeEvent::pick(rng, probabilities) {
+            self.mutate_event(evt, rng, innogen, config);
+        }
+    }
+
+    /// Perform exactly `n` mutations, each picked with chances proportional to `weights`. Unlike
+    /// [PROBABILITIES](Genome::PROBABILITIES), weights needn't add up to anything in particular,
+    /// so `[1, 0, 1, 0, 0]` is an even split between new connections and connection mutations.
+    /// As with [mutate](Genome::mutate), impossible mutations fall back to something else or
+    /// leave the genome as it was, so `n` is a count of attempts. Each mutation is configured by
+    /// `config`, as with [mutate_configured](Genome::mutate_configured). This is meant for
+    /// ablation studies and tests, which want control over how much a genome changes.
+    fn mutate_n(
+        &mut self,
+        n: usize,
+        weights: GenomeProbabilities,
+        rng: &mut impl RngCore,
+        innogen: &mut InnoGen,
+        config: &MutationConfig,
+    ) {
+        for _ in 0..n {
+            let evt = GenomeEvent::pick_weighted(rng, weights);
+            self.mutate_event(evt, rng, innogen, config);
+        }
+    }
+
+    /// Perform the mutation `evt`, as configured by `config`. Events that are impossible for
+    /// this genome or not allowed by its constraints fall back to mutating connections.
+    fn mutate_event(
+        &mut self,
+        evt: GenomeEvent,
+        rng: &mut impl RngCore,
+        innogen: &mut InnoGen,
+        config: &MutationConfig,
+    ) {
+        let constraints = &config.constraints;
+        let (connections, nodes) = (self.connections().len(), self.nodes().len());
+        let evt = match evt {
+            GenomeEvent::NewConnection if !constraints.allows(connections + 1, nodes) => {
+                GenomeEvent::MutateConnection
+            }
+            GenomeEvent::BisectConnection if !constraints.allows(connections + 2, nodes + 1) => {
+                GenomeEvent::MutateConnection
+            }
+            GenomeEvent::MutateActivation if self.node_activations().is_none() => {
+                GenomeEvent::MutateConnection
+            }
+            GenomeEvent::MutateBias
+                if self.node_biases().is_none() && self.sensory_gains().is_none() =>
+            {
+                GenomeEvent::MutateConnection
+            }
+            evt => evt,
+        };
+        match evt {
+            GenomeEvent::NewConnection => {
+                let init = config.weight_init.unwrap_or(C::WEIGHT_INIT);
+                // the only error is that a saturated genome has no open path to connect, and
+                // such a genome is left as it is
+                let _ = self.new_connection_with(rng, innogen, &init);
+            }
+            // at worst, every connection is copied along with the node
+            GenomeEvent::BisectConnection
+                if Self::DUPLICATE_NODE_PROBABILITY > 0
+                    && constraints.allows(connections * 2, nodes + 1)
+                    && rng.next_u64() < Self::DUPLICATE_NODE_PROBABILITY
+                    && self
+                        .duplicate_node(rng, innogen, &self.mutation_params(config))
+                        .is_some() => {}
+            GenomeEvent::BisectConnection => {
+                let duplicate = if !self.modules().is_empty()
+                    && rng.next_u64() < Self::DUPLICATE_MODULE_PROBABILITY
+                {
+                    // duplicating halves the weights leaving a module, which frozen ones
+                    // can't have done to them
+                    (0..self.modules().len())
+                        .filter(|idx| {
+                            let (grow_connections, grow_nodes) = self.module_growth(*idx);
+                            let module = &self.modules()[*idx].nodes;
+                            constraints.allows(connections + grow_connections, nodes + grow_nodes)
+                                && !self
+                                    .connections()
+                                    .iter()
+                                    .any(|c| c.frozen() && module.contains(&c.source()))
+                        })
+                        .choose(rng)
+                } else {
+                    None
+                };
+                match duplicate {
+                    Some(module) => {
+                        self.duplicate_module(module, innogen)
+                            .expect("module with frozen outgoing connections");
                     }
-                }
-                GenomeEvent::MutateConnection => {
-                    if !self.connections().is_empty() {
-                        self.mutate_connection(rng)
+                    None => {
+                        // the only error is that every connection is frozen or there are none,
+                        // and such a genome has nothing to bisect, so is left as it is
+                        let _ = self.bisect_connection(rng, innogen);
                     }
                 }
-                GenomeEvent::MutateNode => unreachable!("nodes may not be mutated"),
             }
+            GenomeEvent::MutateConnection => {
+                let mut params = self.mutation_params(config);
+                if !self.connections().is_empty() {
+                    params = self.adapt_sigma(rng, &params);
+                    self.mutate_connection_with(rng, &params);
+                }
+                self.mutate_output(rng);
+                self.mutate_time_constants(rng, &params);
+            }
+            GenomeEvent::MutateBias => {
+                let params = config.params.unwrap_or(C::MUTATION_PARAMS);
+                self.mutate_biases(rng, &params);
+                self.mutate_sensory_gains(rng, &params);
+            }
+            GenomeEvent::MutateActivation => self.mutate_activation(rng),
         }
+
+        // new and bisected connections may start out of bounds, as well as mutated ones
+        if config.bounded() {
+            for c in self.connections_mut().iter_mut().filter(|c| !c.frozen()) {
+                c.set_weight(c.weight().clamp(config.weight_min, config.weight_max));
+            }
+        }
+    }
+
+    /// The weight of every enabled connection that isn't [frozen](Connection::frozen), in order.
+    /// Holding topology fixed, these may be optimized by something other than mutation and
+    /// written back with [set_weights](Genome::set_weights).
+    fn weights(&self) -> Vec<f64> {
+        self.connections()
+            .iter()
+            .filter(|c| c.enabled() && !c.frozen())
+            .map(|c| c.weight())
+            .collect()
+    }
+
+    /// Overwrite the weight of every enabled connection that isn't frozen, laid out as in
+    /// [weights](Genome::weights)
+    fn set_weights(&mut self, weights: &[f64]) {
+        let mut weights = weights.iter();
+        for c in self
+            .connections_mut()
+            .iter_mut()
+            .filter(|c| c.enabled() && !c.frozen())
+        {
+            c.set_weight(*weights.next().expect("too few weights"));
+        }
+        assert!(weights.next().is_none(), "too many weights");
+    }
+
+    /// Copy weights back from `network`, a network decoded from this genome, into the enabled
+    /// connections along the same paths. Networks who change their weights as they're stepped,
+    /// such as by Hebbian learning or some local search, may so have what they learned inherited
+    /// by offspring. Connections whose weight the network doesn't [give](Network::weight) are left
+    /// as they are, as are frozen ones. Returns how many connections were written.
+    fn absorb_network<NN: Network>(&mut self, network: &NN) -> usize {
+        let mut absorbed = 0;
+        for c in self
+            .connections_mut()
+            .iter_mut()
+            .filter(|c| c.enabled() && !c.frozen())
+        {
+            if let Some(weight) = network.weight(c.from(), c.to()) {
+                c.set_weight(weight);
+                absorbed += 1;
+            }
+        }
+        absorbed
+    }
+
+    /// Every evolvable continuous parameter of this genome as one flat vector: the
+    /// [params](Connection::params) of each connection, enabled or not, in order, followed by
+    /// the gain and offset of each [output_affine](Genome::output_affine) gene, followed by each
+    /// [node time constant](Genome::node_time_constants), followed by each
+    /// [node bias](Genome::node_biases), followed by each [sensory gain](Genome::sensory_gains).
+    /// The layout only
+    /// changes with structure, so parameters may be tuned by an external optimizer, or shared
+    /// between genomes of one topology, and written back with [set_params](Genome::set_params).
+    fn params(&self) -> Vec<f64> {
+        let mut params = Vec::with_capacity(self.connections().len() * C::PARAMS);
+        for c in self.connections() {
+            params.extend(c.params());
+        }
+        for a in self.output_affine().unwrap_or_default() {
+            params.extend([a.gain, a.offset]);
+        }
+        params.extend_from_slice(self.node_time_constants().unwrap_or_default());
+        params.extend_from_slice(self.node_biases().unwrap_or_default());
+        params.extend_from_slice(self.sensory_gains().unwrap_or_default());
+        params
+    }
+
+    /// Overwrite every evolvable continuous parameter, laid out as in [params](Genome::params)
+    fn set_params(&mut self, params: &[f64]) {
+        let mut chunks = params.chunks(C::PARAMS);
+        for c in self.connections_mut() {
+            c.set_params(chunks.next().expect("too few params"));
+        }
+
+        let mut rest = chunks.flatten();
+        for a in self.output_affine_mut().unwrap_or_default() {
+            a.gain = *rest.next().expect("too few params");
+            a.offset = *rest.next().expect("too few params");
+        }
+        for τ in self.node_time_constants_mut().unwrap_or_default() {
+            *τ = *rest.next().expect("too few params");
+        }
+        for bias in self.node_biases_mut().unwrap_or_default() {
+            *bias = *rest.next().expect("too few params");
+        }
+        for gain in self.sensory_gains_mut().unwrap_or_default() {
+            *gain = *rest.next().expect("too few params");
+        }
+        assert!(rest.next().is_none(), "too many params");
+    }
+
+    /// Approximate bytes held by this genome, including its nodes and connections.
+    fn memory(&self) -> usize {
+        size_of::<Self>()
+            + size_of_val(self.nodes())
+            + size_of_val(self.connections())
+            + self.output_affine().map_or(0, size_of_val)
+            + self.node_activations().map_or(0, size_of_val)
+            + self.node_time_constants().map_or(0, size_of_val)
+            + self.node_biases().map_or(0, size_of_val)
+            + self.sensory_gains().map_or(0, size_of_val)
+    }
+
+    /// Counts of this genome's structure, for parsimony pressure or tracking bloat. Its depth
+    /// ignores [recurrent](Genome::recurrent) connections.
+    fn complexity(&self) -> Complexity {
+        let nodes = self.nodes().len();
+        let forward = self
+            .connections()
+            .iter()
+            .zip(self.recurrent())
+            .filter(|(c, recurrent)| c.enabled() && !recurrent)
+            .map(|(c, _)| c.path())
+            .collect::<Vec<_>>();
+
+        let mut incoming = vec![0usize; nodes];
+        for (_, to) in forward.iter() {
+            incoming[*to] += 1;
+        }
+        let mut order = (0..nodes)
+            .filter(|node| incoming[*node] == 0)
+            .collect::<Vec<_>>();
+        let mut depth = vec![0; nodes];
+        let mut next = 0;
+        while let Some(&from) = order.get(next) {
+            for (_, to) in forward.iter().filter(|(f, _)| *f == from) {
+                depth[*to] = usize::max(depth[*to], depth[from] + 1);
+                incoming[*to] -= 1;
+                if incoming[*to] == 0 {
+                    order.push(*to);
+                }
+            }
+            next += 1;
+        }
+
+        Complexity {
+            nodes,
+            connections: self.connections().iter().filter(|c| c.enabled()).count(),
+            hidden: self
+                .nodes()
+                .iter()
+                .filter(|node| matches!(node, NodeKind::Internal))
+                .count(),
+            depth: depth.into_iter().max().unwrap_or(0),
+        }
+    }
+
+    /// The size of this genome, and of the dense network that it would decode into
+    fn footprint(&self) -> Footprint {
+        let nodes = self.nodes().len();
+        Footprint {
+            nodes,
+            connections: self.connections().iter().filter(|c| c.enabled()).count(),
+            dense: nodes * nodes,
+            bytes: self.memory(),
+        }
+    }
+
+    /// A key describing only the parts of this genome that affect the network it decodes into:
+    /// its io shape, node kinds, activations and time constants, output and sensory gain genes,
+    /// and the path, [kind](Connection::kind) and [params](Connection::params) of every enabled
+    /// connection. Genomes with equal keys produce the same network, so a network cache keyed on
+    /// this may skip rebuilds for genomes whose metadata ( innovation ids, disabled genes, the
+    /// order of connections ) changed but whose phenotype did not. Genomes that carry more
+    /// phenotype-relevant genes should override this.
+    fn phenotype_cache_key(&self) -> u64 {
+        let mut h = DefaultHasher::new();
+        self.sensory().hash(&mut h);
+        self.action().hash(&mut h);
+        for node in self.nodes() {
+            discriminant(node).hash(&mut h);
+        }
+        // sorted by path, so that genomes holding the same connections in another order agree
+        let mut connections = self
+            .connections()
+            .iter()
+            .filter(|c| c.enabled())
+            .map(|c| {
+                (
+                    c.path(),
+                    c.kind(),
+                    c.params().map(f64::to_bits).collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        connections.sort_unstable();
+        connections.hash(&mut h);
+        for a in self.output_affine().unwrap_or_default() {
+            a.gain.to_bits().hash(&mut h);
+            a.offset.to_bits().hash(&mut h);
+        }
+        self.node_activations().hash(&mut h);
+        for τ in self.node_time_constants().unwrap_or_default() {
+            τ.to_bits().hash(&mut h);
+        }
+        for bias in self.node_biases().unwrap_or_default() {
+            bias.to_bits().hash(&mut h);
+        }
+        for gain in self.sensory_gains().unwrap_or_default() {
+            gain.to_bits().hash(&mut h);
+        }
+        h.finish()
+    }
+
+    /// Whether this genome and `other` decode into the same network: they share an io shape,
+    /// node kinds and per-node genes, and the same enabled connections, matched by path and
+    /// compared by [kind](Connection::kind) and [params](Connection::params). Disabled
+    /// connections, the order that genes are stored in, and innovation ids are ignored.
+    fn phenotype_eq(&self, other: &Self) -> bool {
+        let enabled = |genome: &Self| {
+            let mut connections = genome
+                .connections()
+                .iter()
+                .filter(|c| c.enabled())
+                .map(|c| {
+                    (
+                        c.path(),
+                        c.kind(),
+                        c.params().map(f64::to_bits).collect::<Vec<_>>(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            connections.sort_unstable();
+            connections
+        };
+
+        self.sensory() == other.sensory()
+            && self.action() == other.action()
+            && self.nodes() == other.nodes()
+            && self.node_activations() == other.node_activations()
+            && self.node_time_constants() == other.node_time_constants()
+            && self.node_biases() == other.node_biases()
+            && self.sensory_gains() == other.sensory_gains()
+            && self.output_affine() == other.output_affine()
+            && enabled(self) == enabled(other)
+    }
+
+    /// A hash of this genome's genes: its io shape, node kinds and activations, each connection's
+    /// innovation id, enabled state, [kind](Connection::kind) and [params](Connection::params)
+    /// taken in innovation order, and its per-node params. Params are quantized to
+    /// [FINGERPRINT_RESOLUTION](Genome::FINGERPRINT_RESOLUTION) first, so that genomes differing
+    /// only by float noise usually share a fingerprint. Quantizing rounds to the nearest step, so
+    /// two params that straddle a rounding boundary ( a half step ) land on different steps and
+    /// fingerprint differently, however close they are.
+    ///
+    /// Unlike [phenotype_cache_key](Genome::phenotype_cache_key), the fingerprint is stable: it
+    /// does not depend on the order that genes are stored in, and it is the same between runs,
+    /// processes and platforms, so it may be persisted alongside genomes. It may change between
+    /// releases of this crate that change what a genome holds. Equal genomes have equal
+    /// fingerprints, but as with any hash, equal fingerprints don't guarantee equal genomes.
+    fn fingerprint(&self) -> u64 {
+        let quantize = |x: f64| (x / Self::FINGERPRINT_RESOLUTION).round() as i64 as u64;
+        let mut h = fxhash::FxHasher64::default();
+        h.write_u64(self.sensory().len() as u64);
+        h.write_u64(self.action().len() as u64);
+        for node in self.nodes() {
+            h.write_u8(match node {
+                NodeKind::Sensory => 0,
+                NodeKind::Action => 1,
+                NodeKind::Internal => 2,
+                NodeKind::Static => 3,
+            });
+        }
+        for id in self.node_activations().unwrap_or_default() {
+            h.write_u64(*id as u64);
+        }
+
+        let mut connections = self.connections().iter().collect::<Vec<_>>();
+        connections.sort_unstable_by_key(|c| c.inno());
+        for c in connections {
+            h.write_u64(c.inno() as u64);
+            h.write_u8(c.enabled() as u8);
+            h.write_u8(c.kind());
+            for param in c.params() {
+                h.write_u64(quantize(param));
+            }
+        }
+
+        let params = self.params();
+        for param in &params[self.connections().len() * C::PARAMS..] {
+            h.write_u64(quantize(*param));
+        }
+        h.finish()
     }
 
     /// Perform crossover reproduction with other, where our fitness is `fitness_cmp` compared to other
     fn reproduce_with(&self, other: &Self, fitness_cmp: Ordering, rng: &mut impl RngCore) -> Self;
 
+    /// Like [reproduce_with](Genome::reproduce_with), but inheriting connections matched in both
+    /// parents with `probabilities` rather than
+    /// [CROSSOVER_PROBABILITIES](Connection::CROSSOVER_PROBABILITIES). Genomes who don't override
+    /// this ignore `probabilities`, and reproduce as [reproduce_with](Genome::reproduce_with) does
+    fn reproduce_configured(
+        &self,
+        other: &Self,
+        fitness_cmp: Ordering,
+        probabilities: CrossoverProbabilities,
+        rng: &mut impl RngCore,
+    ) -> Self {
+        let _ = probabilities;
+        self.reproduce_with(other, fitness_cmp, rng)
+    }
+
+    /// Like [reproduce_configured](Genome::reproduce_configured), but crossing connections over
+    /// into `scratch` and giving its buffer to the child, leaving `scratch` empty. A caller
+    /// reproducing many offspring may hand a fresh buffer with room to spare to each, so that
+    /// neither crossover nor the mutation after it need grow one. Genomes who don't override this
+    /// ignore `scratch`, and reproduce as [reproduce_configured](Genome::reproduce_configured)
+    /// does
+    fn reproduce_buffered(
+        &self,
+        other: &Self,
+        fitness_cmp: Ordering,
+        probabilities: CrossoverProbabilities,
+        rng: &mut impl RngCore,
+        scratch: &mut Vec<C>,
+    ) -> Self {
+        let _ = scratch;
+        self.reproduce_configured(other, fitness_cmp, probabilities, rng)
+    }
+
     /// Serialize this genome to a JSON string
     fn to_string(&self) -> Result<String, Box<dyn Error>> {
         Ok(serde_json::to_string(self)?)
     }
 
-    /// Deserialize this genome from a JSON string
+    /// Serialize this genome to an indented JSON string, for reading and editing by hand. Such
+    /// edits may be checked with [validate](crate::serialize::validate).
+    fn to_string_pretty(&self) -> Result<String, Box<dyn Error>> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Deserialize this genome from a JSON string, also accepting the older `CTRGenome` layout
     #[allow(clippy::should_implement_trait)]
     fn from_str(s: &str) -> Result<Self, Box<dyn Error>> {
-        serde_json::from_str(s).map_err(|op| op.into())
+        serde_json::from_str(s).or_else(|op| {
+            crate::serialize::from_legacy_str(s).map_err(|_| Box::<dyn Error>::from(op))
+        })
     }
 
+    #[cfg(feature = "fs")]
     fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
         fs::write(path, self.to_string()?)?;
         Ok(())
     }
 
+    /// Like [to_file](Genome::to_file), but indented like
+    /// [to_string_pretty](Genome::to_string_pretty)
+    #[cfg(feature = "fs")]
+    fn to_file_pretty<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        fs::write(path, self.to_string_pretty()?)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "fs")]
     fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
         Self::from_str(&fs::read_to_string(path)?)
     }
@@ -1,12 +1,20 @@
-use super::{Connection, Genome, NodeKind};
+use super::{
+    feedforward::open_acyclic_path, Affine, Connection, Genome, IoLabels, Module, NodeKind,
+    Recurrence,
+};
 use crate::{
-    crossover::crossover,
+    crossover::crossover_with_into,
+    random::CrossoverProbabilities,
     serialize::{deserialize_connections, deserialize_nodes},
 };
-use core::cmp::{max, Ordering};
-use rand::{seq::IteratorRandom, RngCore};
+use core::{
+    cmp::{max, Ordering},
+    error::Error,
+};
+use fxhash::FxHashSet;
+use rand::{seq::IteratorRandom, Rng, RngCore};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::{collections::HashSet, sync::OnceLock};
 
 /// A genome that allows recurrent connections
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +25,174 @@ pub struct Recurrent<C: Connection> {
     nodes: Vec<NodeKind>,
     #[serde(deserialize_with = "deserialize_connections")]
     connections: Vec<C>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    output: Option<Vec<Affine>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    activation: Option<Vec<usize>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time_constant: Option<Vec<f64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bias: Option<Vec<f64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gain: Option<Vec<f64>>,
+    #[serde(default, skip_serializing_if = "Recurrence::is_any")]
+    recurrence: Recurrence,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    modules: Vec<Module>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sigma: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    labels: Option<IoLabels>,
+    /// Sensory nodes grown by [extend_io](Genome::extend_io), see
+    /// [io_appended](Genome::io_appended)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sensory_appended: Vec<usize>,
+    /// Action nodes grown by [extend_io](Genome::extend_io), see
+    /// [io_appended](Genome::io_appended)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    action_appended: Vec<usize>,
+    /// Destinations of every connection leaving each node, built by the first call to
+    /// [open_path](Genome::open_path) and kept up to date by
+    /// [push_connection](Genome::push_connection) after that. Editing weights or other params
+    /// through [connections_mut](Genome::connections_mut) keeps it, and moving a connection onto
+    /// another path is seen by the next call, which won't trust it until it's rebuilt.
+    #[serde(skip)]
+    outgoing: OnceLock<Outgoing>,
+}
+
+/// Destinations of every connection leaving each node, along with the path that each connection
+/// took when they were built, so that a connection moved since can be told apart.
+#[derive(Debug, Default, Clone)]
+struct Outgoing {
+    paths: Vec<(usize, usize)>,
+    to: Vec<FxHashSet<usize>>,
+}
+
+impl Outgoing {
+    fn new<C: Connection>(connections: &[C]) -> Self {
+        let mut outgoing = Self::default();
+        for c in connections {
+            outgoing.push(c.path());
+        }
+        outgoing
+    }
+
+    fn push(&mut self, (from, to): (usize, usize)) {
+        if self.to.len() <= from {
+            self.to.resize_with(from + 1, FxHashSet::default);
+        }
+        self.to[from].insert(to);
+        self.paths.push((from, to));
+    }
+
+    /// Whether every one of `connections` still takes the path that it did when this was built
+    fn fresh<C: Connection>(&self, connections: &[C]) -> bool {
+        self.paths.len() == connections.len()
+            && self
+                .paths
+                .iter()
+                .zip(connections)
+                .all(|(path, c)| *path == c.path())
+    }
+}
+
+impl<C: Connection> Recurrent<C> {
+    /// Give this genome an identity [Affine] gene for every action, which will then be evolved
+    /// alongside its connections.
+    pub fn with_output_affine(mut self) -> Self {
+        self.output = Some(vec![Affine::default(); self.outputs()]);
+        self
+    }
+
+    /// Give every node the first of [ACTIVATIONS](Genome::ACTIVATIONS), which will then be
+    /// evolved alongside its connections. Nodes added later start out with it as well.
+    pub fn with_node_activations(mut self) -> Self {
+        self.activation = Some(vec![Self::ACTIVATIONS[0]; self.nodes.len()]);
+        self
+    }
+
+    /// Give every node [TIME_CONSTANT](Genome::TIME_CONSTANT), which will then be evolved
+    /// alongside its connections. Nodes added later start out with it as well.
+    pub fn with_node_time_constants(mut self) -> Self {
+        self.time_constant = Some(vec![Self::TIME_CONSTANT; self.nodes.len()]);
+        self
+    }
+
+    /// Give every node a bias of 0, which will then be evolved alongside its connections. Nodes
+    /// added later start out with it as well.
+    pub fn with_node_biases(mut self) -> Self {
+        self.bias = Some(vec![0.; self.nodes.len()]);
+        self
+    }
+
+    /// Give every sensory node a gain of 1, which will then be evolved alongside its connections.
+    /// See [sensory_gains](Genome::sensory_gains).
+    pub fn with_sensory_gains(mut self) -> Self {
+        self.gain = Some(vec![1.; self.inputs()]);
+        self
+    }
+
+    /// Label this genome's sensory and action nodes, see [io_labels](Genome::io_labels). Panics
+    /// if there aren't as many labels as there are nodes of each.
+    pub fn with_io_labels(mut self, labels: IoLabels) -> Self {
+        assert_eq!(
+            labels.sensory.len(),
+            self.inputs(),
+            "wrong count of sensory labels"
+        );
+        assert_eq!(
+            labels.action.len(),
+            self.outputs(),
+            "wrong count of action labels"
+        );
+        self.labels = Some(labels);
+        self
+    }
+
+    /// Give this genome its own weight-perturbation sigma, starting at `sigma`, which will then
+    /// self-adapt alongside its connections. See [mutation_sigma](Genome::mutation_sigma).
+    pub fn with_mutation_sigma(mut self, sigma: f64) -> Self {
+        assert!(sigma > 0., "sigma must be positive, got {sigma}");
+        self.sigma = Some(sigma);
+        self
+    }
+
+    /// Restrict which paths new connections may take to `recurrence`, for this genome and its
+    /// offspring.
+    pub fn with_recurrence(mut self, recurrence: Recurrence) -> Self {
+        self.recurrence = recurrence;
+        self
+    }
+
+    /// Per-node genes of a child with `size` nodes, from parents who each hold `Some` genes or
+    /// none for however many nodes they have. A node that both parents have is picked from either,
+    /// and one only a single parent has comes from them. A parent without genes holds `default` for
+    /// each of its nodes, and the child has genes if either parent does.
+    fn inherit_node_genes<T: Copy>(
+        (l, l_nodes): (Option<&[T]>, usize),
+        (r, r_nodes): (Option<&[T]>, usize),
+        size: usize,
+        default: T,
+        rng: &mut impl RngCore,
+    ) -> Option<Vec<T>> {
+        if l.is_none() && r.is_none() {
+            return None;
+        }
+
+        let gene = |genes: Option<&[T]>, nodes: usize, idx: usize| match genes {
+            Some(genes) => genes.get(idx).copied(),
+            None => (idx < nodes).then_some(default),
+        };
+        Some(
+            (0..size)
+                .map(|idx| match (gene(l, l_nodes, idx), gene(r, r_nodes, idx)) {
+                    (Some(_), Some(r)) if rng.next_u64() < C::PROBABILITY_PICK_RL => r,
+                    (Some(l), _) => l,
+                    (None, r) => r.unwrap_or(default),
+                })
+                .collect(),
+        )
+    }
 }
 
 impl<C: Connection> Genome<C> for Recurrent<C> {
@@ -36,6 +212,18 @@ impl<C: Connection> Genome<C> for Recurrent<C> {
                 action,
                 nodes,
                 connections: vec![],
+                output: None,
+                activation: None,
+                time_constant: None,
+                bias: None,
+                gain: None,
+                recurrence: Recurrence::Any,
+                modules: vec![],
+                sigma: None,
+                labels: None,
+                sensory_appended: vec![],
+                action_appended: vec![],
+                outgoing: OnceLock::new(),
             },
             (sensory + 1) * action,
         )
@@ -49,6 +237,10 @@ impl<C: Connection> Genome<C> for Recurrent<C> {
         self.sensory..self.sensory + self.action
     }
 
+    fn io_appended(&self) -> (&[usize], &[usize]) {
+        (&self.sensory_appended, &self.action_appended)
+    }
+
     fn nodes(&self) -> &[NodeKind] {
         &self.nodes
     }
@@ -59,6 +251,15 @@ impl<C: Connection> Genome<C> for Recurrent<C> {
 
     fn push_node(&mut self, node: NodeKind) {
         self.nodes.push(node);
+        if let Some(activation) = self.activation.as_mut() {
+            activation.push(Self::ACTIVATIONS[0]);
+        }
+        if let Some(time_constant) = self.time_constant.as_mut() {
+            time_constant.push(Self::TIME_CONSTANT);
+        }
+        if let Some(bias) = self.bias.as_mut() {
+            bias.push(0.);
+        }
     }
 
     fn connections(&self) -> &[C] {
@@ -70,60 +271,309 @@ impl<C: Connection> Genome<C> for Recurrent<C> {
     }
 
     fn push_connection(&mut self, connection: C) {
+        match self.outgoing.get_mut() {
+            Some(outgoing) if outgoing.fresh(&self.connections) => outgoing.push(connection.path()),
+            Some(_) => drop(self.outgoing.take()),
+            None => {}
+        }
         self.connections.push(connection);
     }
 
+    fn retain_nodes(&mut self, keep: &[bool]) {
+        debug_assert_eq!(keep.len(), self.nodes.len());
+        self.outgoing.take();
+        let mut renumbered = Vec::with_capacity(keep.len());
+        let mut head = 0;
+        for (node, keep) in self.nodes.iter().zip(keep) {
+            debug_assert!(*keep || matches!(node, NodeKind::Internal));
+            renumbered.push(keep.then_some(head));
+            head += *keep as usize;
+        }
+
+        let mut idx = 0;
+        self.nodes.retain(|_| {
+            idx += 1;
+            keep[idx - 1]
+        });
+        if let Some(activation) = self.activation.as_mut() {
+            let mut idx = 0;
+            activation.retain(|_| {
+                idx += 1;
+                keep[idx - 1]
+            });
+        }
+        if let Some(time_constant) = self.time_constant.as_mut() {
+            let mut idx = 0;
+            time_constant.retain(|_| {
+                idx += 1;
+                keep[idx - 1]
+            });
+        }
+        if let Some(bias) = self.bias.as_mut() {
+            let mut idx = 0;
+            bias.retain(|_| {
+                idx += 1;
+                keep[idx - 1]
+            });
+        }
+        for module in self.modules.iter_mut() {
+            module
+                .nodes
+                .retain_mut(|node| renumbered[node.0].map(|idx| node.0 = idx).is_some());
+        }
+        self.modules.retain(|module| !module.nodes.is_empty());
+        for node in self
+            .sensory_appended
+            .iter_mut()
+            .chain(self.action_appended.iter_mut())
+        {
+            *node = renumbered[*node].expect("io nodes are kept");
+        }
+        self.connections
+            .retain_mut(|c| match (renumbered[c.from()], renumbered[c.to()]) {
+                (Some(from), Some(to)) => {
+                    c.set_path((from, to));
+                    true
+                }
+                _ => false,
+            });
+    }
+
+    fn output_affine(&self) -> Option<&[Affine]> {
+        self.output.as_deref()
+    }
+
+    fn output_affine_mut(&mut self) -> Option<&mut [Affine]> {
+        self.output.as_deref_mut()
+    }
+
+    fn set_io_boundary(&mut self, sensory: usize) -> Result<(), Box<dyn Error>> {
+        let io = self.sensory + self.action;
+        if sensory > io {
+            return Err(format!("boundary {sensory} is past {io} io nodes").into());
+        }
+        if !(self.sensory_appended.is_empty() && self.action_appended.is_empty()) {
+            return Err("boundary can't move once io is appended".into());
+        }
+        for (idx, node) in self.nodes[..io].iter_mut().enumerate() {
+            *node = if idx < sensory {
+                NodeKind::Sensory
+            } else {
+                NodeKind::Action
+            };
+        }
+        if let Some(output) = self.output.as_mut() {
+            while output.len() > io - sensory {
+                output.remove(0);
+            }
+            while output.len() < io - sensory {
+                output.insert(0, Affine::default());
+            }
+        }
+        if let Some(gain) = self.gain.as_mut() {
+            gain.resize(sensory, 1.);
+        }
+        if let Some(labels) = self.labels.as_mut() {
+            labels.sensory.append(&mut labels.action);
+            labels.action = labels.sensory.split_off(sensory);
+        }
+        (self.sensory, self.action) = (sensory, io - sensory);
+        Ok(())
+    }
+
+    fn append_io(&mut self, sensory: usize, action: usize) -> Result<(), Box<dyn Error>> {
+        for _ in 0..sensory {
+            self.sensory_appended.push(self.nodes.len());
+            self.push_node(NodeKind::Sensory);
+        }
+        for _ in 0..action {
+            self.action_appended.push(self.nodes.len());
+            self.push_node(NodeKind::Action);
+        }
+        if let Some(gain) = self.gain.as_mut() {
+            gain.extend((0..sensory).map(|_| 1.));
+        }
+        if let Some(output) = self.output.as_mut() {
+            output.extend((0..action).map(|_| Affine::default()));
+        }
+        if let Some(labels) = self.labels.as_mut() {
+            labels.sensory.extend((0..sensory).map(|_| String::new()));
+            labels.action.extend((0..action).map(|_| String::new()));
+        }
+        Ok(())
+    }
+
+    fn retain_connections(&mut self, keep: &[bool]) {
+        debug_assert_eq!(keep.len(), self.connections.len());
+        self.outgoing.take();
+        let mut idx = 0;
+        self.connections.retain(|_| {
+            idx += 1;
+            keep[idx - 1]
+        });
+    }
+
+    fn node_activations(&self) -> Option<&[usize]> {
+        self.activation.as_deref()
+    }
+
+    fn node_activations_mut(&mut self) -> Option<&mut [usize]> {
+        self.activation.as_deref_mut()
+    }
+
+    fn node_time_constants(&self) -> Option<&[f64]> {
+        self.time_constant.as_deref()
+    }
+
+    fn node_time_constants_mut(&mut self) -> Option<&mut [f64]> {
+        self.time_constant.as_deref_mut()
+    }
+
+    fn recurrence(&self) -> Recurrence {
+        self.recurrence
+    }
+
+    fn node_biases(&self) -> Option<&[f64]> {
+        self.bias.as_deref()
+    }
+
+    fn node_biases_mut(&mut self) -> Option<&mut [f64]> {
+        self.bias.as_deref_mut()
+    }
+
+    fn sensory_gains(&self) -> Option<&[f64]> {
+        self.gain.as_deref()
+    }
+
+    fn sensory_gains_mut(&mut self) -> Option<&mut [f64]> {
+        self.gain.as_deref_mut()
+    }
+
+    fn mutation_sigma(&self) -> Option<f64> {
+        self.sigma
+    }
+
+    fn mutation_sigma_mut(&mut self) -> Option<&mut f64> {
+        self.sigma.as_mut()
+    }
+
+    fn io_labels(&self) -> Option<&IoLabels> {
+        self.labels.as_ref()
+    }
+
+    fn io_labels_mut(&mut self) -> Option<&mut IoLabels> {
+        self.labels.as_mut()
+    }
+
+    fn modules(&self) -> &[Module] {
+        &self.modules
+    }
+
+    fn modules_mut(&mut self) -> Option<&mut Vec<Module>> {
+        Some(&mut self.modules)
+    }
+
     fn open_path(&self, rng: &mut impl RngCore) -> Option<(usize, usize)> {
-        let mut saturated = HashSet::new();
-        loop {
-            let (from, _) = self
-                .nodes()
-                .iter()
-                .enumerate()
-                .filter(|(from, node)| {
-                    !matches!(node, NodeKind::Action) && !saturated.contains(from)
-                })
-                .choose(rng)?;
+        if self.recurrence == Recurrence::Acyclic {
+            return open_acyclic_path(self, rng);
+        }
 
-            let exclude = self
-                .connections
-                .iter()
-                .filter_map(|c| (c.from() == from).then_some(c.to()))
-                .collect::<HashSet<_>>();
+        let rebuilt;
+        let outgoing = match self
+            .outgoing
+            .get_or_init(|| Outgoing::new(&self.connections))
+        {
+            outgoing if outgoing.fresh(&self.connections) => outgoing,
+            _ => {
+                rebuilt = Outgoing::new(&self.connections);
+                &rebuilt
+            }
+        };
+        let no_self_loops = self.recurrence == Recurrence::NoSelfLoops;
+
+        let (mut sources, mut targets) = (vec![], vec![]);
+        for (idx, node) in self.nodes.iter().enumerate() {
+            if !matches!(node, NodeKind::Action) {
+                sources.push(idx);
+            }
+            if !matches!(node, NodeKind::Static | NodeKind::Sensory) {
+                targets.push(idx);
+            }
+        }
 
-            if let Some((to, _)) = self
-                .nodes()
+        // draw sources until one has an open target, dropping the saturated ones
+        while !sources.is_empty() {
+            let pick = rng.random_range(0..sources.len());
+            let from = sources[pick];
+            let exclude = outgoing.to.get(from);
+            if let Some(&to) = targets
                 .iter()
-                .enumerate()
-                .filter(|(to, node)| {
-                    !matches!(node, NodeKind::Static | NodeKind::Sensory) && !exclude.contains(to)
+                .filter(|&&to| {
+                    !(no_self_loops && to == from)
+                        && !exclude.is_some_and(|exclude| exclude.contains(&to))
                 })
                 .choose(rng)
             {
-                break Some((from, to));
+                return Some((from, to));
             }
-
-            saturated.insert(from);
+            sources.swap_remove(pick);
         }
+        None
     }
 
     fn reproduce_with(&self, other: &Self, self_fit: Ordering, rng: &mut impl RngCore) -> Self {
-        let connections = crossover(&self.connections, &other.connections, self_fit, rng);
+        self.reproduce_configured(other, self_fit, C::CROSSOVER_PROBABILITIES, rng)
+    }
+
+    fn reproduce_configured(
+        &self,
+        other: &Self,
+        self_fit: Ordering,
+        probabilities: CrossoverProbabilities,
+        rng: &mut impl RngCore,
+    ) -> Self {
+        self.reproduce_buffered(other, self_fit, probabilities, rng, &mut Vec::new())
+    }
+
+    fn reproduce_buffered(
+        &self,
+        other: &Self,
+        self_fit: Ordering,
+        probabilities: CrossoverProbabilities,
+        rng: &mut impl RngCore,
+        scratch: &mut Vec<C>,
+    ) -> Self {
+        crossover_with_into(
+            &self.connections,
+            &other.connections,
+            self_fit,
+            probabilities,
+            rng,
+            scratch,
+        );
+        let connections = core::mem::take(scratch);
         let nodes_size = connections
             .iter()
             .fold(0, |prev, c| max(prev, max(c.from(), c.to())));
 
-        let mut nodes = Vec::with_capacity(self.sensory + self.action + 1);
-        for _ in 0..self.sensory {
-            nodes.push(NodeKind::Sensory);
-        }
-        for _ in self.sensory..self.sensory + self.action {
-            nodes.push(NodeKind::Action);
-        }
-        nodes.push(NodeKind::Static);
-        for _ in self.sensory + self.action..nodes_size {
-            nodes.push(NodeKind::Internal);
-        }
+        // each node is of the kind it is in whichever parent has it, so that converted nodes
+        // stay converted. The child's io is ours, so io that only the other parent appended is
+        // internal to the child
+        let held = self
+            .sensory_appended
+            .iter()
+            .chain(self.action_appended.iter())
+            .fold(self.sensory + self.action + 1, |held, idx| {
+                max(held, idx + 1)
+            });
+        let nodes = (0..max(nodes_size + 1, held))
+            .map(|idx| match (self.nodes.get(idx), other.nodes.get(idx)) {
+                (Some(node), _) => *node,
+                (None, Some(NodeKind::Sensory | NodeKind::Action)) => NodeKind::Internal,
+                (None, Some(node)) => *node,
+                (None, None) => NodeKind::Internal,
+            })
+            .collect::<Vec<_>>();
 
         debug_assert!(
             connections
@@ -132,19 +582,120 @@ impl<C: Connection> Genome<C> for Recurrent<C> {
                 < nodes.len()
         );
 
-        Self {
+        // node genes are aligned by index, and a parent without some gene is as though each of its
+        // nodes held the default, so that genes evolved in either parent survive
+        let (l_nodes, r_nodes) = (self.nodes.len(), other.nodes.len());
+        let output = Self::inherit_node_genes(
+            (self.output.as_deref(), self.outputs()),
+            (other.output.as_deref(), other.outputs()),
+            self.outputs(),
+            Affine::default(),
+            rng,
+        );
+        let activation = Self::inherit_node_genes(
+            (self.activation.as_deref(), l_nodes),
+            (other.activation.as_deref(), r_nodes),
+            nodes.len(),
+            0,
+            rng,
+        );
+        let time_constant = Self::inherit_node_genes(
+            (self.time_constant.as_deref(), l_nodes),
+            (other.time_constant.as_deref(), r_nodes),
+            nodes.len(),
+            Self::TIME_CONSTANT,
+            rng,
+        );
+        let bias = Self::inherit_node_genes(
+            (self.bias.as_deref(), l_nodes),
+            (other.bias.as_deref(), r_nodes),
+            nodes.len(),
+            0.,
+            rng,
+        );
+        let gain = Self::inherit_node_genes(
+            (self.gain.as_deref(), self.inputs()),
+            (other.gain.as_deref(), other.inputs()),
+            self.inputs(),
+            1.,
+            rng,
+        );
+
+        // modules by name, from the fitter parent first, keeping the internal nodes that the child
+        // has and that no module before has claimed
+        let (fitter, weaker) = match self_fit {
+            Ordering::Less => (other, self),
+            _ => (self, other),
+        };
+        let mut claimed = HashSet::new();
+        let mut modules: Vec<Module> = Vec::new();
+        for module in fitter.modules.iter().chain(weaker.modules.iter()) {
+            if modules.iter().any(|m| m.name == module.name) {
+                continue;
+            }
+            let nodes = module
+                .nodes
+                .iter()
+                .filter(|node| nodes.get(node.0) == Some(&NodeKind::Internal))
+                .filter(|node| claimed.insert(**node))
+                .copied()
+                .collect::<Vec<_>>();
+            if !nodes.is_empty() {
+                modules.push(Module {
+                    name: module.name.clone(),
+                    nodes,
+                });
+            }
+        }
+
+        // and a sigma from either parent who has one
+        let sigma = match (self.sigma, other.sigma) {
+            (Some(_), Some(r)) if rng.next_u64() < C::PROBABILITY_PICK_RL => Some(r),
+            (l, r) => l.or(r),
+        };
+
+        let mut child = Self {
             sensory: self.sensory,
             action: self.action,
             nodes,
             connections,
+            output,
+            activation,
+            time_constant,
+            bias,
+            gain,
+            recurrence: self.recurrence,
+            modules,
+            sigma,
+            labels: self.labels.clone().or_else(|| other.labels.clone()),
+            sensory_appended: self.sensory_appended.clone(),
+            action_appended: self.action_appended.clone(),
+            outgoing: OnceLock::new(),
+        };
+        if child.recurrence == Recurrence::Acyclic {
+            for (idx, recurrent) in child.recurrent().into_iter().enumerate() {
+                if recurrent {
+                    child.connections[idx].disable();
+                }
+            }
         }
+        child
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{genome::InnoGen, genome::WConnection, random::default_rng, test_t};
+    use crate::{
+        activate, assert_f64_approx, assert_matrix_approx,
+        genome::{
+            Complexity, ConnId, Constraints, InnoGen, MutationConfig, MutationParams, NodeId,
+            Perturbation, Removed, WConnection, WeightInit,
+        },
+        network::{Continuous, FromGenome, Network, ToNetwork},
+        random::{default_rng, percent},
+        test_t,
+    };
 
     type C = WConnection;
     type RecurrentContinuous = Recurrent<C>;
@@ -231,7 +782,7 @@ mod test {
         genome.push_connection(C::new(1, 2, &mut inno));
 
         let before = genome.clone();
-        genome.new_connection(&mut default_rng(), &mut inno);
+        genome.new_connection(&mut default_rng(), &mut inno).unwrap();
 
         assert_eq!(genome.connections().len(), before.connections().len() + 1);
 
@@ -241,6 +792,36 @@ mod test {
         assert_eq!(tail.weight(), 1.);
     });
 
+    test_t!(
+    test_open_path_saturates[T: RecurrentContinuous]() {
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = T::new(2, 2);
+        genome.push_node(NodeKind::Internal);
+        let paths = |genome: &T| {
+            let paths = genome
+                .connections()
+                .iter()
+                .map(|c| c.path())
+                .collect::<HashSet<_>>();
+            assert_eq!(paths.len(), genome.connections().len());
+            paths.len()
+        };
+
+        // 4 nodes lead into 3 nodes
+        while genome.new_connection(&mut rng, &mut inno).is_ok() {}
+        assert_eq!(paths(&genome), 12);
+
+        // paths freed by removal open back up, as do those of a decoded genome
+        genome.retain_connections(&(0..12).map(|idx| idx % 2 == 0).collect::<Vec<_>>());
+        let mut decoded = T::from_str(&genome.to_string().unwrap()).unwrap();
+        for genome in [&mut genome, &mut decoded] {
+            assert!(genome.open_path(&mut rng).is_some());
+            while genome.new_connection(&mut rng, &mut inno).is_ok() {}
+            assert_eq!(paths(genome), 12);
+        }
+    });
+
     test_t!(
     test_mutate_bisection[T: RecurrentContinuous]() {
         let mut inno = InnoGen::new(0);
@@ -253,7 +834,7 @@ mod test {
         });
 
         let innogen = &mut InnoGen::new(1);
-        genome.bisect_connection(&mut default_rng(), innogen);
+        genome.bisect_connection(&mut default_rng(), innogen).unwrap();
 
         assert!(!genome.connections()[0].enabled);
 
@@ -285,17 +866,1058 @@ mod test {
     });
 
     test_t!(
-    #[should_panic(expected = "no connections available to bisect")]
     test_mutate_bisection_empty_genome[T: RecurrentContinuous]() {
         let (mut genome, _) = T::new(0, 0);
-        genome.bisect_connection(&mut default_rng(), &mut InnoGen::new(0));
+        let err = genome
+            .bisect_connection(&mut default_rng(), &mut InnoGen::new(0))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "no connections available to bisect");
+        assert_eq!(genome.nodes().len(), 1);
     });
 
     test_t!(
-    #[should_panic(expected = "no connections available to bisect")]
     test_mutate_bisection_no_connections[T: RecurrentContinuous]() {
         let (mut genome, _) = T::new(2, 2);
         genome.connections = vec![];
-        genome.bisect_connection(&mut default_rng(), &mut InnoGen::new(0));
+        genome
+            .bisect_connection(&mut default_rng(), &mut InnoGen::new(0))
+            .unwrap_err();
+        assert_eq!(genome.nodes().len(), 5);
     });
+
+    test_t!(
+    test_mutate_saturated[T: RecurrentContinuous]() {
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = T::new(1, 1);
+        for from in 0..3 {
+            genome.push_connection(C::new(from, 1, &mut inno));
+        }
+        let err = genome.new_connection(&mut rng, &mut inno).unwrap_err();
+        assert_eq!(err.to_string(), "connections on genome are fully saturated");
+        assert_eq!(genome.connections().len(), 3);
+
+        // mutation carries on regardless
+        for _ in 0..20 {
+            genome.mutate_with(&mut rng, &mut inno, [percent(100), 0, 0, 0, 0]);
+        }
+        assert_eq!(genome.connections().len(), 3);
+    });
+
+    test_t!(
+    test_phenotype_cache_key[T: RecurrentContinuous]() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = T::new(2, 1);
+        genome.push_connection(C::new(0, 2, &mut inno));
+        let key = genome.phenotype_cache_key();
+
+        let mut relabeled = genome.clone();
+        relabeled.connections[0].inno = 10;
+        relabeled.push_connection({
+            let mut c = C::new(1, 2, &mut inno);
+            c.disable();
+            c
+        });
+        assert_eq!(key, relabeled.phenotype_cache_key());
+
+        let mut reweighted = genome.clone();
+        reweighted.connections[0].weight = 0.5;
+        assert_ne!(key, reweighted.phenotype_cache_key());
+
+        genome.push_connection(C::new(1, 2, &mut inno));
+        assert_ne!(key, genome.phenotype_cache_key());
+
+        // held in another order, the same connections decode into the same network
+        let key = genome.phenotype_cache_key();
+        let mut reordered = genome.clone();
+        reordered.connections.reverse();
+        assert_eq!(key, reordered.phenotype_cache_key());
+    });
+
+    test_t!(
+    test_phenotype_eq[T: RecurrentContinuous]() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = T::new(2, 1);
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.push_connection(C::new(1, 2, &mut inno));
+        assert!(genome.phenotype_eq(&genome.clone()));
+
+        let mut other = genome.clone();
+        other.connections.reverse();
+        other.connections[0].inno = 10;
+        other.push_connection({
+            let mut c = C::new(2, 2, &mut inno);
+            c.disable();
+            c
+        });
+        assert!(genome.phenotype_eq(&other));
+        assert!(other.phenotype_eq(&genome));
+
+        other.connections[2].enable();
+        assert!(!genome.phenotype_eq(&other));
+
+        let mut reweighted = genome.clone();
+        reweighted.connections[0].weight += 0.5;
+        assert!(!genome.phenotype_eq(&reweighted));
+
+        let mut grown = genome.clone();
+        grown.push_node(NodeKind::Internal);
+        assert!(!genome.phenotype_eq(&grown));
+    });
+
+    test_t!(
+    test_fingerprint[T: RecurrentContinuous]() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = T::new(2, 1);
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.push_connection(C::new(1, 2, &mut inno));
+        let fingerprint = genome.fingerprint();
+        assert_eq!(fingerprint, genome.clone().fingerprint());
+
+        let mut reordered = genome.clone();
+        reordered.connections.reverse();
+        assert_eq!(fingerprint, reordered.fingerprint());
+
+        let mut noisy = genome.clone();
+        noisy.connections[0].weight += T::FINGERPRINT_RESOLUTION / 10.;
+        assert_eq!(fingerprint, noisy.fingerprint());
+
+        let mut reweighted = genome.clone();
+        reweighted.connections[0].weight += 0.5;
+        assert_ne!(fingerprint, reweighted.fingerprint());
+
+        let mut disabled = genome.clone();
+        disabled.connections[1].disable();
+        assert_ne!(fingerprint, disabled.fingerprint());
+
+        let mut relabeled = genome.clone();
+        relabeled.connections[1].inno = 10;
+        assert_ne!(fingerprint, relabeled.fingerprint());
+
+        let mut biased = genome.clone().with_node_biases();
+        let unbiased = biased.fingerprint();
+        biased.node_biases_mut().unwrap()[2] = 0.5;
+        assert_ne!(unbiased, biased.fingerprint());
+    });
+
+    test_t!(
+    test_pruned[T: RecurrentContinuous]() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = T::new(2, 1);
+        // 0 -> 4 -> 2 reaches the action, 1 -> 5 is a dead end, and 6 -> 2 only has no input
+        genome.push_node(NodeKind::Internal);
+        genome.push_node(NodeKind::Internal);
+        genome.push_node(NodeKind::Internal);
+        genome.push_connection(C::new(0, 4, &mut inno));
+        genome.push_connection(C::new(4, 2, &mut inno));
+        genome.push_connection(C::new(1, 5, &mut inno));
+        genome.push_connection(C::new(6, 2, &mut inno));
+
+        assert_eq!(
+            genome.reaches_action(),
+            vec![true, false, true, false, true, false, true]
+        );
+        let pruned = genome.pruned().unwrap();
+        assert_eq!(pruned.nodes().len(), 6);
+        assert_eq!(
+            pruned.connections().iter().map(|c| c.path()).collect::<Vec<_>>(),
+            vec![(0, 4), (4, 2), (5, 2)]
+        );
+        assert!(pruned.pruned().is_none());
+    });
+
+    test_t!(
+    test_simplify[T: RecurrentContinuous]() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = T::new(2, 1);
+        // 0 -> 4 -> 2 is enabled, and 1 -> 5 -> 2 isn't, nor is 0 -> 2 through 6
+        genome.push_node(NodeKind::Internal);
+        genome.push_node(NodeKind::Internal);
+        genome.push_node(NodeKind::Internal);
+        for path in [(0, 4), (4, 2), (1, 5), (5, 2), (0, 6), (6, 2), (3, 6)] {
+            genome.push_connection(C::new(path.0, path.1, &mut inno));
+        }
+        for idx in [2, 3, 4] {
+            genome.connections[idx].disable();
+        }
+
+        let mut nn = Continuous::from_genome(&genome);
+        let removed = genome.simplify();
+        assert_eq!(removed.connections, vec![2, 3, 4]);
+        assert_eq!(removed.nodes, vec![NodeId(5)]);
+        assert_eq!(
+            genome.connections().iter().map(|c| c.path()).collect::<Vec<_>>(),
+            vec![(0, 4), (4, 2), (5, 2), (3, 5)]
+        );
+        assert_eq!(genome.nodes().len(), 6);
+        assert!(genome.dangling().is_empty());
+
+        let mut simplified = Continuous::from_genome(&genome);
+        for _ in 0..3 {
+            nn.step(2, &[1., -1.], activate::relu);
+            simplified.step(2, &[1., -1.], activate::relu);
+        }
+        assert_matrix_approx!(nn.output(), simplified.output());
+        assert_eq!(genome.simplify(), Removed::default());
+    });
+
+    test_t!(
+    test_convert_node[T: RecurrentContinuous]() {
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(0);
+        let (genome, _) = T::new(3, 2);
+        let mut genome = genome
+            .with_output_affine()
+            .with_sensory_gains()
+            .with_io_labels(IoLabels::new(["a", "b", "c"], ["x", "y"]));
+        genome.output_affine_mut().unwrap()[1].offset = 0.5;
+        genome.sensory_gains_mut().unwrap()[2] = 2.;
+        genome.push_node(NodeKind::Internal);
+        genome.push_connection(C::new(5, 6, &mut inno));
+        genome.push_connection(C::new(6, 4, &mut inno));
+
+        // the last sensory node becomes the first action node, and back
+        assert!(genome.convert_node(NodeId(0), NodeKind::Action).is_err());
+        genome.convert_node(NodeId(2), NodeKind::Action).unwrap();
+        assert_eq!((genome.sensory(), genome.action()), (0..2, 2..5));
+        assert_eq!(genome.output_affine().unwrap().len(), 3);
+        assert_eq!(genome.output_affine().unwrap()[2].offset, 0.5);
+        assert_eq!(genome.sensory_gains(), Some(&[1., 1.][..]));
+        let labels = genome.io_labels().unwrap();
+        assert_eq!((labels.sensory.len(), labels.action[0].as_str()), (2, "c"));
+        genome.convert_node(NodeId(2), NodeKind::Sensory).unwrap();
+        assert_eq!((genome.sensory(), genome.action()), (0..3, 3..5));
+        assert_eq!(genome.output_affine().unwrap()[1].offset, 0.5);
+        assert_eq!(genome.sensory_gains(), Some(&[1.; 3][..]));
+        let labels = genome.io_labels().unwrap();
+        assert_eq!(labels, &IoLabels::new(["a", "b", "c"], ["x", "y"]));
+
+        // 4 has an incoming connection, and 6 can't leave the io range
+        assert!(genome.convert_node(NodeId(3), NodeKind::Sensory).is_ok());
+        assert!(genome.convert_node(NodeId(3), NodeKind::Action).is_ok());
+        genome.convert_node(NodeId(4), NodeKind::Sensory).unwrap_err();
+        genome.convert_node(NodeId(6), NodeKind::Action).unwrap_err();
+        genome.convert_node(NodeId(6), NodeKind::Static).unwrap_err();
+        genome.convert_node(NodeId(9), NodeKind::Static).unwrap_err();
+        assert_eq!((genome.sensory(), genome.action()), (0..3, 3..5));
+
+        // the bias becomes internal, and a new one takes its place
+        assert_eq!(genome.promote_static(&mut rng), Some(NodeId(5)));
+        assert_eq!(genome.nodes()[5], NodeKind::Internal);
+        assert_eq!(genome.nodes()[7], NodeKind::Static);
+        genome.push_connection(C::new(7, 5, &mut inno));
+        let child = genome.reproduce_with(&genome, Ordering::Greater, &mut rng);
+        assert_eq!(child.nodes(), genome.nodes());
+    });
+
+    test_t!(
+    test_extend_io[T: RecurrentContinuous]() {
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(0);
+        let (genome, _) = T::new(2, 1);
+        let mut genome = genome.with_output_affine().with_node_time_constants();
+        genome.push_node(NodeKind::Internal);
+        for path in [(0, 4), (4, 2), (1, 2), (3, 2)] {
+            genome.push_connection(C::new(path.0, path.1, &mut inno));
+        }
+        genome.connections[1].weight = 0.5;
+        genome.node_time_constants_mut().unwrap()[4] = 0.3;
+        let before = genome.clone();
+        let mut nn = Continuous::from_genome(&genome);
+
+        genome.extend_io(2, 1, &mut inno).unwrap();
+        assert_eq!((genome.sensory(), genome.action()), (0..2, 2..3));
+        assert_eq!(genome.io_appended(), (&[5, 6][..], &[7][..]));
+        assert_eq!(genome.sensory_nodes(), vec![0, 1, 5, 6]);
+        assert_eq!(genome.action_nodes(), vec![2, 7]);
+        assert_eq!((genome.inputs(), genome.outputs()), (4, 2));
+        assert_eq!(&genome.nodes()[..5], before.nodes());
+        assert_eq!(
+            &genome.nodes()[5..],
+            &[NodeKind::Sensory, NodeKind::Sensory, NodeKind::Action]
+        );
+        assert_eq!(genome.output_affine().unwrap().len(), 2);
+        assert_eq!(genome.node_time_constants().unwrap()[4], 0.3);
+        assert_eq!(&genome.connections()[..4], before.connections());
+        assert_eq!(
+            genome.connections()[4..]
+                .iter()
+                .map(|c| (c.inno(), c.path()))
+                .collect::<Vec<_>>(),
+            vec![
+                (4, (5, 2)),
+                (5, (5, 7)),
+                (6, (6, 2)),
+                (7, (6, 7)),
+                (8, (0, 7)),
+                (9, (1, 7)),
+            ]
+        );
+        assert!(genome.connections[4..].iter().all(|c| c.weight == 0.));
+
+        // the old action behaves as it did, whatever the new sensory nodes see
+        let mut extended = Continuous::from_genome(&genome);
+        for _ in 0..3 {
+            nn.step(2, &[1., -1.], activate::relu);
+            extended.step(2, &[1., -1., 5., 5.], activate::relu);
+            assert_eq!(extended.output().len(), 2);
+            assert_f64_approx!(nn.output()[0], extended.output()[0]);
+        }
+
+        // and offspring keep the io, along with nodes added after it
+        genome.bisect_connection(&mut rng, &mut inno).unwrap();
+        let child = genome.reproduce_with(&before, Ordering::Greater, &mut rng);
+        assert_eq!(child.io_appended(), genome.io_appended());
+        assert_eq!(child.nodes(), genome.nodes());
+        genome
+            .convert_node(NodeId(2), NodeKind::Sensory)
+            .unwrap_err();
+    });
+
+    test_t!(
+    test_extend_io_stale[T: RecurrentContinuous]() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = T::new(1, 1);
+        genome.push_connection(C::new(0, 1, &mut inno));
+        let before = genome.clone();
+
+        // a stale InnoGen gives the new path (3, 1) the id that (0, 1) already holds
+        let err = genome.extend_io(1, 0, &mut InnoGen::new(0)).unwrap_err();
+        assert!(err.to_string().contains("innovation id 0"), "{err}");
+        assert_eq!(genome.nodes(), before.nodes());
+        assert_eq!(genome.connections(), before.connections());
+        assert_eq!(genome.io_appended(), (&[][..], &[][..]));
+    });
+
+    test_t!(
+    test_duplicate_node[T: RecurrentContinuous]() {
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = T::new(2, 1);
+        assert_eq!(genome.duplicate_node(&mut rng, &mut inno, &C::MUTATION_PARAMS), None);
+
+        genome.push_node(NodeKind::Internal);
+        for path in [(0, 4), (4, 2), (4, 4), (1, 2)] {
+            genome.push_connection(C::new(path.0, path.1, &mut inno));
+        }
+        genome.connections[1].disable();
+        assert_eq!(
+            genome.duplicate_node(&mut rng, &mut inno, &C::MUTATION_PARAMS),
+            Some(NodeId(5))
+        );
+        assert_eq!(genome.nodes()[5], NodeKind::Internal);
+        assert_eq!(
+            genome.connections()[4..]
+                .iter()
+                .map(|c| (c.inno(), c.path(), c.enabled()))
+                .collect::<Vec<_>>(),
+            vec![
+                (4, (0, 5), true),
+                (5, (5, 2), false),
+                (6, (5, 5), true)
+            ]
+        );
+
+        // copies are mutated as described by the params passed
+        for c in genome.connections_mut() {
+            c.set_weight(1.);
+        }
+        let still = MutationParams {
+            sigma: 0.,
+            replace_probability: 0,
+            ..C::MUTATION_PARAMS
+        };
+        assert_eq!(genome.duplicate_node(&mut rng, &mut inno, &still), Some(NodeId(6)));
+        assert!(genome.connections()[7..].iter().all(|c| c.weight() == 1.));
+    });
+
+    test_t!(
+    test_modules[T: RecurrentContinuous]() {
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = T::new(2, 1);
+        genome.push_node(NodeKind::Internal);
+        genome.push_node(NodeKind::Internal);
+        for path in [(0, 4), (1, 4), (4, 5), (5, 2), (3, 2)] {
+            genome.push_connection(C::new(path.0, path.1, &mut inno));
+        }
+        genome.connections[3].weight = 0.8;
+
+        genome.define_module("m", &[NodeId(2)]).unwrap_err();
+        genome.define_module("m", &[NodeId(4), NodeId(4)]).unwrap_err();
+        genome.define_module("m", &[NodeId(4), NodeId(5)]).unwrap();
+        genome.define_module("m", &[NodeId(4)]).unwrap_err();
+        let mut nn = Continuous::from_genome(&genome);
+        let plain = genome.clone();
+
+        assert_eq!(genome.module_growth(0), (4, 2));
+        genome.connections[3].set_frozen(true);
+        genome.duplicate_module(0, &mut inno).unwrap_err();
+        assert_eq!(genome.nodes().len(), plain.nodes().len());
+        assert_eq!(genome.connections().len(), plain.connections().len());
+        assert_eq!(genome.connections[3].weight, 0.8);
+        genome.connections[3].set_frozen(false);
+        assert_eq!(genome.duplicate_module(0, &mut inno).unwrap(), 1);
+        assert_eq!(genome.modules()[1].name, "m'");
+        assert_eq!(genome.modules()[1].nodes, vec![NodeId(6), NodeId(7)]);
+        assert_eq!(
+            genome.connections()[5..]
+                .iter()
+                .map(|c| (c.path(), c.weight))
+                .collect::<Vec<_>>(),
+            vec![((0, 6), 1.), ((1, 6), 1.), ((6, 7), 1.), ((7, 2), 0.4)]
+        );
+        assert_eq!(genome.connections[3].weight, 0.4);
+
+        // the copy splits the original's output between them
+        let mut duplicated = Continuous::from_genome(&genome);
+        for _ in 0..3 {
+            nn.step(3, &[1., 0.5], activate::relu);
+            duplicated.step(3, &[1., 0.5], activate::relu);
+        }
+        assert_matrix_approx!(nn.output(), duplicated.output());
+
+        // modules are inherited by name, and removed nodes leave them
+        assert_f64_approx!(genome.module_diff(&plain), 0.5);
+        let child = plain.reproduce_with(&genome, Ordering::Less, &mut rng);
+        assert_eq!(child.modules(), genome.modules());
+        genome.retain_nodes(&[true, true, true, true, true, false, false, true]);
+        assert_eq!(genome.modules()[0].nodes, vec![NodeId(4)]);
+        assert_eq!(genome.modules()[1].nodes, vec![NodeId(5)]);
+    });
+
+    test_t!(
+    test_complexity[T: RecurrentContinuous]() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = T::new(2, 1);
+        assert_eq!(
+            genome.complexity(),
+            Complexity {
+                nodes: 4,
+                connections: 0,
+                hidden: 0,
+                depth: 0
+            }
+        );
+
+        // 0 -> 4 -> 5 -> 2 and 1 -> 2, with 5 -> 4 recurrent and 3 -> 2 disabled
+        genome.push_node(NodeKind::Internal);
+        genome.push_node(NodeKind::Internal);
+        for path in [(0, 4), (4, 5), (5, 2), (1, 2), (5, 4), (3, 2)] {
+            genome.push_connection(C::new(path.0, path.1, &mut inno));
+        }
+        genome.connections[5].disable();
+        assert_eq!(
+            genome.complexity(),
+            Complexity {
+                nodes: 6,
+                connections: 5,
+                hidden: 2,
+                depth: 3
+            }
+        );
+    });
+
+    test_t!(
+    test_node_activations[T: RecurrentContinuous]() {
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(0);
+        let (genome, _) = T::new(2, 1);
+        let mut genome = genome.with_node_activations();
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.bisect_connection(&mut rng, &mut inno).unwrap();
+        assert_eq!(genome.node_activations(), Some(&[0; 5][..]));
+
+        let before = genome.clone();
+        genome.mutate_activation(&mut rng);
+        let changed = (0..5)
+            .filter(|idx| genome.node_activations().unwrap()[*idx] != 0)
+            .collect::<Vec<_>>();
+        assert!(changed == vec![2] || changed == vec![4], "{changed:?}");
+        assert_f64_approx!(genome.activation_diff(&before), 0.2);
+        assert_ne!(genome.phenotype_cache_key(), before.phenotype_cache_key());
+
+        let decoded = T::from_str(&genome.to_string().unwrap()).unwrap();
+        assert_eq!(decoded.node_activations(), genome.node_activations());
+        let child = genome.reproduce_with(&before, Ordering::Greater, &mut rng);
+        assert_eq!(child.node_activations().unwrap().len(), child.nodes().len());
+
+        let (mut plain, _) = T::new(2, 1);
+        plain.mutate_with(&mut rng, &mut inno, [0, 0, 0, 0, percent(100)]);
+        assert!(plain.node_activations().is_none());
+        assert_eq!(plain.activation_diff(&before), 0.);
+    });
+
+    test_t!(
+    test_node_time_constants[T: RecurrentContinuous]() {
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(0);
+        let (genome, _) = T::new(2, 1);
+        let mut genome = genome.with_node_time_constants();
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.bisect_connection(&mut rng, &mut inno).unwrap();
+        assert_eq!(genome.node_time_constants(), Some(&[T::TIME_CONSTANT; 5][..]));
+
+        let before = genome.clone();
+        for _ in 0..20 {
+            genome.mutate_time_constants(&mut rng, &C::MUTATION_PARAMS);
+        }
+        let time_constants = genome.node_time_constants().unwrap();
+        assert!(time_constants.iter().all(|τ| *τ > 0.));
+        assert_ne!(time_constants, before.node_time_constants().unwrap());
+        assert!(genome.time_constant_diff(&before) > 0.);
+        assert!(genome.params().ends_with(time_constants));
+        assert_ne!(genome.phenotype_cache_key(), before.phenotype_cache_key());
+
+        let nn = Continuous::from_genome(&genome);
+        assert_eq!(nn.τ.data(), time_constants);
+        let decoded = T::from_str(&genome.to_string().unwrap()).unwrap();
+        assert_matrix_approx!(
+            decoded.node_time_constants().unwrap(),
+            genome.node_time_constants().unwrap()
+        );
+        let child = genome.reproduce_with(&before, Ordering::Greater, &mut rng);
+        assert_eq!(child.node_time_constants().unwrap().len(), child.nodes().len());
+
+        let mutated = genome.clone();
+        let still = MutationParams {
+            sigma: 0.,
+            ..C::MUTATION_PARAMS
+        };
+        for _ in 0..20 {
+            genome.mutate_time_constants(&mut rng, &still);
+        }
+        assert_eq!(genome.node_time_constants(), mutated.node_time_constants());
+
+        let (plain, _) = T::new(2, 1);
+        assert_eq!(plain.time_constants(), vec![T::TIME_CONSTANT; 4]);
+        assert_eq!(plain.time_constant_diff(&plain.clone()), 0.);
+    });
+
+    test_t!(
+    test_node_biases[T: RecurrentContinuous]() {
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(0);
+        let (genome, _) = T::new(2, 1);
+        let mut genome = genome.with_node_biases();
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.bisect_connection(&mut rng, &mut inno).unwrap();
+        assert_eq!(genome.node_biases(), Some(&[0.; 5][..]));
+        assert_eq!(genome.biases(), vec![0., 0., 0., 1., 0.]);
+
+        // only biases are mutated by their event, and connections are left alone
+        let before = genome.clone();
+        for _ in 0..20 {
+            genome.mutate_with(&mut rng, &mut inno, [0, 0, 0, percent(100), 0]);
+        }
+        assert_eq!(genome.connections(), before.connections());
+        let biases = genome.node_biases().unwrap().to_vec();
+        assert_ne!(biases, before.node_biases().unwrap());
+        assert!(genome.bias_diff(&before) > 0.);
+        assert!(genome.params().ends_with(&biases));
+        assert_ne!(genome.phenotype_cache_key(), before.phenotype_cache_key());
+
+        let nn = Continuous::from_genome(&genome);
+        assert_eq!(nn.θ.data()[3], 1.);
+        assert_eq!(nn.θ.data()[4], biases[4]);
+        let child = genome.reproduce_with(&before, Ordering::Greater, &mut rng);
+        assert_eq!(child.node_biases().unwrap().len(), child.nodes().len());
+
+        let (plain, _) = T::new(2, 1);
+        assert_eq!(plain.biases(), vec![0., 0., 0., 1.]);
+        assert_eq!(plain.bias_diff(&plain.clone()), 0.);
+    });
+
+    test_t!(
+    test_reproduce_configured[T: RecurrentContinuous]() {
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(0);
+        let (mut l, _) = T::new(2, 1);
+        l.push_connection(C::new(0, 2, &mut inno));
+        let mut r = l.clone();
+        l.connections_mut()[0].weight = 1.;
+        r.connections_mut()[0].weight = -0.5;
+
+        let child = l.reproduce_configured(&r, Ordering::Greater, [0, u64::MAX], &mut rng);
+        assert_eq!(child.connections()[0].weight, 0.25);
+        let child = l.reproduce_configured(&r, Ordering::Greater, [u64::MAX, 0], &mut rng);
+        assert!([1., -0.5].contains(&child.connections()[0].weight));
+
+        // the child takes a buffer with room to spare, so that mutation needn't grow it
+        let mut scratch = Vec::with_capacity(4);
+        let buffer = scratch.as_ptr();
+        let mut child =
+            l.reproduce_buffered(&r, Ordering::Greater, [0, u64::MAX], &mut rng, &mut scratch);
+        assert!(scratch.is_empty());
+        child.push_connection(C::new(1, 2, &mut inno));
+        assert_eq!(child.connections().as_ptr(), buffer);
+        assert_eq!(child.connections()[0].weight, 0.25);
+    });
+
+    test_t!(
+    test_node_gene_inheritance[T: RecurrentContinuous]() {
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(0);
+        let (mut plain, _) = T::new(2, 1);
+        plain.push_connection(C::new(0, 2, &mut inno));
+        let mut evolved = plain.clone().with_node_biases().with_node_time_constants();
+        evolved.bisect_connection(&mut rng, &mut inno).unwrap();
+        evolved.node_biases_mut().unwrap().fill(0.5);
+        evolved.node_time_constants_mut().unwrap().fill(2.);
+
+        // genes survive the less fit parent having them, and a parent without them counts as
+        // holding the default for each of its nodes
+        let mut inherited = [false; 2];
+        for _ in 0..50 {
+            let child = plain.reproduce_with(&evolved, Ordering::Less, &mut rng);
+            let biases = child.node_biases().unwrap();
+            assert_eq!(biases.len(), child.nodes().len());
+            assert_eq!(biases[4], 0.5);
+            assert_eq!(child.node_time_constants().unwrap()[4], 2.);
+            for (idx, bias) in biases[..4].iter().enumerate() {
+                assert!(*bias == 0. || *bias == 0.5, "{idx}: {bias}");
+            }
+            inherited[0] |= biases[..4].contains(&0.5);
+            inherited[1] |= biases[..4].contains(&0.);
+        }
+        assert_eq!(inherited, [true, true]);
+
+        let child = plain.reproduce_with(&plain, Ordering::Equal, &mut rng);
+        assert!(child.node_biases().is_none());
+    });
+
+    test_t!(
+    test_dropped_out[T: RecurrentContinuous]() {
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = T::new(2, 1);
+        for from in [0, 1, 3] {
+            genome.push_connection(C::new(from, 2, &mut inno));
+        }
+        genome.connections[2].disable();
+
+        assert_eq!(genome.dropped_out(0., &mut rng).connections(), genome.connections());
+        let dropped = genome.dropped_out(1., &mut rng);
+        assert!(dropped.connections().iter().all(|c| !c.enabled()));
+        assert_eq!(dropped.weights(), Vec::<f64>::new());
+
+        // half of many connections are dropped, give or take
+        let (mut wide, _) = T::new(40, 1);
+        for from in 0..40 {
+            wide.push_connection(C::new(from, 40, &mut inno));
+        }
+        let kept = wide.dropped_out(0.5, &mut rng).weights().len();
+        assert!((5..35).contains(&kept), "kept {kept} of 40");
+
+        let mut nn: Continuous = genome.network_dropout(1., &mut rng);
+        nn.step(2, &[1., 1.], activate::relu);
+        assert_eq!(nn.output(), &[0.]);
+    });
+
+    test_t!(
+    test_sensory_gains[T: RecurrentContinuous]() {
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(0);
+        let (genome, _) = T::new(2, 1);
+        let mut genome = genome.with_sensory_gains();
+        genome.push_connection(C::new(0, 2, &mut inno));
+
+        // gains are mutated by the bias event, even on genomes without biases
+        let before = genome.clone();
+        for _ in 0..20 {
+            genome.mutate_with(&mut rng, &mut inno, [0, 0, 0, percent(100), 0]);
+        }
+        assert_eq!(genome.connections(), before.connections());
+        let gains = genome.sensory_gains().unwrap().to_vec();
+        assert_ne!(gains, before.sensory_gains().unwrap());
+        assert!(genome.params().ends_with(&gains));
+        assert!(!genome.phenotype_eq(&before));
+
+        let child = genome.reproduce_with(&before, Ordering::Greater, &mut rng);
+        assert_eq!(child.sensory_gains().unwrap().len(), 2);
+
+        genome.append_io(1, 0).unwrap();
+        assert_eq!(genome.sensory_gains().unwrap(), &[gains[0], gains[1], 1.]);
+    });
+
+    test_t!(
+    test_mutate_constrained[T: RecurrentContinuous]() {
+        let mut rng = default_rng();
+        let (mut genome, inno_head) = T::new(3, 2);
+        let mut inno = InnoGen::new(inno_head);
+        let constraints = Constraints {
+            max_connections: 5,
+            max_nodes: 8,
+        };
+        let structural = [percent(50), percent(50), percent(0), percent(0), percent(0)];
+
+        let config = MutationConfig::NONE.with_constraints(constraints);
+
+        for _ in 0..100 {
+            genome.mutate_configured(&mut rng, &mut inno, structural, &config);
+            assert!(constraints.allows(genome.connections().len(), genome.nodes().len()));
+        }
+        assert!(genome.connections().len() >= 4);
+    });
+
+    test_t!(
+    test_mutate_configured[T: RecurrentContinuous]() {
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = T::new(2, 1);
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.push_connection(C::new(1, 2, &mut inno));
+        genome.connections_mut()[1].weight = 4.;
+        let config = MutationConfig::NONE.with_weight_bounds(-0.5, 0.5);
+
+        for _ in 0..200 {
+            genome.mutate_configured(&mut rng, &mut inno, [0, 0, percent(100), 0, 0], &config);
+            assert!(genome
+                .connections()
+                .iter()
+                .all(|c| (-0.5..=0.5).contains(&c.weight)));
+        }
+
+        // new and bisected connections, who start at a weight of 1, are bounded too
+        let structural = [percent(50), percent(50), 0, 0, 0];
+        for _ in 0..20 {
+            genome.mutate_configured(&mut rng, &mut inno, structural, &config);
+            assert!(genome
+                .connections()
+                .iter()
+                .all(|c| (-0.5..=0.5).contains(&c.weight)));
+        }
+    });
+
+    test_t!(
+    test_mutate_n[T: RecurrentContinuous]() {
+        let mut rng = default_rng();
+        let (mut genome, inno_head) = T::new(3, 2);
+        let mut inno = InnoGen::new(inno_head);
+
+        let nodes = genome.nodes().len();
+        genome.mutate_n(4, [1, 0, 0, 0, 0], &mut rng, &mut inno, &T::MUTATION);
+        assert_eq!(genome.connections().len(), 4);
+        genome.mutate_n(3, [0, 1, 0, 0, 0], &mut rng, &mut inno, &T::MUTATION);
+        assert_eq!(genome.nodes().len(), nodes + 3);
+        assert_eq!(genome.connections().len(), 10);
+
+        // weights are relative, so these are an even split, and either adds one more connection
+        // than it does nodes
+        let before = genome.clone();
+        genome.mutate_n(0, [u64::MAX, u64::MAX, 0, 0, 0], &mut rng, &mut inno, &T::MUTATION);
+        assert_eq!(genome.connections(), before.connections());
+        genome.mutate_n(2, [u64::MAX, u64::MAX, 0, 0, 0], &mut rng, &mut inno, &T::MUTATION);
+        assert_eq!(
+            genome.connections().len() - before.connections().len(),
+            genome.nodes().len() - before.nodes().len() + 2
+        );
+
+        // constrained by config, new connections fall back to mutating the ones already held
+        let before = genome.clone();
+        let full = Constraints::NONE.with_max_connections(before.connections().len());
+        let config = MutationConfig::NONE.with_constraints(full);
+        genome.mutate_n(4, [1, 1, 0, 0, 0], &mut rng, &mut inno, &config);
+        assert_eq!(genome.connections().len(), before.connections().len());
+        assert_eq!(genome.nodes().len(), before.nodes().len());
+    });
+
+    test_t!(
+    test_from_dense[T: RecurrentContinuous]() {
+        let weights = vec![
+            vec![0., 0., 0., 0.5],
+            vec![0., 0., 0., 0.],
+            vec![0., 0.25, 0., 0.],
+            vec![0., 2., 0., -1.],
+        ];
+        let mut inno = InnoGen::new(0);
+        let genome = T::from_dense(&weights, 1, 1, &mut inno);
+        assert_eq!(genome.nodes()[3], NodeKind::Internal);
+        assert!(genome
+            .connections()
+            .iter()
+            .map(|c| (c.path(), c.weight))
+            .eq([((0, 3), 0.5), ((2, 1), 0.25), ((3, 1), 2.), ((3, 3), -1.)]));
+
+        // the same path gets the same innovation id in either import
+        let again = T::from_dense(&weights, 1, 1, &mut inno);
+        assert_eq!(again.connections(), genome.connections());
+        let nn = Continuous::from_genome(&genome);
+        assert_eq!(nn.w.data()[3 * 4 + 1], 2.);
+    });
+
+    test_t!(
+    #[should_panic(expected = "row 1 of weights isn't 3 long")]
+    test_from_dense_ragged[T: RecurrentContinuous]() {
+        T::from_dense(&[vec![0.; 3], vec![0.; 2], vec![0.; 3]], 1, 1, &mut InnoGen::new(0));
+    });
+
+    test_t!(
+    test_frozen[T: RecurrentContinuous]() {
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = T::new(2, 1);
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.push_connection(C::new(1, 2, &mut inno));
+        genome.freeze_all();
+        let template = genome.clone();
+
+        // frozen connections can't be bisected, and mutations leave them as they are
+        let err = genome.bisect_connection(&mut rng, &mut inno).unwrap_err();
+        assert_eq!(err.to_string(), "no connections available to bisect");
+        let config = MutationConfig::NONE.with_weight_bounds(-0.5, 0.5);
+        for _ in 0..100 {
+            genome.mutate_configured(&mut rng, &mut inno, T::PROBABILITIES, &config);
+        }
+        assert_eq!(&genome.connections()[..2], template.connections());
+        assert!(genome.connections()[2..].iter().all(|c| !c.frozen));
+
+        let saved = genome.to_string().unwrap();
+        assert!(!saved.contains("\"frozen\":false"));
+        assert!(T::from_str(&saved).unwrap().connections()[0].frozen);
+    });
+
+    test_t!(
+    test_weight_init[T: RecurrentContinuous]() {
+        let mut rng = default_rng();
+        let (mut genome, inno_head) = T::new(3, 2);
+        let mut inno = InnoGen::new(inno_head);
+        genome.new_connection(&mut rng, &mut inno).unwrap();
+        assert_eq!(genome.connections()[0].weight, 1.);
+
+        let uniform = WeightInit::Uniform { min: -1., max: 1. };
+        let config = MutationConfig::NONE.with_weight_init(uniform);
+        for _ in 0..5 {
+            genome.mutate_configured(&mut rng, &mut inno, [percent(100), 0, 0, 0, 0], &config);
+        }
+        let weights = genome.connections()[1..].iter().map(|c| c.weight);
+        assert!(weights.clone().all(|w| (-1. ..=1.).contains(&w)));
+        assert!(weights.clone().any(|w| w != 1.));
+
+        let still = WeightInit::Gaussian { mean: 0.5, sigma: 0. };
+        genome.new_connection_with(&mut rng, &mut inno, &still).unwrap();
+        assert_eq!(genome.connections().last().unwrap().weight, 0.5);
+    });
+
+    test_t!(
+    test_mutation_params[T: RecurrentContinuous]() {
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = T::new(2, 1);
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.connections_mut()[0].weight = 10.;
+
+        // nothing is replaced, and perturbations are scaled to nothing
+        let still = MutationParams {
+            sigma: 0.,
+            replace_probability: 0,
+            distribution: Perturbation::Cauchy,
+        };
+        let config = MutationConfig::NONE.with_params(still);
+        for _ in 0..50 {
+            genome.mutate_configured(&mut rng, &mut inno, [0, 0, percent(100), 0, 0], &config);
+            assert_eq!(genome.connections()[0].weight, 10.);
+        }
+
+        // every param is replaced by a draw, which for a uniform never leaves [-3, 3]
+        let uniform = MutationParams {
+            replace_probability: percent(100),
+            ..MutationParams::DEFAULT
+        };
+        let mut c = genome.connections()[0].clone();
+        c.mutate_param_with(&mut rng, &uniform);
+        assert!((-3. ..=3.).contains(&c.weight));
+
+        let gaussian = MutationParams {
+            distribution: Perturbation::Gaussian,
+            ..MutationParams::DEFAULT
+        };
+        let drift = (0..1000).map(|_| gaussian.sample(&mut rng)).sum::<f64>() / 1000.;
+        assert!(drift.abs() < 0.2, "mean of {drift}");
+    });
+
+    test_t!(
+    test_mutation_sigma[T: RecurrentContinuous]() {
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(0);
+        let (genome, _) = T::new(2, 1);
+        assert_eq!(genome.mutation_sigma(), None);
+        let mut genome = genome.with_mutation_sigma(T::SIGMA_MIN);
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.connections_mut()[0].weight = 1.;
+
+        // perturbed by the genome's own sigma, which adapts as it's used
+        let config = MutationConfig::NONE.with_params(MutationParams {
+            replace_probability: 0,
+            ..MutationParams::DEFAULT
+        });
+        let mut sigmas = Vec::new();
+        for _ in 0..20 {
+            genome.mutate_configured(&mut rng, &mut inno, [0, 0, percent(100), 0, 0], &config);
+            sigmas.push(genome.mutation_sigma().unwrap());
+        }
+        assert!(sigmas.iter().all(|sigma| *sigma >= T::SIGMA_MIN));
+        assert!(sigmas.windows(2).any(|pair| pair[0] != pair[1]));
+        let max = sigmas.iter().copied().fold(0., f64::max);
+        assert!((genome.connections()[0].weight - 1.).abs() <= 20. * 3. * max);
+
+        let child = T::new(2, 1).0.reproduce_with(&genome, Ordering::Less, &mut rng);
+        assert_eq!(child.mutation_sigma(), genome.mutation_sigma());
+        let decoded = T::from_str(&genome.to_string().unwrap()).unwrap();
+        assert_f64_approx!(
+            decoded.mutation_sigma().unwrap(),
+            genome.mutation_sigma().unwrap()
+        );
+    });
+
+    test_t!(
+    test_params[T: RecurrentContinuous]() {
+        let mut inno = InnoGen::new(0);
+        let (genome, _) = T::new(2, 1);
+        let mut genome = genome.with_output_affine();
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.push_connection(C::new(1, 2, &mut inno));
+        genome.connections_mut()[1].disable();
+
+        let params = genome.params();
+        assert_eq!(params.len(), 2 + 2);
+        genome.set_params(&[0.5, -1., 2., 0.25]);
+        assert_eq!(genome.params(), vec![0.5, -1., 2., 0.25]);
+        assert_eq!(genome.connections()[1].weight(), -1.);
+        assert_eq!(genome.output_affine().unwrap()[0].offset, 0.25);
+        assert_eq!(genome.weights(), vec![0.5]);
+
+        // frozen weights are left out of the layout, and never overwritten
+        genome.push_connection(C::new(3, 2, &mut inno));
+        genome.connections_mut()[0].set_frozen(true);
+        assert_eq!(genome.weights(), vec![genome.connections()[2].weight()]);
+        genome.set_weights(&[4.]);
+        assert_eq!(genome.connections()[0].weight(), 0.5);
+        assert_eq!(genome.connections()[2].weight(), 4.);
+    });
+
+    test_t!(
+    #[should_panic(expected = "too many params")]
+    test_params_oversized[T: RecurrentContinuous]() {
+        let (mut genome, _) = T::new(2, 1);
+        genome.set_params(&[1.]);
+    });
+
+    test_t!(
+    test_recurrent[T: RecurrentContinuous]() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = T::new(1, 1);
+        // 0 -> 3 -> 4 -> 1, with 4 -> 3 and 3 -> 3 closing cycles
+        genome.nodes.extend([NodeKind::Internal, NodeKind::Internal]);
+        for path in [(0, 3), (3, 4), (4, 1), (4, 3), (3, 3), (0, 1)] {
+            genome.push_connection(C::new(path.0, path.1, &mut inno));
+        }
+        assert_eq!(genome.recurrent(), vec![false, false, false, true, true, false]);
+
+        genome.connections_mut()[3].disable();
+        genome.connections_mut()[1].disable();
+        assert_eq!(genome.recurrent(), vec![false, false, false, false, true, false]);
+    });
+
+    test_t!(
+    test_ids[T: RecurrentContinuous]() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = T::new(2, 1);
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.push_connection(C::new(1, 2, &mut inno));
+        assert!(matches!(genome.node(NodeId(2)), Some(NodeKind::Action)));
+        assert!(genome.node(NodeId(4)).is_none());
+        assert_eq!(genome.connection(ConnId(1)).unwrap().source(), NodeId(1));
+        assert!(genome.connection(ConnId(2)).is_none());
+        assert_eq!(
+            genome.node_ids().map(|(id, _)| id.0).collect::<Vec<_>>(),
+            vec![0, 1, 2, 3]
+        );
+        assert!(genome
+            .connection_ids()
+            .all(|(id, c)| genome.connection(id) == Some(c)));
+        assert!(genome.dangling().is_empty());
+
+        genome.connections[0].set_path((0, 7));
+        assert_eq!(genome.dangling(), vec![ConnId(0)]);
+    });
+
+    #[test]
+    fn test_recurrence() {
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(0);
+        let (genome, _) = RecurrentContinuous::new(1, 1);
+        let mut genome = genome.with_recurrence(Recurrence::NoSelfLoops);
+        genome.push_node(NodeKind::Internal);
+        // 0 and 2 may reach 1 and 3, and 3 may reach 1 but not itself
+        while let Some((from, to)) = genome.open_path(&mut rng) {
+            assert_ne!(from, to);
+            genome.push_connection(C::new(from, to, &mut inno));
+        }
+        assert_eq!(genome.connections().len(), 5);
+
+        let decoded = RecurrentContinuous::from_str(&genome.to_string().unwrap()).unwrap();
+        assert_eq!(decoded.recurrence(), Recurrence::NoSelfLoops);
+        let child = genome.reproduce_with(&genome, Ordering::Greater, &mut rng);
+        assert_eq!(child.recurrence(), Recurrence::NoSelfLoops);
+
+        let (genome, _) = RecurrentContinuous::new(1, 1);
+        let mut genome = genome.with_recurrence(Recurrence::Acyclic);
+        genome.push_node(NodeKind::Internal);
+        while let Some((from, to)) = genome.open_path(&mut rng) {
+            genome.push_connection(C::new(from, to, &mut inno));
+        }
+        assert!(genome.recurrent().iter().all(|recurrent| !recurrent));
+
+        let (genome, _) = RecurrentContinuous::new(1, 1);
+        assert_eq!(genome.recurrence(), Recurrence::Any);
+        assert!(!genome.to_string().unwrap().contains("recurrence"));
+    }
+
+    #[test]
+    fn test_open_path_connections_mut() {
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(0);
+        let (genome, _) = RecurrentContinuous::new(1, 1);
+        let mut genome = genome.with_recurrence(Recurrence::NoSelfLoops);
+        genome.push_node(NodeKind::Internal);
+        for _ in 0..4 {
+            let (from, to) = genome.open_path(&mut rng).unwrap();
+            genome.push_connection(C::new(from, to, &mut inno));
+        }
+
+        // moving a connection onto the last open path opens the one that it left
+        let open = genome.open_path(&mut rng).unwrap();
+        let left = genome.connections()[0].path();
+        genome.connections_mut()[0].set_path(open);
+        for _ in 0..16 {
+            assert_eq!(genome.open_path(&mut rng), Some(left));
+        }
+    }
+
+    #[test]
+    fn test_open_path_survives_weights() {
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = RecurrentContinuous::new(2, 2);
+        for _ in 0..4 {
+            let (from, to) = genome.open_path(&mut rng).unwrap();
+            genome.push_connection(C::new(from, to, &mut inno));
+        }
+        let built = genome.outgoing.get().unwrap() as *const Outgoing;
+
+        // weight edits leave every path where it was, so the same cache is still trusted
+        for _ in 0..16 {
+            genome.mutate_connection(&mut rng);
+        }
+        genome.connections_mut()[0].weight = 8.;
+        genome.connections_mut()[1].disable();
+        let outgoing = genome.outgoing.get().unwrap();
+        assert!(core::ptr::eq(outgoing, built));
+        assert!(outgoing.fresh(&genome.connections));
+        let (from, to) = genome.open_path(&mut rng).unwrap();
+        assert!(!genome.connections().iter().any(|c| c.path() == (from, to)));
+        genome.push_connection(C::new(from, to, &mut inno));
+        assert_eq!(genome.outgoing.get().unwrap().paths.len(), 5);
+    }
 }
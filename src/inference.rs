@@ -0,0 +1,565 @@
+//! Network evaluation that only needs `core` and `alloc`, for running evolved networks on targets
+//! without an operating system. Evolution and serialization live behind the `std` feature,
+//! networks are evolved there and converted into a [Ctrnn] to be shipped.
+use alloc::{boxed::Box, vec::Vec};
+use core::error::Error;
+
+pub mod activate {
+    use alloc::{collections::BTreeMap, string::String};
+
+    pub fn steep_sigmoid(x: f64) -> f64 {
+        1. / (1. + libm::exp(-4.9 * x))
+    }
+
+    pub fn relu(x: f64) -> f64 {
+        if x < 0. {
+            0.
+        } else {
+            x
+        }
+    }
+
+    pub fn sigmoid(x: f64) -> f64 {
+        1. / (1. + libm::exp(-x))
+    }
+
+    pub fn tanh(x: f64) -> f64 {
+        libm::tanh(x)
+    }
+
+    pub fn sin(x: f64) -> f64 {
+        libm::sin(x)
+    }
+
+    /// Gaussian bump peaking at 1 for x = 0
+    pub fn gauss(x: f64) -> f64 {
+        libm::exp(-x * x)
+    }
+
+    /// relu whose negative side is scaled by 0.01 rather than flattened, so that its gradient
+    /// never vanishes
+    pub fn leaky_relu(x: f64) -> f64 {
+        if x < 0. {
+            0.01 * x
+        } else {
+            x
+        }
+    }
+
+    /// Exponential linear unit, smoothly approaching -1 for negative x
+    pub fn elu(x: f64) -> f64 {
+        if x < 0. {
+            libm::expm1(x)
+        } else {
+            x
+        }
+    }
+
+    /// Like tanh, but approaching ±1 polynomially rather than exponentially
+    pub fn softsign(x: f64) -> f64 {
+        x / (1. + x.abs())
+    }
+
+    /// Heaviside step, 1 for positive x and 0 otherwise
+    pub fn step(x: f64) -> f64 {
+        if x > 0. {
+            1.
+        } else {
+            0.
+        }
+    }
+
+    /// The identity, clamped to [-1, 1]
+    pub fn clamped(x: f64) -> f64 {
+        x.clamp(-1., 1.)
+    }
+
+    /// Magnitude of x, symmetric about 0
+    pub fn abs(x: f64) -> f64 {
+        x.abs()
+    }
+
+    /// Activations that a node may evolve to use, indexed by its activation [id]
+    pub const PALETTE: [fn(f64) -> f64; 7] = [steep_sigmoid, relu, sigmoid, tanh, sin, gauss, abs];
+
+    /// Activation ids, each the index of an activation in [PALETTE]
+    pub mod id {
+        pub const STEEP_SIGMOID: usize = 0;
+        pub const RELU: usize = 1;
+        pub const SIGMOID: usize = 2;
+        pub const TANH: usize = 3;
+        pub const SIN: usize = 4;
+        pub const GAUSS: usize = 5;
+        pub const ABS: usize = 6;
+        pub const ALL: [usize; 7] = [STEEP_SIGMOID, RELU, SIGMOID, TANH, SIN, GAUSS, ABS];
+    }
+
+    /// Activation functions by name, so that anything choosing between activations may store
+    /// ( and serialize ) only a name, and look up the function again wherever it's used. Starts
+    /// out with every activation in this module, and user-defined ones may be registered beside
+    /// them.
+    #[derive(Debug, Clone)]
+    pub struct Registry {
+        named: BTreeMap<String, fn(f64) -> f64>,
+    }
+
+    impl Registry {
+        /// Register `σ` as `name`, replacing whatever was registered as `name` before
+        pub fn register(&mut self, name: impl Into<String>, σ: fn(f64) -> f64) {
+            self.named.insert(name.into(), σ);
+        }
+
+        pub fn get(&self, name: &str) -> Option<fn(f64) -> f64> {
+            self.named.get(name).copied()
+        }
+
+        /// The name that `σ` is registered as, if it is. Functions are compared by address,
+        /// which may not be unique for functions that compile to the same code.
+        pub fn name_of(&self, σ: fn(f64) -> f64) -> Option<&str> {
+            self.named
+                .iter()
+                .find(|(_, f)| core::ptr::fn_addr_eq(**f, σ))
+                .map(|(name, _)| name.as_str())
+        }
+
+        /// Every registered name, in order
+        pub fn names(&self) -> impl Iterator<Item = &str> {
+            self.named.keys().map(String::as_str)
+        }
+    }
+
+    impl Default for Registry {
+        fn default() -> Self {
+            let mut registry = Self {
+                named: BTreeMap::new(),
+            };
+            registry.register("steep_sigmoid", steep_sigmoid);
+            registry.register("relu", relu);
+            registry.register("sigmoid", sigmoid);
+            registry.register("tanh", tanh);
+            registry.register("sin", sin);
+            registry.register("gauss", gauss);
+            registry.register("leaky_relu", leaky_relu);
+            registry.register("elu", elu);
+            registry.register("softsign", softsign);
+            registry.register("step", step);
+            registry.register("clamped", clamped);
+            registry.register("abs", abs);
+            registry
+        }
+    }
+}
+
+/// The order in which a [Ctrnn]'s nodes are updated within a single sub-step
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Update {
+    /// Every node reads the state of the others from before the sub-step, as
+    /// [Continuous](crate::network::Continuous) does
+    #[default]
+    Synchronous,
+    /// Nodes are updated in place in order, each reading the state of those before it as of this
+    /// sub-step. Dynamics so depend on the order of nodes.
+    Asynchronous,
+}
+
+/// A plain-vec continuous time recurrent network, stepping exactly as
+/// [Continuous](crate::network::Continuous) with
+/// [Solver::Euler](crate::network::continuous::Solver::Euler) does
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ctrnn {
+    /// state of neurons 0-N
+    pub y: Vec<f64>,
+    /// bias of neurons 0-N
+    pub θ: Vec<f64>,
+    /// membrane resistance time constant
+    pub τ: Vec<f64>,
+    /// weights between neurons, indexed as [from * N + to]
+    pub w: Vec<f64>,
+    /// Range of input neurons, indexing into y
+    pub sensory: (usize, usize),
+    /// Range of output neurons, indexing into y
+    pub action: (usize, usize),
+    /// Time integrated over by a single call to step, split evenly across `prec` sub-steps
+    pub dt: f64,
+    pub update: Update,
+    /// Scratch buffer for activated state, so that stepping doesn't allocate
+    fired: Vec<f64>,
+}
+
+impl Ctrnn {
+    pub fn new(
+        θ: Vec<f64>,
+        τ: Vec<f64>,
+        w: Vec<f64>,
+        sensory: (usize, usize),
+        action: (usize, usize),
+        dt: f64,
+    ) -> Self {
+        let n = θ.len();
+        assert_eq!(n, τ.len(), "τ is sized {}, expected {n}", τ.len());
+        assert_eq!(n * n, w.len(), "w is sized {}, expected {}", w.len(), n * n);
+        Self {
+            y: alloc::vec![0.; n],
+            θ,
+            τ,
+            w,
+            sensory,
+            action,
+            dt,
+            update: Update::default(),
+            fired: alloc::vec![0.; n],
+        }
+    }
+
+    pub fn with_update(mut self, update: Update) -> Self {
+        self.update = update;
+        self
+    }
+
+    /// Given some sensory input, step the network with it `prec` times, activating with σ
+    pub fn step<F: Fn(f64) -> f64>(&mut self, prec: usize, input: &[f64], σ: F) {
+        let n = self.y.len();
+        let h = self.dt / (prec as f64);
+        for _ in 0..prec {
+            for (f, (y, θ)) in self.fired.iter_mut().zip(self.y.iter().zip(&self.θ)) {
+                *f = σ(y + θ);
+            }
+
+            for to in 0..n {
+                let mut dy = -self.y[to];
+                for from in 0..n {
+                    dy += self.fired[from] * self.w[from * n + to];
+                }
+                if (self.sensory.0..self.sensory.1).contains(&to) {
+                    dy += input[to - self.sensory.0];
+                }
+                self.y[to] += dy * self.τ[to] * h;
+                if self.update == Update::Asynchronous {
+                    self.fired[to] = σ(self.y[to] + self.θ[to]);
+                }
+            }
+        }
+    }
+
+    pub fn flush(&mut self) {
+        self.y.iter_mut().for_each(|y| *y = 0.);
+    }
+
+    pub fn output(&self) -> &[f64] {
+        &self.y[self.action.0..self.action.1]
+    }
+}
+
+/// Fractional bits of the fixed point values used by [QCtrnn], which are i16 in Q7.8
+pub const FRAC: u32 = 8;
+/// 1 in [QCtrnn]'s fixed point
+pub const ONE: i32 = 1 << FRAC;
+
+/// Quantize `x` to fixed point, saturating at the bounds of i16
+pub fn quantize(x: f64) -> i16 {
+    let q = x * ONE as f64;
+    if q >= i16::MAX as f64 {
+        i16::MAX
+    } else if q <= i16::MIN as f64 {
+        i16::MIN
+    } else {
+        q as i16
+    }
+}
+
+/// The f64 value of a fixed point `q`
+pub fn dequantize(q: i16) -> f64 {
+    q as f64 / ONE as f64
+}
+
+#[inline]
+fn saturate<T: Into<i64>>(x: T) -> i16 {
+    x.into().clamp(i16::MIN as i64, i16::MAX as i64) as i16
+}
+
+/// Fixed point activation functions for [QCtrnn], taking and returning values in its fixed point
+pub mod activate_q {
+    use super::{saturate, ONE};
+
+    pub fn relu(x: i32) -> i16 {
+        saturate(x.max(0))
+    }
+
+    /// Piecewise linear approximation of [steep_sigmoid](super::activate::steep_sigmoid),
+    /// matching its slope at 0 and saturating at 0 and 1
+    pub fn hard_sigmoid(x: i32) -> i16 {
+        saturate((ONE / 2 + x * 49 / 40).clamp(0, ONE))
+    }
+}
+
+/// A [Ctrnn] quantized to 16 bit fixed point, for targets where floating point is slow or
+/// missing. Values are i16 with [FRAC] fractional bits, products accumulate in i64, and
+/// results saturate rather than wrap. Stepping matches its [Ctrnn] within quantization error.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QCtrnn {
+    pub y: Vec<i16>,
+    pub θ: Vec<i16>,
+    pub τ: Vec<i16>,
+    /// weights between neurons, indexed as [from * N + to]
+    pub w: Vec<i16>,
+    pub sensory: (usize, usize),
+    pub action: (usize, usize),
+    pub dt: i16,
+    fired: Vec<i16>,
+}
+
+impl QCtrnn {
+    /// Given some fixed point sensory input, step the network with it `prec` times, activating
+    /// with a fixed point σ such as those in [activate_q]. Stepping 0 times leaves it as it is.
+    pub fn step<F: Fn(i32) -> i16>(&mut self, prec: usize, input: &[i16], σ: F) {
+        let n = self.y.len();
+        let h = self.dt as i64 / prec.max(1) as i64;
+        for _ in 0..prec {
+            for (f, (y, θ)) in self.fired.iter_mut().zip(self.y.iter().zip(&self.θ)) {
+                *f = σ(*y as i32 + *θ as i32);
+            }
+
+            for to in 0..n {
+                let mut dy = -(self.y[to] as i64);
+                for from in 0..n {
+                    dy += (self.fired[from] as i64 * self.w[from * n + to] as i64) >> FRAC;
+                }
+                if (self.sensory.0..self.sensory.1).contains(&to) {
+                    dy += input[to - self.sensory.0] as i64;
+                }
+                let dy = (((dy * self.τ[to] as i64) >> FRAC) * h) >> FRAC;
+                self.y[to] = saturate(self.y[to] as i64 + dy);
+            }
+        }
+    }
+
+    pub fn flush(&mut self) {
+        self.y.iter_mut().for_each(|y| *y = 0);
+    }
+
+    pub fn output(&self) -> &[i16] {
+        &self.y[self.action.0..self.action.1]
+    }
+}
+
+/// Quantize a [Ctrnn], which is an error if it doesn't update synchronously
+impl TryFrom<&Ctrnn> for QCtrnn {
+    type Error = Box<dyn Error>;
+
+    fn try_from(nn: &Ctrnn) -> Result<Self, Self::Error> {
+        if nn.update != Update::Synchronous {
+            return Err("only synchronous updates are supported in fixed point".into());
+        }
+        let quantized = |v: &[f64]| v.iter().copied().map(quantize).collect::<Vec<_>>();
+        Ok(Self {
+            y: quantized(&nn.y),
+            θ: quantized(&nn.θ),
+            τ: quantized(&nn.τ),
+            w: quantized(&nn.w),
+            sensory: nn.sensory,
+            action: nn.action,
+            dt: quantize(nn.dt),
+            fired: alloc::vec![0; nn.y.len()],
+        })
+    }
+}
+
+/// Ship a [Continuous](crate::network::Continuous) network, which is an error if it uses
+/// anything that a [Ctrnn] can't express
+#[cfg(feature = "std")]
+impl TryFrom<&crate::network::Continuous> for Ctrnn {
+    type Error = Box<dyn Error>;
+
+    fn try_from(nn: &crate::network::Continuous) -> Result<Self, Self::Error> {
+        if !matches!(nn.solver, crate::network::continuous::Solver::Euler) {
+            return Err("only euler integration is supported outside of std".into());
+        }
+        if !nn.appended.is_empty() {
+            return Err("io grown by extend_io isn't supported outside of std".into());
+        }
+        if nn.activation.is_some() {
+            return Err("per-node activations aren't supported outside of std".into());
+        }
+        let mut ctrnn = Self::new(
+            nn.θ.data().to_vec(),
+            nn.τ.data().to_vec(),
+            nn.w.data().to_vec(),
+            nn.sensory,
+            nn.action,
+            nn.dt,
+        );
+        ctrnn.y.copy_from_slice(nn.y.data());
+        Ok(ctrnn)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::{
+        assert_f64_approx,
+        genome::{Genome, InnoGen, Recurrent, WConnection},
+        network::{continuous::Solver, Continuous, FromGenome, Network},
+        random::WyRng,
+    };
+
+    #[test]
+    fn test_ctrnn_matches_continuous() {
+        for seed in 0..16 {
+            let mut rng = WyRng::seeded(seed);
+            let (mut genome, head) = Recurrent::<WConnection>::new(3, 2);
+            let mut inno = InnoGen::new(head);
+            for _ in 0..20 {
+                genome.mutate(&mut rng, &mut inno);
+            }
+
+            let mut continuous = Continuous::from_genome(&genome);
+            let mut ctrnn = Ctrnn::try_from(&continuous).unwrap();
+            for input in [[1., 0., -1.], [0.5, 0.5, 0.5], [0., 0., 0.]] {
+                continuous.step(3, &input, activate::steep_sigmoid);
+                ctrnn.step(3, &input, activate::steep_sigmoid);
+                // both take the same euler steps, but sum each node's input in another order, so
+                // they drift apart by a few ulps of the largest term for every step taken. A real
+                // mismatch, such as a transposed weight, is off by many orders more than this.
+                for (l, r) in continuous.output().iter().zip(ctrnn.output()) {
+                    assert!((l - r).abs() <= 1e-9 * l.abs().max(1.), "{l} !~ {r}");
+                }
+            }
+        }
+
+        let mut continuous = Continuous::from_genome(&Recurrent::<WConnection>::new(3, 2).0);
+        continuous.solver = Solver::Rk4;
+        assert!(Ctrnn::try_from(&continuous).is_err());
+    }
+
+    #[test]
+    fn test_qctrnn_matches_ctrnn() {
+        let mut ctrnn = Ctrnn::new(
+            alloc::vec![0., 0., 1.],
+            alloc::vec![0.5; 3],
+            alloc::vec![0., 0.8, 0., 0., 0.5, 0., 0., -0.3, 0.],
+            (0, 1),
+            (1, 2),
+            1.,
+        );
+        let mut qctrnn = QCtrnn::try_from(&ctrnn).unwrap();
+        for input in [0.5, 1., -0.25, 0.] {
+            ctrnn.step(4, &[input], activate::relu);
+            qctrnn.step(4, &[quantize(input)], activate_q::relu);
+            assert!((ctrnn.output()[0] - dequantize(qctrnn.output()[0])).abs() < 0.05);
+        }
+
+        assert_eq!(quantize(1000.), i16::MAX);
+        assert_eq!(dequantize(quantize(-1.5)), -1.5);
+        assert_eq!(activate_q::hard_sigmoid(0), (ONE / 2) as i16);
+        assert_eq!(activate_q::hard_sigmoid(ONE * 4), ONE as i16);
+    }
+
+    #[test]
+    fn test_qctrnn_saturates() {
+        // weights and time constants as large as fixed point holds, from a state at its bounds
+        let ctrnn = Ctrnn::new(
+            alloc::vec![0.; 2],
+            alloc::vec![4.; 2],
+            alloc::vec![100.; 4],
+            (0, 1),
+            (1, 2),
+            1.,
+        );
+        let mut qctrnn = QCtrnn::try_from(&ctrnn).unwrap();
+        qctrnn.y = alloc::vec![i16::MAX; 2];
+        qctrnn.step(1, &[i16::MAX], activate_q::relu);
+        assert_eq!(qctrnn.y, alloc::vec![i16::MAX, i16::MAX]);
+
+        qctrnn.w = alloc::vec![i16::MIN; 4];
+        qctrnn.step(1, &[i16::MIN], saturate::<i32>);
+        assert_eq!(qctrnn.y, alloc::vec![i16::MIN, i16::MIN]);
+
+        // stepping 0 times changes nothing, rather than dividing dt by 0
+        qctrnn.step(0, &[i16::MAX], activate_q::relu);
+        assert_eq!(qctrnn.y, alloc::vec![i16::MIN, i16::MIN]);
+    }
+
+    #[test]
+    fn test_ctrnn_update() {
+        // 0 -> 1 -> 2, with a unit delay per node when updated synchronously
+        let ctrnn = Ctrnn::new(
+            alloc::vec![0.; 3],
+            alloc::vec![1.; 3],
+            alloc::vec![0., 1., 0., 0., 0., 1., 0., 0., 0.],
+            (0, 1),
+            (2, 3),
+            1.,
+        );
+        let mut sync = ctrnn.clone();
+        let mut not_sync = ctrnn.with_update(Update::Asynchronous);
+        sync.step(1, &[1.], activate::relu);
+        not_sync.step(1, &[1.], activate::relu);
+
+        assert_eq!(sync.y, alloc::vec![1., 0., 0.]);
+        assert_eq!(not_sync.y, alloc::vec![1., 1., 1.]);
+        assert!(QCtrnn::try_from(&sync).is_ok());
+        assert!(QCtrnn::try_from(&not_sync).is_err());
+    }
+
+    #[test]
+    fn test_activate_bounded() {
+        for x in [-100., -2., -0.5, 0., 0.5, 2., 100.] {
+            assert!((0. ..=1.).contains(&activate::sigmoid(x)));
+            assert!((0. ..=1.).contains(&activate::gauss(x)));
+            assert!((0. ..=1.).contains(&activate::step(x)));
+            for bounded in [
+                activate::tanh,
+                activate::sin,
+                activate::softsign,
+                activate::clamped,
+            ] {
+                assert!((-1. ..=1.).contains(&bounded(x)), "{x}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_activate_values() {
+        assert_f64_approx!(activate::sigmoid(0.), 0.5);
+        assert_f64_approx!(activate::tanh(0.), 0.);
+        assert_f64_approx!(activate::sin(core::f64::consts::FRAC_PI_2), 1.);
+        assert_f64_approx!(activate::gauss(0.), 1.);
+        assert_f64_approx!(activate::gauss(1.), libm::exp(-1.));
+        assert_f64_approx!(activate::softsign(1.), 0.5);
+        assert_f64_approx!(activate::clamped(0.25), 0.25);
+        assert_f64_approx!(activate::clamped(-4.), -1.);
+        assert_f64_approx!(activate::step(0.), 0.);
+        assert_f64_approx!(activate::step(1e-9), 1.);
+    }
+
+    #[test]
+    fn test_activate_negative_linear() {
+        assert_f64_approx!(activate::leaky_relu(2.), 2.);
+        assert_f64_approx!(activate::leaky_relu(-2.), -0.02);
+        assert_f64_approx!(activate::elu(2.), 2.);
+        assert!((activate::elu(-1.) - (libm::exp(-1.) - 1.)).abs() < 1e-12);
+        assert!(activate::elu(-5.) > -1.);
+        assert!(activate::elu(-100.) >= -1.);
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = activate::Registry::default();
+        assert_eq!(registry.get("tanh").unwrap()(0.), 0.);
+        assert_eq!(registry.get("gauss").unwrap()(0.), 1.);
+        assert!(registry.get("softplus").is_none());
+
+        registry.register("softplus", |x| libm::log(1. + libm::exp(x)));
+        let names = ["relu", "softplus", "sin"];
+        let encoded = serde_json::to_string(&names).unwrap();
+        let decoded = serde_json::from_str::<Vec<alloc::string::String>>(&encoded)
+            .unwrap()
+            .iter()
+            .map(|name| registry.get(name).unwrap())
+            .collect::<Vec<_>>();
+        assert_f64_approx!(decoded[1](0.), libm::log(2.));
+        assert_eq!(registry.name_of(decoded[2]), Some("sin"));
+        assert_eq!(registry.names().count(), 13);
+    }
+}
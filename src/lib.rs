@@ -1,19 +1,52 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![feature(generic_const_exprs)]
 #![allow(confusable_idents)]
 #![allow(incomplete_features)]
 #![allow(mixed_script_confusables)]
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod adversarial;
+#[cfg(feature = "std")]
 pub mod crossover;
+#[cfg(feature = "std")]
+pub mod fit;
+#[cfg(feature = "std")]
 pub mod genome;
+pub mod inference;
 pub mod macros;
+#[cfg(feature = "std")]
 pub mod network;
+#[cfg(feature = "std")]
+pub mod online;
+#[cfg(feature = "std")]
+pub mod optimize;
+#[cfg(feature = "plot")]
+pub mod plot;
+#[cfg(feature = "std")]
 pub mod population;
+#[cfg(feature = "std")]
 pub mod random;
+#[cfg(feature = "std")]
 pub mod reproduce;
+#[cfg(feature = "std")]
 pub mod scenario;
+#[cfg(feature = "std")]
 pub mod serialize;
+#[cfg(feature = "std")]
+pub mod substrate;
+#[cfg(feature = "fs")]
+pub mod testing;
 
+#[cfg(feature = "std")]
+pub use fit::Fit;
+#[cfg(feature = "std")]
 pub use genome::{Connection, Genome};
-pub use network::{activate, Network};
+pub use inference::activate;
+#[cfg(feature = "std")]
+pub use network::Network;
+#[cfg(feature = "std")]
 pub use population::Specie;
+#[cfg(feature = "std")]
 pub use scenario::{Hook, Scenario, Stats};
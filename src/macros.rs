@@ -92,19 +92,22 @@ macro_rules! mutate_param {
     ([$($evt:ident),+]: [$($prob:expr),+]) => {
         ::paste::paste! {
             fn mutate_param(&mut self, rng: &mut impl rand::RngCore) {
+                self.mutate_param_with(rng, &Self::MUTATION_PARAMS)
+            }
+
+            fn mutate_param_with(
+                &mut self,
+                rng: &mut impl rand::RngCore,
+                params: &$crate::genome::MutationParams,
+            ) {
                 use $crate::random::EventKind;
-                use rand::Rng;
                 $crate::events!(Param[$($evt),*]);
-                const PARAM_PROBABILITIES: [u64; ParamEvent::COUNT] = [$($prob),*];
+                const PARAM_PROBABILITIES: ParamProbabilities = [$($prob),*];
 
                 if let Some(evt) = ParamEvent::pick(rng, PARAM_PROBABILITIES) {
-                    let replace = rng.next_u64() < Self::PARAM_REPLACE_PROBABILITY;
-                    let v: f64 = rng.sample(rand::distr::Uniform::new_inclusive(-3., 3.).expect("distribution of -3. ..= 3. failed"));
                     match evt {
-                        $(ParamEvent::[<$evt:camel>] => self.[<$evt:lower>] = if replace {
-                            v
-                        } else {
-                            self.[<$evt:lower>] + ( Self::PARAM_PERTURB_FAC * v )
+                        $(ParamEvent::[<$evt:camel>] => {
+                            self.[<$evt:lower>] = params.apply(self.[<$evt:lower>], rng)
                         },)*
                     }
                 }
@@ -113,6 +116,18 @@ macro_rules! mutate_param {
             fn param_diff(&self, other: &Self) -> f64 {
                 [$((self.[<$evt:lower>] - other.[<$evt:lower>])),*].iter().sum()
             }
+
+            const PARAMS: usize = [$(stringify!($evt)),*].len();
+
+            fn params(&self) -> impl Iterator<Item = f64> {
+                [$(self.[<$evt:lower>]),*].into_iter()
+            }
+
+            fn set_params(&mut self, params: &[f64]) {
+                assert_eq!(params.len(), Self::PARAMS, "expected {} params", Self::PARAMS);
+                let mut params = params.iter();
+                $(self.[<$evt:lower>] = *params.next().unwrap();)*
+            }
         }
     };
 }
@@ -150,6 +165,10 @@ macro_rules! events {
                 $($evt,)*
             }
 
+            /// Probability of each event, indexed by
+            /// [EventKind::idx](crate::random::EventKind::idx)
+            pub type [<$scope Probabilities>] = [u64; $crate::count!($($evt),+)];
+
             impl $crate::random::EventKind for [<$scope Event>] {
                 const COUNT: usize = $crate::count!($($evt),+);
 
@@ -0,0 +1,164 @@
+//! Compile evolved networks into standalone Rust source.
+//!
+//! The emitted code is a single function of straight-line arithmetic, with every weight, bias
+//! and time constant inlined as a literal. It depends on nothing, so a champion may be pasted
+//! into ( or `include!`ed by ) another binary without pulling in this crate or rulinalg.
+use super::{continuous::Solver, Continuous, FromGenome};
+use crate::{Connection, Genome};
+use rulinalg::matrix::BaseMatrix;
+use std::fmt::Write;
+
+/// A literal for `x` that round-trips through rustc
+fn lit(x: f64) -> String {
+    if x.is_nan() {
+        "f64::NAN".into()
+    } else if x.is_infinite() {
+        format!("{}f64::INFINITY", if x < 0. { "-" } else { "" })
+    } else {
+        format!("{x:?}")
+    }
+}
+
+/// Emit a function named `name` that steps the network exactly as its
+/// [step](super::Network::step) with [Solver::Euler] would. The generated function has the
+/// signature
+///
+/// ```text
+/// pub fn name<F: Fn(f64) -> f64>(
+///     y: &mut [f64; N],
+///     input: &[f64; S],
+///     prec: usize,
+///     sigma: F,
+/// ) -> [f64; A]
+/// ```
+///
+/// where `y` is the network's state, owned by the caller and zeroed to flush, `sigma` is the
+/// activation, and the return value is the network's output after stepping. Zero weights are
+/// skipped entirely. Only ASCII identifiers are emitted, so the output builds anywhere.
+pub fn continuous(nn: &Continuous, name: &str) -> String {
+    assert!(
+        matches!(nn.solver, Solver::Euler),
+        "only euler integration can be compiled"
+    );
+    assert!(
+        nn.activation.is_none(),
+        "per-node activations can't be compiled"
+    );
+    let n = nn.y.cols();
+    let w = nn.w.data();
+    let (θ, τ) = (nn.θ.data(), nn.τ.data());
+    let sensory = (nn.sensory.0..nn.sensory.1)
+        .chain(nn.appended.sensory.iter().copied())
+        .collect::<Vec<_>>();
+    let action = (nn.action.0..nn.action.1)
+        .chain(nn.appended.action.iter().copied())
+        .collect::<Vec<_>>();
+    let fires = (0..n)
+        .filter(|from| (0..n).any(|to| w[from * n + to] != 0.))
+        .collect::<Vec<_>>();
+
+    let mut src = String::new();
+    writeln!(src, "pub fn {name}<F: Fn(f64) -> f64>(").unwrap();
+    writeln!(src, "    y: &mut [f64; {n}],").unwrap();
+    writeln!(src, "    input: &[f64; {}],", sensory.len()).unwrap();
+    writeln!(src, "    prec: usize,").unwrap();
+    writeln!(src, "    sigma: F,").unwrap();
+    writeln!(src, ") -> [f64; {}] {{", action.len()).unwrap();
+    writeln!(src, "    let h = {} / prec as f64;", lit(nn.dt)).unwrap();
+    writeln!(src, "    for _ in 0..prec {{").unwrap();
+    for &from in fires.iter() {
+        writeln!(
+            src,
+            "        let f{from} = sigma(y[{from}] + {});",
+            lit(θ[from])
+        )
+        .unwrap();
+    }
+    for to in 0..n {
+        write!(src, "        let d{to} = (-y[{to}]").unwrap();
+        for &from in fires.iter().filter(|&&from| w[from * n + to] != 0.) {
+            write!(src, " + f{from} * {}", lit(w[from * n + to])).unwrap();
+        }
+        if let Some(input) = sensory.iter().position(|&idx| idx == to) {
+            write!(src, " + input[{input}]").unwrap();
+        }
+        writeln!(src, ") * {};", lit(τ[to])).unwrap();
+    }
+    for to in 0..n {
+        writeln!(src, "        y[{to}] += d{to} * h;").unwrap();
+    }
+    writeln!(src, "    }}").unwrap();
+    writeln!(
+        src,
+        "    [{}]",
+        action
+            .iter()
+            .map(|i| format!("y[{i}]"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+    .unwrap();
+    writeln!(src, "}}").unwrap();
+    src
+}
+
+/// Emit a function named `name` for the [Continuous] network expressed by `genome`, see
+/// [continuous]
+pub fn genome<C: Connection, G: Genome<C>>(genome: &G, name: &str) -> String {
+    continuous(&Continuous::from_genome(genome), name)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rulinalg::matrix::Matrix;
+
+    #[test]
+    fn test_continuous() {
+        let nn = Continuous {
+            y: Matrix::zeros(1, 3),
+            θ: Matrix::new(1, 3, vec![0., 0., 1.]),
+            τ: Matrix::new(1, 3, vec![0.1; 3]),
+            w: Matrix::new(3, 3, vec![0., 0.5, 0., 0., 0., 0., 0., -2., 0.]),
+            sensory: (0, 1),
+            action: (1, 2),
+            appended: Default::default(),
+            activation: None,
+            dt: 1.,
+            solver: Solver::Euler,
+        };
+
+        assert_eq!(
+            continuous(&nn, "champion"),
+            "\
+pub fn champion<F: Fn(f64) -> f64>(
+    y: &mut [f64; 3],
+    input: &[f64; 1],
+    prec: usize,
+    sigma: F,
+) -> [f64; 1] {
+    let h = 1.0 / prec as f64;
+    for _ in 0..prec {
+        let f0 = sigma(y[0] + 0.0);
+        let f2 = sigma(y[2] + 1.0);
+        let d0 = (-y[0] + input[0]) * 0.1;
+        let d1 = (-y[1] + f0 * 0.5 + f2 * -2.0) * 0.1;
+        let d2 = (-y[2]) * 0.1;
+        y[0] += d0 * h;
+        y[1] += d1 * h;
+        y[2] += d2 * h;
+    }
+    [y[1]]
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_lit() {
+        assert_eq!(lit(1e-7), "1e-7");
+        assert_eq!(lit(-0.5), "-0.5");
+        assert_eq!(lit(f64::NEG_INFINITY), "-f64::INFINITY");
+        assert_eq!(lit(f64::NAN), "f64::NAN");
+    }
+}
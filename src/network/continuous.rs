@@ -1,12 +1,25 @@
-use super::{FromGenome, Recurrent, Stateful};
+use super::{fire, Appended, Footprint, FromGenome, Recurrent, Stateful};
 use crate::{
-    genome::NodeKind,
     serialize::{deserialize_matrix_flat, deserialize_matrix_square, serialize_matrix},
     Connection, Genome, Network,
 };
 use rulinalg::matrix::{BaseMatrix, BaseMatrixMut, Matrix};
 use serde::{Deserialize, Serialize};
 
+/// Numerical method used to integrate a [Continuous] network's state through time.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Solver {
+    /// Forward euler, cheap but inaccurate over large timesteps
+    #[default]
+    Euler,
+    /// Classic 4th order Runge–Kutta, ~4x the cost of [Solver::Euler] per step
+    Rk4,
+}
+
+fn default_dt() -> f64 {
+    1.
+}
+
 /// A stateful NN who receives input continuously, useful for realtime problems
 /// and genomes whos connections may be recurrent.
 ///
@@ -43,27 +56,105 @@ pub struct Continuous {
     pub sensory: (usize, usize),
     /// Range of output neurons, indexing into y
     pub action: (usize, usize),
+    /// Io nodes grown past the rest, see [Appended]
+    #[serde(default, skip_serializing_if = "Appended::is_empty")]
+    pub appended: Appended,
+    /// [Activation id](super::activate::PALETTE) of each neuron, used in place of the σ given to
+    /// [step](Network::step), if the genome evolves them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activation: Option<Vec<usize>>,
+    /// Time integrated over by a single call to step, split evenly across `prec` sub-steps
+    #[serde(default = "default_dt")]
+    pub dt: f64,
+    /// Integration method for each sub-step
+    #[serde(default)]
+    pub solver: Solver,
+}
+
+impl Continuous {
+    pub fn with_dt(mut self, dt: f64) -> Self {
+        self.dt = dt;
+        self
+    }
+
+    pub fn with_solver(mut self, solver: Solver) -> Self {
+        self.solver = solver;
+        self
+    }
+
+    /// dy/dt for some state `y`
+    #[inline]
+    fn dy<F: Fn(f64) -> f64>(&self, y: &Matrix<f64>, input: &Matrix<f64>, σ: &F) -> Matrix<f64> {
+        let mut fired = y + &self.θ;
+        fire(self.activation.as_deref(), fired.mut_data(), σ);
+        ((fired * &self.w) - y + input).elemul(&self.τ)
+    }
 }
 
 impl Network for Continuous {
     fn step<F: Fn(f64) -> f64>(&mut self, prec: usize, input: &[f64], σ: F) {
         let mut m_input = Matrix::zeros(1, self.y.cols());
-        m_input.mut_data()[self.sensory.0..self.sensory.1].copy_from_slice(input);
-
-        let inv = 1. / (prec as f64);
-        for _ in 0..prec {
-            self.y += (((&self.y + &self.θ).apply(&σ) * &self.w) - &self.y + &m_input)
-                .elemul(&self.τ)
-                .apply(&|v| v * inv);
+        self.appended
+            .scatter(self.sensory.0..self.sensory.1, input, m_input.mut_data());
+
+        let h = self.dt / (prec as f64);
+        match self.solver {
+            Solver::Euler => {
+                for _ in 0..prec {
+                    self.y += self.dy(&self.y, &m_input, &σ).apply(&|v| v * h);
+                }
+            }
+            Solver::Rk4 => {
+                for _ in 0..prec {
+                    let k1 = self.dy(&self.y, &m_input, &σ);
+                    let k2 = self.dy(&(&self.y + &k1 * (h / 2.)), &m_input, &σ);
+                    let k3 = self.dy(&(&self.y + &k2 * (h / 2.)), &m_input, &σ);
+                    let k4 = self.dy(&(&self.y + &k3 * h), &m_input, &σ);
+                    self.y += (k1 + k2 * 2. + k3 * 2. + k4) * (h / 6.);
+                }
+            }
         }
+        self.appended
+            .gather(self.action.0..self.action.1, self.y.data());
     }
 
     fn flush(&mut self) {
         self.y = Matrix::zeros(1, self.y.cols());
+        self.appended
+            .gather(self.action.0..self.action.1, self.y.data());
     }
 
     fn output(&self) -> &[f64] {
-        &self.y.data()[self.action.0..self.action.1]
+        self.appended
+            .output(self.action.0..self.action.1, self.y.data())
+    }
+
+    fn activations(&self) -> &[f64] {
+        self.y.data()
+    }
+
+    fn state(&self) -> Vec<f64> {
+        self.y.data().to_vec()
+    }
+
+    fn set_state(&mut self, state: &[f64]) {
+        self.y.mut_data().copy_from_slice(state);
+        self.appended
+            .gather(self.action.0..self.action.1, self.y.data());
+    }
+
+    fn footprint(&self) -> Footprint {
+        let held = [&self.y, &self.θ, &self.τ, &self.w].map(|m| m.data().len());
+        Footprint::of_weights(
+            self.y.cols(),
+            self.w.data(),
+            size_of::<Self>() + size_of::<f64>() * held.iter().sum::<usize>(),
+        )
+    }
+
+    fn weight(&self, from: usize, to: usize) -> Option<f64> {
+        let cols = self.w.cols();
+        (from < cols && to < cols).then(|| self.w.data()[from * cols + to])
     }
 }
 
@@ -76,22 +167,8 @@ impl<C: Connection, G: Genome<C>> FromGenome<C, G> for Continuous {
         let cols = genome.nodes().len();
         Self {
             y: Matrix::zeros(1, cols),
-            θ: Matrix::new(
-                1,
-                cols,
-                genome
-                    .nodes()
-                    .iter()
-                    .map(|n| {
-                        if matches!(n, NodeKind::Static) {
-                            1.
-                        } else {
-                            0.
-                        }
-                    })
-                    .collect::<Vec<_>>(),
-            ),
-            τ: Matrix::new(1, cols, vec![0.1; cols]),
+            θ: Matrix::new(1, cols, genome.biases()),
+            τ: Matrix::new(1, cols, genome.time_constants()),
             w: {
                 let mut w = vec![0.; cols * cols];
                 for c in genome.connections().iter().filter(|c| c.enabled()) {
@@ -101,8 +178,40 @@ impl<C: Connection, G: Genome<C>> FromGenome<C, G> for Continuous {
             },
             sensory: (genome.sensory().start, genome.sensory().end),
             action: (genome.action().start, genome.action().end),
+            appended: Appended::of(genome),
+            activation: genome.node_activations().map(<[usize]>::to_vec),
+            dt: default_dt(),
+            solver: Solver::Euler,
         }
     }
+
+    /// Reuses this network's matrices when it has as many nodes as `genome`. Its state is
+    /// reset, but [dt](Continuous::dt) and [solver](Continuous::solver) are kept.
+    fn rebuild(&mut self, genome: &G) {
+        let cols = genome.nodes().len();
+        if self.y.cols() != cols {
+            let (dt, solver) = (self.dt, self.solver);
+            *self = Self::from_genome(genome);
+            (self.dt, self.solver) = (dt, solver);
+            return;
+        }
+
+        self.y.mut_data().fill(0.);
+        self.θ.mut_data().copy_from_slice(&genome.biases());
+        match genome.node_time_constants() {
+            Some(time_constants) => self.τ.mut_data().copy_from_slice(time_constants),
+            None => self.τ.mut_data().fill(G::TIME_CONSTANT),
+        }
+        let w = self.w.mut_data();
+        w.fill(0.);
+        for c in genome.connections().iter().filter(|c| c.enabled()) {
+            w[c.from() * cols + c.to()] = c.weight();
+        }
+        self.sensory = (genome.sensory().start, genome.sensory().end);
+        self.action = (genome.action().start, genome.action().end);
+        self.appended = Appended::of(genome);
+        self.activation = genome.node_activations().map(<[usize]>::to_vec);
+    }
 }
 
 #[cfg(test)]
@@ -112,6 +221,7 @@ mod test {
         activate, assert_f64_approx, assert_matrix_approx,
         genome::InnoGen,
         genome::{self, NodeKind, WConnection},
+        network::ToNetwork,
         random::default_rng,
     };
     use rand_distr::{num_traits::Float, Distribution, Uniform};
@@ -147,6 +257,10 @@ mod test {
             w: Matrix::new(n_neurons, n_neurons, w_data),
             sensory: (0, 2),
             action: (3, 5),
+            appended: Default::default(),
+            activation: None,
+            dt: 1.,
+            solver: Solver::Euler,
         };
 
         let serialized = original.to_string().expect("Failed to serialize");
@@ -190,6 +304,10 @@ mod test {
             w: Matrix::new(n_neurons, n_neurons, w_data),
             sensory: (0, 2),
             action: (3, 5),
+            appended: Default::default(),
+            activation: None,
+            dt: 1.,
+            solver: Solver::Euler,
         };
 
         let mut deserialized =
@@ -255,4 +373,236 @@ mod test {
                 .is_some_and(|n| matches!(n, NodeKind::Action)))
         }
     }
+
+    #[test]
+    fn test_step_until_stable() {
+        type C = WConnection;
+
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(1, 1);
+        genome.push_connection(C::new(0, 1, &mut inno));
+
+        let mut nn = Continuous::from_genome(&genome);
+        let taken = nn.step_until_stable(10_000, 1e-9, &[1.], activate::steep_sigmoid);
+        assert!(taken < 10_000, "never settled");
+
+        let settled = nn.output()[0];
+        nn.step(1, &[1.], activate::steep_sigmoid);
+        assert!((nn.output()[0] - settled).abs() < 1e-9);
+
+        nn.flush();
+        assert_eq!(
+            nn.step_until_stable(50, 0., &[1.], activate::steep_sigmoid),
+            50
+        );
+    }
+
+    #[test]
+    fn test_rk4_accuracy() {
+        type C = WConnection;
+
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(1, 2);
+        genome.push_connection(C::new(0, 1, &mut inno));
+        genome.push_connection(C::new(1, 2, &mut inno));
+        genome.push_connection(C::new(2, 1, &mut inno));
+        genome.connections_mut()[2].weight = -2.;
+
+        let run = |solver, prec| {
+            let mut nn = Continuous::from_genome(&genome)
+                .with_dt(5.)
+                .with_solver(solver);
+            nn.step(prec, &[1.], activate::steep_sigmoid);
+            nn.output().to_vec()
+        };
+
+        let truth = run(Solver::Euler, 100_000);
+        let euler = run(Solver::Euler, 4);
+        let rk4 = run(Solver::Rk4, 4);
+        for ((t, e), r) in truth.iter().zip(euler.iter()).zip(rk4.iter()) {
+            assert!((t - r).abs() < 1e-4, "rk4 {r} too far from {t}");
+            assert!(
+                (t - r).abs() < (t - e).abs(),
+                "rk4 {r} less accurate than euler {e}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_snapshot() {
+        type C = WConnection;
+
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(2, 1);
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.push_connection(C::new(3, 2, &mut inno));
+
+        let mut nn = Continuous::from_genome(&genome);
+        nn.step(2, &[0.5, 1.], activate::steep_sigmoid);
+
+        let snapshot = nn.snapshot(genome.nodes());
+        assert_eq!(snapshot.len(), genome.nodes().len());
+        for ((kind, value), (node, activation)) in snapshot
+            .iter()
+            .zip(genome.nodes().iter().zip(nn.activations()))
+        {
+            assert_eq!(kind, node);
+            assert_f64_approx!(value, activation);
+        }
+        assert_f64_approx!(snapshot[2].1, nn.output()[0]);
+    }
+
+    #[test]
+    fn test_network_pruned() {
+        type C = WConnection;
+
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(2, 1);
+        genome.push_node(NodeKind::Internal);
+        genome.push_node(NodeKind::Internal);
+        genome.push_connection(C::new(0, 4, &mut inno));
+        genome.push_connection(C::new(4, 2, &mut inno));
+        genome.push_connection(C::new(1, 5, &mut inno));
+        genome.push_connection(C::new(5, 5, &mut inno));
+
+        let mut full = Continuous::from_genome(&genome);
+        let mut pruned: Continuous = genome.network_pruned();
+        assert_eq!(pruned.y.cols(), full.y.cols() - 1);
+        for _ in 0..5 {
+            full.step(2, &[1., -1.], activate::steep_sigmoid);
+            pruned.step(2, &[1., -1.], activate::steep_sigmoid);
+            assert_matrix_approx!(full.output(), pruned.output());
+        }
+    }
+
+    #[test]
+    fn test_network_lines_up_with_genome() {
+        type C = WConnection;
+
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(1, 1);
+        genome.push_node(NodeKind::Internal);
+        genome.push_node(NodeKind::Internal);
+        genome.push_connection(C::new(0, 3, &mut inno));
+        genome.push_connection(C::new(0, 4, &mut inno));
+        genome.push_connection(C::new(4, 1, &mut inno));
+        genome.connections_mut()[0].weight = 7.;
+        genome.connections_mut()[1].weight = 2.;
+        genome.connections_mut()[2].weight = 3.;
+
+        let mut nn: Continuous = genome.network();
+        nn.step(2, &[1.], activate::steep_sigmoid);
+        assert_eq!(nn.activations().len(), genome.nodes().len());
+        assert_eq!(nn.snapshot(genome.nodes()).len(), genome.nodes().len());
+        assert_eq!(nn.weight(0, 3), Some(7.));
+        assert_eq!(nn.weight(4, 1), Some(3.));
+
+        assert_eq!(genome.absorb_network(&nn), 3);
+        assert_eq!(
+            genome
+                .connections()
+                .iter()
+                .map(|c| c.weight())
+                .collect::<Vec<_>>(),
+            vec![7., 2., 3.]
+        );
+
+        genome.connections_mut()[0].weight = 1.;
+        genome.connections_mut()[0].set_frozen(true);
+        assert_eq!(genome.absorb_network(&nn), 2);
+        assert_eq!(genome.connections()[0].weight(), 1.);
+    }
+
+    #[test]
+    fn test_state() {
+        type C = WConnection;
+
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(2, 1);
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.push_connection(C::new(2, 2, &mut inno));
+
+        let mut nn = Continuous::from_genome(&genome);
+        nn.step(3, &[0.5, 1.], activate::steep_sigmoid);
+        let checkpoint = serde_json::to_string(&nn.state()).unwrap();
+        nn.step(3, &[1., 0.], activate::steep_sigmoid);
+
+        let mut resumed = Continuous::from_genome(&genome);
+        resumed.set_state(&serde_json::from_str::<Vec<f64>>(&checkpoint).unwrap());
+        resumed.step(3, &[1., 0.], activate::steep_sigmoid);
+        assert_matrix_approx!(resumed.y.data(), nn.y.data());
+    }
+
+    #[test]
+    fn test_network_into() {
+        type C = WConnection;
+
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(2, 1);
+        genome.push_connection(C::new(0, 2, &mut inno));
+        let mut nn: Continuous = genome.network();
+        nn.dt = 0.5;
+        nn.step(3, &[1., 1.], activate::steep_sigmoid);
+
+        genome.push_connection(C::new(1, 2, &mut inno));
+        genome.connections_mut()[1].weight = -2.;
+        genome.network_into(&mut nn);
+        let fresh: Continuous = genome.network();
+        assert_matrix_approx!(nn.y.data(), fresh.y.data());
+        assert_matrix_approx!(nn.w.data(), fresh.w.data());
+        assert_eq!(nn.dt, 0.5);
+
+        genome
+            .bisect_connection(&mut default_rng(), &mut inno)
+            .unwrap();
+        genome.network_into(&mut nn);
+        assert_eq!(nn.y.cols(), genome.nodes().len());
+        assert_eq!(nn.dt, 0.5);
+    }
+
+    #[test]
+    fn test_footprint() {
+        type C = WConnection;
+
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(2, 1);
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.push_connection(C::new(1, 2, &mut inno));
+        genome.push_connection(C::new(3, 2, &mut inno));
+        genome.connections_mut()[2].disable();
+
+        let nn = Continuous::from_genome(&genome);
+        let footprint = nn.footprint();
+        assert_eq!(footprint.nodes, 4);
+        assert_eq!(footprint.connections, 2);
+        assert_eq!(footprint.dense, 16);
+        assert!(footprint.bytes >= size_of::<f64>() * (16 + 3 * 4));
+        assert_eq!(
+            genome.footprint(),
+            Footprint {
+                bytes: genome.memory(),
+                ..footprint
+            }
+        );
+    }
+
+    #[test]
+    fn test_node_activations() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<WConnection>::new(1, 1);
+        genome.push_connection(WConnection::new(0, 1, &mut inno));
+
+        let output = |genome: &genome::Recurrent<WConnection>| {
+            let mut nn = Continuous::from_genome(genome);
+            nn.step(2, &[-1.], activate::steep_sigmoid);
+            nn.output()[0]
+        };
+        // every node starts out with the steep sigmoid that it's stepped with
+        let plain = output(&genome);
+        let mut genome = genome.with_node_activations();
+        assert_f64_approx!(output(&genome), plain);
+
+        genome.node_activations_mut().unwrap()[0] = activate::id::ABS;
+        assert_ne!(output(&genome), plain);
+    }
 }
@@ -0,0 +1,128 @@
+use super::{Footprint, FromGenome, Linear, Network, Recurrent, Stateful};
+use crate::{Connection, Genome};
+use serde::{Deserialize, Serialize};
+
+/// A network who feeds the last `K` values of each sensory input to its inner network alongside
+/// the current ones, so that genomes may respond to short histories of their input without
+/// either recurrence or the scenario needing to track it.
+///
+/// The inner network's sensory range should be [Delayed::sensory] wide for the raw input, which
+/// is laid out newest first, ie `[x(t), x(t-1), .., x(t-K)]`. History is zeroed on creation
+/// and [flush](Network::flush).
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Delayed<NN: Network, const K: usize> {
+    pub inner: NN,
+    history: Vec<f64>,
+    tapped: Vec<f64>,
+}
+
+impl<NN: Network, const K: usize> Delayed<NN, K> {
+    /// Sensory width of a genome whose raw input is `raw` wide
+    pub const fn sensory(raw: usize) -> usize {
+        raw * (K + 1)
+    }
+
+    /// Wrap `inner`, who receives raw input `raw` wide
+    pub fn new(inner: NN, raw: usize) -> Self {
+        Self {
+            inner,
+            history: vec![0.; raw * K],
+            tapped: vec![0.; Self::sensory(raw)],
+        }
+    }
+}
+
+impl<NN: Network, const K: usize> Network for Delayed<NN, K> {
+    fn step<F: Fn(f64) -> f64>(&mut self, prec: usize, input: &[f64], σ: F) {
+        let raw = input.len();
+        debug_assert_eq!(self.tapped.len(), Self::sensory(raw));
+        self.tapped[..raw].copy_from_slice(input);
+        self.tapped[raw..].copy_from_slice(&self.history);
+        self.inner.step(prec, &self.tapped, σ);
+        self.history.copy_from_slice(&self.tapped[..raw * K]);
+    }
+
+    fn flush(&mut self) {
+        self.inner.flush();
+        self.history.fill(0.);
+    }
+
+    fn output(&self) -> &[f64] {
+        self.inner.output()
+    }
+
+    fn activations(&self) -> &[f64] {
+        self.inner.activations()
+    }
+
+    fn state(&self) -> Vec<f64> {
+        [self.history.as_slice(), &self.inner.state()].concat()
+    }
+
+    fn set_state(&mut self, state: &[f64]) {
+        let (history, inner) = state.split_at(self.history.len());
+        self.history.copy_from_slice(history);
+        self.inner.set_state(inner);
+    }
+
+    fn footprint(&self) -> Footprint {
+        self.inner
+            .footprint()
+            .with_bytes(size_of::<f64>() * (self.history.len() + self.tapped.len()))
+    }
+
+    fn weight(&self, from: usize, to: usize) -> Option<f64> {
+        self.inner.weight(from, to)
+    }
+}
+
+impl<NN: Recurrent, const K: usize> Recurrent for Delayed<NN, K> {}
+
+impl<NN: Linear, const K: usize> Linear for Delayed<NN, K> {}
+
+impl<NN: Network, const K: usize> Stateful for Delayed<NN, K> {}
+
+impl<C: Connection, G: Genome<C>, NN: FromGenome<C, G>, const K: usize> FromGenome<C, G>
+    for Delayed<NN, K>
+{
+    fn from_genome(genome: &G) -> Self {
+        let sensory = genome.inputs();
+        debug_assert_eq!(sensory % (K + 1), 0, "sensory not divisible by taps");
+        Self::new(NN::from_genome(genome), sensory / (K + 1))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        assert_f64_approx,
+        genome::{self, InnoGen, WConnection},
+        network::Simple,
+    };
+
+    type C = WConnection;
+
+    #[test]
+    fn test_delayed_input() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(Delayed::<Simple<C>, 2>::sensory(1), 1);
+        genome.push_connection(C::new(2, 3, &mut inno));
+
+        let mut nn = Delayed::<Simple<C>, 2>::from_genome(&genome);
+        let id = |x| x;
+        nn.step(1, &[1.], id);
+        assert_f64_approx!(nn.output()[0], 0.);
+        nn.step(1, &[2.], id);
+        assert_f64_approx!(nn.output()[0], 0.);
+        nn.step(1, &[3.], id);
+        assert_f64_approx!(nn.output()[0], 1.);
+        nn.step(1, &[4.], id);
+        assert_f64_approx!(nn.output()[0], 1. + 2.);
+
+        nn.flush();
+        nn.step(1, &[5.], id);
+        assert_f64_approx!(nn.output()[0], 0.);
+    }
+}
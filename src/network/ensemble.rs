@@ -0,0 +1,184 @@
+use super::{Footprint, FromGenome, Linear, Network, Recurrent, Stateful, Stateless};
+use crate::{Connection, Genome};
+use serde::{Deserialize, Serialize};
+
+/// How an [Ensemble] combines the outputs of its members
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Combine {
+    /// Each action is the mean of that action across members
+    #[default]
+    Mean,
+    /// Each member votes for its strongest action, and each action is the fraction of votes
+    /// that it received
+    Vote,
+}
+
+/// A network made of several networks of the same io, like the champions kept by a
+/// [HallOfFame](crate::adversarial::HallOfFame), who are all stepped with the same input and
+/// whose outputs are [combined](Combine). Robust where any single member may not be.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Ensemble<NN: Network> {
+    pub members: Vec<NN>,
+    pub combine: Combine,
+    out: Vec<f64>,
+    activations: Vec<f64>,
+}
+
+impl<NN: Network> Ensemble<NN> {
+    pub fn new(members: Vec<NN>, combine: Combine) -> Self {
+        assert!(!members.is_empty(), "ensemble needs at least one member");
+        let width = members[0].output().len();
+        assert!(
+            members.iter().all(|nn| nn.output().len() == width),
+            "ensemble members differ in output width"
+        );
+        let mut ensemble = Self {
+            out: vec![0.; width],
+            activations: Vec::new(),
+            members,
+            combine,
+        };
+        ensemble.combine();
+        ensemble
+    }
+
+    /// An ensemble of the networks expressed by each of `genomes`
+    pub fn from_genomes<'a, C: Connection, G: Genome<C> + 'a>(
+        genomes: impl IntoIterator<Item = &'a G>,
+        combine: Combine,
+    ) -> Self
+    where
+        NN: FromGenome<C, G>,
+    {
+        Self::new(genomes.into_iter().map(NN::from_genome).collect(), combine)
+    }
+
+    fn combine(&mut self) {
+        self.out.fill(0.);
+        let share = 1. / self.members.len() as f64;
+        for nn in self.members.iter() {
+            match self.combine {
+                Combine::Mean => {
+                    for (out, x) in self.out.iter_mut().zip(nn.output()) {
+                        *out += x * share;
+                    }
+                }
+                Combine::Vote => {
+                    if let Some((idx, _)) = nn
+                        .output()
+                        .iter()
+                        .enumerate()
+                        .max_by(|(_, l), (_, r)| l.total_cmp(r))
+                    {
+                        self.out[idx] += share;
+                    }
+                }
+            }
+        }
+
+        self.activations.clear();
+        for nn in self.members.iter() {
+            self.activations.extend_from_slice(nn.activations());
+        }
+    }
+}
+
+impl<NN: Network> Network for Ensemble<NN> {
+    fn step<F: Fn(f64) -> f64>(&mut self, prec: usize, input: &[f64], σ: F) {
+        for nn in self.members.iter_mut() {
+            nn.step(prec, input, &σ);
+        }
+        self.combine();
+    }
+
+    fn flush(&mut self) {
+        self.members.iter_mut().for_each(Network::flush);
+        self.combine();
+    }
+
+    fn output(&self) -> &[f64] {
+        &self.out
+    }
+
+    /// The activations of every member, one after another
+    fn activations(&self) -> &[f64] {
+        &self.activations
+    }
+
+    fn state(&self) -> Vec<f64> {
+        self.members.iter().flat_map(|nn| nn.state()).collect()
+    }
+
+    fn set_state(&mut self, state: &[f64]) {
+        let mut rest = state;
+        for nn in self.members.iter_mut() {
+            let (head, tail) = rest.split_at(nn.state().len());
+            nn.set_state(head);
+            rest = tail;
+        }
+        assert!(rest.is_empty(), "state is sized for a larger ensemble");
+        self.combine();
+    }
+
+    /// The footprint of every member together
+    fn footprint(&self) -> Footprint {
+        self.members
+            .iter()
+            .map(Network::footprint)
+            .fold(Footprint::default(), |sum, member| sum + member)
+            .with_bytes(size_of::<Self>() + size_of_val(self.out.as_slice()))
+    }
+}
+
+impl<NN: Recurrent> Recurrent for Ensemble<NN> {}
+
+impl<NN: Linear> Linear for Ensemble<NN> {}
+
+impl<NN: Stateful> Stateful for Ensemble<NN> {}
+
+impl<NN: Stateless> Stateless for Ensemble<NN> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        activate, assert_f64_approx,
+        genome::{self, InnoGen, WConnection},
+        network::Continuous,
+    };
+
+    type C = WConnection;
+
+    #[test]
+    fn test_combine() {
+        let mut inno = InnoGen::new(0);
+        let (base, _) = genome::Recurrent::<C>::new(2, 2);
+        let mut left = base.clone();
+        left.push_connection(C::new(0, 2, &mut inno));
+        let mut right = base;
+        right.push_connection(C::new(1, 3, &mut inno));
+
+        let mut nns = [
+            Continuous::from_genome(&left),
+            Continuous::from_genome(&right),
+        ];
+        let mut mean = Ensemble::<Continuous>::from_genomes([&left, &right], Combine::Mean);
+        let mut vote = Ensemble::<Continuous>::from_genomes([&left, &right, &left], Combine::Vote);
+        for _ in 0..5 {
+            nns.iter_mut()
+                .for_each(|nn| nn.step(2, &[1., -1.], activate::relu));
+            mean.step(2, &[1., -1.], activate::relu);
+            vote.step(2, &[1., -1.], activate::relu);
+            for idx in 0..2 {
+                assert_f64_approx!(
+                    mean.output()[idx],
+                    (nns[0].output()[idx] + nns[1].output()[idx]) / 2.
+                );
+            }
+        }
+
+        assert_f64_approx!(vote.output().iter().sum::<f64>(), 1.);
+        assert_eq!(mean.activations().len(), 2 * nns[0].activations().len());
+    }
+}
@@ -0,0 +1,247 @@
+use super::{fire, Appended, Footprint, FromGenome, Network, Recurrent, Stateful};
+use crate::{
+    genome::Plastic,
+    serialize::{deserialize_matrix_flat, deserialize_matrix_square, serialize_matrix},
+    Genome,
+};
+use rulinalg::matrix::{BaseMatrix, BaseMatrixMut, Matrix};
+use serde::{Deserialize, Serialize};
+
+/// A [Continuous](super::Continuous)-like network whose weights are plastic, adapting while it's
+/// stepped by the hebbian rule `dw[i, j] = η[i, j] * σ(y[i] + θ[i]) * σ(y[j] + θ[j])`, where η
+/// is the evolved learning rate of each [Plastic] connection. Flushing the network also restores
+/// its weights to those it was built with.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Hebbian {
+    /// 1d state of neurons 0-N
+    #[serde(
+        serialize_with = "serialize_matrix",
+        deserialize_with = "deserialize_matrix_flat"
+    )]
+    pub y: Matrix<f64>,
+    /// 1d bias of neurons 0-N
+    #[serde(
+        serialize_with = "serialize_matrix",
+        deserialize_with = "deserialize_matrix_flat"
+    )]
+    pub θ: Matrix<f64>,
+    /// 1d membrane resistance time constant
+    #[serde(
+        serialize_with = "serialize_matrix",
+        deserialize_with = "deserialize_matrix_flat"
+    )]
+    pub τ: Matrix<f64>,
+    /// Nd weights between neurons, indexed as [from, to]
+    #[serde(
+        serialize_with = "serialize_matrix",
+        deserialize_with = "deserialize_matrix_square"
+    )]
+    pub w: Matrix<f64>,
+    /// Nd weights between neurons as they were before any learning, indexed as [from, to]
+    #[serde(
+        serialize_with = "serialize_matrix",
+        deserialize_with = "deserialize_matrix_square"
+    )]
+    pub w_init: Matrix<f64>,
+    /// Nd learning rate between neurons, indexed as [from, to]
+    #[serde(
+        serialize_with = "serialize_matrix",
+        deserialize_with = "deserialize_matrix_square"
+    )]
+    pub η: Matrix<f64>,
+    /// Range of input neurons, indexing into y
+    pub sensory: (usize, usize),
+    /// Range of output neurons, indexing into y
+    pub action: (usize, usize),
+    /// Io nodes grown past the rest, see [Appended]
+    #[serde(default, skip_serializing_if = "Appended::is_empty")]
+    pub appended: Appended,
+    /// [Activation id](super::activate::PALETTE) of each neuron, used in place of the σ given to
+    /// [step](Network::step), if the genome evolves them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activation: Option<Vec<usize>>,
+}
+
+impl Network for Hebbian {
+    fn step<F: Fn(f64) -> f64>(&mut self, prec: usize, input: &[f64], σ: F) {
+        let mut m_input = Matrix::zeros(1, self.y.cols());
+        self.appended
+            .scatter(self.sensory.0..self.sensory.1, input, m_input.mut_data());
+
+        let inv = 1. / (prec as f64);
+        for _ in 0..prec {
+            let mut fired = &self.y + &self.θ;
+            fire(self.activation.as_deref(), fired.mut_data(), &σ);
+            self.y += ((&fired * &self.w) - &self.y + &m_input)
+                .elemul(&self.τ)
+                .apply(&|v| v * inv);
+            self.w += (fired.transpose() * &fired).elemul(&self.η) * inv;
+        }
+        self.appended
+            .gather(self.action.0..self.action.1, self.y.data());
+    }
+
+    fn flush(&mut self) {
+        self.y = Matrix::zeros(1, self.y.cols());
+        self.w = self.w_init.clone();
+        self.appended
+            .gather(self.action.0..self.action.1, self.y.data());
+    }
+
+    fn output(&self) -> &[f64] {
+        self.appended
+            .output(self.action.0..self.action.1, self.y.data())
+    }
+
+    fn activations(&self) -> &[f64] {
+        self.y.data()
+    }
+
+    fn state(&self) -> Vec<f64> {
+        [self.y.data().as_slice(), self.w.data()].concat()
+    }
+
+    fn set_state(&mut self, state: &[f64]) {
+        let (y, w) = state.split_at(self.y.cols());
+        self.y.mut_data().copy_from_slice(y);
+        self.w.mut_data().copy_from_slice(w);
+        self.appended
+            .gather(self.action.0..self.action.1, self.y.data());
+    }
+
+    fn footprint(&self) -> Footprint {
+        let held =
+            [&self.y, &self.θ, &self.τ, &self.w, &self.w_init, &self.η].map(|m| m.data().len());
+        Footprint::of_weights(
+            self.y.cols(),
+            self.w.data(),
+            size_of::<Self>() + size_of::<f64>() * held.iter().sum::<usize>(),
+        )
+    }
+
+    fn weight(&self, from: usize, to: usize) -> Option<f64> {
+        let cols = self.w.cols();
+        (from < cols && to < cols).then(|| self.w.data()[from * cols + to])
+    }
+}
+
+impl Recurrent for Hebbian {}
+
+impl Stateful for Hebbian {}
+
+impl<C: Plastic, G: Genome<C>> FromGenome<C, G> for Hebbian {
+    fn from_genome(genome: &G) -> Self {
+        let cols = genome.nodes().len();
+        let mut w = vec![0.; cols * cols];
+        let mut η = vec![0.; cols * cols];
+        for c in genome.connections().iter().filter(|c| c.enabled()) {
+            w[c.from() * cols + c.to()] = c.weight();
+            η[c.from() * cols + c.to()] = c.rate();
+        }
+
+        Self {
+            y: Matrix::zeros(1, cols),
+            θ: Matrix::new(1, cols, genome.biases()),
+            τ: Matrix::new(1, cols, genome.time_constants()),
+            w: Matrix::new(cols, cols, w.clone()),
+            w_init: Matrix::new(cols, cols, w),
+            η: Matrix::new(cols, cols, η),
+            sensory: (genome.sensory().start, genome.sensory().end),
+            action: (genome.action().start, genome.action().end),
+            appended: Appended::of(genome),
+            activation: genome.node_activations().map(<[usize]>::to_vec),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        activate, assert_f64_approx,
+        genome::{self, HConnection, InnoGen},
+        Connection,
+    };
+
+    type C = HConnection;
+
+    #[test]
+    fn test_hebbian_learns() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(1, 2);
+        genome.push_connection(C::new(0, 1, &mut inno));
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.connections_mut()[0].rate = 0.5;
+
+        let mut nn = Hebbian::from_genome(&genome);
+        for _ in 0..10 {
+            nn.step(2, &[1.], activate::steep_sigmoid);
+        }
+
+        unsafe {
+            assert!(*nn.w.get_unchecked([0, 1]) > 1.);
+            assert_f64_approx!(nn.w.get_unchecked([0, 2]), 1.);
+            assert_f64_approx!(nn.w.get_unchecked([1, 2]), 0.);
+        }
+
+        nn.flush();
+        unsafe {
+            assert_f64_approx!(nn.w.get_unchecked([0, 1]), 1.);
+        }
+    }
+
+    #[test]
+    fn test_absorb_network() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(1, 2);
+        genome.push_connection(C::new(0, 1, &mut inno));
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.connections_mut()[0].rate = 0.5;
+
+        let mut nn = Hebbian::from_genome(&genome);
+        for _ in 0..10 {
+            nn.step(2, &[1.], activate::steep_sigmoid);
+        }
+        assert_eq!(genome.absorb_network(&nn), 2);
+        assert_eq!(genome.connections()[0].weight(), nn.weight(0, 1).unwrap());
+        assert!(genome.connections()[0].weight() > 1.);
+        assert_f64_approx!(genome.connections()[1].weight(), 1.);
+
+        // what was learned is where offspring start out
+        let learned = Hebbian::from_genome(&genome);
+        assert_eq!(learned.w_init.data(), nn.w.data());
+        assert_eq!(nn.weight(0, 4), None);
+    }
+
+    #[test]
+    fn test_rate_phenotype_cache_key() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(1, 1);
+        genome.push_connection(C::new(0, 1, &mut inno));
+        let mut plastic = genome.clone();
+        plastic.connections_mut()[0].rate = 0.5;
+
+        assert_ne!(genome.phenotype_cache_key(), plastic.phenotype_cache_key());
+        assert!(!genome.phenotype_eq(&plastic));
+    }
+
+    #[test]
+    fn test_node_activations() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(1, 1);
+        genome.push_connection(C::new(0, 1, &mut inno));
+
+        let output = |genome: &genome::Recurrent<C>| {
+            let mut nn = Hebbian::from_genome(genome);
+            nn.step(2, &[-1.], activate::steep_sigmoid);
+            nn.output()[0]
+        };
+        // every node starts out with the steep sigmoid that it's stepped with
+        let plain = output(&genome);
+        let mut genome = genome.with_node_activations();
+        assert_f64_approx!(output(&genome), plain);
+
+        genome.node_activations_mut().unwrap()[0] = activate::id::ABS;
+        assert_ne!(output(&genome), plain);
+    }
+}
@@ -0,0 +1,127 @@
+use super::{Footprint, FromGenome, Linear, Network, Recurrent, Stateful, Stateless};
+use crate::{genome::IoLabels, Connection, Genome};
+use serde::{Deserialize, Serialize};
+
+/// A network who knows the [labels](Genome::io_labels) of the genome it was built from, so that
+/// its output may be read by name. It otherwise behaves exactly as its inner network. Genomes
+/// without labels give a network whose names all miss.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Labelled<NN: Network> {
+    pub inner: NN,
+    pub labels: IoLabels,
+}
+
+impl<NN: Network> Labelled<NN> {
+    pub fn new(inner: NN, labels: IoLabels) -> Self {
+        Self { inner, labels }
+    }
+
+    /// The output of the action node labelled `name`
+    pub fn output_by_name(&self, name: &str) -> Option<f64> {
+        self.labels
+            .action_index(name)
+            .and_then(|idx| self.inner.output().get(idx).copied())
+    }
+}
+
+impl<NN: Network> Network for Labelled<NN> {
+    fn step<F: Fn(f64) -> f64>(&mut self, prec: usize, input: &[f64], σ: F) {
+        self.inner.step(prec, input, σ);
+    }
+
+    fn flush(&mut self) {
+        self.inner.flush();
+    }
+
+    fn output(&self) -> &[f64] {
+        self.inner.output()
+    }
+
+    fn activations(&self) -> &[f64] {
+        self.inner.activations()
+    }
+
+    fn state(&self) -> Vec<f64> {
+        self.inner.state()
+    }
+
+    fn set_state(&mut self, state: &[f64]) {
+        self.inner.set_state(state);
+    }
+
+    fn footprint(&self) -> Footprint {
+        let labels = self.labels.sensory.iter().chain(self.labels.action.iter());
+        self.inner
+            .footprint()
+            .with_bytes(labels.map(String::capacity).sum())
+    }
+
+    fn weight(&self, from: usize, to: usize) -> Option<f64> {
+        self.inner.weight(from, to)
+    }
+}
+
+impl<NN: Recurrent> Recurrent for Labelled<NN> {}
+
+impl<NN: Linear> Linear for Labelled<NN> {}
+
+impl<NN: Stateful> Stateful for Labelled<NN> {}
+
+impl<NN: Stateless> Stateless for Labelled<NN> {}
+
+impl<C: Connection, G: Genome<C>, NN: FromGenome<C, G>> FromGenome<C, G> for Labelled<NN> {
+    fn from_genome(genome: &G) -> Self {
+        Self::new(
+            NN::from_genome(genome),
+            genome.io_labels().cloned().unwrap_or_default(),
+        )
+    }
+
+    fn rebuild(&mut self, genome: &G) {
+        self.inner.rebuild(genome);
+        self.labels = genome.io_labels().cloned().unwrap_or_default();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        activate,
+        genome::{self, InnoGen, WConnection},
+        network::Continuous,
+    };
+
+    type C = WConnection;
+
+    #[test]
+    fn test_output_by_name() {
+        let mut inno = InnoGen::new(0);
+        let (genome, _) = genome::Recurrent::<C>::new(2, 2);
+        let mut genome = genome.with_io_labels(IoLabels::new(
+            ["speed", "heading"],
+            ["throttle", "steering"],
+        ));
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.push_connection(C::new(1, 3, &mut inno));
+
+        let saved = genome::Genome::to_string(&genome).unwrap();
+        let genome: genome::Recurrent<C> = genome::Genome::from_str(&saved).unwrap();
+        assert_eq!(
+            genome.io_labels().unwrap().sensory_index("heading"),
+            Some(1)
+        );
+
+        let mut nn = Labelled::<Continuous>::from_genome(&genome);
+        nn.step(2, &[0.5, -0.5], activate::relu);
+        assert_eq!(nn.output_by_name("throttle"), Some(nn.output()[0]));
+        assert_eq!(nn.output_by_name("steering"), Some(nn.output()[1]));
+        assert_ne!(nn.output_by_name("throttle"), Some(0.));
+        assert_eq!(nn.output_by_name("brake"), None);
+
+        let (plain, _) = genome::Recurrent::<C>::new(2, 2);
+        let nn = Labelled::<Continuous>::from_genome(&plain);
+        assert_eq!(nn.output_by_name("throttle"), None);
+    }
+}
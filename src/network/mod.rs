@@ -4,34 +4,43 @@
 //! express their behaviour. The NEAT paper calls for a recurrent network with no per-connection
 //! bias, though maybe we can do more than that here. The code inside is quite experimental.
 
+pub mod codegen;
 pub mod continuous;
+pub mod delayed;
+pub mod ensemble;
+pub mod hebbian;
+pub mod labelled;
+pub mod modulated;
+pub mod noisy;
 pub mod non_bias;
+pub mod scaled;
 pub mod simple;
+pub mod smoothed;
+pub mod spiking;
+pub mod view;
 
 pub use continuous::Continuous;
+pub use delayed::Delayed;
+pub use ensemble::{Combine, Ensemble};
+pub use hebbian::Hebbian;
+pub use labelled::Labelled;
+pub use modulated::Modulated;
+pub use noisy::{Noise, Noisy};
 pub use non_bias::NonBias;
+pub use scaled::Scaled;
 pub use simple::Simple;
+pub use smoothed::Smoothed;
+pub use spiking::Spiking;
+pub use view::NetworkView;
 
-use crate::{Connection, Genome};
-use core::error::Error;
+use crate::{genome::NodeKind, Connection, Genome};
+use core::{error::Error, ops::Range};
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "fs")]
 use std::{fs, path::Path};
 
-pub mod activate {
-    use core::f64::consts::E;
-
-    pub fn steep_sigmoid(x: f64) -> f64 {
-        1. / (1. + E.powf(-4.9 * x))
-    }
-
-    pub fn relu(x: f64) -> f64 {
-        if x < 0. {
-            0.
-        } else {
-            x
-        }
-    }
-}
+pub use crate::inference::activate;
 
 pub mod loss {
     pub fn decay_quadratic(want: f64, x: f64) -> f64 {
@@ -47,12 +56,171 @@ pub mod loss {
     }
 }
 
+/// The size of a network or genome, for tracking bloat of evolved solutions
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Footprint {
+    pub nodes: usize,
+    /// Enabled connections of a genome, or nonzero weights of a network
+    pub connections: usize,
+    /// Entries of a dense weight matrix over every node, whether or not one is held
+    pub dense: usize,
+    /// Approximate bytes held, including heap allocations
+    pub bytes: usize,
+}
+
+impl Footprint {
+    /// Footprint of a network over `nodes` nodes, whose weights are `w`, holding `bytes` in all
+    pub fn of_weights(nodes: usize, w: &[f64], bytes: usize) -> Self {
+        Self {
+            nodes,
+            connections: w.iter().filter(|w| **w != 0.).count(),
+            dense: nodes * nodes,
+            bytes,
+        }
+    }
+
+    /// This footprint, holding `bytes` more
+    pub fn with_bytes(self, bytes: usize) -> Self {
+        Self {
+            bytes: self.bytes + bytes,
+            ..self
+        }
+    }
+}
+
+impl core::ops::Add for Footprint {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            nodes: self.nodes + rhs.nodes,
+            connections: self.connections + rhs.connections,
+            dense: self.dense + rhs.dense,
+            bytes: self.bytes + rhs.bytes,
+        }
+    }
+}
+
+/// Io nodes that a genome grew with [Genome::extend_io], which follow its other nodes rather
+/// than sitting within its sensory and action ranges. Networks give input past their sensory
+/// range to these in order, and gather output from their action range followed by these.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Appended {
+    pub sensory: Vec<usize>,
+    pub action: Vec<usize>,
+    /// Output gathered by the most recent call to [gather](Appended::gather)
+    #[serde(default)]
+    output: Vec<f64>,
+}
+
+impl Appended {
+    /// The io appended to `genome`, see [Genome::io_appended]
+    pub fn of<C: Connection, G: Genome<C>>(genome: &G) -> Self {
+        let (sensory, action) = genome.io_appended();
+        Self {
+            sensory: sensory.to_vec(),
+            action: action.to_vec(),
+            output: if action.is_empty() {
+                vec![]
+            } else {
+                vec![0.; genome.outputs()]
+            },
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sensory.is_empty() && self.action.is_empty()
+    }
+
+    /// Write `input` into the nodes of `values`, the first of it across `sensory` and the rest
+    /// across the appended sensory nodes
+    pub fn scatter(&self, sensory: Range<usize>, input: &[f64], values: &mut [f64]) {
+        debug_assert_eq!(
+            input.len(),
+            sensory.len() + self.sensory.len(),
+            "input is sized {}, expected {}",
+            input.len(),
+            sensory.len() + self.sensory.len()
+        );
+        let (head, tail) = input.split_at(sensory.len());
+        values[sensory].copy_from_slice(head);
+        for (idx, x) in self.sensory.iter().zip(tail) {
+            values[*idx] = *x;
+        }
+    }
+
+    /// Gather output from `values` once they've changed, if any action is appended
+    pub fn gather(&mut self, action: Range<usize>, values: &[f64]) {
+        if self.action.is_empty() {
+            return;
+        }
+        self.output.clear();
+        self.output.extend_from_slice(&values[action]);
+        self.output
+            .extend(self.action.iter().map(|idx| values[*idx]));
+    }
+
+    /// The output of `values`: those in `action`, or what was last
+    /// [gathered](Appended::gather) if any action is appended
+    pub fn output<'a>(&'a self, action: Range<usize>, values: &'a [f64]) -> &'a [f64] {
+        if self.action.is_empty() {
+            &values[action]
+        } else {
+            &self.output
+        }
+    }
+}
+
+/// Activate `x` as `node` would: through its own [activation](activate::PALETTE) if
+/// `activation` holds one for every node, or through `σ` otherwise
+#[inline]
+fn fire_node<F: Fn(f64) -> f64>(activation: Option<&[usize]>, node: usize, x: f64, σ: &F) -> f64 {
+    match activation {
+        Some(activation) => activate::PALETTE[activation[node]](x),
+        None => σ(x),
+    }
+}
+
+/// Activate every node's value in `values` in place, see [fire_node]
+fn fire<F: Fn(f64) -> f64>(activation: Option<&[usize]>, values: &mut [f64], σ: &F) {
+    for (node, x) in values.iter_mut().enumerate() {
+        *x = fire_node(activation, node, *x, σ);
+    }
+}
+
 /// The trait for all networks. Right now, only f64 values are used.
 pub trait Network: Serialize + for<'de> Deserialize<'de> {
     /// Given some sensory input, step the network with it `prec` times, activating with σ.
     /// Input must be sized to fit within [Genome::sensory].
     fn step<F: Fn(f64) -> f64>(&mut self, prec: usize, input: &[f64], σ: F);
 
+    /// Given some sensory input, step the network with it one step at a time until its output
+    /// settles, that is until no output moves by `tol` or more between steps, or `max_steps` is
+    /// reached. Returns the number of steps taken.
+    fn step_until_stable<F: Fn(f64) -> f64>(
+        &mut self,
+        max_steps: usize,
+        tol: f64,
+        input: &[f64],
+        σ: F,
+    ) -> usize {
+        let mut prev = self.output().to_vec();
+        for taken in 1..=max_steps {
+            self.step(1, input, &σ);
+            if self
+                .output()
+                .iter()
+                .zip(prev.iter())
+                .all(|(now, prev)| (now - prev).abs() < tol)
+            {
+                return taken;
+            }
+            prev.copy_from_slice(self.output());
+        }
+
+        max_steps
+    }
+
     /// If the network is stateful, flush it's state
     fn flush(&mut self);
 
@@ -60,6 +228,70 @@ pub trait Network: Serialize + for<'de> Deserialize<'de> {
     /// [Genome::action].
     fn output(&self) -> &[f64];
 
+    /// The current activation of every node in the network, not just those in
+    /// [output](Network::output). Indexed the same as the [Genome::nodes] it was built from.
+    /// Networks who don't keep an activation for every node give none.
+    fn activations(&self) -> &[f64] {
+        &[]
+    }
+
+    /// Everything about the network that changes as it's stepped, such that restoring it with
+    /// [set_state](Network::set_state) resumes the network exactly where it was. Useful for
+    /// checkpointing long running tasks mid-episode. The layout is particular to each network.
+    /// Networks who don't override this are taken to be stateless, and give an empty state.
+    fn state(&self) -> Vec<f64> {
+        Vec::new()
+    }
+
+    /// Restore a [state](Network::state) taken from this network, or another built from the
+    /// same genome. Panics if `state` is sized for some other network. Networks who don't
+    /// override this take only the empty state, and are [flushed](Network::flush) by it.
+    fn set_state(&mut self, state: &[f64]) {
+        assert!(
+            state.is_empty(),
+            "state of {} for a stateless network",
+            state.len()
+        );
+        self.flush();
+    }
+
+    /// The size of this network. By default this only knows of its nodes and state, networks who
+    /// hold weights should count them.
+    fn footprint(&self) -> Footprint {
+        let nodes = self.activations().len();
+        Footprint {
+            nodes,
+            connections: 0,
+            dense: nodes * nodes,
+            bytes: size_of_val(self) + size_of::<f64>() * self.state().len(),
+        }
+    }
+
+    /// The weight of the path `from` → `to`, indexed as the [Genome::nodes] that this network was
+    /// built from, if this network holds its weights that way. Networks who learn as they're
+    /// stepped, like [Hebbian], give what they've learned. See [Genome::absorb_network].
+    fn weight(&self, from: usize, to: usize) -> Option<f64> {
+        let _ = (from, to);
+        None
+    }
+
+    /// Every node's activation paired with its kind, given the [Genome::nodes] that this network
+    /// was built from. Useful for hooks and debuggers inspecting what hidden nodes are doing.
+    /// Empty for networks who give no [activations](Network::activations).
+    fn snapshot(&self, nodes: &[NodeKind]) -> Vec<(NodeKind, f64)> {
+        debug_assert!(
+            self.activations().is_empty() || nodes.len() == self.activations().len(),
+            "{} nodes for {} activations",
+            nodes.len(),
+            self.activations().len()
+        );
+        nodes
+            .iter()
+            .copied()
+            .zip(self.activations().iter().copied())
+            .collect()
+    }
+
     fn to_string(&self) -> Result<String, Box<dyn Error>> {
         Ok(serde_json::to_string(self)?)
     }
@@ -71,11 +303,13 @@ pub trait Network: Serialize + for<'de> Deserialize<'de> {
         serde_json::from_str(s).map_err(|op| op.into())
     }
 
+    #[cfg(feature = "fs")]
     fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
         fs::write(path, self.to_string()?)?;
         Ok(())
     }
 
+    #[cfg(feature = "fs")]
     fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>>
     where
         Self: Sized,
@@ -102,12 +336,40 @@ pub trait Stateless: Network {}
 /// For some [Genome], a network may construct itself from it.
 pub trait FromGenome<C: Connection, G: Genome<C>>: Network {
     fn from_genome(genome: &G) -> Self;
+
+    /// Rebuild this network from `genome` in place, as [from_genome](FromGenome::from_genome)
+    /// would, reusing what buffers it can. Networks who can't reuse theirs are rebuilt outright.
+    fn rebuild(&mut self, genome: &G)
+    where
+        Self: Sized,
+    {
+        *self = Self::from_genome(genome);
+    }
 }
 
 /// The inverse of [FromGenome], implemented automatically by any [Network] for every
 /// [Genome] from whom it knows how to construct itself.
 pub trait ToNetwork<NN: Network, C: Connection>: Genome<C> {
     fn network(&self) -> NN;
+
+    /// Decode from this genome [pruned](Genome::pruned) of nodes that can't affect its output,
+    /// for a smaller network with the same output. Its nodes no longer line up with
+    /// [Genome::nodes], so it's not for [snapshot](Network::snapshot),
+    /// [weight](Network::weight), or [Genome::absorb_network]; decode with
+    /// [network](ToNetwork::network) for those.
+    fn network_pruned(&self) -> NN;
+
+    /// Decode into an existing network, rather than allocating a fresh one. Useful when
+    /// evaluating many genomes one after another, where a network of the right size is likely
+    /// already on hand.
+    fn network_into(&self, nn: &mut NN);
+
+    /// Decode with each enabled connection dropped with probability `rate`, see
+    /// [dropped_out](Genome::dropped_out). Each call drops a fresh subset, so a scenario may
+    /// evaluate under several to reward genomes who don't hinge on any one connection.
+    fn network_dropout(&self, rate: f64, rng: &mut impl RngCore) -> NN {
+        self.dropped_out(rate, rng).network()
+    }
 }
 
 impl<NN: Network, C: Connection, G: Genome<C>> ToNetwork<NN, C> for G
@@ -115,6 +377,92 @@ where
     NN: FromGenome<C, G>,
 {
     fn network(&self) -> NN {
+        debug_assert!(
+            self.dangling().is_empty(),
+            "connections {:?} lead to nodes that don't exist",
+            self.dangling()
+        );
         NN::from_genome(self)
     }
+
+    fn network_pruned(&self) -> NN {
+        debug_assert!(
+            self.dangling().is_empty(),
+            "connections {:?} lead to nodes that don't exist",
+            self.dangling()
+        );
+        match self.pruned() {
+            Some(pruned) => NN::from_genome(&pruned),
+            None => NN::from_genome(self),
+        }
+    }
+
+    fn network_into(&self, nn: &mut NN) {
+        debug_assert!(
+            self.dangling().is_empty(),
+            "connections {:?} lead to nodes that don't exist",
+            self.dangling()
+        );
+        nn.rebuild(self);
+    }
+}
+
+/// Step every network of `nns` with its own input from `inputs` in parallel, each `prec` times
+/// with σ, as when simulating many agents at once. Runs on rayon's global pool.
+#[cfg(feature = "parallel")]
+pub fn step_population<NN: Network + Send, I: AsRef<[f64]> + Sync, F: Fn(f64) -> f64 + Sync>(
+    nns: &mut [NN],
+    inputs: &[I],
+    prec: usize,
+    σ: F,
+) {
+    use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+
+    assert_eq!(
+        nns.len(),
+        inputs.len(),
+        "{} inputs for {} networks",
+        inputs.len(),
+        nns.len()
+    );
+    nns.par_iter_mut()
+        .zip(inputs)
+        .for_each(|(nn, input)| nn.step(prec, input.as_ref(), &σ));
+}
+
+#[cfg(all(test, feature = "parallel"))]
+mod test {
+    use super::*;
+    use crate::{
+        assert_matrix_approx,
+        genome::{self, InnoGen, WConnection},
+    };
+
+    type C = WConnection;
+
+    #[test]
+    fn test_step_population() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(2, 1);
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.push_connection(C::new(1, 2, &mut inno));
+
+        let inputs = (0..16)
+            .map(|i| vec![i as f64 / 16., 1. - i as f64 / 16.])
+            .collect::<Vec<_>>();
+        let mut nns = (0..16)
+            .map(|_| Continuous::from_genome(&genome))
+            .collect::<Vec<_>>();
+        let mut sequential = (0..16)
+            .map(|_| Continuous::from_genome(&genome))
+            .collect::<Vec<_>>();
+
+        step_population(&mut nns, &inputs, 3, activate::steep_sigmoid);
+        for (nn, input) in sequential.iter_mut().zip(&inputs) {
+            nn.step(3, input, activate::steep_sigmoid);
+        }
+        for (nn, want) in nns.iter().zip(&sequential) {
+            assert_matrix_approx!(nn.activations(), want.activations());
+        }
+    }
 }
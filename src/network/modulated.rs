@@ -0,0 +1,278 @@
+use super::{fire, Appended, Footprint, FromGenome, Network, Recurrent, Stateful};
+use crate::{
+    genome::Modulatory,
+    serialize::{deserialize_matrix_flat, deserialize_matrix_square, serialize_matrix},
+    Genome,
+};
+use rulinalg::matrix::{BaseMatrix, BaseMatrixMut, Matrix};
+use serde::{Deserialize, Serialize};
+
+/// A [Hebbian](super::Hebbian)-like network whose plasticity is neuromodulated. Modulatory
+/// connections don't feed activation, but instead sum into a modulation `m[j] = tanh(Σ σ(y[i] +
+/// θ[i]) * mw[i, j])` of each node, which scales the hebbian update of every weight into it, as
+/// `dw[i, j] = m[j] * η[i, j] * σ(y[i] + θ[i]) * σ(y[j] + θ[j])`. Nodes without modulatory
+/// input don't learn at all. Flushing the network also restores its weights to those it was
+/// built with.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Modulated {
+    /// 1d state of neurons 0-N
+    #[serde(
+        serialize_with = "serialize_matrix",
+        deserialize_with = "deserialize_matrix_flat"
+    )]
+    pub y: Matrix<f64>,
+    /// 1d bias of neurons 0-N
+    #[serde(
+        serialize_with = "serialize_matrix",
+        deserialize_with = "deserialize_matrix_flat"
+    )]
+    pub θ: Matrix<f64>,
+    /// 1d membrane resistance time constant
+    #[serde(
+        serialize_with = "serialize_matrix",
+        deserialize_with = "deserialize_matrix_flat"
+    )]
+    pub τ: Matrix<f64>,
+    /// Nd weights between neurons, indexed as [from, to]
+    #[serde(
+        serialize_with = "serialize_matrix",
+        deserialize_with = "deserialize_matrix_square"
+    )]
+    pub w: Matrix<f64>,
+    /// Nd weights between neurons as they were before any learning, indexed as [from, to]
+    #[serde(
+        serialize_with = "serialize_matrix",
+        deserialize_with = "deserialize_matrix_square"
+    )]
+    pub w_init: Matrix<f64>,
+    /// Nd learning rate between neurons, indexed as [from, to]
+    #[serde(
+        serialize_with = "serialize_matrix",
+        deserialize_with = "deserialize_matrix_square"
+    )]
+    pub η: Matrix<f64>,
+    /// Nd weights of modulatory connections between neurons, indexed as [from, to]
+    #[serde(
+        serialize_with = "serialize_matrix",
+        deserialize_with = "deserialize_matrix_square"
+    )]
+    pub mw: Matrix<f64>,
+    /// Range of input neurons, indexing into y
+    pub sensory: (usize, usize),
+    /// Range of output neurons, indexing into y
+    pub action: (usize, usize),
+    /// Io nodes grown past the rest, see [Appended]
+    #[serde(default, skip_serializing_if = "Appended::is_empty")]
+    pub appended: Appended,
+    /// [Activation id](super::activate::PALETTE) of each neuron, used in place of the σ given to
+    /// [step](Network::step), if the genome evolves them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activation: Option<Vec<usize>>,
+}
+
+impl Network for Modulated {
+    fn step<F: Fn(f64) -> f64>(&mut self, prec: usize, input: &[f64], σ: F) {
+        let mut m_input = Matrix::zeros(1, self.y.cols());
+        self.appended
+            .scatter(self.sensory.0..self.sensory.1, input, m_input.mut_data());
+
+        let cols = self.y.cols();
+        let inv = 1. / (prec as f64);
+        for _ in 0..prec {
+            let mut fired = &self.y + &self.θ;
+            fire(self.activation.as_deref(), fired.mut_data(), &σ);
+            let m = (&fired * &self.mw).apply(&f64::tanh);
+            self.y += ((&fired * &self.w) - &self.y + &m_input)
+                .elemul(&self.τ)
+                .apply(&|v| v * inv);
+
+            let (fired, m) = (fired.data(), m.data());
+            let η = self.η.data();
+            for (idx, w) in self.w.mut_data().iter_mut().enumerate() {
+                let (from, to) = (idx / cols, idx % cols);
+                *w += inv * m[to] * η[idx] * fired[from] * fired[to];
+            }
+        }
+        self.appended
+            .gather(self.action.0..self.action.1, self.y.data());
+    }
+
+    fn flush(&mut self) {
+        self.y = Matrix::zeros(1, self.y.cols());
+        self.w = self.w_init.clone();
+        self.appended
+            .gather(self.action.0..self.action.1, self.y.data());
+    }
+
+    fn output(&self) -> &[f64] {
+        self.appended
+            .output(self.action.0..self.action.1, self.y.data())
+    }
+
+    fn activations(&self) -> &[f64] {
+        self.y.data()
+    }
+
+    fn state(&self) -> Vec<f64> {
+        [self.y.data().as_slice(), self.w.data()].concat()
+    }
+
+    fn set_state(&mut self, state: &[f64]) {
+        let (y, w) = state.split_at(self.y.cols());
+        self.y.mut_data().copy_from_slice(y);
+        self.w.mut_data().copy_from_slice(w);
+        self.appended
+            .gather(self.action.0..self.action.1, self.y.data());
+    }
+
+    fn footprint(&self) -> Footprint {
+        let held = [
+            &self.y,
+            &self.θ,
+            &self.τ,
+            &self.w,
+            &self.w_init,
+            &self.η,
+            &self.mw,
+        ]
+        .map(|m| m.data().len());
+        Footprint::of_weights(
+            self.y.cols(),
+            self.w.data(),
+            size_of::<Self>() + size_of::<f64>() * held.iter().sum::<usize>(),
+        )
+    }
+
+    /// The weight of the path `from` → `to`, read from [Modulated::mw] if that path is
+    /// modulatory, and from what's been learned in [Modulated::w] otherwise
+    fn weight(&self, from: usize, to: usize) -> Option<f64> {
+        let cols = self.w.cols();
+        if from >= cols || to >= cols {
+            return None;
+        }
+
+        let idx = from * cols + to;
+        match self.mw.data()[idx] {
+            0. => Some(self.w.data()[idx]),
+            mw => Some(mw),
+        }
+    }
+}
+
+impl Recurrent for Modulated {}
+
+impl Stateful for Modulated {}
+
+impl<C: Modulatory, G: Genome<C>> FromGenome<C, G> for Modulated {
+    fn from_genome(genome: &G) -> Self {
+        let cols = genome.nodes().len();
+        let mut w = vec![0.; cols * cols];
+        let mut η = vec![0.; cols * cols];
+        let mut mw = vec![0.; cols * cols];
+        for c in genome.connections().iter().filter(|c| c.enabled()) {
+            let idx = c.from() * cols + c.to();
+            if c.modulatory() {
+                mw[idx] = c.weight();
+            } else {
+                w[idx] = c.weight();
+                η[idx] = c.rate();
+            }
+        }
+
+        Self {
+            y: Matrix::zeros(1, cols),
+            θ: Matrix::new(1, cols, genome.biases()),
+            τ: Matrix::new(1, cols, genome.time_constants()),
+            w: Matrix::new(cols, cols, w.clone()),
+            w_init: Matrix::new(cols, cols, w),
+            η: Matrix::new(cols, cols, η),
+            mw: Matrix::new(cols, cols, mw),
+            sensory: (genome.sensory().start, genome.sensory().end),
+            action: (genome.action().start, genome.action().end),
+            appended: Appended::of(genome),
+            activation: genome.node_activations().map(<[usize]>::to_vec),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        activate, assert_f64_approx,
+        genome::{self, InnoGen, MConnection},
+        Connection,
+    };
+
+    type C = MConnection;
+
+    #[test]
+    fn test_modulated_learns() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(1, 2);
+        genome.push_connection(C::new(0, 1, &mut inno));
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.push_connection(C::new(3, 1, &mut inno));
+        genome.connections_mut()[0].rate = 0.5;
+        genome.connections_mut()[1].rate = 0.5;
+        genome.connections_mut()[2].modulatory = true;
+
+        let mut nn = Modulated::from_genome(&genome);
+        assert_eq!(nn.w.data()[3 * 4 + 1], 0.);
+        assert_eq!(nn.mw.data()[3 * 4 + 1], 1.);
+        for _ in 0..10 {
+            nn.step(2, &[1.], activate::steep_sigmoid);
+        }
+
+        // only the modulated node learns, though both are plastic
+        assert!(nn.weight(0, 1).unwrap() > 1.);
+        assert_f64_approx!(nn.weight(0, 2).unwrap(), 1.);
+
+        nn.flush();
+        assert_f64_approx!(nn.weight(0, 1).unwrap(), 1.);
+        assert_f64_approx!(nn.weight(3, 1).unwrap(), 1.);
+        assert_eq!(nn.weight(4, 1), None);
+    }
+
+    #[test]
+    fn test_modulatory_phenotype() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(1, 1);
+        genome.push_connection(C::new(0, 1, &mut inno));
+        let mut modulated = genome.clone();
+        modulated.connections_mut()[0].modulatory = true;
+
+        assert!(!genome.phenotype_eq(&modulated));
+        assert_ne!(
+            genome.phenotype_cache_key(),
+            modulated.phenotype_cache_key()
+        );
+        assert_ne!(genome.fingerprint(), modulated.fingerprint());
+
+        let mut nn = Modulated::from_genome(&genome);
+        let mut nn_modulated = Modulated::from_genome(&modulated);
+        nn.step(2, &[1.], activate::steep_sigmoid);
+        nn_modulated.step(2, &[1.], activate::steep_sigmoid);
+        assert_ne!(nn.output(), nn_modulated.output());
+    }
+
+    #[test]
+    fn test_node_activations() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(1, 1);
+        genome.push_connection(C::new(0, 1, &mut inno));
+
+        let output = |genome: &genome::Recurrent<C>| {
+            let mut nn = Modulated::from_genome(genome);
+            nn.step(2, &[-1.], activate::steep_sigmoid);
+            nn.output()[0]
+        };
+        // every node starts out with the steep sigmoid that it's stepped with
+        let plain = output(&genome);
+        let mut genome = genome.with_node_activations();
+        assert_f64_approx!(output(&genome), plain);
+
+        genome.node_activations_mut().unwrap()[0] = activate::id::ABS;
+        assert_ne!(output(&genome), plain);
+    }
+}
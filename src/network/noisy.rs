@@ -0,0 +1,163 @@
+use super::{Footprint, FromGenome, Linear, Network, Recurrent, Stateful, Stateless};
+use crate::{
+    random::{seed_default, WyRng},
+    Connection, Genome,
+};
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
+use serde::{Deserialize, Serialize};
+
+/// Noise added to each sensory input of a [Noisy] network. Noise is symmetric about 0, so only
+/// the magnitude of its scale matters, and a negative `σ` or `width` is as good as a positive one.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Noise {
+    /// Normally distributed about 0 with standard deviation `σ`
+    Gaussian { σ: f64 },
+    /// Uniformly distributed over `[-width, width]`
+    Uniform { width: f64 },
+}
+
+impl Noise {
+    fn sample(&self, rng: &mut impl Rng) -> f64 {
+        match *self {
+            Noise::Gaussian { σ } => Normal::new(0., σ.abs())
+                .unwrap_or_else(|_| panic!("normal distribution of σ {σ} failed"))
+                .sample(rng),
+            Noise::Uniform { width } => rng.random_range(-width.abs()..=width.abs()),
+        }
+    }
+}
+
+fn noise_rng() -> WyRng {
+    WyRng::seeded(seed_default())
+}
+
+/// A network whose sensory input is perturbed with fresh [Noise] before every step, so that a
+/// scenario evaluating it rewards controllers that are robust to noisy sensors.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Noisy<NN: Network> {
+    pub inner: NN,
+    pub noise: Noise,
+    #[serde(skip, default = "noise_rng")]
+    rng: WyRng,
+    perturbed: Vec<f64>,
+}
+
+impl<NN: Network> Noisy<NN> {
+    pub fn new(inner: NN, noise: Noise) -> Self {
+        Self {
+            inner,
+            noise,
+            rng: noise_rng(),
+            perturbed: Vec::new(),
+        }
+    }
+
+    /// Draw noise from an rng seeded with `seed`, for reproducible evaluation
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = WyRng::seeded(seed);
+        self
+    }
+}
+
+impl<NN: Network> Network for Noisy<NN> {
+    fn step<F: Fn(f64) -> f64>(&mut self, prec: usize, input: &[f64], σ: F) {
+        self.perturbed.clear();
+        for x in input {
+            self.perturbed.push(x + self.noise.sample(&mut self.rng));
+        }
+        self.inner.step(prec, &self.perturbed, σ);
+    }
+
+    fn flush(&mut self) {
+        self.inner.flush();
+    }
+
+    fn output(&self) -> &[f64] {
+        self.inner.output()
+    }
+
+    fn activations(&self) -> &[f64] {
+        self.inner.activations()
+    }
+
+    fn state(&self) -> Vec<f64> {
+        self.inner.state()
+    }
+
+    fn set_state(&mut self, state: &[f64]) {
+        self.inner.set_state(state);
+    }
+
+    fn footprint(&self) -> Footprint {
+        self.inner
+            .footprint()
+            .with_bytes(size_of_val(self.perturbed.as_slice()))
+    }
+
+    fn weight(&self, from: usize, to: usize) -> Option<f64> {
+        self.inner.weight(from, to)
+    }
+}
+
+impl<NN: Recurrent> Recurrent for Noisy<NN> {}
+
+impl<NN: Linear> Linear for Noisy<NN> {}
+
+impl<NN: Stateful> Stateful for Noisy<NN> {}
+
+impl<NN: Stateless> Stateless for Noisy<NN> {}
+
+/// Noise defaults to none at all, set [Noisy::noise] after construction
+impl<C: Connection, G: Genome<C>, NN: FromGenome<C, G>> FromGenome<C, G> for Noisy<NN> {
+    fn from_genome(genome: &G) -> Self {
+        Self::new(NN::from_genome(genome), Noise::Uniform { width: 0. })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        activate, assert_matrix_approx,
+        genome::{self, InnoGen, WConnection},
+        network::Continuous,
+    };
+
+    type C = WConnection;
+
+    #[test]
+    fn test_noisy() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(2, 1);
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.push_connection(C::new(1, 2, &mut inno));
+
+        let mut plain = Continuous::from_genome(&genome);
+        let mut silent = Noisy::<Continuous>::from_genome(&genome);
+        let mut noisy =
+            Noisy::new(Continuous::from_genome(&genome), Noise::Gaussian { σ: 0.5 }).with_seed(1);
+        let mut replay =
+            Noisy::new(Continuous::from_genome(&genome), Noise::Gaussian { σ: 0.5 }).with_seed(1);
+        for _ in 0..5 {
+            plain.step(2, &[1., -1.], activate::relu);
+            silent.step(2, &[1., -1.], activate::relu);
+            noisy.step(2, &[1., -1.], activate::relu);
+            replay.step(2, &[1., -1.], activate::relu);
+        }
+
+        assert_matrix_approx!(plain.activations(), silent.activations());
+        assert_matrix_approx!(noisy.activations(), replay.activations());
+        assert_ne!(plain.activations(), noisy.activations());
+    }
+
+    #[test]
+    fn test_noise_negative() {
+        let mut rng = WyRng::seeded(1);
+        for noise in [Noise::Gaussian { σ: -0.5 }, Noise::Uniform { width: -0.5 }] {
+            assert!((0..100).any(|_| noise.sample(&mut rng) != 0.));
+        }
+        assert!((0..100).all(|_| Noise::Uniform { width: -0.5 }.sample(&mut rng).abs() <= 0.5));
+    }
+}
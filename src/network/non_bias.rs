@@ -1,4 +1,4 @@
-use super::{FromGenome, Network, Recurrent, Stateful};
+use super::{fire, Appended, Footprint, FromGenome, Network, Recurrent, Stateful};
 use crate::{
     serialize::{deserialize_matrix_flat, deserialize_matrix_square, serialize_matrix},
     Connection, Genome,
@@ -20,25 +20,67 @@ pub struct NonBias {
     pub w: Matrix<f64>,
     pub sensory: (usize, usize),
     pub action: (usize, usize),
+    /// Io nodes grown past the rest, see [Appended]
+    #[serde(default, skip_serializing_if = "Appended::is_empty")]
+    pub appended: Appended,
+    /// [Activation id](super::activate::PALETTE) of each neuron, used in place of the σ given to
+    /// [step](Network::step), if the genome evolves them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activation: Option<Vec<usize>>,
 }
 
 impl Network for NonBias {
     fn step<F: Fn(f64) -> f64>(&mut self, prec: usize, input: &[f64], σ: F) {
         let mut m_input = Matrix::zeros(1, self.y.cols());
-        m_input.mut_data()[self.sensory.0..self.sensory.1].copy_from_slice(input);
+        self.appended
+            .scatter(self.sensory.0..self.sensory.1, input, m_input.mut_data());
 
         let inv = 1. / (prec as f64);
         for _ in 0..prec {
-            self.y = ((&self.y + &m_input).apply(&σ) * &self.w).apply(&|v| v * inv);
+            let mut fired = &self.y + &m_input;
+            fire(self.activation.as_deref(), fired.mut_data(), &σ);
+            self.y = (fired * &self.w).apply(&|v| v * inv);
         }
+        self.appended
+            .gather(self.action.0..self.action.1, self.y.data());
     }
 
     fn flush(&mut self) {
         self.y = Matrix::zeros(1, self.y.cols());
+        self.appended
+            .gather(self.action.0..self.action.1, self.y.data());
     }
 
     fn output(&self) -> &[f64] {
-        &self.y.data()[self.action.0..self.action.1]
+        self.appended
+            .output(self.action.0..self.action.1, self.y.data())
+    }
+
+    fn activations(&self) -> &[f64] {
+        self.y.data()
+    }
+
+    fn state(&self) -> Vec<f64> {
+        self.y.data().to_vec()
+    }
+
+    fn set_state(&mut self, state: &[f64]) {
+        self.y.mut_data().copy_from_slice(state);
+        self.appended
+            .gather(self.action.0..self.action.1, self.y.data());
+    }
+
+    fn footprint(&self) -> Footprint {
+        Footprint::of_weights(
+            self.y.cols(),
+            self.w.data(),
+            size_of::<Self>() + size_of::<f64>() * (self.y.data().len() + self.w.data().len()),
+        )
+    }
+
+    fn weight(&self, from: usize, to: usize) -> Option<f64> {
+        let cols = self.w.cols();
+        (from < cols && to < cols).then(|| self.w.data()[from * cols + to])
     }
 }
 
@@ -60,6 +102,8 @@ impl<C: Connection, G: Genome<C>> FromGenome<C, G> for NonBias {
             },
             sensory: (genome.sensory().start, genome.sensory().end),
             action: (genome.action().start, genome.action().end),
+            appended: Appended::of(genome),
+            activation: genome.node_activations().map(<[usize]>::to_vec),
         }
     }
 }
@@ -0,0 +1,215 @@
+use super::{Footprint, FromGenome, Linear, Network, Recurrent, Stateful, Stateless};
+use crate::{genome::Affine, Connection, Genome};
+use serde::{Deserialize, Serialize};
+
+/// A network whose output is passed through a per-action [Affine] transform after activation,
+/// and whose input may be multiplied by a per-sensory gain before it propagates. The transform
+/// is read from [Genome::output_affine] and the gains from [Genome::sensory_gains], and each is
+/// the identity for genomes who don't evolve them.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Scaled<NN: Network> {
+    pub inner: NN,
+    pub affine: Vec<Affine>,
+    /// Gain of each sensory input, or empty if input is passed through as it is
+    #[serde(default)]
+    pub gain: Vec<f64>,
+    out: Vec<f64>,
+    #[serde(skip)]
+    input: Vec<f64>,
+}
+
+impl<NN: Network> Scaled<NN> {
+    pub fn new(inner: NN, affine: Vec<Affine>) -> Self {
+        let mut scaled = Self {
+            inner,
+            out: vec![0.; affine.len()],
+            affine,
+            gain: vec![],
+            input: vec![],
+        };
+        scaled.rescale();
+        scaled
+    }
+
+    /// This network, with its input multiplied by `gain`, one for each sensory input
+    pub fn with_gain(mut self, gain: Vec<f64>) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    fn rescale(&mut self) {
+        for ((out, a), x) in self
+            .out
+            .iter_mut()
+            .zip(self.affine.iter())
+            .zip(self.inner.output())
+        {
+            *out = a.apply(*x);
+        }
+    }
+}
+
+impl<NN: Network> Network for Scaled<NN> {
+    fn step<F: Fn(f64) -> f64>(&mut self, prec: usize, input: &[f64], σ: F) {
+        if self.gain.is_empty() {
+            self.inner.step(prec, input, σ);
+        } else {
+            assert_eq!(
+                input.len(),
+                self.gain.len(),
+                "input is sized {}, expected {}",
+                input.len(),
+                self.gain.len()
+            );
+            self.input.clear();
+            self.input
+                .extend(input.iter().zip(self.gain.iter()).map(|(x, gain)| x * gain));
+            self.inner.step(prec, &self.input, σ);
+        }
+        self.rescale();
+    }
+
+    fn flush(&mut self) {
+        self.inner.flush();
+        self.rescale();
+    }
+
+    fn output(&self) -> &[f64] {
+        &self.out
+    }
+
+    fn activations(&self) -> &[f64] {
+        self.inner.activations()
+    }
+
+    fn state(&self) -> Vec<f64> {
+        self.inner.state()
+    }
+
+    fn set_state(&mut self, state: &[f64]) {
+        self.inner.set_state(state);
+        self.rescale();
+    }
+
+    fn footprint(&self) -> Footprint {
+        self.inner.footprint().with_bytes(
+            size_of_val(self.affine.as_slice())
+                + size_of_val(self.gain.as_slice())
+                + size_of_val(self.out.as_slice()),
+        )
+    }
+
+    fn weight(&self, from: usize, to: usize) -> Option<f64> {
+        self.inner.weight(from, to)
+    }
+}
+
+impl<NN: Recurrent> Recurrent for Scaled<NN> {}
+
+impl<NN: Linear> Linear for Scaled<NN> {}
+
+impl<NN: Stateful> Stateful for Scaled<NN> {}
+
+impl<NN: Stateless> Stateless for Scaled<NN> {}
+
+impl<C: Connection, G: Genome<C>, NN: FromGenome<C, G>> FromGenome<C, G> for Scaled<NN> {
+    fn from_genome(genome: &G) -> Self {
+        Self::new(
+            NN::from_genome(genome),
+            genome
+                .output_affine()
+                .map(<[_]>::to_vec)
+                .unwrap_or_else(|| vec![Affine::default(); genome.outputs()]),
+        )
+        .with_gain(
+            genome
+                .sensory_gains()
+                .map(<[_]>::to_vec)
+                .unwrap_or_default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        activate, assert_f64_approx,
+        genome::{self, InnoGen, WConnection},
+        network::Continuous,
+    };
+
+    type C = WConnection;
+
+    #[test]
+    fn test_from_genome_identity() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(2, 2);
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.push_connection(C::new(1, 3, &mut inno));
+
+        let mut plain = Continuous::from_genome(&genome);
+        let mut scaled = Scaled::<Continuous>::from_genome(&genome);
+        for _ in 0..10 {
+            plain.step(2, &[0.5, -0.5], activate::relu);
+            scaled.step(2, &[0.5, -0.5], activate::relu);
+            for (l, r) in plain.output().iter().zip(scaled.output()) {
+                assert_f64_approx!(l, r);
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_genome_affine() {
+        let mut inno = InnoGen::new(0);
+        let (genome, _) = genome::Recurrent::<C>::new(2, 2);
+        let mut genome = genome.with_output_affine();
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.push_connection(C::new(1, 3, &mut inno));
+        genome.output_affine_mut().unwrap()[0] = Affine {
+            gain: 10.,
+            offset: -1.,
+        };
+
+        let mut plain = Continuous::from_genome(&genome);
+        let mut scaled = Scaled::<Continuous>::from_genome(&genome);
+        assert_f64_approx!(scaled.output()[0], -1.);
+        for _ in 0..10 {
+            plain.step(2, &[0.5, -0.5], activate::relu);
+            scaled.step(2, &[0.5, -0.5], activate::relu);
+            assert_f64_approx!(scaled.output()[0], plain.output()[0] * 10. - 1.);
+            assert_f64_approx!(scaled.output()[1], plain.output()[1]);
+        }
+    }
+
+    #[test]
+    fn test_from_genome_gain() {
+        let mut inno = InnoGen::new(0);
+        let (genome, _) = genome::Recurrent::<C>::new(2, 1);
+        let mut genome = genome.with_sensory_gains();
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.push_connection(C::new(1, 2, &mut inno));
+        assert_eq!(genome.sensory_gains(), Some(&[1., 1.][..]));
+        genome
+            .sensory_gains_mut()
+            .unwrap()
+            .copy_from_slice(&[0.01, 2.]);
+
+        let mut plain = Continuous::from_genome(&genome);
+        let mut scaled = Scaled::<Continuous>::from_genome(&genome);
+        for _ in 0..10 {
+            plain.step(2, &[1., -0.25], activate::relu);
+            scaled.step(2, &[100., -0.125], activate::relu);
+            assert_f64_approx!(scaled.output()[0], plain.output()[0]);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "input is sized 1, expected 2")]
+    fn test_gain_wrong_len() {
+        let (genome, _) = genome::Recurrent::<C>::new(2, 1);
+        let mut scaled = Scaled::<Continuous>::from_genome(&genome.with_sensory_gains());
+        scaled.step(1, &[1.], activate::relu);
+    }
+}
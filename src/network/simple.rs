@@ -1,14 +1,20 @@
-use super::{FromGenome, Network};
-use crate::{genome::NodeKind, serialize::deserialize_connections, Connection, Genome};
-use core::ops::Range;
+use super::{fire_node, Appended, Footprint, FromGenome, Network};
+use crate::{serialize::deserialize_connections, Connection, Genome};
+use core::{error::Error, ops::Range};
 use serde::{Deserialize, Serialize};
 
 /// A simple neural network, because man, what the fuck is going on. lol
 /// Walks through connections oldest to newest, evaluating them on a flat state
+///
+/// A walk can't evaluate a cycle, so [recurrent](Genome::recurrent) connections of the genome
+/// are set aside rather than walked, and are kept in [recurrent](Simple::recurrent). Build with
+/// [try_from_genome](Simple::try_from_genome) to reject such genomes instead.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Simple<C: Connection> {
     #[serde(deserialize_with = "deserialize_connections")]
     connections: Vec<C>, // TODO this is copying because of deserialization
+    #[serde(default, deserialize_with = "deserialize_connections")]
+    recurrent: Vec<C>,
     bias: Vec<f64>,
     #[serde(skip_serializing)]
     state: Vec<f64>,
@@ -16,49 +22,158 @@ pub struct Simple<C: Connection> {
     sensory: Range<usize>,
     #[serde(skip_serializing)]
     action: Range<usize>,
+    #[serde(default, skip_serializing)]
+    appended: Appended,
+    /// [Activation id](super::activate::PALETTE) of each node, used in place of the σ given to
+    /// [step](Network::step) for connections into it, if the genome evolves them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    activation: Option<Vec<usize>>,
 }
 
 impl<C: Connection> Network for Simple<C> {
     fn step<F: Fn(f64) -> f64>(&mut self, prec: usize, input: &[f64], σ: F) {
-        debug_assert!(input.len() == self.sensory.len());
-        self.state[self.sensory.start..self.sensory.end].copy_from_slice(input);
+        self.appended
+            .scatter(self.sensory.clone(), input, &mut self.state);
         if !self.connections.is_empty() {
             for _ in 0..prec {
                 for c in self.connections.iter() {
-                    self.state[c.to()] +=
-                        σ((self.bias[c.from()] + self.state[c.from()]) * c.weight())
+                    let x = (self.bias[c.from()] + self.state[c.from()]) * c.weight();
+                    self.state[c.to()] += fire_node(self.activation.as_deref(), c.to(), x, &σ);
                 }
             }
         }
+        self.appended.gather(self.action.clone(), &self.state);
     }
 
     fn flush(&mut self) {
         self.state = vec![0.; self.state.len()];
+        self.appended.gather(self.action.clone(), &self.state);
     }
 
     fn output(&self) -> &[f64] {
-        &self.state[self.action.start..self.action.end]
+        self.appended.output(self.action.clone(), &self.state)
+    }
+
+    fn activations(&self) -> &[f64] {
+        &self.state
+    }
+
+    fn state(&self) -> Vec<f64> {
+        self.state.clone()
+    }
+
+    fn set_state(&mut self, state: &[f64]) {
+        self.state.copy_from_slice(state);
+        self.appended.gather(self.action.clone(), &self.state);
+    }
+
+    fn footprint(&self) -> Footprint {
+        Footprint {
+            nodes: self.state.len(),
+            connections: self.connections.iter().filter(|c| c.enabled()).count(),
+            dense: self.state.len() * self.state.len(),
+            bytes: size_of::<Self>()
+                + size_of_val(self.connections.as_slice())
+                + size_of::<f64>() * (self.bias.len() + self.state.len()),
+        }
+    }
+
+    fn weight(&self, from: usize, to: usize) -> Option<f64> {
+        self.connections
+            .iter()
+            .chain(self.recurrent.iter())
+            .rfind(|c| c.enabled() && c.path() == (from, to))
+            .map(|c| c.weight())
+    }
+}
+
+impl<C: Connection> Simple<C> {
+    /// Connections of the genome who close a cycle, and aren't evaluated
+    pub fn recurrent(&self) -> &[C] {
+        &self.recurrent
+    }
+
+    /// Like [from_genome](FromGenome::from_genome), but it's an error if `genome` has any
+    /// [recurrent](Genome::recurrent) connections, rather than them being set aside
+    pub fn try_from_genome<G: Genome<C>>(genome: &G) -> Result<Self, Box<dyn Error>> {
+        let nn = Self::from_genome(genome);
+        match nn.recurrent.first() {
+            Some(c) => Err(format!(
+                "{} recurrent connections can't be walked, the first along {:?}",
+                nn.recurrent.len(),
+                c.path()
+            )
+            .into()),
+            None => Ok(nn),
+        }
     }
 }
 
 impl<C: Connection, G: Genome<C>> FromGenome<C, G> for Simple<C> {
     fn from_genome(genome: &G) -> Self {
+        let (recurrent, connections) = genome
+            .connections()
+            .iter()
+            .zip(genome.recurrent())
+            .partition::<Vec<_>, _>(|(_, recurrent)| *recurrent);
         Simple {
-            connections: genome.connections().to_owned(),
-            bias: genome
-                .nodes()
-                .iter()
-                .map(|n| {
-                    if matches!(n, NodeKind::Static) {
-                        1.
-                    } else {
-                        0.
-                    }
-                })
-                .collect(),
+            connections: connections.into_iter().map(|(c, _)| c.clone()).collect(),
+            recurrent: recurrent.into_iter().map(|(c, _)| c.clone()).collect(),
+            bias: genome.biases(),
             state: vec![0.; genome.nodes().len()],
             sensory: genome.sensory(),
             action: genome.action(),
+            appended: Appended::of(genome),
+            activation: genome.node_activations().map(<[usize]>::to_vec),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        activate, assert_f64_approx,
+        genome::{self, InnoGen, WConnection},
+    };
+
+    type C = WConnection;
+
+    #[test]
+    fn test_simple_recurrent() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(1, 1);
+        genome.push_connection(C::new(0, 1, &mut inno));
+        genome.push_connection(C::new(1, 1, &mut inno));
+
+        let mut nn = Simple::from_genome(&genome);
+        assert_eq!(nn.recurrent(), &genome.connections()[1..]);
+        nn.step(1, &[1.], |x| x);
+        nn.step(1, &[1.], |x| x);
+        assert_f64_approx!(nn.output()[0], 2.);
+
+        assert!(Simple::try_from_genome(&genome).is_err());
+        genome.connections_mut()[1].disable();
+        assert!(Simple::try_from_genome(&genome).is_ok());
+    }
+
+    #[test]
+    fn test_node_activations() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(1, 1);
+        genome.push_connection(C::new(0, 1, &mut inno));
+
+        let output = |genome: &genome::Recurrent<C>| {
+            let mut nn = Simple::from_genome(genome);
+            nn.step(2, &[-1.], activate::steep_sigmoid);
+            nn.output()[0]
+        };
+        // every node starts out with the steep sigmoid that it's stepped with
+        let plain = output(&genome);
+        let mut genome = genome.with_node_activations();
+        assert_f64_approx!(output(&genome), plain);
+
+        genome.node_activations_mut().unwrap()[1] = activate::id::ABS;
+        assert_ne!(output(&genome), plain);
+    }
+}
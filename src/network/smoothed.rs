@@ -0,0 +1,145 @@
+use super::{Footprint, FromGenome, Linear, Network, Recurrent, Stateful};
+use crate::{Connection, Genome};
+use serde::{Deserialize, Serialize};
+
+/// A network whose output is low-pass filtered, as an exponential moving average of its inner
+/// network's output over steps. Each step moves the output `alpha` of the way towards the inner
+/// output, so an `alpha` of 1 doesn't smooth at all and smaller values smooth harder. Useful for
+/// control tasks, where raw recurrent outputs tend to chatter.
+///
+/// The filter holds state of its own, so a smoothed network is always [Stateful]. Its
+/// [activations](Network::activations) are those of the inner network, unfiltered.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Smoothed<NN: Network> {
+    pub inner: NN,
+    pub alpha: f64,
+    out: Vec<f64>,
+}
+
+impl<NN: Network> Smoothed<NN> {
+    pub fn new(inner: NN, alpha: f64) -> Self {
+        assert!(
+            alpha > 0. && alpha <= 1.,
+            "alpha must be within (0, 1], got {alpha}"
+        );
+        Self {
+            out: inner.output().to_vec(),
+            inner,
+            alpha,
+        }
+    }
+}
+
+impl<NN: Network> Network for Smoothed<NN> {
+    fn step<F: Fn(f64) -> f64>(&mut self, prec: usize, input: &[f64], σ: F) {
+        self.inner.step(prec, input, σ);
+        for (out, x) in self.out.iter_mut().zip(self.inner.output()) {
+            *out += self.alpha * (x - *out);
+        }
+    }
+
+    fn flush(&mut self) {
+        self.inner.flush();
+        self.out.copy_from_slice(self.inner.output());
+    }
+
+    fn output(&self) -> &[f64] {
+        &self.out
+    }
+
+    fn activations(&self) -> &[f64] {
+        self.inner.activations()
+    }
+
+    /// The inner network's state, followed by the filtered output
+    fn state(&self) -> Vec<f64> {
+        let mut state = self.inner.state();
+        state.extend_from_slice(&self.out);
+        state
+    }
+
+    fn set_state(&mut self, state: &[f64]) {
+        assert!(
+            state.len() >= self.out.len(),
+            "state is sized {}, too small for {} outputs",
+            state.len(),
+            self.out.len()
+        );
+        let (inner, out) = state.split_at(state.len() - self.out.len());
+        self.inner.set_state(inner);
+        self.out.copy_from_slice(out);
+    }
+
+    fn footprint(&self) -> Footprint {
+        self.inner
+            .footprint()
+            .with_bytes(size_of_val(self.out.as_slice()))
+    }
+
+    fn weight(&self, from: usize, to: usize) -> Option<f64> {
+        self.inner.weight(from, to)
+    }
+}
+
+impl<NN: Recurrent> Recurrent for Smoothed<NN> {}
+
+impl<NN: Linear> Linear for Smoothed<NN> {}
+
+impl<NN: Network> Stateful for Smoothed<NN> {}
+
+/// Alpha defaults to 1, which doesn't smooth at all. Set [Smoothed::alpha] after construction
+impl<C: Connection, G: Genome<C>, NN: FromGenome<C, G>> FromGenome<C, G> for Smoothed<NN> {
+    fn from_genome(genome: &G) -> Self {
+        Self::new(NN::from_genome(genome), 1.)
+    }
+
+    fn rebuild(&mut self, genome: &G) {
+        self.inner.rebuild(genome);
+        self.out.clear();
+        self.out.extend_from_slice(self.inner.output());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        activate, assert_f64_approx, assert_matrix_approx,
+        genome::{self, InnoGen, WConnection},
+        network::Continuous,
+    };
+
+    type C = WConnection;
+
+    #[test]
+    fn test_smoothed() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(1, 1);
+        genome.push_connection(C::new(0, 1, &mut inno));
+
+        let mut plain = Continuous::from_genome(&genome);
+        let mut unfiltered = Smoothed::<Continuous>::from_genome(&genome);
+        let mut smoothed = Smoothed::new(Continuous::from_genome(&genome), 0.25);
+        let mut want = 0.;
+        for input in [1., -1., 1., -1.] {
+            plain.step(1, &[input], activate::relu);
+            unfiltered.step(1, &[input], activate::relu);
+            smoothed.step(1, &[input], activate::relu);
+            want += 0.25 * (plain.output()[0] - want);
+            assert_matrix_approx!(plain.output(), unfiltered.output());
+            assert_f64_approx!(smoothed.output()[0], want);
+        }
+
+        let state = smoothed.state();
+        smoothed.step(1, &[1.], activate::relu);
+        let stepped = smoothed.output().to_vec();
+        smoothed.set_state(&state);
+        smoothed.step(1, &[1.], activate::relu);
+        assert_matrix_approx!(smoothed.output(), stepped);
+
+        smoothed.flush();
+        plain.flush();
+        assert_matrix_approx!(smoothed.output(), plain.output());
+    }
+}
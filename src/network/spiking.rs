@@ -0,0 +1,269 @@
+use super::{Appended, Footprint, FromGenome, Network, Recurrent, Stateful};
+use crate::{Connection, Genome};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+/// A stateful spiking NN of leaky integrate-and-fire neurons. Every tick, each neuron's membrane
+/// potential leaks towards 0 and integrates the weighted spikes of the last tick, firing and
+/// resetting when it crosses the threshold.
+///
+/// Input is injected as current into sensory neurons every tick, so inputs may either be analog
+/// values held for a whole step, or spike trains ( see [poisson_train] ) stepped one tick at a
+/// time. Output is rate decoded, and is the fraction of ticks in the most recent step that
+/// each action neuron fired. Spikes are all-or-nothing, so σ is unused.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Spiking {
+    /// membrane potential of neurons 0-N
+    pub v: Vec<f64>,
+    /// 1. for neurons that fired on the last tick, otherwise 0.
+    pub spiked: Vec<f64>,
+    /// A spike every tick for neurons that always fire, like the bias, and a partial one for nodes
+    /// with an evolved [bias](Genome::biases), clamped into 0 ..= 1
+    pub bias: Vec<f64>,
+    /// Nd weights between neurons, indexed as [from * N + to]
+    pub w: Vec<f64>,
+    /// fraction of potential retained between ticks
+    pub leak: f64,
+    /// potential at which a neuron fires
+    pub threshold: f64,
+    rate: Vec<f64>,
+    /// Range of input neurons, indexing into v
+    pub sensory: (usize, usize),
+    /// Range of output neurons, indexing into v
+    pub action: (usize, usize),
+    /// Io nodes grown past the rest, see [Appended]
+    #[serde(default, skip_serializing_if = "Appended::is_empty")]
+    pub appended: Appended,
+}
+
+impl Spiking {
+    pub fn with_leak(mut self, leak: f64) -> Self {
+        self.leak = leak;
+        self
+    }
+
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    fn tick(&mut self, input: &[f64]) {
+        let cols = self.v.len();
+        for (from, spiked) in self.spiked.iter_mut().enumerate() {
+            *spiked = f64::max(*spiked, self.bias[from]);
+        }
+
+        for to in 0..cols {
+            let current = (0..cols).fold(0., |acc, from| {
+                acc + self.spiked[from] * self.w[from * cols + to]
+            });
+            self.v[to] = self.leak * self.v[to] + current;
+        }
+
+        let (head, tail) = input.split_at(self.sensory.1 - self.sensory.0);
+        for (v, x) in self.v[self.sensory.0..self.sensory.1].iter_mut().zip(head) {
+            *v += x;
+        }
+        for (idx, x) in self.appended.sensory.iter().zip(tail) {
+            self.v[*idx] += x;
+        }
+
+        for (v, spiked) in self.v.iter_mut().zip(self.spiked.iter_mut()) {
+            if *v >= self.threshold {
+                *v = 0.;
+                *spiked = 1.;
+            } else {
+                *spiked = 0.;
+            }
+        }
+    }
+}
+
+impl Network for Spiking {
+    fn step<F: Fn(f64) -> f64>(&mut self, prec: usize, input: &[f64], _: F) {
+        debug_assert!(input.len() == self.sensory.1 - self.sensory.0 + self.appended.sensory.len());
+        self.rate.fill(0.);
+        for _ in 0..prec {
+            self.tick(input);
+            let spiked = self.spiked[self.action.0..self.action.1]
+                .iter()
+                .chain(self.appended.action.iter().map(|idx| &self.spiked[*idx]));
+            for (rate, spiked) in self.rate.iter_mut().zip(spiked) {
+                *rate += spiked;
+            }
+        }
+
+        // a step of no ticks fires nothing, rather than dividing 0 spikes by 0 ticks
+        let inv = 1. / (prec.max(1) as f64);
+        for rate in self.rate.iter_mut() {
+            *rate *= inv;
+        }
+    }
+
+    fn flush(&mut self) {
+        self.v.fill(0.);
+        self.spiked.fill(0.);
+        self.rate.fill(0.);
+    }
+
+    fn output(&self) -> &[f64] {
+        &self.rate
+    }
+
+    fn activations(&self) -> &[f64] {
+        &self.v
+    }
+
+    fn state(&self) -> Vec<f64> {
+        [self.v.as_slice(), &self.spiked, &self.rate].concat()
+    }
+
+    fn set_state(&mut self, state: &[f64]) {
+        let (v, rest) = state.split_at(self.v.len());
+        let (spiked, rate) = rest.split_at(self.spiked.len());
+        self.v.copy_from_slice(v);
+        self.spiked.copy_from_slice(spiked);
+        self.rate.copy_from_slice(rate);
+    }
+
+    fn footprint(&self) -> Footprint {
+        let held = [&self.v, &self.spiked, &self.bias, &self.w, &self.rate].map(|v| v.len());
+        Footprint::of_weights(
+            self.v.len(),
+            &self.w,
+            size_of::<Self>() + size_of::<f64>() * held.iter().sum::<usize>(),
+        )
+    }
+
+    fn weight(&self, from: usize, to: usize) -> Option<f64> {
+        let cols = self.v.len();
+        (from < cols && to < cols).then(|| self.w[from * cols + to])
+    }
+}
+
+impl Recurrent for Spiking {}
+
+impl Stateful for Spiking {}
+
+impl<C: Connection, G: Genome<C>> FromGenome<C, G> for Spiking {
+    fn from_genome(genome: &G) -> Self {
+        let cols = genome.nodes().len();
+        Self {
+            v: vec![0.; cols],
+            spiked: vec![0.; cols],
+            bias: genome
+                .biases()
+                .into_iter()
+                .map(|bias| bias.clamp(0., 1.))
+                .collect(),
+            w: {
+                let mut w = vec![0.; cols * cols];
+                for c in genome.connections().iter().filter(|c| c.enabled()) {
+                    w[c.from() * cols + c.to()] = c.weight();
+                }
+                w
+            },
+            leak: 0.9,
+            threshold: 1.,
+            rate: vec![0.; genome.outputs()],
+            sensory: (genome.sensory().start, genome.sensory().end),
+            action: (genome.action().start, genome.action().end),
+            appended: Appended::of(genome),
+        }
+    }
+}
+
+/// Encode `rates` ( each in 0..=1 ) as `ticks` ticks of poisson spike trains, where each input
+/// fires on any tick with probability equal to its rate. Each tick may be handed to
+/// [Spiking::step] with a `prec` of 1.
+pub fn poisson_train(rates: &[f64], ticks: usize, rng: &mut impl RngCore) -> Vec<Vec<f64>> {
+    (0..ticks)
+        .map(|_| {
+            rates
+                .iter()
+                .map(|rate| {
+                    if (rng.next_u64() as f64 / u64::MAX as f64) < *rate {
+                        1.
+                    } else {
+                        0.
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Decode spike trains, as produced by [poisson_train], into the firing rate of each channel.
+pub fn decode_rate(train: &[Vec<f64>]) -> Vec<f64> {
+    let Some(width) = train.first().map(Vec::len) else {
+        return vec![];
+    };
+
+    let inv = 1. / (train.len() as f64);
+    train.iter().fold(vec![0.; width], |mut acc, tick| {
+        for (rate, spike) in acc.iter_mut().zip(tick) {
+            *rate += spike * inv;
+        }
+        acc
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        activate,
+        genome::{self, InnoGen, WConnection},
+        random::default_rng,
+    };
+
+    type C = WConnection;
+
+    #[test]
+    fn test_spiking_rate() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(1, 2);
+        genome.push_connection(C::new(0, 1, &mut inno));
+
+        let mut nn = Spiking::from_genome(&genome);
+        nn.step(100, &[1.], activate::relu);
+        assert!(nn.output()[0] > 0.9, "fired too little: {}", nn.output()[0]);
+        assert_eq!(nn.output()[1], 0.);
+
+        nn.step(100, &[0.], activate::relu);
+        assert!(nn.output()[0] < 0.1, "fired too much: {}", nn.output()[0]);
+
+        nn.step(0, &[1.], activate::relu);
+        assert_eq!(nn.output(), &[0., 0.]);
+
+        nn.flush();
+        assert!(nn.v.iter().all(|v| *v == 0.));
+    }
+
+    #[test]
+    fn test_spiking_bias() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(1, 1);
+        genome.push_connection(C::new(2, 1, &mut inno));
+
+        let mut nn = Spiking::from_genome(&genome);
+        nn.step(100, &[0.], activate::relu);
+        assert!(nn.output()[0] > 0.9, "fired too little: {}", nn.output()[0]);
+    }
+
+    #[test]
+    fn test_poisson_train() {
+        let rates = [0., 0.25, 1.];
+        let train = poisson_train(&rates, 10_000, &mut default_rng());
+        assert_eq!(train.len(), 10_000);
+
+        let decoded = decode_rate(&train);
+        for (rate, decoded) in rates.iter().zip(decoded) {
+            assert!(
+                (rate - decoded).abs() < 0.05,
+                "{decoded} too far from {rate}"
+            );
+        }
+        assert!(decode_rate(&[]).is_empty());
+    }
+}
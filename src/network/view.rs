@@ -0,0 +1,160 @@
+use crate::{activate, genome::NodeKind, Connection, Genome};
+
+/// A feedforward network that borrows its genome's connections rather than copying them, for
+/// when building a whole network would cost more than using it, such as when probing the output
+/// of thousands of genomes on a few inputs. Evaluation is a single pass in topological order,
+/// so it holds no state between calls, and [recurrent](Genome::recurrent) connections are
+/// ignored.
+///
+/// Each node is σ of the weighted sum of its inputs plus its [bias](Genome::biases), sensory nodes
+/// are their input, and the bias node is 1. Genomes who
+/// [evolve activations](Genome::node_activations) have each node use its own from the
+/// [palette](crate::activate::PALETTE) rather than σ. This is not the same as the settled output
+/// of a [Continuous](crate::network::Continuous) network, which integrates state over time.
+#[derive(Debug, Clone)]
+pub struct NetworkView<'a, C: Connection> {
+    connections: &'a [C],
+    nodes: &'a [NodeKind],
+    activations: Option<&'a [usize]>,
+    biases: Option<&'a [f64]>,
+    /// Every node, each after all that feed into it
+    order: Vec<usize>,
+    /// Enabled forward connections, ordered by the position of their destination in `order`
+    forward: Vec<usize>,
+    /// Every sensory node, in the order that input is given to them
+    sensory: Vec<usize>,
+    /// Every action node, in the order that output is read from them
+    action: Vec<usize>,
+}
+
+impl<'a, C: Connection> NetworkView<'a, C> {
+    pub fn new<G: Genome<C>>(genome: &'a G) -> Self {
+        let (connections, nodes) = (genome.connections(), genome.nodes());
+        let mut forward = genome
+            .recurrent()
+            .into_iter()
+            .enumerate()
+            .filter(|(idx, recurrent)| connections[*idx].enabled() && !recurrent)
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+
+        // kahn's algorithm, over the forward connections leaving each node
+        let mut incoming = vec![0usize; nodes.len()];
+        let mut outgoing = vec![Vec::new(); nodes.len()];
+        for &idx in forward.iter() {
+            incoming[connections[idx].to()] += 1;
+            outgoing[connections[idx].from()].push(idx);
+        }
+        let mut order = (0..nodes.len())
+            .filter(|node| incoming[*node] == 0)
+            .collect::<Vec<_>>();
+        let mut next = 0;
+        while let Some(&from) = order.get(next) {
+            for &idx in outgoing[from].iter() {
+                let to = connections[idx].to();
+                incoming[to] -= 1;
+                if incoming[to] == 0 {
+                    order.push(to);
+                }
+            }
+            next += 1;
+        }
+
+        let mut rank = vec![0; nodes.len()];
+        for (position, &node) in order.iter().enumerate() {
+            rank[node] = position;
+        }
+        forward.sort_by_key(|idx| rank[connections[*idx].to()]);
+
+        Self {
+            connections,
+            nodes,
+            activations: genome.node_activations(),
+            biases: genome.node_biases(),
+            order,
+            forward,
+            sensory: genome.sensory_nodes(),
+            action: genome.action_nodes(),
+        }
+    }
+
+    /// Evaluate every node given some sensory input, activating with σ, and return the output
+    pub fn eval<F: Fn(f64) -> f64>(&self, input: &[f64], σ: F) -> Vec<f64> {
+        assert_eq!(
+            input.len(),
+            self.sensory.len(),
+            "input is sized {}, expected {}",
+            input.len(),
+            self.sensory.len()
+        );
+
+        let mut values = vec![0.; self.nodes.len()];
+        for (idx, x) in self.sensory.iter().zip(input) {
+            values[*idx] = *x;
+        }
+        let mut forward = self
+            .forward
+            .iter()
+            .map(|idx| &self.connections[*idx])
+            .peekable();
+        for &node in self.order.iter() {
+            let mut sum = self.biases.map_or(0., |biases| biases[node]);
+            while let Some(c) = forward.next_if(|c| c.to() == node) {
+                sum += values[c.from()] * c.weight();
+            }
+
+            values[node] = match self.nodes[node] {
+                NodeKind::Sensory => values[node],
+                NodeKind::Static => 1.,
+                NodeKind::Action | NodeKind::Internal => match self.activations {
+                    Some(activations) => activate::PALETTE[activations[node]](sum),
+                    None => σ(sum),
+                },
+            };
+        }
+
+        self.action.iter().map(|idx| values[*idx]).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        activate, assert_f64_approx,
+        genome::{self, InnoGen, WConnection},
+    };
+
+    type C = WConnection;
+
+    #[test]
+    fn test_network_view() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = genome::Recurrent::<C>::new(2, 1);
+        genome.push_node(NodeKind::Internal);
+        // 0 -> 4 -> 2 and 1 -> 2 and 3 -> 4, with 2 -> 4 recurrent, pushed out of order
+        for (path, weight) in [
+            ((4, 2), 2.),
+            ((0, 4), 1.),
+            ((1, 2), -1.),
+            ((3, 4), 0.5),
+            ((2, 4), 100.),
+        ] {
+            genome.push_connection(C::new(path.0, path.1, &mut inno));
+            genome.connections_mut().last_mut().unwrap().weight = weight;
+        }
+
+        let view = NetworkView::new(&genome);
+        let σ = activate::relu;
+        let hidden = σ(1. * 3. + 0.5 * 1.);
+        let want = σ(2. * hidden - 1. * 2.);
+        assert_f64_approx!(view.eval(&[3., 2.], σ)[0], want);
+        assert_f64_approx!(view.eval(&[0., 10.], σ)[0], σ(2. * 0.5 - 10.));
+
+        let mut genome = genome.with_node_biases();
+        genome.node_biases_mut().unwrap()[4] = -1.;
+        let view = NetworkView::new(&genome);
+        let hidden = σ(1. * 3. + 0.5 * 1. - 1.);
+        assert_f64_approx!(view.eval(&[3., 2.], σ)[0], σ(2. * hidden - 1. * 2.));
+    }
+}
@@ -0,0 +1,165 @@
+//! Fitness accounting for non-episodic evolution, where networks live on in a persistent world
+//! rather than being evaluated once per generation.
+//!
+//! There are no generations here. Individuals are rewarded as they act, rewards decay over time
+//! so that recent behavior counts for more, and every so often the least fit of those old enough
+//! to have been judged is replaced in place by the offspring of fitter ones.
+use crate::{genome::InnoGen, Connection, Fit, Genome};
+use core::marker::PhantomData;
+use rand::{Rng, RngCore};
+
+/// Fitness accumulated by a single individual over its life
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Lifetime {
+    /// Decayed sum of every reward received
+    pub fitness: f64,
+    /// Ticks lived
+    pub age: usize,
+}
+
+impl Lifetime {
+    /// Decayed average reward per tick. Unlike [fitness](Lifetime::fitness), which grows with
+    /// age, this compares individuals of different ages fairly.
+    pub fn rate(&self, decay: f64) -> f64 {
+        match self.age {
+            0 => 0.,
+            age if decay == 1. => self.fitness / age as f64,
+            age => self.fitness * (1. - decay) / (1. - decay.powi(age as i32)),
+        }
+    }
+}
+
+/// A population living in a persistent world, whose fitness accumulates as it's rewarded.
+/// Individuals keep their index for as long as they live, so a world may keep a network for each
+/// one, rebuilding it only when [replace](Online::replace) puts offspring at its index.
+pub struct Online<C: Connection, G: Genome<C>> {
+    /// Factor that every individual's fitness decays by each tick
+    pub decay: f64,
+    /// Ticks that an individual must live before it may be replaced, or reproduce
+    pub maturity: usize,
+    pub living: Vec<(G, Lifetime)>,
+    innogen: InnoGen,
+    connection: PhantomData<C>,
+}
+
+impl<C: Connection, G: Genome<C>> Online<C, G> {
+    pub fn new(genomes: Vec<G>, inno_head: usize, decay: f64, maturity: usize) -> Self {
+        assert!((0. ..=1.).contains(&decay), "decay must be within 0..=1");
+        Self {
+            decay,
+            maturity,
+            living: genomes
+                .into_iter()
+                .map(|genome| (genome, Lifetime::default()))
+                .collect(),
+            innogen: InnoGen::new(inno_head),
+            connection: PhantomData,
+        }
+    }
+
+    /// Reward the individual at `idx`
+    pub fn reward(&mut self, idx: usize, reward: f64) {
+        self.living[idx].1.fitness += reward;
+    }
+
+    /// Advance time by one tick, aging every individual and decaying its fitness
+    pub fn tick(&mut self) {
+        for (_, lifetime) in self.living.iter_mut() {
+            lifetime.fitness *= self.decay;
+            lifetime.age += 1;
+        }
+    }
+
+    pub fn inno_head(&self) -> usize {
+        self.innogen.head
+    }
+
+    /// Of the mature individuals, pick the fitter of two at random
+    fn pick(&self, mature: &[usize], rng: &mut impl RngCore) -> usize {
+        let (l, r) = (
+            mature[rng.random_range(0..mature.len())],
+            mature[rng.random_range(0..mature.len())],
+        );
+        if self.living[l].1.rate(self.decay) >= self.living[r].1.rate(self.decay) {
+            l
+        } else {
+            r
+        }
+    }
+
+    /// Replace the mature individual with the lowest [rate](Lifetime::rate) with the mutated
+    /// offspring of two mature parents, each the fitter of two picked at random. Returns the
+    /// index of the offspring, or None if fewer than 2 individuals are mature.
+    pub fn replace(&mut self, rng: &mut impl RngCore) -> Option<usize> {
+        let mature = (0..self.living.len())
+            .filter(|idx| self.living[*idx].1.age >= self.maturity)
+            .collect::<Vec<_>>();
+        if mature.len() < 2 {
+            return None;
+        }
+
+        let worst = *mature
+            .iter()
+            .min_by_key(|idx| Fit(self.living[**idx].1.rate(self.decay)))
+            .unwrap();
+        let (l, r) = (self.pick(&mature, rng), self.pick(&mature, rng));
+        let (l_fit, r_fit) = (
+            Fit(self.living[l].1.rate(self.decay)),
+            Fit(self.living[r].1.rate(self.decay)),
+        );
+        let mut child = self.living[l]
+            .0
+            .reproduce_with(&self.living[r].0, l_fit.cmp(&r_fit), rng);
+        child.mutate(rng, &mut self.innogen);
+        self.living[worst] = (child, Lifetime::default());
+        Some(worst)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        genome::{Recurrent, WConnection},
+        random::default_rng,
+    };
+
+    type C = WConnection;
+    type G = Recurrent<C>;
+
+    #[test]
+    fn test_lifetime_rate() {
+        for decay in [0.5, 0.9, 1.] {
+            let mut online = Online::<C, G>::new(vec![G::new(1, 1).0], 0, decay, 0);
+            for _ in 0..10 {
+                online.tick();
+                online.reward(0, 2.);
+                let rate = online.living[0].1.rate(decay);
+                assert!((rate - 2.).abs() < 1e-9, "{rate} !~ 2");
+            }
+        }
+        assert_eq!(Lifetime::default().rate(0.5), 0.);
+    }
+
+    #[test]
+    fn test_online_replace() {
+        let mut rng = default_rng();
+        let (genome, inno_head) = G::new(2, 1);
+        let mut online = Online::<C, G>::new(vec![genome; 4], inno_head, 0.9, 3);
+
+        for _ in 0..2 {
+            online.tick();
+            for idx in 0..4 {
+                online.reward(idx, idx as f64);
+            }
+        }
+        assert_eq!(online.replace(&mut rng), None);
+
+        online.tick();
+        online.living[2].1.age = 0;
+        assert_eq!(online.replace(&mut rng), Some(0));
+        assert_eq!(online.living[0].1, Lifetime::default());
+        assert_eq!(online.replace(&mut rng), Some(1));
+        assert_eq!(online.replace(&mut rng), None);
+    }
+}
@@ -0,0 +1,323 @@
+//! Continuous optimization of a genome's weights, holding its topology fixed.
+//!
+//! NEAT searches structure and weights together, but mutation is a slow way to tune weights once
+//! a good topology is found. An [Es] may be run over the champion of every specie every so
+//! often, see [Scenario::es](crate::Scenario::es), combining NEAT's structure search with a
+//! stronger local search over weights.
+//!
+//! [sensitivity] measures how much each of a genome's [params](Genome::params) matters to its
+//! fitness, which may show what parts of a champion may be pruned or left alone.
+use crate::{
+    scenario::{eval_genome, PopulationView},
+    Connection, Fit, Genome, Scenario,
+};
+use rand::RngCore;
+use rand_distr::{Distribution, StandardNormal};
+
+/// Settings for a (μ/μ, λ) evolution strategy. Each generation, λ candidates are sampled about
+/// the mean with isotropic gaussian noise of deviation σ, and the mean moves to the average of
+/// the fittest μ of them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Es {
+    /// Run once every `every` generations of evolution
+    pub every: usize,
+    /// ES generations per run
+    pub generations: usize,
+    pub λ: usize,
+    pub μ: usize,
+    pub σ: f64,
+    /// Factor by which σ shrinks every ES generation
+    pub decay: f64,
+}
+
+impl Default for Es {
+    fn default() -> Self {
+        Self {
+            every: 10,
+            generations: 10,
+            λ: 12,
+            μ: 3,
+            σ: 0.1,
+            decay: 0.95,
+        }
+    }
+}
+
+impl Es {
+    /// Fitness evaluations spent by a single [optimize](Es::optimize) of a genome with weights
+    pub fn evaluations(&self) -> usize {
+        self.generations * self.λ
+    }
+
+    /// Optimize the weights of `genome` whose fitness is `fit`, returning the fittest genome
+    /// found along with its fitness. That is `genome` itself if nothing fitter was found.
+    /// Candidates are evaluated as [evolve](crate::scenario::evolve) would, seeing `population`
+    /// if the scenario asks to. Scenarios that [score whole populations](Scenario::eval_population)
+    /// score each generation's candidates together with the fittest genome so far, which a
+    /// candidate must outscore to replace it. Those scores mean nothing outside of their batch, so
+    /// `fit` is kept as the fitness of whatever is returned.
+    pub fn optimize<C: Connection, G: Genome<C>, A: Fn(f64) -> f64, S: Scenario<C, G, A>>(
+        &self,
+        genome: &G,
+        fit: Fit,
+        scenario: &S,
+        σ: &A,
+        population: &PopulationView<C, G>,
+        rng: &mut impl RngCore,
+    ) -> (G, Fit) {
+        assert!(
+            0 < self.μ && self.μ <= self.λ,
+            "μ must be within 1..=λ, got {}",
+            self.μ
+        );
+        let mut best = (genome.clone(), fit);
+        let mut mean = genome.weights();
+        if mean.is_empty() {
+            return best;
+        }
+
+        let mut step = self.σ;
+        for _ in 0..self.generations {
+            let mut batch = Vec::with_capacity(self.λ + 1);
+            batch.push(best.0.clone());
+            batch.extend((0..self.λ).map(|_| {
+                let weights = mean
+                    .iter()
+                    .map(|w| {
+                        let z: f64 = StandardNormal.sample(rng);
+                        w + step * z
+                    })
+                    .collect::<Vec<_>>();
+                let mut candidate = genome.clone();
+                candidate.set_weights(&weights);
+                candidate
+            }));
+
+            let (mut baseline, fits, batched) = match scenario.eval_population(&batch, σ, rng) {
+                Some(fits) => {
+                    assert_eq!(fits.len(), batch.len(), "fitness count mismatch");
+                    let mut fits = fits.into_iter().map(Fit::scored);
+                    (fits.next().unwrap(), fits.collect::<Vec<_>>(), true)
+                }
+                None => {
+                    let fits = batch[1..]
+                        .iter()
+                        .map(|candidate| {
+                            Fit::scored(eval_genome(scenario, candidate, σ, population))
+                        })
+                        .collect();
+                    (best.1, fits, false)
+                }
+            };
+
+            let mut sampled = batch
+                .into_iter()
+                .skip(1)
+                .zip(fits)
+                .map(|(candidate, fit)| {
+                    let weights = candidate.weights();
+                    if fit > baseline {
+                        baseline = fit;
+                        best = (candidate, if batched { best.1 } else { fit });
+                    }
+                    (weights, fit)
+                })
+                .collect::<Vec<_>>();
+
+            sampled.sort_by_key(|(_, fit)| core::cmp::Reverse(*fit));
+            mean.fill(0.);
+            for (weights, _) in sampled.iter().take(self.μ) {
+                for (m, w) in mean.iter_mut().zip(weights) {
+                    *m += w / self.μ as f64;
+                }
+            }
+            step *= self.decay;
+        }
+
+        best
+    }
+}
+
+/// The sensitivity of `genome`'s fitness to each of its [params](Genome::params), as a central
+/// finite difference `(f(p + h) - f(p - h)) / 2h` over `scenario`, laid out as those params are.
+/// Params whose sensitivity is near 0 barely affect fitness. This costs 2 evaluations per param,
+/// and assumes that `scenario` is deterministic. Each pair is evaluated as
+/// [evolve](crate::scenario::evolve) would, seeing `population` if the scenario asks to, or scored
+/// together if it [scores whole populations](Scenario::eval_population).
+pub fn sensitivity<C: Connection, G: Genome<C>, A: Fn(f64) -> f64, S: Scenario<C, G, A>>(
+    genome: &G,
+    scenario: &S,
+    σ: &A,
+    population: &PopulationView<C, G>,
+    h: f64,
+    rng: &mut impl RngCore,
+) -> Vec<f64> {
+    assert!(h > 0., "h must be positive, got {h}");
+    let params = genome.params();
+    let mut perturbed = params.clone();
+    let mut pair = [genome.clone(), genome.clone()];
+    (0..params.len())
+        .map(|idx| {
+            for (candidate, h) in pair.iter_mut().zip([h, -h]) {
+                perturbed[idx] = params[idx] + h;
+                candidate.set_params(&perturbed);
+            }
+            perturbed[idx] = params[idx];
+            let (up, down) = match scenario.eval_population(&pair, σ, rng).as_deref() {
+                Some(&[up, down]) => (up, down),
+                Some(fits) => panic!("fitness count mismatch: {} for 2 genomes", fits.len()),
+                None => (
+                    eval_genome(scenario, &pair[0], σ, population),
+                    eval_genome(scenario, &pair[1], σ, population),
+                ),
+            };
+            (up - down) / (2. * h)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        genome::{InnoGen, Recurrent, WConnection},
+        random::default_rng,
+    };
+
+    type C = WConnection;
+    type G = Recurrent<C>;
+    type A = fn(f64) -> f64;
+
+    /// Fitter the closer that every weight is to 0.5
+    struct Target;
+
+    impl Scenario<C, G, A> for Target {
+        fn io(&self) -> (usize, usize) {
+            (2, 1)
+        }
+
+        fn eval(&self, genome: &G, _: &A) -> f64 {
+            -genome
+                .weights()
+                .iter()
+                .map(|w| (w - 0.5).powi(2))
+                .sum::<f64>()
+        }
+    }
+
+    /// [Target], scored relative to the mean of the population evaluated alongside
+    struct Relative;
+
+    impl Scenario<C, G, A> for Relative {
+        fn io(&self) -> (usize, usize) {
+            (2, 1)
+        }
+
+        fn eval(&self, _: &G, _: &A) -> f64 {
+            unreachable!("evaluated without the population")
+        }
+
+        fn eval_population(&self, genomes: &[G], σ: &A, _: &mut dyn RngCore) -> Option<Vec<f64>> {
+            let fits = genomes
+                .iter()
+                .map(|genome| Target.eval(genome, σ))
+                .collect::<Vec<_>>();
+            let mean = fits.iter().sum::<f64>() / fits.len() as f64;
+            Some(fits.into_iter().map(|fit| fit - mean).collect())
+        }
+    }
+
+    /// [Target], less fit the larger the previous generation was
+    struct Crowded;
+
+    impl Scenario<C, G, A> for Crowded {
+        fn io(&self) -> (usize, usize) {
+            (2, 1)
+        }
+
+        fn eval(&self, _: &G, _: &A) -> f64 {
+            unreachable!("evaluated without the population")
+        }
+
+        fn eval_with_population(
+            &self,
+            genome: &G,
+            σ: &A,
+            population: &PopulationView<C, G>,
+        ) -> f64 {
+            Target.eval(genome, σ) - population.len() as f64
+        }
+
+        fn sees_population(&self) -> bool {
+            true
+        }
+    }
+
+    fn genome() -> G {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = G::new(2, 1);
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.push_connection(C::new(1, 2, &mut inno));
+        genome.set_weights(&[-1., 2.]);
+        genome
+    }
+
+    #[test]
+    fn test_optimize() {
+        let genome = genome();
+        let σ: A = |x| x;
+        let view = PopulationView { species: &[] };
+        let fit = Fit::scored(Target.eval(&genome, &σ));
+        let es = Es {
+            generations: 50,
+            σ: 0.5,
+            ..Es::default()
+        };
+        let (optimized, optimized_fit) =
+            es.optimize(&genome, fit, &Target, &σ, &view, &mut default_rng());
+
+        assert!(optimized_fit > fit);
+        assert_eq!(optimized_fit, Fit::scored(Target.eval(&optimized, &σ)));
+        assert!(optimized_fit > Fit(-0.1), "{optimized_fit}");
+        assert_eq!(
+            optimized.connections()[0].path(),
+            genome.connections()[0].path()
+        );
+
+        // seeing the population, which is empty and so costs nothing
+        let (seen, seen_fit) = es.optimize(&genome, fit, &Crowded, &σ, &view, &mut default_rng());
+        assert!(seen_fit > fit);
+        assert_eq!(seen_fit, Fit::scored(Target.eval(&seen, &σ)));
+
+        // scored against each other, so that only whether it got fitter can be told
+        let (relative, relative_fit) =
+            es.optimize(&genome, fit, &Relative, &σ, &view, &mut default_rng());
+        assert_eq!(relative_fit, fit);
+        assert!(Target.eval(&relative, &σ) > -0.1);
+    }
+
+    #[test]
+    fn test_sensitivity() {
+        let mut inno = InnoGen::new(0);
+        let mut genome = genome();
+        genome.push_connection(C::new(1, 1, &mut inno));
+        genome.connections_mut()[2].disable();
+        genome.set_params(&[-1., 0.5, 3.]);
+
+        let σ: A = |x| x;
+        let view = PopulationView { species: &[] };
+        let mut rng = default_rng();
+        // a pair scored against each other differs just as much as when scored apart
+        for sensitivity in [
+            sensitivity(&genome, &Target, &σ, &view, 1e-3, &mut rng),
+            sensitivity(&genome, &Crowded, &σ, &view, 1e-3, &mut rng),
+            sensitivity(&genome, &Relative, &σ, &view, 1e-3, &mut rng),
+        ] {
+            assert_eq!(sensitivity.len(), 3);
+            // d/dw -(w - 0.5)^2 = -2(w - 0.5)
+            for (have, want) in sensitivity.iter().zip([3., 0., 0.]) {
+                assert!((have - want).abs() < 1e-6, "{have} !~ {want}");
+            }
+        }
+    }
+}
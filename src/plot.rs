@@ -0,0 +1,158 @@
+//! Quick plots of how a run went, rendered to SVG from the [StatsSnapshot] of each of its
+//! generations, so that a glance at an experiment doesn't need exporting to other tools.
+//!
+//! Collect snapshots with a hook:
+//!
+//! ```ignore
+//! let snapshots = Rc::new(RefCell::new(Vec::new()));
+//! let hook_snapshots = snapshots.clone();
+//! let hook: Hook<C, G> = Box::new(move |stats| {
+//!     hook_snapshots.borrow_mut().push(stats.snapshot());
+//!     ControlFlow::Continue(())
+//! });
+//! ```
+use crate::scenario::StatsSnapshot;
+use plotters::prelude::*;
+use std::{error::Error, path::Path};
+
+const SIZE: (u32, u32) = (800, 480);
+
+/// Range of generations over `snapshots`, which must not be empty
+fn generations(snapshots: &[StatsSnapshot]) -> Result<std::ops::Range<usize>, Box<dyn Error>> {
+    match (snapshots.first(), snapshots.last()) {
+        (Some(first), Some(last)) => Ok(first.generation..last.generation + 1),
+        _ => Err("no snapshots".into()),
+    }
+}
+
+/// Plot the best and mean fitness of every generation as lines, written as an SVG to `path`
+pub fn fitness(snapshots: &[StatsSnapshot], path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+    let x = generations(snapshots)?;
+    let (min, max) = snapshots
+        .iter()
+        .flat_map(|s| [s.best.0, s.mean.0])
+        .filter(|fit| fit.is_finite() && fit.abs() < f64::MAX)
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), fit| {
+            (min.min(fit), max.max(fit))
+        });
+    let (min, max) = if min < max {
+        (min, max)
+    } else {
+        (min - 1., min + 1.)
+    };
+
+    let root = SVGBackend::new(path.as_ref(), SIZE).into_drawing_area();
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(50)
+        .build_cartesian_2d(x, min..max)?;
+    chart
+        .configure_mesh()
+        .x_desc("generation")
+        .y_desc("fitness")
+        .draw()?;
+
+    for (label, color, fit) in [
+        (
+            "best",
+            RED,
+            (|s: &StatsSnapshot| s.best.0) as fn(&StatsSnapshot) -> f64,
+        ),
+        ("mean", BLUE, |s| s.mean.0),
+    ] {
+        chart
+            .draw_series(LineSeries::new(
+                snapshots.iter().map(|s| (s.generation, fit(s))),
+                color,
+            ))?
+            .label(label)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+    chart
+        .configure_series_labels()
+        .background_style(WHITE)
+        .border_style(BLACK)
+        .draw()?;
+
+    root.present()?;
+    Ok(())
+}
+
+/// Plot the size of every specie of every generation as stacked areas, written as an SVG to
+/// `path`. Species are stacked in the order that they're held in each generation.
+pub fn species(snapshots: &[StatsSnapshot], path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+    let x = generations(snapshots)?;
+    let layers = snapshots.iter().map(|s| s.species.len()).max().unwrap_or(0);
+    let max = snapshots
+        .iter()
+        .map(|s| s.species.iter().sum::<usize>())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    let root = SVGBackend::new(path.as_ref(), SIZE).into_drawing_area();
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(50)
+        .build_cartesian_2d(x, 0..max)?;
+    chart
+        .configure_mesh()
+        .x_desc("generation")
+        .y_desc("population")
+        .draw()?;
+
+    // topmost layer first, so that each one below is drawn over it
+    for layer in (0..layers).rev() {
+        chart.draw_series(AreaSeries::new(
+            snapshots.iter().map(|s| {
+                (
+                    s.generation,
+                    s.species.iter().take(layer + 1).sum::<usize>(),
+                )
+            }),
+            0,
+            Palette99::pick(layer).filled(),
+        ))?;
+    }
+
+    root.present()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Fit;
+    use std::{env, fs};
+
+    #[test]
+    fn test_plot() {
+        let snapshots = (0..10)
+            .map(|generation| StatsSnapshot {
+                generation,
+                best: Fit(generation as f64),
+                mean: Fit(generation as f64 / 2.),
+                species: vec![4, 2 + generation % 3, 1],
+                innovations: 0,
+                evaluations: 0,
+            })
+            .collect::<Vec<_>>();
+
+        let dir = env::temp_dir().join(format!("eevee-plot-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fitness(&snapshots, dir.join("fitness.svg")).unwrap();
+        species(&snapshots, dir.join("species.svg")).unwrap();
+        let fitness_svg = fs::read_to_string(dir.join("fitness.svg")).unwrap();
+        let species_svg = fs::read_to_string(dir.join("species.svg")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(fitness_svg.starts_with("<svg"));
+        assert!(fitness_svg.contains("best"));
+        assert!(species_svg.contains("<polygon"));
+        assert!(fitness(&[], "never.svg").is_err());
+    }
+}
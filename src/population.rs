@@ -1,28 +1,80 @@
 //! Functions and structs related to managing genomes at the specie and global population scale.
 
 use crate::{
-    crossover::delta,
-    genome::{Connection, Genome},
+    crossover::{delta_with, node_delta, DisjointExcess, Normalization},
+    genome::{Connection, Genome, InnoGen, MutationConfig, NodeGenes},
+    Fit,
 };
 use core::{
     error::Error,
     f64,
     hash::{Hash, Hasher},
+    mem,
+};
+use fxhash::FxHashMap;
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::hash::DefaultHasher;
+#[cfg(feature = "fs")]
+use std::{
+    fs::{self, read_dir},
+    iter::empty,
+    path::Path,
 };
-use std::{fs::read_dir, hash::DefaultHasher, iter::empty, path::Path};
 
 /// The representative member of a particular specie. Is retained inter-generationally to better
-/// track when a specie deviates
-#[derive(Debug, Clone)]
-pub struct SpecieRepr<C: Connection>(Vec<C>);
+/// track when a specie deviates. Holds the connections of the member, and its [NodeGenes] if it
+/// evolves any.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "", from = "ReprSerde<C>", into = "ReprSerde<C>")]
+pub struct SpecieRepr<C: Connection>(Vec<C>, NodeGenes);
+
+/// A [SpecieRepr] as it's serialized: only its connections when it holds no node genes, as reprs
+/// were before they held any, or its connections and node genes
+#[derive(Serialize, Deserialize)]
+#[serde(untagged, bound = "")]
+enum ReprSerde<C: Connection> {
+    Connections(Vec<C>),
+    Genes(Vec<C>, NodeGenes),
+}
+
+impl<C: Connection> From<ReprSerde<C>> for SpecieRepr<C> {
+    fn from(repr: ReprSerde<C>) -> Self {
+        match repr {
+            ReprSerde::Connections(connections) => Self(connections, NodeGenes::default()),
+            ReprSerde::Genes(connections, nodes) => Self(connections, nodes),
+        }
+    }
+}
+
+impl<C: Connection> From<SpecieRepr<C>> for ReprSerde<C> {
+    fn from(SpecieRepr(connections, nodes): SpecieRepr<C>) -> Self {
+        if nodes.is_empty() {
+            Self::Connections(connections)
+        } else {
+            Self::Genes(connections, nodes)
+        }
+    }
+}
 
 impl<C: Connection> SpecieRepr<C> {
+    /// A repr of `v`, without node genes
     pub fn new(v: Vec<C>) -> Self {
-        Self(v)
+        Self(v, NodeGenes::default())
     }
 
-    fn delta(&self, other: &[C]) -> f64 {
-        delta(&self.0, other)
+    /// A repr of `genome`, its connections and node genes
+    pub fn of<G: Genome<C>>(genome: &G) -> Self {
+        Self(genome.connections().to_vec(), NodeGenes::of(genome))
+    }
+
+    /// The node genes of this repr
+    pub fn nodes(&self) -> &NodeGenes {
+        &self.1
+    }
+
+    fn delta(&self, connections: &[C], nodes: &NodeGenes, compatibility: &Compatibility) -> f64 {
+        compatibility.delta(&self.0, connections) + compatibility.node_delta(&self.1, nodes)
     }
 
     #[inline]
@@ -32,6 +84,11 @@ impl<C: Connection> SpecieRepr<C> {
 }
 
 impl<C: Connection> SpecieRepr<C> {
+    /// Approximate bytes held by this repr
+    pub fn memory(&self) -> usize {
+        size_of::<Self>() + size_of_val(self.0.as_slice()) + self.1.memory()
+    }
+
     fn id(&self) -> u64 {
         let mut h = DefaultHasher::new();
         self.hash(&mut h);
@@ -42,6 +99,7 @@ impl<C: Connection> SpecieRepr<C> {
 impl<C: Connection> Hash for SpecieRepr<C> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.0.hash(state);
+        self.1.hash(state);
     }
 }
 
@@ -60,10 +118,13 @@ impl<C: Connection> AsRef<[C]> for SpecieRepr<C> {
 }
 
 /// A collection of fitted [Genome]s who are closely related to the same [SpecieRepr]
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
 pub struct Specie<C: Connection, G: Genome<C>> {
     pub repr: SpecieRepr<C>,
-    pub members: Vec<(G, f64)>,
+    pub members: Vec<(G, Fit)>,
+    /// How many generations this specie's repr has persisted for
+    pub age: usize,
 }
 
 impl<C: Connection, G: Genome<C>> Specie<C, G> {
@@ -78,48 +139,254 @@ impl<C: Connection, G: Genome<C>> Specie<C, G> {
     }
 
     #[inline]
-    pub fn last(&self) -> Option<&(G, f64)> {
+    pub fn last(&self) -> Option<&(G, Fit)> {
         self.members.last()
     }
 
     #[inline]
-    pub fn cloned(&self) -> (Vec<C>, Vec<(G, f64)>) {
+    pub fn cloned(&self) -> (Vec<C>, Vec<(G, Fit)>) {
         (
             self.repr.cloned(),
             self.members.iter().map(|(g, s)| (g.clone(), *s)).collect(),
         )
     }
 
-    pub fn fit_adjusted(&self) -> f64 {
+    /// Approximate bytes held by this specie, including its repr and every member
+    pub fn memory(&self) -> usize {
+        size_of::<Self>()
+            + self.repr.memory()
+            + self.members.iter().fold(0, |acc, (genome, _)| {
+                acc + genome.memory() + size_of::<Fit>()
+            })
+    }
+
+    /// Shift the innovation id of every connection in this specie's repr and members by
+    /// `offset`, so that they may not collide with ids below it. Each is left sorted by id, as
+    /// crossover expects.
+    pub fn offset_inno(&mut self, offset: usize) {
+        let connections = self.repr.0.iter_mut().chain(
+            self.members
+                .iter_mut()
+                .flat_map(|(genome, _)| genome.connections_mut().iter_mut()),
+        );
+        for c in connections {
+            c.set_inno(c.inno() + offset);
+        }
+        self.sort_inno();
+    }
+
+    /// Sort the connections of this specie's repr and every member by innovation id, as crossover
+    /// expects, such as after they've been renumbered
+    fn sort_inno(&mut self) {
+        let sets = std::iter::once(&mut self.repr.0[..]).chain(
+            self.members
+                .iter_mut()
+                .map(|(genome, _)| genome.connections_mut()),
+        );
+        for connections in sets {
+            if !connections.is_sorted_by_key(|c| c.inno()) {
+                connections.sort_by_key(|c| c.inno());
+            }
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, Box<dyn Error>> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, Box<dyn Error>> {
+        serde_json::from_str(s).map_err(|op| op.into())
+    }
+
+    /// Export this specie, its repr and every member, to a single file at `path`
+    #[cfg(feature = "fs")]
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "fs")]
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Mean fitness of this specie's members, where those at [Fit::MIN], such as ones who are yet
+    /// to be evaluated or who [scored](Fit::scored) NaN, count as 0
+    pub fn fit_adjusted(&self) -> Fit {
         let l = self.len() as f64;
-        self.members.iter().fold(0., |acc, (_, fit)| acc + *fit / l)
+        self.members
+            .iter()
+            .filter(|(_, fit)| *fit > Fit::MIN)
+            .map(|(_, fit)| *fit / l)
+            .sum()
+    }
+}
+
+/// How genomes are measured against specie reprs when they're speciated, which may be set at
+/// runtime, see [Scenario::compatibility](crate::scenario::Scenario::compatibility)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Compatibility {
+    /// Genomes within this [delta](crate::crossover::delta) of a specie's repr belong to it
+    pub threshold: f64,
+    /// How disjoint and excess genes are normalized
+    pub normalization: Normalization,
+    /// How disjoint and excess genes are counted
+    pub disjoint_excess: DisjointExcess,
+    /// Weight of the [node_delta] between genomes, added to the delta of their connections
+    #[serde(default = "default_nodes")]
+    pub nodes: f64,
+}
+
+fn default_nodes() -> f64 {
+    Compatibility::DEFAULT.nodes
+}
+
+impl Compatibility {
+    pub const DEFAULT: Self = Self {
+        threshold: 4.,
+        normalization: Normalization::DEFAULT,
+        disjoint_excess: DisjointExcess::Merge,
+        nodes: 1.,
+    };
+
+    /// This config, with genomes within `threshold` of a repr belonging to its specie
+    pub fn with_threshold(self, threshold: f64) -> Self {
+        Self { threshold, ..self }
+    }
+
+    /// This config, with disjoint and excess genes normalized by `normalization`
+    pub fn with_normalization(self, normalization: Normalization) -> Self {
+        Self {
+            normalization,
+            ..self
+        }
+    }
+
+    /// This config, with disjoint and excess genes counted as `disjoint_excess` says
+    pub fn with_disjoint_excess(self, disjoint_excess: DisjointExcess) -> Self {
+        Self {
+            disjoint_excess,
+            ..self
+        }
+    }
+
+    /// This config, with the node delta between genomes weighted by `nodes`
+    pub fn with_nodes(self, nodes: f64) -> Self {
+        Self { nodes, ..self }
+    }
+
+    /// Delta between the connections `l` and `r`, as configured
+    pub fn delta<C: Connection>(&self, l: &[C], r: &[C]) -> f64 {
+        delta_with(l, r, self.normalization, self.disjoint_excess)
+    }
+
+    /// Delta between the node genes `l` and `r`, as configured
+    pub fn node_delta(&self, l: &NodeGenes, r: &NodeGenes) -> f64 {
+        self.nodes * node_delta(l, r)
+    }
+
+    /// Whether genomes `delta` apart belong to the same specie
+    pub fn compatible(&self, delta: f64) -> bool {
+        delta < self.threshold
     }
 }
 
-const SPECIE_THRESHOLD: f64 = 4.;
+impl Default for Compatibility {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
 
 /// Partition an unordered collection of [Genome]s into species. An initial collection of empty
 /// species is created from repr, and if some genome matches none of them, a new specie is
 /// formed with them as the repr.
 pub fn speciate<C: Connection, G: Genome<C>>(
-    genomes: impl Iterator<Item = (G, f64)>,
+    genomes: impl Iterator<Item = (G, Fit)>,
+    reprs: impl Iterator<Item = SpecieRepr<C>>,
+) -> Vec<Specie<C, G>> {
+    speciate_hinted(
+        genomes.map(|(genome, fit)| (genome, fit, None)),
+        reprs,
+        &Compatibility::DEFAULT,
+    )
+}
+
+/// Like [speciate], but with a hint for each genome of the specie it likely belongs to, by its
+/// index in `reprs`, such as that of its parents. A hinted specie is tried before any other, so
+/// that offspring who stay close to their parents, as most do, are speciated with a single delta.
+/// Genomes are measured against reprs as configured by `compatibility`.
+pub fn speciate_hinted<C: Connection, G: Genome<C>>(
+    genomes: impl Iterator<Item = (G, Fit, Option<usize>)>,
     reprs: impl Iterator<Item = SpecieRepr<C>>,
+    compatibility: &Compatibility,
+) -> Vec<Specie<C, G>> {
+    speciate_with(genomes, reprs, compatibility, None)
+}
+
+/// Like [speciate_hinted], but looking up deltas in `cache` before computing them. Given the same
+/// cache every generation, genomes carried over unchanged, such as elites, aren't compared
+/// against reprs that they've already been compared against. Deltas aren't keyed by
+/// `compatibility`, so a cache should only ever be used with the same one.
+pub fn speciate_cached<C: Connection, G: Genome<C>>(
+    genomes: impl Iterator<Item = (G, Fit, Option<usize>)>,
+    reprs: impl Iterator<Item = SpecieRepr<C>>,
+    compatibility: &Compatibility,
+    cache: &mut DeltaCache,
+) -> Vec<Specie<C, G>> {
+    cache.advance();
+    speciate_with(genomes, reprs, compatibility, Some(cache))
+}
+
+fn speciate_with<C: Connection, G: Genome<C>>(
+    genomes: impl Iterator<Item = (G, Fit, Option<usize>)>,
+    reprs: impl Iterator<Item = SpecieRepr<C>>,
+    compatibility: &Compatibility,
+    mut cache: Option<&mut DeltaCache>,
 ) -> Vec<Specie<C, G>> {
     let mut sp = Vec::from_iter(reprs.map(|repr| Specie {
         repr,
         members: Vec::new(),
+        age: 0,
     }));
+    let mut ids = match cache {
+        Some(_) => sp
+            .iter()
+            .map(|specie| fingerprint(&specie.repr.0, &specie.repr.1))
+            .collect(),
+        None => Vec::new(),
+    };
 
-    for (genome, fitness) in genomes {
-        match sp
-            .iter_mut()
-            .find(|Specie { repr, .. }| repr.delta(genome.connections()) < SPECIE_THRESHOLD)
-        {
+    for (genome, fitness, hint) in genomes {
+        let nodes = NodeGenes::of(&genome);
+        let genome_id = cache
+            .as_ref()
+            .map(|_| fingerprint(genome.connections(), &nodes));
+        let mut matches = |idx: usize| {
+            let repr = &sp[idx].repr;
+            let delta = match (cache.as_deref_mut(), genome_id) {
+                (Some(cache), Some(genome_id)) => cache.delta((ids[idx], genome_id), || {
+                    repr.delta(genome.connections(), &nodes, compatibility)
+                }),
+                _ => repr.delta(genome.connections(), &nodes, compatibility),
+            };
+            compatibility.compatible(delta)
+        };
+        let hinted = hint.filter(|idx| *idx < sp.len() && matches(*idx));
+        let found = match hinted {
+            Some(idx) => Some(idx),
+            None => (0..sp.len()).find(|idx| matches(*idx)),
+        };
+        match found.map(|idx| &mut sp[idx]) {
             Some(Specie { members, .. }) => members.push((genome, fitness)),
             None => {
+                let repr = SpecieRepr(genome.connections().to_vec(), nodes);
+                if cache.is_some() {
+                    ids.push(fingerprint(&repr.0, &repr.1));
+                }
                 sp.push(Specie {
-                    repr: SpecieRepr::new(genome.connections().to_vec()),
+                    repr,
                     members: vec![(genome, fitness)],
+                    age: 0,
                 });
             }
         }
@@ -128,6 +395,74 @@ pub fn speciate<C: Connection, G: Genome<C>>(
     sp
 }
 
+/// Deltas between specie reprs and genomes, for [speciate_cached]. Each is keyed by a fingerprint
+/// of the repr's and the genome's connections and node genes, which unlike [Genome::fingerprint]
+/// is exact and follows their order, as [delta](crate::crossover::delta) does. Each speciation
+/// forgets whatever the one before it didn't use, so the cache holds about a generation's worth
+/// of deltas.
+#[derive(Debug, Default, Clone)]
+pub struct DeltaCache {
+    current: FxHashMap<(u64, u64), f64>,
+    previous: FxHashMap<(u64, u64), f64>,
+    hits: usize,
+}
+
+/// An exact hash of `connections` in order and of `nodes`, as far as
+/// [delta](crate::crossover::delta) and [node_delta] are concerned
+fn fingerprint<C: Connection>(connections: &[C], nodes: &NodeGenes) -> u64 {
+    let mut h = fxhash::FxHasher64::default();
+    nodes.hash(&mut h);
+    for c in connections {
+        // a connection's own hash is lossy, but covers any genes other than its params
+        c.hash(&mut h);
+        h.write_u8(c.enabled() as u8);
+        for param in c.params() {
+            h.write_u64(param.to_bits());
+        }
+    }
+    h.finish()
+}
+
+impl DeltaCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of deltas held
+    pub fn len(&self) -> usize {
+        self.current.len() + self.previous.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// How many deltas were looked up rather than computed, over the life of this cache
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    fn advance(&mut self) {
+        self.previous = mem::take(&mut self.current);
+    }
+
+    fn delta(&mut self, key: (u64, u64), compute: impl FnOnce() -> f64) -> f64 {
+        if let Some(delta) = self.current.get(&key) {
+            self.hits += 1;
+            return *delta;
+        }
+        let delta = match self.previous.remove(&key) {
+            Some(delta) => {
+                self.hits += 1;
+                delta
+            }
+            None => compute(),
+        };
+        self.current.insert(key, delta);
+        delta
+    }
+}
+
 pub type SpecieGroup<C, G> = (Vec<Specie<C, G>>, usize);
 
 /// initial population of a single specie consisting of single connection genomes
@@ -141,14 +476,223 @@ pub fn population_init<C: Connection, G: Genome<C>>(
     let (genome, inno_head) = G::new(sensory, action);
     (
         vec![Specie {
-            repr: SpecieRepr::new(genome.connections().to_vec()),
-            members: vec![(genome, f64::MIN); population],
+            repr: SpecieRepr::of(&genome),
+            members: vec![(genome, Fit::MIN); population],
+            age: 0,
         }],
         inno_head,
     )
 }
 
+/// Initial population of a single specie whose genomes connect every sensory node to every action
+/// node, each connection with a uniformly random weight in `[-1, 1]`, as in the original NEAT
+/// paper. Connections along the same path share an innovation id in every genome.
+pub fn population_init_connected<C: Connection, G: Genome<C>>(
+    sensory: usize,
+    action: usize,
+    population: usize,
+    rng: &mut impl RngCore,
+) -> SpecieGroup<C, G> {
+    let (genome, inno_head) = G::new(sensory, action);
+    let mut innogen = InnoGen::new(inno_head);
+    let template = genome
+        .sensory()
+        .flat_map(|from| genome.action().map(move |to| (from, to)))
+        .map(|(from, to)| C::new(from, to, &mut innogen))
+        .collect::<Vec<_>>();
+
+    let members = (0..population)
+        .map(|_| {
+            let mut genome = genome.clone();
+            for c in template.iter() {
+                let mut c = c.clone();
+                c.set_weight(rng.random_range(-1. ..=1.));
+                genome.push_connection(c);
+            }
+            (genome, Fit::MIN)
+        })
+        .collect();
+
+    (
+        vec![Specie {
+            repr: SpecieRepr(template, NodeGenes::of(&genome)),
+            members,
+            age: 0,
+        }],
+        innogen.head,
+    )
+}
+
+/// The least [time constant](Genome::node_time_constants) that
+/// [population_from_template] jitters a node to, as a network diverges when one reaches 0.
+const MIN_TIME_CONSTANT: f64 = 1e-3;
+
+/// Initial population of a single specie seeded from a `template` genome, such as the champion
+/// of a previous run or some topology known to suit the task. Every member but the first, which
+/// is the template itself, has each of its [params](Genome::params) jittered uniformly by up to
+/// `jitter`, though time constants are kept positive. The inno_head is past every innovation id
+/// of the template.
+pub fn population_from_template<C: Connection, G: Genome<C>>(
+    template: G,
+    population: usize,
+    jitter: f64,
+    rng: &mut impl RngCore,
+) -> SpecieGroup<C, G> {
+    let (_, inno_head) = G::new(template.sensory().len(), template.action().len());
+    let inno_head = template
+        .connections()
+        .iter()
+        .map(|c| c.inno() + 1)
+        .fold(inno_head, usize::max);
+
+    let params = template.params();
+    let members = (0..population)
+        .map(|idx| {
+            let mut genome = template.clone();
+            if idx > 0 && jitter > 0. {
+                let jittered = params
+                    .iter()
+                    .map(|p| p + rng.random_range(-jitter..=jitter))
+                    .collect::<Vec<_>>();
+                genome.set_params(&jittered);
+                for τ in genome.node_time_constants_mut().unwrap_or_default() {
+                    *τ = τ.max(MIN_TIME_CONSTANT);
+                }
+            }
+            (genome, Fit::MIN)
+        })
+        .collect();
+
+    (
+        vec![Specie {
+            repr: SpecieRepr::of(&template),
+            members,
+            age: 0,
+        }],
+        inno_head,
+    )
+}
+
+/// Transplant a `specie` exported from some other run into `group`, such as one from
+/// [population_init]. The specie's innovation ids are moved past those of `group` so that they
+/// can't collide, and its members are reset to be unevaluated. Passed to
+/// [evolve](crate::scenario::evolve), it's kept as a specie of its own rather than being
+/// re-speciated into the population, and has the usual grace period that new species get before
+/// they must improve.
+pub fn population_transplant<C: Connection, G: Genome<C>>(
+    (mut species, inno_head): SpecieGroup<C, G>,
+    mut specie: Specie<C, G>,
+) -> SpecieGroup<C, G> {
+    specie.offset_inno(inno_head);
+    specie.age = 0;
+    specie
+        .members
+        .iter_mut()
+        .for_each(|(_, fit)| *fit = Fit::MIN);
+    let inno_head = specie
+        .members
+        .iter()
+        .flat_map(|(genome, _)| genome.connections().iter().map(|c| c.inno() + 1))
+        .chain(specie.repr.0.iter().map(|c| c.inno() + 1))
+        .fold(inno_head, usize::max);
+
+    species.push(specie);
+    (species, inno_head)
+}
+
+/// An operator injecting freshly initialized genomes into every generation, so that some
+/// exploration pressure remains late in a run, when the population has otherwise converged.
+/// Immigrants are speciated with the rest of the population, joining whatever specie they're
+/// close enough to or founding their own. [evolve](crate::scenario::evolve) initializes them from
+/// the first genome of its initial population.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Immigration {
+    /// Immigrants per generation, taking the place of as many offspring
+    pub count: usize,
+    /// Random connections that each immigrant starts out with beyond those of its template, if it
+    /// has room for them
+    pub connections: usize,
+}
+
+impl Immigration {
+    /// Initialize `count` immigrants from `template`, registering their new connections'
+    /// innovations with `innogen`. Each keeps the template's topology, node genes, and
+    /// [recurrence](Genome::recurrence), but has the weight of every connection that isn't
+    /// [frozen](Connection::frozen) drawn anew, and grows new connections only where the template
+    /// may. Weights are drawn from `config`'s [weight_init](MutationConfig::weight_init) and kept
+    /// within its bounds, as those of new connections made by mutation are.
+    ///
+    /// Passing the InnoGen that the rest of their generation was
+    /// [reproduced](crate::reproduce::population_reproduce_slabs) with gives immigrants the same
+    /// ids as offspring for the same paths, as immigrants of the same generation share with each
+    /// other.
+    pub fn genomes<C: Connection, G: Genome<C>>(
+        &self,
+        template: &G,
+        config: &MutationConfig,
+        innogen: &mut InnoGen,
+        rng: &mut impl RngCore,
+    ) -> Vec<G> {
+        let init = config.weight_init.unwrap_or(C::WEIGHT_INIT);
+        (0..self.count)
+            .map(|_| {
+                let mut genome = template.clone();
+                for c in genome.connections_mut().iter_mut().filter(|c| !c.frozen()) {
+                    c.set_weight(init.sample(rng));
+                }
+                for _ in 0..self.connections {
+                    if genome.new_connection_with(rng, innogen, &init).is_err() {
+                        break;
+                    }
+                }
+                for c in genome.connections_mut().iter_mut().filter(|c| !c.frozen()) {
+                    c.set_weight(c.weight().clamp(config.weight_min, config.weight_max));
+                }
+                genome
+            })
+            .collect()
+    }
+}
+
+/// Merge species evolved by separate runs who started from the same population, such as one
+/// from [population_init] whose inno_head is `base`. Innovation ids below `base` are shared by
+/// every run and kept, but those from `base` on were given out by each run on its own, so that
+/// the same id may mean different paths in different runs. These are renumbered by path, so that
+/// connections through the same path share an id whichever run they come from.
+///
+/// Every specie is kept as its own, and its members are reset to be unevaluated. Renumbered
+/// connections are sorted by their new ids.
+pub fn population_merge<C: Connection, G: Genome<C>>(
+    runs: impl IntoIterator<Item = Vec<Specie<C, G>>>,
+    base: usize,
+) -> SpecieGroup<C, G> {
+    let mut innogen = InnoGen::new(base);
+    let mut merged = Vec::new();
+    for mut specie in runs.into_iter().flatten() {
+        let connections = specie.repr.0.iter_mut().chain(
+            specie
+                .members
+                .iter_mut()
+                .flat_map(|(genome, _)| genome.connections_mut().iter_mut()),
+        );
+        for c in connections.filter(|c| c.inno() >= base) {
+            c.set_inno(innogen.path(c.path()));
+        }
+        specie.sort_inno();
+
+        specie.age = 0;
+        specie
+            .members
+            .iter_mut()
+            .for_each(|(_, fit)| *fit = Fit::MIN);
+        merged.push(specie);
+    }
+
+    (merged, innogen.head)
+}
+
 /// Save a population of [Genome]s to individual files inside of a directory at `path`
+#[cfg(feature = "fs")]
 pub fn population_to_files<P: AsRef<Path>, C: Connection, G: Genome<C>>(
     path: P,
     pop: &[Specie<C, G>],
@@ -166,11 +710,12 @@ pub fn population_to_files<P: AsRef<Path>, C: Connection, G: Genome<C>>(
 
 /// Load a population of [Genome]s from individual files inside of a directory at `path`. Assumes
 /// that every file in `path` is a valid descriptor, and will parse it.
+#[cfg(feature = "fs")]
 pub fn population_from_files<P: AsRef<Path>, C: Connection, G: Genome<C>>(
     path: P,
 ) -> Result<SpecieGroup<C, G>, Box<dyn Error>> {
     let pop_flat = read_dir(path)?
-        .map(|fp| Ok::<_, Box<dyn Error>>((G::from_file(fp?.path())?, f64::MIN)))
+        .map(|fp| Ok::<_, Box<dyn Error>>((G::from_file(fp?.path())?, Fit::MIN)))
         .collect::<Result<Vec<_>, _>>()?;
 
     if pop_flat.is_empty() {
@@ -188,6 +733,7 @@ pub fn population_from_files<P: AsRef<Path>, C: Connection, G: Genome<C>>(
 
 /// Load a single [Genome] from a single file, and clone it `population` times. Useful for
 /// resuming training from a single champion, or inspecting a particular genome.
+#[cfg(feature = "fs")]
 pub fn population_from_genome<P: AsRef<Path>, C: Connection, G: Genome<C>>(
     path: P,
     population: usize,
@@ -201,7 +747,7 @@ pub fn population_from_genome<P: AsRef<Path>, C: Connection, G: Genome<C>>(
         .unwrap_or(0);
 
     Ok((
-        speciate(vec![(muse, f64::MIN); population].into_iter(), empty()),
+        speciate(vec![(muse, Fit::MIN); population].into_iter(), empty()),
         inno_head,
     ))
 }
@@ -210,9 +756,12 @@ pub fn population_from_genome<P: AsRef<Path>, C: Connection, G: Genome<C>>(
 mod test {
     use super::*;
     use crate::{
-        genome::{Recurrent, WConnection},
+        activate,
+        genome::{InnoGen, NodeId, Recurrence, Recurrent, WConnection, WeightInit},
+        random::default_rng,
         test_t,
     };
+    use std::{collections::HashMap, iter::empty};
 
     type BasicGenomeCtrnn = Recurrent<WConnection>;
 
@@ -237,7 +786,348 @@ mod test {
         }
         for (genome, fit) in species.iter().flat_map(|Specie { members, .. }| members) {
             assert_eq!(0, genome.connections().len());
-            assert_eq!(f64::MIN, *fit);
+            assert_eq!(Fit::MIN, *fit);
         }
     });
+
+    test_t!(specie_memory[T: BasicGenomeCtrnn]() {
+        let (mut species, _) = population_init::<WConnection, T>(2, 2, 10);
+        let specie = &mut species[0];
+        let before = specie.memory();
+        assert!(before > 10 * specie.members[0].0.memory());
+
+        let mut inno = InnoGen::new(0);
+        specie.members[0]
+            .0
+            .push_connection(WConnection::new(0, 2, &mut inno));
+        assert_eq!(
+            specie.memory(),
+            before + size_of::<WConnection>()
+        );
+    });
+
+    test_t!(population_transplant[T: BasicGenomeCtrnn]() {
+        let mut inno = InnoGen::new(0);
+        let (mut exported, _) = population_init::<WConnection, T>(2, 2, 3);
+        for (genome, fit) in exported[0].members.iter_mut() {
+            // out of order, as a hand-edited export may be
+            genome.push_connection(WConnection::new(1, 3, &mut InnoGen::new(1)));
+            genome.push_connection(WConnection::new(0, 2, &mut inno));
+            *fit = Fit(1.);
+        }
+        let exported = exported[0].to_json().unwrap();
+        let exported = Specie::<WConnection, T>::from_json(&exported).unwrap();
+
+        let (species, inno_head) =
+            population_transplant(population_init::<WConnection, T>(2, 2, 5), exported);
+        assert_eq!(species.len(), 2);
+        assert_eq!(species[1].len(), 3);
+        assert_eq!(inno_head, 8);
+        for (genome, fit) in species[1].members.iter() {
+            let innos = genome.connections().iter().map(|c| c.inno());
+            assert_eq!(innos.collect::<Vec<_>>(), vec![6, 7]);
+            assert_eq!(genome.connections()[0].path(), (0, 2));
+            assert_eq!(*fit, Fit::MIN);
+        }
+    });
+
+    test_t!(population_merge[T: BasicGenomeCtrnn]() {
+        let (_, base) = population_init::<WConnection, T>(2, 2, 1);
+        let run = |paths: &[(usize, usize)]| {
+            let mut inno = InnoGen::new(base);
+            let (mut species, _) = population_init::<WConnection, T>(2, 2, 2);
+            for (genome, fit) in species[0].members.iter_mut() {
+                genome.push_connection(WConnection::new(1, 2, &mut InnoGen::new(0)));
+                for path in paths {
+                    genome.push_connection(WConnection::new(path.0, path.1, &mut inno));
+                }
+                *fit = Fit(1.);
+            }
+            species
+        };
+
+        // the second run's ids are given out in another order than they're renumbered in
+        let (species, inno_head) =
+            population_merge([run(&[(0, 2), (1, 3)]), run(&[(0, 3), (1, 3)])], base);
+        assert_eq!(species.len(), 2);
+        assert_eq!(inno_head, base + 3);
+        let innos = |specie: &Specie<WConnection, T>| {
+            specie.members[0]
+                .0
+                .connections()
+                .iter()
+                .map(|c| c.inno())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(innos(&species[0]), vec![0, base, base + 1]);
+        assert_eq!(innos(&species[1]), vec![0, base + 1, base + 2]);
+        assert_eq!(species[1].members[0].0.connections()[1].path(), (1, 3));
+        assert!(species[1].repr.0.is_sorted_by_key(|c| c.inno()));
+        for (_, fit) in species.iter().flat_map(|specie| specie.members.iter()) {
+            assert_eq!(*fit, Fit::MIN);
+        }
+    });
+
+    #[test]
+    fn test_immigration() {
+        type G = Recurrent<WConnection>;
+        let mut rng = default_rng();
+        let immigration = Immigration {
+            count: 5,
+            connections: 3,
+        };
+        let (template, _) = G::new(2, 1);
+        let template = template
+            .with_node_activations()
+            .with_recurrence(Recurrence::Acyclic);
+        let config = MutationConfig::NONE
+            .with_weight_init(WeightInit::Uniform { min: -4., max: 4. })
+            .with_weight_bounds(-2., 2.);
+        let mut innogen = InnoGen::new(10);
+        // a path that some offspring of the same generation mutated
+        let native = innogen.path((0, 2));
+        let immigrants = immigration.genomes(&template, &config, &mut innogen, &mut rng);
+        assert_eq!(immigrants.len(), 5);
+        assert!(innogen.head > 11);
+        let mut paths = HashMap::from([((0, 2), native)]);
+        for genome in immigrants.iter() {
+            assert_eq!(genome.connections().len(), 3);
+            assert_eq!(genome.recurrence(), Recurrence::Acyclic);
+            assert_eq!(genome.node_activations(), template.node_activations());
+            for c in genome.connections() {
+                assert!((10..innogen.head).contains(&c.inno()));
+                assert_eq!(*paths.entry(c.path()).or_insert(c.inno()), c.inno());
+                assert!((-2. ..=2.).contains(&c.weight()));
+            }
+        }
+
+        // only 2 paths fit a genome of io (1, 1), from the sensory and bias nodes
+        let (template, _) = G::new(1, 1);
+        let immigrants = Immigration {
+            count: 1,
+            connections: 10,
+        }
+        .genomes(&template, &config, &mut InnoGen::new(0), &mut rng);
+        assert_eq!(immigrants[0].connections().len(), 2);
+
+        // a template's connections are kept, with their weights drawn anew
+        let mut template = immigrants[0].clone();
+        template.connections_mut()[0].set_weight(10.);
+        let constant = MutationConfig::NONE.with_weight_init(WeightInit::Constant(0.5));
+        let immigrants = Immigration {
+            count: 1,
+            connections: 0,
+        }
+        .genomes(&template, &constant, &mut InnoGen::new(0), &mut rng);
+        assert_eq!(immigrants[0].connections().len(), 2);
+        for (c, t) in immigrants[0]
+            .connections()
+            .iter()
+            .zip(template.connections())
+        {
+            assert_eq!(c.path(), t.path());
+            assert_eq!(c.weight(), 0.5);
+        }
+    }
+
+    #[test]
+    fn test_speciate_hinted() {
+        type G = Recurrent<WConnection>;
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = G::new(2, 1);
+        genome.push_connection(WConnection::new(0, 2, &mut inno));
+        let (mut far, _) = G::new(2, 1);
+        for path in [(1, 2), (3, 2), (2, 2), (0, 4), (4, 2)] {
+            far.push_connection(WConnection::new(path.0, path.1, &mut inno));
+        }
+        let reprs = || [&genome, &genome, &far].map(SpecieRepr::of).into_iter();
+        let sizes = |species: Vec<Specie<WConnection, G>>| {
+            species.iter().map(Specie::len).collect::<Vec<_>>()
+        };
+
+        // with no hint, or a hint to a specie too far, the first that matches is joined
+        let hinted = |hint, compatibility: &Compatibility| {
+            let genomes = [(genome.clone(), Fit::MIN, hint)].into_iter();
+            sizes(speciate_hinted(genomes, reprs(), compatibility))
+        };
+        for hint in [None, Some(2), Some(7)] {
+            assert_eq!(hinted(hint, &Compatibility::DEFAULT), vec![1, 0, 0]);
+        }
+        assert_eq!(hinted(Some(1), &Compatibility::DEFAULT), vec![0, 1, 0]);
+
+        // far enough apart, any genome is compatible with every specie
+        let loose = Compatibility::DEFAULT.with_threshold(f64::INFINITY);
+        assert_eq!(hinted(Some(2), &loose), vec![0, 0, 1]);
+        // and one that's no closer than 0 to anything forms its own
+        let strict = Compatibility::DEFAULT.with_threshold(0.);
+        assert_eq!(hinted(None, &strict), vec![0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_speciate_cached() {
+        type G = Recurrent<WConnection>;
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(0);
+        let (genome, _) = G::new(2, 1);
+        let genomes = (0..20)
+            .map(|_| {
+                let mut genome = genome.clone();
+                genome.mutate_n(6, [1, 1, 1, 0, 0], &mut rng, &mut inno, &G::MUTATION);
+                (genome, Fit::MIN, None)
+            })
+            .collect::<Vec<_>>();
+        let compatibility = Compatibility::DEFAULT;
+        let reprs = speciate_hinted(genomes.iter().cloned(), empty(), &compatibility)
+            .into_iter()
+            .map(|specie| specie.repr)
+            .collect::<Vec<_>>();
+        let sizes = |species: Vec<Specie<WConnection, G>>| {
+            species.iter().map(Specie::len).collect::<Vec<_>>()
+        };
+
+        let mut cache = DeltaCache::new();
+        let plain = sizes(speciate_hinted(
+            genomes.iter().cloned(),
+            reprs.iter().cloned(),
+            &compatibility,
+        ));
+        let cached = |genomes: &[(G, Fit, Option<usize>)], cache: &mut DeltaCache| {
+            let genomes = genomes.iter().cloned();
+            sizes(speciate_cached(
+                genomes,
+                reprs.iter().cloned(),
+                &compatibility,
+                cache,
+            ))
+        };
+        let cold = cached(&genomes, &mut cache);
+        assert_eq!(cold, plain);
+        assert!(!cache.is_empty());
+
+        // the same genomes against the same reprs are all looked up, where a cold cache only
+        // looks up genomes that happened to be mutated alike
+        let (held, cold_hits) = (cache.len(), cache.hits());
+        let warm = cached(&genomes, &mut cache);
+        assert_eq!(warm, plain);
+        assert_eq!(cache.hits() - cold_hits, held + cold_hits);
+
+        // deltas unused by a speciation are forgotten
+        cached(&[], &mut cache);
+        cached(&[], &mut cache);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_speciate_node_genes() {
+        type G = Recurrent<WConnection>;
+        let mut inno = InnoGen::new(0);
+        let (genome, _) = G::new(2, 1);
+        let mut genome = genome
+            .with_node_activations()
+            .with_node_time_constants()
+            .with_node_biases()
+            .with_sensory_gains();
+        genome.push_connection(WConnection::new(0, 2, &mut inno));
+        genome
+            .bisect_connection(&mut default_rng(), &mut inno)
+            .unwrap();
+
+        // each with the same connections, but a single node gene that differs
+        let mut others = vec![genome.clone(); 5];
+        others[0].node_activations_mut().unwrap()[2] = activate::id::ABS;
+        others[1].node_time_constants_mut().unwrap()[2] = 1.;
+        others[2].node_biases_mut().unwrap()[2] = 1.;
+        others[3].sensory_gains_mut().unwrap()[0] = 2.;
+        others[4].define_module("hidden", &[NodeId(4)]).unwrap();
+
+        let strict = Compatibility::DEFAULT.with_threshold(0.1);
+        for other in others.iter() {
+            let count = |compatibility: &Compatibility| {
+                let genomes = [&genome, other].map(|g| (g.clone(), Fit::MIN, None));
+                speciate_hinted(genomes.into_iter(), empty(), compatibility).len()
+            };
+            assert_eq!(count(&strict), 2);
+            assert_eq!(count(&strict.with_nodes(0.)), 1);
+
+            let mut cache = DeltaCache::new();
+            let genomes = [&genome, other].map(|g| (g.clone(), Fit::MIN, None));
+            let species = speciate_cached(genomes.into_iter(), empty(), &strict, &mut cache);
+            assert_eq!(species.len(), 2);
+            assert_ne!(species[0].repr, species[1].repr);
+
+            // a repr keeps its node genes through serialization
+            let json = serde_json::to_string(&species[1].repr).unwrap();
+            let repr = serde_json::from_str::<SpecieRepr<WConnection>>(&json).unwrap();
+            assert_eq!(repr.nodes(), &NodeGenes::of(other));
+        }
+    }
+
+    #[test]
+    fn test_population_from_template() {
+        type G = Recurrent<WConnection>;
+        let mut rng = default_rng();
+        let mut inno = InnoGen::new(40);
+        let (mut template, _) = G::new(2, 1);
+        template.push_connection(WConnection::new(0, 2, &mut inno));
+        template.push_connection(WConnection::new(1, 2, &mut inno));
+
+        let (species, inno_head) = population_from_template(template.clone(), 6, 0.1, &mut rng);
+        assert_eq!(inno_head, 42);
+        assert_eq!(species.len(), 1);
+        let members = &species[0].members;
+        assert_eq!(members.len(), 6);
+        assert_eq!(members[0].0.params(), template.params());
+        for (genome, fit) in members.iter().skip(1) {
+            assert_eq!(*fit, Fit::MIN);
+            assert_eq!(genome.connections().len(), 2);
+            assert_ne!(genome.params(), template.params());
+            assert!(genome
+                .params()
+                .iter()
+                .zip(template.params())
+                .all(|(l, r)| (l - r).abs() <= 0.1));
+        }
+
+        // jittered by far more than the time constants, they're still kept positive
+        let template = template.with_node_time_constants();
+        let (species, _) = population_from_template(template, 32, 1., &mut rng);
+        for (genome, _) in species[0].members.iter() {
+            let time_constants = genome.node_time_constants().unwrap();
+            assert!(time_constants.iter().all(|τ| *τ >= MIN_TIME_CONSTANT));
+        }
+    }
+
+    #[test]
+    fn test_population_init_connected() {
+        type G = Recurrent<WConnection>;
+        let mut rng = default_rng();
+        let (species, inno_head) = population_init_connected::<WConnection, G>(3, 2, 5, &mut rng);
+        let base = G::new(3, 2).1;
+        assert_eq!(inno_head, base + 6);
+
+        let members = &species[0].members;
+        assert_eq!(members.len(), 5);
+        for (genome, _) in members.iter() {
+            let paths = genome
+                .connections()
+                .iter()
+                .map(|c| (c.inno(), c.path()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                paths,
+                members[0]
+                    .0
+                    .connections()
+                    .iter()
+                    .map(|c| (c.inno(), c.path()))
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(paths.len(), 6);
+            assert!(paths
+                .iter()
+                .all(|(_, (from, to))| *from < 3 && (3..5).contains(to)));
+            assert!(genome.weights().iter().all(|w| (-1. ..=1.).contains(w)));
+        }
+        assert_ne!(members[0].0.weights(), members[1].0.weights());
+    }
 }
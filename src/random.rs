@@ -2,11 +2,17 @@
 
 use core::cmp::min;
 use find_fold::FindFold;
-use rand::RngCore;
+use rand::{Rng, RngCore};
+use std::ops::ControlFlow;
+#[cfg(feature = "fs")]
 use std::{
     fs::File,
     io::{self, Read},
-    ops::ControlFlow,
+};
+#[cfg(not(feature = "fs"))]
+use std::{
+    hash::{BuildHasher, Hasher, RandomState},
+    sync::atomic::{AtomicU64, Ordering},
 };
 
 use crate::events;
@@ -35,6 +41,7 @@ pub const fn percent(x: u64) -> u64 {
 
 /// A quick and dirty way to get an RNG seed from urandom, onsystems that support it. Useful
 /// because our implementation of WyRng always needs a seed
+#[cfg(feature = "fs")]
 pub fn seed_urandom() -> io::Result<u64> {
     let mut file = File::open("/dev/urandom")?;
     let mut buffer = [0u8; 8];
@@ -44,13 +51,31 @@ pub fn seed_urandom() -> io::Result<u64> {
     ]))
 }
 
+/// A seed for [default_rng]. Read from urandom when the `fs` feature is enabled, otherwise
+/// derived from std's hasher keys, which are fixed on targets like `wasm32-unknown-unknown`.
+/// Those targets should seed a [WyRng] themselves from some host entropy if it matters.
+pub fn seed_default() -> u64 {
+    #[cfg(feature = "fs")]
+    {
+        seed_urandom().unwrap()
+    }
+    #[cfg(not(feature = "fs"))]
+    {
+        static COUNT: AtomicU64 = AtomicU64::new(0);
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(COUNT.fetch_add(1, Ordering::Relaxed));
+        hasher.finish()
+    }
+}
+
 /// For getting a handle on an RngCore when you don't want to think too much about it. This is
-/// why Eevee doesn't work on Windows.
+/// why Eevee doesn't work on Windows, unless built without the `fs` feature.
 pub fn default_rng() -> impl RngCore {
-    WyRng::seeded(seed_urandom().unwrap())
+    WyRng::seeded(seed_default())
 }
 
 /// A really small but also fast random number generator. Lifted from smol-rs/fastrand
+#[derive(Debug, Clone)]
 pub struct WyRng {
     state: u64,
 }
@@ -89,6 +114,41 @@ impl RngCore for WyRng {
     }
 }
 
+/// An RngCore that counts how many times it's been drawn from, each of `next_u32`, `next_u64`,
+/// and `fill_bytes` counting as one draw
+#[derive(Debug, Clone)]
+pub struct Counted<R: RngCore> {
+    inner: R,
+    draws: usize,
+}
+
+impl<R: RngCore> Counted<R> {
+    pub fn new(inner: R) -> Self {
+        Self { inner, draws: 0 }
+    }
+
+    pub fn draws(&self) -> usize {
+        self.draws
+    }
+}
+
+impl<R: RngCore> RngCore for Counted<R> {
+    fn next_u32(&mut self) -> u32 {
+        self.draws += 1;
+        self.inner.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.draws += 1;
+        self.inner.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        self.draws += 1;
+        self.inner.fill_bytes(dst)
+    }
+}
+
 /// A struct for describing discrete events that may occur, typically related to what mutation
 /// happens when any mutation is invoked. Mostly here so that we can use
 pub trait EventKind: Copy {
@@ -112,7 +172,28 @@ pub trait EventKind: Copy {
             }
         })
     }
+
+    /// Pick some event, with chances proportional to `weights`. Unlike [pick](EventKind::pick),
+    /// weights are relative to their sum rather than to [u64::MAX], so some event is always
+    /// picked. Panics if every weight is 0.
+    fn pick_weighted<R: RngCore>(rng: &mut R, weights: [u64; Self::COUNT]) -> Self {
+        let total = weights.iter().map(|w| *w as u128).sum::<u128>();
+        assert!(total > 0, "no event has any weight");
+        let roll = rng.random_range(0..total);
+        weights
+            .into_iter()
+            .enumerate()
+            .find_fold(0u128, |acc, (idx, w)| {
+                if roll < w as u128 + acc {
+                    ControlFlow::Break(Self::variants()[idx])
+                } else {
+                    ControlFlow::Continue(w as u128 + acc)
+                }
+            })
+            .expect("roll is within the total weight")
+    }
 }
 
-events!(Genome[NewConnection, BisectConnection, MutateConnection, MutateNode]);
+events!(Genome[NewConnection, BisectConnection, MutateConnection, MutateBias, MutateActivation]);
 events!(Connection[Disable, MutateParam]);
+events!(Crossover[Pick, Blend]);
@@ -1,22 +1,109 @@
 //! Functions related to reproducing on the specie and global population scale.
 
 use crate::{
-    genome::{Connection, Genome, InnoGen},
-    population::SpecieRepr,
-    Specie,
+    genome::{Connection, Genome, InnoGen, MutationConfig},
+    random::{CrossoverProbabilities, GenomeProbabilities},
+    Fit, Specie,
 };
-use core::{error::Error, f64};
+use core::{error::Error, ops::Range};
 use rand::RngCore;
-use std::collections::HashMap;
+use std::cmp::Reverse;
+
+/// A mutation policy where young species mutate with `young` probabilities, easing linearly
+/// into `old` probabilities as they approach `maturity` generations old. Typically, young
+/// species should search structure aggressively and old ones should mostly fine-tune params.
+#[derive(Debug, Clone, Copy)]
+pub struct Annealing {
+    pub young: GenomeProbabilities,
+    pub old: GenomeProbabilities,
+    pub maturity: usize,
+}
+
+impl Annealing {
+    /// Event probabilities for a specie that's `age` generations old
+    pub fn probabilities(&self, age: usize) -> GenomeProbabilities {
+        if age >= self.maturity {
+            return self.old;
+        }
+
+        let mut probabilities: GenomeProbabilities = Default::default();
+        for (p, (young, old)) in probabilities
+            .iter_mut()
+            .zip(self.young.iter().zip(self.old.iter()))
+        {
+            let (young, old) = (*young as i128, *old as i128);
+            *p = (young + (old - young) * age as i128 / self.maturity as i128) as u64;
+        }
+        probabilities
+    }
+}
+
+/// Knobs for how a population reproduces that may be set at runtime, see
+/// [Scenario::reproduction](crate::scenario::Scenario::reproduction). Anything left `None` is
+/// as the genome or connection kind has it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Reproduction {
+    /// How offspring are mutated, in place of [Genome::MUTATION]
+    pub mutation: Option<MutationConfig>,
+    /// How genes matched in both parents are inherited by crossover, in place of
+    /// [CROSSOVER_PROBABILITIES](Connection::CROSSOVER_PROBABILITIES)
+    pub crossover: Option<CrossoverProbabilities>,
+    /// When `Some`, offspring are only ever copied from fit parents rather than crossed over
+    /// between them, and mutated this many times to make up for the variation that crossover
+    /// would have brought. Useful as a baseline, or for genomes whose crossover isn't meaningful.
+    pub asexual: Option<usize>,
+}
+
+impl Reproduction {
+    /// How many times each offspring of an [asexual](Reproduction::asexual) reproduction is
+    /// mutated, unless it says otherwise
+    pub const ASEXUAL_MUTATIONS: usize = 2;
+
+    /// This config, with offspring mutated as configured by `mutation`
+    pub fn with_mutation(self, mutation: MutationConfig) -> Self {
+        Self {
+            mutation: Some(mutation),
+            ..self
+        }
+    }
+
+    /// This config, reproducing asexually with `mutations` mutations of each offspring
+    pub fn with_asexual(self, mutations: usize) -> Self {
+        Self {
+            asexual: Some(mutations),
+            ..self
+        }
+    }
+
+    /// This config, with matched genes inherited with `probabilities`
+    pub fn with_crossover(self, probabilities: CrossoverProbabilities) -> Self {
+        Self {
+            crossover: Some(probabilities),
+            ..self
+        }
+    }
+
+    /// How offspring of `G` are mutated
+    pub fn mutation<C: Connection, G: Genome<C>>(&self) -> MutationConfig {
+        self.mutation.unwrap_or(G::MUTATION)
+    }
+}
+
+/// Connections of room to spare in the buffer that each crossed over offspring is reproduced
+/// into, past the size of the offspring before it, for mutation to grow into
+const SCRATCH_SPARE: usize = 4;
 
 fn reproduce_crossover<C: Connection, G: Genome<C>>(
-    genomes: &[(G, f64)],
+    genomes: &[&(G, Fit)],
     size: usize,
+    probabilities: GenomeProbabilities,
+    reproduction: &Reproduction,
     rng: &mut impl RngCore,
     innogen: &mut InnoGen,
-) -> Result<Vec<G>, Box<dyn Error>> {
+    into: &mut Vec<G>,
+) -> Result<(), Box<dyn Error>> {
     if size == 0 {
-        return Ok(vec![]);
+        return Ok(());
     }
 
     if genomes.len() < 2 {
@@ -44,35 +131,39 @@ fn reproduce_crossover<C: Connection, G: Genome<C>>(
                     })
             })
             .collect::<Vec<_>>();
-        pairs.sort_by(|l, r| {
-            let r = r.0 .1 + r.1 .1;
-            let l = l.0 .1 + l.1 .1;
-            (r).partial_cmp(&l)
-                .unwrap_or_else(|| panic!("cannot partial_cmp {l} and {r}"))
-        });
+        pairs.sort_by_key(|((_, l_fit), (_, r_fit))| Reverse(**l_fit + **r_fit));
         pairs
     };
 
-    pairs
-        .into_iter()
-        .cycle()
-        .take(size)
-        .map(|((l, _), (r, _))| {
-            let mut child = l.reproduce_with(r, std::cmp::Ordering::Greater, rng);
-            child.mutate(rng, innogen);
-            Ok(child)
-        })
-        .collect()
+    let crossover = reproduction.crossover.unwrap_or(C::CROSSOVER_PROBABILITIES);
+    let mut scratch = Vec::new();
+    for ((l, _), (r, _)) in pairs.into_iter().cycle().take(size) {
+        let mut child =
+            l.reproduce_buffered(r, std::cmp::Ordering::Greater, crossover, rng, &mut scratch);
+        child.mutate_configured(
+            rng,
+            innogen,
+            probabilities,
+            &reproduction.mutation::<C, G>(),
+        );
+        // the child took the buffer, so the next is made ahead sized like its connections, with
+        // room for a few more, so that neither crossover nor mutation has to grow it again
+        scratch = Vec::with_capacity(child.connections().len() + SCRATCH_SPARE);
+        into.push(child);
+    }
+    Ok(())
 }
 
+/// Copy the fittest of `genomes` into `into` until there are `size` copies, each then changed by
+/// `mutate`
 fn reproduce_copy<C: Connection, G: Genome<C>>(
-    genomes: &[(G, f64)],
+    genomes: &[&(G, Fit)],
     size: usize,
-    rng: &mut impl RngCore,
-    innogen: &mut InnoGen,
-) -> Result<Vec<G>, Box<dyn Error>> {
+    mut mutate: impl FnMut(&mut G),
+    into: &mut Vec<G>,
+) -> Result<(), Box<dyn Error>> {
     if size == 0 {
-        return Ok(vec![]);
+        return Ok(());
     }
 
     if genomes.is_empty() {
@@ -83,30 +174,138 @@ fn reproduce_copy<C: Connection, G: Genome<C>>(
         .into());
     }
 
-    let mut top = genomes.iter().collect::<Vec<_>>();
-    top.sort_by(|(_, l), (_, r)| {
-        r.partial_cmp(l)
-            .unwrap_or_else(|| panic!("cannot partial_cmp {l} and {r}"))
-    });
-    top.into_iter()
-        .cycle()
-        .take(size)
-        .map(|(genome, _)| {
-            let mut child = genome.clone();
-            child.mutate(rng, innogen);
-            Ok(child)
-        })
-        .collect()
+    let mut top = genomes.to_vec();
+    top.sort_by_key(|(_, fit)| Reverse(*fit));
+    for (genome, _) in top.into_iter().cycle().take(size) {
+        let mut child = genome.clone();
+        mutate(&mut child);
+        into.push(child);
+    }
+    Ok(())
 }
 
+/// Reproduce `size` offspring from a single specie's `genomes`, mutating them with
+/// `probabilities` as configured by `reproduction`. The fittest genome is always carried over
+/// unchanged. When `reproduction` is [asexual](Reproduction::asexual), offspring are only ever
+/// copied and mutated, never crossed over.
 pub fn reproduce<C: Connection, G: Genome<C>>(
-    genomes: Vec<(G, f64)>,
+    genomes: Vec<(G, Fit)>,
     size: usize,
+    probabilities: GenomeProbabilities,
+    reproduction: &Reproduction,
     innogen: &mut InnoGen,
     rng: &mut impl RngCore,
 ) -> Result<Vec<G>, Box<dyn Error>> {
+    let mut pop = Vec::with_capacity(size);
+    reproduce_into(
+        &genomes.iter().collect::<Vec<_>>(),
+        size,
+        probabilities,
+        reproduction,
+        innogen,
+        rng,
+        &mut pop,
+    )?;
+    Ok(pop)
+}
+
+/// Like [reproduce], but borrowing `genomes` and pushing offspring onto the end of `into`, such
+/// as a buffer holding the whole next generation. Returns the range of `into` that they fill.
+pub fn reproduce_into<C: Connection, G: Genome<C>>(
+    genomes: &[&(G, Fit)],
+    size: usize,
+    probabilities: GenomeProbabilities,
+    reproduction: &Reproduction,
+    innogen: &mut InnoGen,
+    rng: &mut impl RngCore,
+    into: &mut Vec<G>,
+) -> Result<Range<usize>, Box<dyn Error>> {
+    if reproduction.asexual.is_some() {
+        return reproduce_asexual_into(
+            genomes,
+            size,
+            probabilities,
+            reproduction,
+            innogen,
+            rng,
+            into,
+        );
+    }
+
+    let start = into.len();
+    if !reproduce_elite(genomes, size, into)? {
+        return Ok(start..into.len());
+    }
+
+    let size = size - 1;
+    let size_copy = size / 4;
+    let size_copy = if size_copy == 0 || genomes.len() == 1 {
+        size
+    } else {
+        size_copy
+    };
+
+    let config = reproduction.mutation::<C, G>();
+    reproduce_copy(
+        genomes,
+        size_copy,
+        |child| child.mutate_configured(rng, innogen, probabilities, &config),
+        into,
+    )?;
+    reproduce_crossover(
+        genomes,
+        size - size_copy,
+        probabilities,
+        reproduction,
+        rng,
+        innogen,
+        into,
+    )?;
+    Ok(start..into.len())
+}
+
+/// Like [reproduce_into], but without crossover: every offspring but the carried over fittest is
+/// a copy of a fit parent, mutated as many times as `reproduction` is
+/// [asexual](Reproduction::asexual), or [ASEXUAL_MUTATIONS](Reproduction::ASEXUAL_MUTATIONS)
+/// times if it isn't. This is how every specie reproduces when `reproduction` is asexual.
+pub fn reproduce_asexual_into<C: Connection, G: Genome<C>>(
+    genomes: &[&(G, Fit)],
+    size: usize,
+    probabilities: GenomeProbabilities,
+    reproduction: &Reproduction,
+    innogen: &mut InnoGen,
+    rng: &mut impl RngCore,
+    into: &mut Vec<G>,
+) -> Result<Range<usize>, Box<dyn Error>> {
+    let start = into.len();
+    if reproduce_elite(genomes, size, into)? {
+        let config = reproduction.mutation::<C, G>();
+        let mutations = reproduction
+            .asexual
+            .unwrap_or(Reproduction::ASEXUAL_MUTATIONS);
+        reproduce_copy(
+            genomes,
+            size - 1,
+            |child| {
+                for _ in 0..mutations {
+                    child.mutate_configured(rng, innogen, probabilities, &config);
+                }
+            },
+            into,
+        )?;
+    }
+    Ok(start..into.len())
+}
+
+/// Carry over the fittest of `genomes` into `into`, returning whether there's room for more
+/// offspring after it
+fn reproduce_elite<C: Connection, G: Genome<C>>(
+    genomes: &[&(G, Fit)],
+    size: usize,
+    into: &mut Vec<G>,
+) -> Result<bool, Box<dyn Error>> {
     if size == 0 {
-        return Ok(vec![]);
+        return Ok(false);
     }
 
     if genomes.is_empty() {
@@ -117,126 +316,127 @@ pub fn reproduce<C: Connection, G: Genome<C>>(
         .into());
     }
 
-    let mut pop: Vec<G> = Vec::with_capacity(size);
-    pop.push(
+    into.push(
         genomes
             .iter()
-            .max_by(|(_, l), (_, r)| {
-                l.partial_cmp(r)
-                    .unwrap_or_else(|| panic!("cannot partial_cmp {l} and {r}"))
-            })
+            .max_by_key(|(_, fit)| *fit)
             .unwrap()
             .0
             .clone(),
     );
-
-    if size == 1 {
-        return Ok(pop);
-    }
-
-    let size = size - 1;
-    let size_copy = size / 4;
-    let size_copy = if size_copy == 0 || genomes.len() == 1 {
-        size
-    } else {
-        size_copy
-    };
-
-    // TODO reproduce_crossover and reproduce_copy can potentially be made faster
-    // if they're handed a slice to write into intead of returning a vec that we then need to copy
-    reproduce_copy(&genomes, size_copy, rng, innogen)?
-        .into_iter()
-        .for_each(|genome| pop.push(genome));
-
-    let size_crossover = size - size_copy;
-    reproduce_crossover(&genomes, size_crossover, rng, innogen)?
-        .into_iter()
-        .for_each(|genome| pop.push(genome));
-
-    Ok(pop)
+    Ok(size > 1)
 }
 
-/// allocate a target population for every specie in an existing population
-fn population_alloc<'a, C: Connection + 'a, G: Genome<C> + 'a>(
-    species: impl Iterator<Item = &'a Specie<C, G>>,
-    population: usize,
-) -> HashMap<SpecieRepr<C>, usize> {
-    let species_fitted = species
-        .map(|s| (s.repr.clone(), s.fit_adjusted()))
-        .collect::<Vec<_>>();
-
-    let fit_total = species_fitted.iter().fold(0., |acc, (_, n)| acc + n);
+/// allocate a target population for every specie, given the adjusted fitness of each
+fn population_alloc(fits_adjusted: &[Fit], population: usize) -> Vec<usize> {
+    let fit_total = fits_adjusted.iter().copied().sum::<Fit>();
     let population_f = population as f64;
-    species_fitted
-        .into_iter()
-        .map(|(specie_repr, fit_adjusted)| {
-            (
-                specie_repr,
-                f64::round(population_f * fit_adjusted / fit_total) as usize,
-            )
-        })
+    fits_adjusted
+        .iter()
+        .map(|fit_adjusted| f64::round(population_f * (*fit_adjusted / fit_total).0) as usize)
         .collect()
 }
 
-fn population_allocated<
-    'a,
-    C: Connection + 'a,
-    G: Genome<C> + 'a,
-    T: Iterator<Item = &'a (Specie<C, G>, f64)>,
->(
-    species: T,
+/// Reproduce a group of species, allocating their populations based on their specie fitness
+/// relative to eachother. Enforces a min_fitness threshold for every specie member, and allows
+/// low-fitness species to naturally die off. Offspring are mutated with the `probabilities` for
+/// their specie's age, such as those of an [Annealing] policy, as configured by `reproduction`.
+///
+/// Every specie reproduces straight into a single buffer sized for the next generation, each
+/// into its own slab of it.
+pub fn population_reproduce<C: Connection, G: Genome<C>>(
+    species: &[(Specie<C, G>, Fit)],
+    population: usize,
+    inno_head: usize,
+    probabilities: impl Fn(usize) -> GenomeProbabilities,
+    reproduction: &Reproduction,
+    rng: &mut impl RngCore,
+) -> (Vec<G>, usize) {
+    let mut innogen = InnoGen::new(inno_head);
+    let (pop, _) = population_reproduce_slabs(
+        species,
+        population,
+        &mut innogen,
+        probabilities,
+        reproduction,
+        rng,
+    );
+    (pop, innogen.head)
+}
+
+/// The range of a population reproduced by each specie, along with that specie's index
+pub type Slabs = Vec<(usize, Range<usize>)>;
+
+/// Like [population_reproduce], but also returning the slab of offspring that each specie
+/// reproduced into, along with that specie's index in `species`. Species who reproduced nothing
+/// are left out. Innovations are registered with `innogen`, which may be kept to give anything
+/// else joining the same generation, such as [immigrants](crate::population::Immigration), the
+/// same ids for the same paths.
+pub fn population_reproduce_slabs<C: Connection, G: Genome<C>>(
+    species: &[(Specie<C, G>, Fit)],
     population: usize,
-) -> impl Iterator<Item = (Vec<(G, f64)>, usize)> {
+    innogen: &mut InnoGen,
+    probabilities: impl Fn(usize) -> GenomeProbabilities,
+    reproduction: &Reproduction,
+    rng: &mut impl RngCore,
+) -> (Vec<G>, Slabs) {
     let viable = species
-        .filter_map(|(specie, min_fitness)| {
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, (specie, min_fitness))| {
             let viable = specie
                 .members
                 .iter()
                 .filter(|&pair| (&pair.1 >= min_fitness))
-                .cloned()
                 .collect::<Vec<_>>();
-
-            // (!viable.is_empty()).then_some((&specie.repr, viable));
-            (!viable.is_empty()).then(|| Specie {
-                repr: specie.repr.clone(),
-                members: viable,
-            })
+            (!viable.is_empty()).then_some((viable, specie.age, idx))
         })
         .collect::<Vec<_>>();
 
-    let alloc = population_alloc(viable.iter(), population);
-
-    viable
-        .into_iter()
-        .filter_map(move |specie| alloc.get(&specie.repr).map(|pop| (specie.members, *pop)))
-}
-
-/// Reproduce a group of species, allocating their populations based on their specie fitness
-/// relative to eachother. Enforces a min_fitness threshold for every specie member, and allows
-/// low-fitness species to naturally die off.
-pub fn population_reproduce<C: Connection, G: Genome<C>>(
-    species: &[(Specie<C, G>, f64)],
-    population: usize,
-    inno_head: usize,
-    rng: &mut impl RngCore,
-) -> (Vec<G>, usize) {
-    // let species = population_viable(species.into_iter());
-    // let species_pop = population_alloc(species, population);
-    let mut innogen = InnoGen::new(inno_head);
-    (
-        population_allocated(species.iter(), population)
-            .flat_map(|(members, pop)| reproduce(members, pop, &mut innogen, rng).unwrap())
+    let alloc = population_alloc(
+        &viable
+            .iter()
+            .map(|(members, ..)| {
+                let l = members.len() as f64;
+                members
+                    .iter()
+                    .filter(|(_, fit)| *fit > Fit::MIN)
+                    .map(|(_, fit)| *fit / l)
+                    .sum()
+            })
             .collect::<Vec<_>>(),
-        innogen.head,
-    )
+        population,
+    );
+
+    let mut pop = Vec::with_capacity(alloc.iter().sum());
+    let mut slabs = Vec::with_capacity(viable.len());
+    let mut start = 0;
+    for ((members, age, idx), size) in viable.iter().zip(alloc) {
+        let slab = reproduce_into(
+            members,
+            size,
+            probabilities(*age),
+            reproduction,
+            innogen,
+            rng,
+            &mut pop,
+        )
+        .unwrap();
+        debug_assert_eq!(slab, start..start + size);
+        start += size;
+        if !slab.is_empty() {
+            slabs.push((*idx, slab));
+        }
+    }
+    (pop, slabs)
 }
 
 #[cfg(test)]
 mod test {
     use crate::{
-        genome::{Recurrent, WConnection},
+        genome::{Constraints, Recurrent, WConnection, WeightInit},
         population::population_init,
-        random::default_rng,
+        random::{default_rng, percent},
         test_t,
     };
 
@@ -256,6 +456,27 @@ mod test {
         assert_eq!(inno2.path((0, 1)), 3);
     }
 
+    #[test]
+    fn test_inno_gen_capacity() {
+        let mut inno = InnoGen::new(0).with_capacity(4);
+        for i in 0..4 {
+            assert_eq!(inno.path((i, i + 1)), i);
+        }
+        assert_eq!(inno.len(), 4);
+        assert_eq!(inno.evicted(), 0);
+
+        // touch the oldest paths so that they're recently used
+        assert_eq!(inno.path((0, 1)), 0);
+        assert_eq!(inno.path((1, 2)), 1);
+
+        assert_eq!(inno.path((9, 9)), 4);
+        assert_eq!(inno.evicted(), 2);
+        assert_eq!(inno.len(), 3);
+        assert_eq!(inno.path((0, 1)), 0);
+        assert_eq!(inno.path((1, 2)), 1);
+        assert_eq!(inno.path((2, 3)), 5);
+    }
+
     type BasicGenomeCtrnn = Recurrent<WConnection>;
 
     test_t!(specie_reproduce[T: BasicGenomeCtrnn]() {
@@ -270,6 +491,8 @@ mod test {
                     reproduce(
                         specie.members.clone(),
                         i,
+                        T::PROBABILITIES,
+                        &Reproduction::default(),
                         &mut InnoGen::new(inno_head),
                         &mut rng
                     )
@@ -279,4 +502,123 @@ mod test {
             }
         }
     });
+
+    test_t!(specie_reproduce_into[T: BasicGenomeCtrnn]() {
+        let mut rng = default_rng();
+        let (species, inno_head) = population_init::<WConnection, T>(2, 2, 4);
+        let members = species[0].members.iter().collect::<Vec<_>>();
+        let mut into = vec![species[0].members[0].0.clone(); 3];
+        let mut innogen = InnoGen::new(inno_head);
+        for (size, want) in [(5, 3..8), (0, 8..8), (1, 8..9)] {
+            let slab = reproduce_into(
+                &members,
+                size,
+                T::PROBABILITIES,
+                &Reproduction::default(),
+                &mut innogen,
+                &mut rng,
+                &mut into,
+            );
+            assert_eq!(slab.unwrap(), want);
+        }
+        assert_eq!(into.len(), 9);
+    });
+
+    test_t!(specie_reproduce_asexual[T: BasicGenomeCtrnn]() {
+        let mut rng = default_rng();
+        let (species, inno_head) = population_init::<WConnection, T>(2, 2, 4);
+        let members = species[0].members.iter().collect::<Vec<_>>();
+        let mut innogen = InnoGen::new(inno_head);
+        let mut into = Vec::new();
+        let grow = [percent(100), 0, 0, 0, 0];
+
+        let reproduction = Reproduction::default().with_asexual(3);
+        let slab = reproduce_asexual_into(
+            &members,
+            6,
+            grow,
+            &reproduction,
+            &mut innogen,
+            &mut rng,
+            &mut into,
+        );
+        assert_eq!(slab.unwrap(), 0..6);
+        // each copy gains a connection for every one of its mutations
+        let parent = members[0].0.connections().len();
+        assert_eq!(into[0].connections().len(), parent);
+        assert!(into[1..].iter().all(|child| child.connections().len() == parent + 3));
+
+        // reproducing asexually is a switch of the whole run, which every specie goes through
+        into.clear();
+        let slab = reproduce_into(
+            &members,
+            6,
+            grow,
+            &reproduction,
+            &mut innogen,
+            &mut rng,
+            &mut into,
+        );
+        assert_eq!(slab.unwrap(), 0..6);
+        assert!(into[1..].iter().all(|child| child.connections().len() == parent + 3));
+
+        let empty = reproduce_asexual_into::<WConnection, T>(
+            &[],
+            1,
+            grow,
+            &reproduction,
+            &mut innogen,
+            &mut rng,
+            &mut into,
+        );
+        assert!(empty.is_err());
+    });
+
+    test_t!(specie_reproduce_configured[T: BasicGenomeCtrnn]() {
+        let mut rng = default_rng();
+        let (species, inno_head) = population_init::<WConnection, T>(2, 2, 8);
+        let grow = [percent(50), percent(50), 0, 0, 0];
+        let reproduction = Reproduction::default().with_mutation(
+            MutationConfig::NONE
+                .with_weight_init(WeightInit::Constant(5.))
+                .with_weight_bounds(-1., 1.)
+                .with_constraints(Constraints::NONE.with_max_connections(6)),
+        );
+
+        let mut pop = species[0].members.clone();
+        for _ in 0..20 {
+            let offspring = reproduce(
+                pop,
+                8,
+                grow,
+                &reproduction,
+                &mut InnoGen::new(inno_head),
+                &mut rng,
+            )
+            .unwrap();
+            for child in offspring.iter() {
+                assert!(child.connections().len() <= 6);
+                assert!(child.connections().iter().all(|c| c.weight().abs() <= 1.));
+            }
+            pop = offspring.into_iter().map(|child| (child, Fit(1.))).collect();
+        }
+    });
+
+    #[test]
+    fn test_annealing() {
+        let annealing = Annealing {
+            young: [percent(40), percent(40), percent(20), 0, 0],
+            old: [0, 0, percent(100), 0, 0],
+            maturity: 10,
+        };
+
+        assert_eq!(annealing.probabilities(0), annealing.young);
+        assert_eq!(annealing.probabilities(10), annealing.old);
+        assert_eq!(annealing.probabilities(100), annealing.old);
+
+        let half = annealing.probabilities(5);
+        assert_eq!(half[0], half[1]);
+        assert_eq!(half[0], percent(20));
+        assert_eq!(half[2], percent(60));
+    }
 }
@@ -1,19 +1,34 @@
 //! Traits related to evaluation, fitting, and evolution of genomes for specific tasks.
 
 use crate::{
-    genome::Genome,
-    population::{speciate, Specie, SpecieRepr},
-    reproduce::population_reproduce,
-    Connection,
+    genome::{Genome, InnoGen},
+    optimize::Es,
+    population::{
+        population_merge, speciate_cached, Compatibility, DeltaCache, Immigration, Specie,
+        SpecieRepr,
+    },
+    random::{Counted, GenomeProbabilities, WyRng},
+    reproduce::{population_reproduce_slabs, Annealing, Reproduction, Slabs},
+    Connection, Fit,
+};
+use core::{
+    cell::Cell,
+    f64,
+    ops::{ControlFlow, Range},
 };
-use core::{f64, ops::ControlFlow};
 use rand::RngCore;
 #[cfg(feature = "parallel")]
 use rayon::{
-    iter::{IntoParallelIterator, ParallelIterator},
-    ThreadPoolBuilder,
+    iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator},
+    slice, ThreadPoolBuilder,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    time::{Duration, Instant},
 };
-use std::collections::HashMap;
 
 const NO_IMPROVEMENT_TRUNCATE: usize = 10;
 
@@ -21,28 +36,215 @@ const NO_IMPROVEMENT_TRUNCATE: usize = 10;
 pub struct Stats<'a, C: Connection, G: Genome<C>> {
     pub generation: usize,
     pub species: &'a [Specie<C, G>],
+    /// Approximate bytes held by every specie and their members
+    pub memory: usize,
+    /// Innovation ids given out while producing this generation, that is new structure found by
+    /// its mutations. Always 0 for the first generation.
+    pub innovations: usize,
+    /// Fitness evaluations spent over the run so far, including this generation's. Every genome
+    /// counts as one whether its fitness came from [eval](Scenario::eval), the rest of its
+    /// population, or some cache or surrogate inside of the scenario, as does every evaluation
+    /// made by [Scenario::es]. Comparing runs at equal evaluations is fair across population
+    /// sizes, where comparing them at equal generations isn't.
+    pub evaluations: usize,
+    /// Everything besides `species` that's needed to [replay](replay_generation) this
+    /// generation's reproduction, and with [replay_evaluation] its evaluation
+    pub replay: &'a Generation<C>,
+    /// The run's rng, for hooks that draw at random, such as a
+    /// [hall_of_fame_hook](crate::adversarial::hall_of_fame_hook), so that their draws are as
+    /// reproducible from the run's seed as the rest of it
+    pub rng: &'a mut dyn RngCore,
+}
+
+/// The gist of a generation's [Stats], small enough to keep for every generation of a run, such
+/// as to [plot](crate::plot) afterwards
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatsSnapshot {
+    pub generation: usize,
+    pub best: Fit,
+    pub mean: Fit,
+    /// Size of each specie
+    pub species: Vec<usize>,
+    /// See [Stats::innovations]
+    #[serde(default)]
+    pub innovations: usize,
+    /// See [Stats::evaluations]
+    #[serde(default)]
+    pub evaluations: usize,
+}
+
+/// A record of the state that a generation was reproduced with, such that passing it and that
+/// generation's species to [replay_generation] produces exactly the same offspring. It may be
+/// kept alongside those species as json, such as to replay a generation from a later process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Generation<C: Connection> {
+    pub index: usize,
+    /// Seed of the rng that this generation was reproduced with, drawn from the rng passed to
+    /// [evolve]
+    pub seed: u64,
+    pub inno_head: usize,
+    /// Target population of offspring
+    pub population: usize,
+    /// The best fitness that each specie has reached, and the generation in which it got there
+    pub scores: Vec<(SpecieRepr<C>, (Fit, usize))>,
+}
+
+impl<C: Connection> Generation<C> {
+    fn scores_by_repr(&self) -> HashMap<&SpecieRepr<C>, (Fit, usize)> {
+        self.scores
+            .iter()
+            .map(|(repr, score)| (repr, *score))
+            .collect()
+    }
 }
 
 impl<C: Connection, G: Genome<C>> Stats<'_, C, G> {
-    pub fn any_fitter_than(&self, target: f64) -> bool {
+    pub fn any_fitter_than(&self, target: impl Into<Fit>) -> bool {
+        let target = target.into();
         self.species
             .iter()
             .any(|Specie { members, .. }| members.iter().any(|(_, fitness)| *fitness > target))
     }
 
-    pub fn fittest(&self) -> Option<&(G, f64)> {
+    pub fn snapshot(&self) -> StatsSnapshot {
+        let members = self
+            .species
+            .iter()
+            .flat_map(|Specie { members, .. }| members.iter())
+            .map(|(_, fit)| *fit)
+            .collect::<Vec<_>>();
+        StatsSnapshot {
+            generation: self.generation,
+            best: members.iter().copied().max().unwrap_or(Fit::MIN),
+            mean: match members.len() {
+                0 => Fit::MIN,
+                len => members.into_iter().sum::<Fit>() / len as f64,
+            },
+            species: self
+                .species
+                .iter()
+                .map(|Specie { members, .. }| members.len())
+                .collect(),
+            innovations: self.innovations,
+            evaluations: self.evaluations,
+        }
+    }
+
+    pub fn fittest(&self) -> Option<&(G, Fit)> {
         self.species
             .iter()
             .flat_map(|Specie { members, .. }| members.iter())
-            .max_by(|(_, l), (_, r)| {
-                l.partial_cmp(r)
-                    .unwrap_or_else(|| panic!("cannot partial_cmp {l} and {r}"))
-            })
+            .max_by_key(|(_, fit)| *fit)
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<C: Connection + Sync, G: Genome<C> + Sync> Stats<'_, C, G> {
+    /// Iterate over every specie in parallel, so that expensive analyses inside of a hook don't
+    /// hold up evolution on a single thread. Runs on rayon's global pool.
+    pub fn par_species(&self) -> slice::Iter<'_, Specie<C, G>> {
+        self.species.par_iter()
+    }
+
+    /// Iterate over every member of every specie in parallel, see [par_species](Stats::par_species)
+    pub fn par_members(&self) -> impl ParallelIterator<Item = &(G, Fit)> {
+        self.par_species()
+            .flat_map(|Specie { members, .. }| members.par_iter())
+    }
+}
+
+/// Read-only access to the previous generation, for scenarios whose fitness depends on the rest of
+/// the population, see [Scenario::eval_with_population]. Empty while evaluating the first
+/// generation.
+pub struct PopulationView<'a, C: Connection, G: Genome<C>> {
+    pub species: &'a [Specie<C, G>],
+}
+
+impl<C: Connection, G: Genome<C>> PopulationView<'_, C, G> {
+    /// Every member of every specie, along with the fitness it was evaluated with
+    pub fn members(&self) -> impl Iterator<Item = &(G, Fit)> {
+        self.species
+            .iter()
+            .flat_map(|Specie { members, .. }| members.iter())
+    }
+
+    pub fn len(&self) -> usize {
+        self.species
+            .iter()
+            .map(|Specie { members, .. }| members.len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Evaluate `genome` alone as [evolve] does, by
+/// [eval_with_population](Scenario::eval_with_population) over `population` if `scenario`
+/// [sees_population](Scenario::sees_population), or else by [eval](Scenario::eval). Scenarios
+/// scoring [whole populations](Scenario::eval_population) must be given their population instead.
+pub fn eval_genome<
+    C: Connection,
+    G: Genome<C>,
+    A: Fn(f64) -> f64,
+    S: Scenario<C, G, A> + ?Sized,
+>(
+    scenario: &S,
+    genome: &G,
+    σ: &A,
+    population: &PopulationView<C, G>,
+) -> f64 {
+    if scenario.sees_population() {
+        scenario.eval_with_population(genome, σ, population)
+    } else {
+        scenario.eval(genome, σ)
     }
 }
 
 pub type Hook<C, G> = Box<dyn Fn(&mut Stats<'_, C, G>) -> ControlFlow<()>>;
 
+/// A hook halting evolution once `evaluations` [fitness evaluations](Stats::evaluations) have
+/// been spent. The generation that reaches the budget may overshoot it.
+pub fn max_evaluations<C: Connection, G: Genome<C>>(evaluations: usize) -> Hook<C, G> {
+    Box::new(move |stats| {
+        if stats.evaluations >= evaluations {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    })
+}
+
+/// A hook calling `on_stall` once structural search has effectively stopped, that is once
+/// `generations` generations in a row have each found at most `max` new
+/// [innovations](Stats::innovations). It's called again every generation for as long as the stall
+/// lasts, and may break to halt evolution.
+pub fn innovation_stall<C: Connection, G: Genome<C>>(
+    generations: usize,
+    max: usize,
+    on_stall: impl Fn(&Stats<'_, C, G>) -> ControlFlow<()> + 'static,
+) -> Hook<C, G> {
+    let stalled = Cell::new(0);
+    Box::new(move |stats| {
+        if stats.generation == 0 {
+            return ControlFlow::Continue(());
+        }
+
+        stalled.set(if stats.innovations <= max {
+            stalled.get() + 1
+        } else {
+            0
+        });
+        if stalled.get() >= generations {
+            on_stall(stats)
+        } else {
+            ControlFlow::Continue(())
+        }
+    })
+}
+
 /// Functions that hook into the evolution process, allowing observation and mutation.
 /// Each hook is called each generation with a [Stats] exposing the current population and
 /// generation number. Hooks are called the order that they're provided in `new`.
@@ -74,6 +276,204 @@ impl<C: Connection, G: Genome<C>> EvolutionHooks<C, G> {
 pub trait Scenario<C: Connection, G: Genome<C>, A: Fn(f64) -> f64> {
     fn io(&self) -> (usize, usize);
     fn eval(&self, genome: &G, σ: &A) -> f64;
+
+    /// Evaluate `genome` with read-only access to every genome of the previous generation and its
+    /// fitness, for fitness that depends on the rest of the population, such as when rewarding
+    /// rare behavior. Only called in place of [eval](Scenario::eval) by [evolve] when
+    /// [sees_population](Scenario::sees_population) is true, and by default is just that.
+    fn eval_with_population(&self, genome: &G, σ: &A, _population: &PopulationView<C, G>) -> f64 {
+        self.eval(genome, σ)
+    }
+
+    /// Evaluate every genome of a generation together, for fitness decided by how genomes fare
+    /// against one another, such as in a [Tournament](crate::adversarial::Tournament). Fitnesses
+    /// are in the same order as `genomes`. Anything drawn at random, such as pairings, should be
+    /// drawn from `rng`, which [evolve] passes its own rng as so that runs stay reproducible from
+    /// their seed. By default there is none, and each genome is evaluated on its own.
+    fn eval_population(&self, _genomes: &[G], _σ: &A, _rng: &mut dyn RngCore) -> Option<Vec<f64>> {
+        None
+    }
+
+    /// Whether [evolve] should evaluate with
+    /// [eval_with_population](Scenario::eval_with_population). When true, each generation is
+    /// held onto through the evaluation of the next, so two are in memory at once.
+    fn sees_population(&self) -> bool {
+        false
+    }
+
+    /// A policy by which offspring of older species are mutated differently from those of young
+    /// species. By default there is none, and [Genome::PROBABILITIES] are used regardless of age.
+    fn annealing(&self) -> Option<Annealing> {
+        None
+    }
+
+    /// How offspring are reproduced and mutated, such as within some bounds on weights or genome
+    /// size. By default, everything is as the genome and connection kinds have it.
+    fn reproduction(&self) -> Option<Reproduction> {
+        None
+    }
+
+    /// How genomes are measured against species when they're speciated, such as how far from a
+    /// specie's repr they may stray. By default, it's [Compatibility::DEFAULT].
+    fn compatibility(&self) -> Option<Compatibility> {
+        None
+    }
+
+    /// A cap on the approximate bytes held by the population. When a generation exceeds it, the
+    /// size of following generations is shrunk proportionally so that they fit beneath it, and
+    /// grows back toward the initial size once they fit again.
+    fn memory_limit(&self) -> Option<usize> {
+        None
+    }
+
+    /// An evolution strategy run over the weights of every specie's champion every so often,
+    /// replacing them if it finds something fitter. By default, weights are only ever mutated.
+    fn es(&self) -> Option<Es> {
+        None
+    }
+
+    /// Fresh genomes injected into every generation in place of some offspring. By default,
+    /// every genome is the offspring of the last generation. Immigrants aren't part of a
+    /// generation's [replay](replay_generation), which only reproduces its offspring.
+    fn immigration(&self) -> Option<Immigration> {
+        None
+    }
+}
+
+#[cfg(not(feature = "parallel"))]
+pub type DynScenario<C, G, A> = Box<dyn Scenario<C, G, A>>;
+#[cfg(feature = "parallel")]
+pub type DynScenario<C, G, A> = Box<dyn Scenario<C, G, A> + Sync>;
+
+/// How a [Battery] combines the fitnesses of its scenarios into one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregate {
+    /// Sum of every weighted fitness
+    WeightedSum,
+    /// The least weighted fitness, rewarding genomes for their worst task
+    Min,
+    /// The [rank_sum] of every weighted fitness across the whole population, as scored by
+    /// [eval_population](Scenario::eval_population). Genomes evaluated alone by
+    /// [eval](Scenario::eval) are scored by their weighted sum instead.
+    RankSum,
+}
+
+/// A battery of scenarios sharing the same io, who are evaluated together as a single scenario
+/// so that generalists may be evolved across every task. Each scenario carries a weight that
+/// its fitness is scaled by before being aggregated.
+pub struct Battery<C: Connection, G: Genome<C>, A: Fn(f64) -> f64> {
+    scenarios: Vec<(DynScenario<C, G, A>, f64)>,
+    aggregate: Aggregate,
+}
+
+impl<C: Connection, G: Genome<C>, A: Fn(f64) -> f64> Battery<C, G, A> {
+    /// Panics if `scenarios` is empty, or if the scenarios don't agree on their io.
+    pub fn new(scenarios: Vec<(DynScenario<C, G, A>, f64)>, aggregate: Aggregate) -> Self {
+        let io = scenarios.first().expect("battery has no scenarios").0.io();
+        if let Some((scenario, _)) = scenarios.iter().find(|(s, _)| s.io() != io) {
+            panic!("battery io mismatch: {io:?} and {:?}", scenario.io());
+        }
+
+        Self {
+            scenarios,
+            aggregate,
+        }
+    }
+
+    /// The weighted fitness of `genome` in each scenario, in the order they were provided
+    pub fn scores(&self, genome: &G, σ: &A) -> Vec<f64> {
+        self.scenarios
+            .iter()
+            .map(|(scenario, weight)| weight * scenario.eval(genome, σ))
+            .collect()
+    }
+}
+
+impl<C: Connection, G: Genome<C>, A: Fn(f64) -> f64> Scenario<C, G, A> for Battery<C, G, A> {
+    fn io(&self) -> (usize, usize) {
+        self.scenarios[0].0.io()
+    }
+
+    fn eval(&self, genome: &G, σ: &A) -> f64 {
+        let scores = self.scores(genome, σ).into_iter();
+        match self.aggregate {
+            Aggregate::WeightedSum | Aggregate::RankSum => scores.sum(),
+            Aggregate::Min => scores.fold(f64::INFINITY, f64::min),
+        }
+    }
+
+    fn eval_population(&self, genomes: &[G], σ: &A, _: &mut dyn RngCore) -> Option<Vec<f64>> {
+        (self.aggregate == Aggregate::RankSum).then(|| {
+            let scores = genomes
+                .iter()
+                .map(|genome| self.scores(genome, σ))
+                .collect::<Vec<_>>();
+            rank_sum(&scores)
+        })
+    }
+}
+
+/// Rank-sum aggregation over the [Battery::scores] of a whole population, for when relative
+/// standing matters more than the scale of each task's fitness. Every genome is ranked in each
+/// task by how many others it beats, and those ranks summed. Because it depends on the rest of the
+/// population, it can't be computed from within a single [Scenario::eval], and a [Battery] evolves
+/// with it by [Aggregate::RankSum].
+pub fn rank_sum(scores: &[Vec<f64>]) -> Vec<f64> {
+    scores
+        .iter()
+        .map(|genome| {
+            genome
+                .iter()
+                .enumerate()
+                .map(|(task, fit)| scores.iter().filter(|other| other[task] < *fit).count() as f64)
+                .sum()
+        })
+        .collect()
+}
+
+/// Where a run of [evolve_report] spent its time and resources, over every generation that it
+/// evolved
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunReport {
+    pub generations: usize,
+    /// Wall time of the whole run, which is about the sum of each phase below
+    pub wall: Duration,
+    /// Time spent evaluating genomes, including by [Scenario::es]
+    pub eval: Duration,
+    /// Time spent speciating evaluated genomes
+    pub speciate: Duration,
+    /// Time spent reproducing and mutating species into the next generation, including immigrants
+    pub reproduce: Duration,
+    /// Time spent in hooks, which is where snapshots, checkpoints, and anything else serialized
+    /// over the run happen
+    pub hooks: Duration,
+    /// The most approximate bytes held by any one generation, see [Stats::memory]
+    pub peak_memory: usize,
+    /// Draws from the run's rng and from the rng that each generation reproduces with. Any rng
+    /// held by the scenario itself isn't counted.
+    pub rng_draws: usize,
+    /// See [Stats::evaluations]
+    pub evaluations: usize,
+}
+
+impl Display for RunReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let share = |phase: Duration| {
+            100. * phase.as_secs_f64() / self.wall.as_secs_f64().max(f64::MIN_POSITIVE)
+        };
+        writeln!(f, "{} generations in {:?}", self.generations, self.wall)?;
+        for (name, phase) in [
+            ("eval", self.eval),
+            ("speciate", self.speciate),
+            ("reproduce", self.reproduce),
+            ("hooks", self.hooks),
+        ] {
+            writeln!(f, "  {name:<9} {phase:>12.3?} {:>5.1}%", share(phase))?;
+        }
+        writeln!(f, "peak memory {} bytes", self.peak_memory)?;
+        writeln!(f, "rng draws {}", self.rng_draws)?;
+        write!(f, "evaluations {}", self.evaluations)
+    }
 }
 
 /// Given a well-defined evolution scenario, evolve is the entrypoint into actually... evolving.
@@ -85,9 +485,10 @@ pub trait Scenario<C: Connection, G: Genome<C>, A: Fn(f64) -> f64> {
 /// per cpu on the host. This in turn requires our arguments ( excluding init, which is called
 /// exactly once ) to implement [Sync]
 pub fn evolve<
-    C: Connection,
+    #[cfg(not(feature = "parallel"))] C: Connection,
+    #[cfg(feature = "parallel")] C: Connection + Sync,
     #[cfg(not(feature = "parallel"))] G: Genome<C>,
-    #[cfg(feature = "parallel")] G: Genome<C> + Send,
+    #[cfg(feature = "parallel")] G: Genome<C> + Send + Sync,
     I: FnOnce((usize, usize)) -> (Vec<Specie<C, G>>, usize),
     #[cfg(not(feature = "parallel"))] A: Fn(f64) -> f64,
     #[cfg(feature = "parallel")] A: Fn(f64) -> f64 + Sync,
@@ -97,10 +498,37 @@ pub fn evolve<
     scenario: S,
     init: I,
     σ: A,
-    mut rng: impl RngCore,
+    rng: impl RngCore,
     hooks: EvolutionHooks<C, G>,
 ) -> (Vec<Specie<C, G>>, usize) {
-    let (mut pop_flat, mut inno_head) = {
+    let (species, inno_head, _) = evolve_report(scenario, init, σ, rng, hooks);
+    (species, inno_head)
+}
+
+/// [evolve], also returning a [RunReport] of where the run spent its time and resources
+pub fn evolve_report<
+    #[cfg(not(feature = "parallel"))] C: Connection,
+    #[cfg(feature = "parallel")] C: Connection + Sync,
+    #[cfg(not(feature = "parallel"))] G: Genome<C>,
+    #[cfg(feature = "parallel")] G: Genome<C> + Send + Sync,
+    I: FnOnce((usize, usize)) -> (Vec<Specie<C, G>>, usize),
+    #[cfg(not(feature = "parallel"))] A: Fn(f64) -> f64,
+    #[cfg(feature = "parallel")] A: Fn(f64) -> f64 + Sync,
+    #[cfg(not(feature = "parallel"))] S: Scenario<C, G, A>,
+    #[cfg(feature = "parallel")] S: Scenario<C, G, A> + Sync,
+>(
+    scenario: S,
+    init: I,
+    σ: A,
+    rng: impl RngCore,
+    hooks: EvolutionHooks<C, G>,
+) -> (Vec<Specie<C, G>>, usize, RunReport) {
+    let started = Instant::now();
+    let mut rng = Counted::new(rng);
+    let mut report = RunReport::default();
+    // species from init keep their reprs, so that any transplanted from other runs aren't
+    // re-speciated into the rest of the population
+    let (mut pop_flat, mut inno_head, mut scores) = {
         let (species, inno_head) = init(scenario.io());
         (
             species
@@ -108,64 +536,182 @@ pub fn evolve<
                 .flat_map(|Specie { members, .. }| members.iter().map(|(genome, _)| genome.clone()))
                 .collect::<Vec<_>>(),
             inno_head,
+            species
+                .iter()
+                .map(|Specie { repr, .. }| (repr.clone(), (Fit::MIN, 0)))
+                .collect::<HashMap<SpecieRepr<C>, _>>(),
         )
     };
 
     #[cfg(feature = "parallel")]
     let thread_pool = ThreadPoolBuilder::new().build().unwrap();
-    let population_lim = pop_flat.len();
+    let population = pop_flat.len();
+    // immigrants are initialized as the first of the initial population was
+    let template = scenario
+        .immigration()
+        .and_then(|_| pop_flat.first().cloned());
 
-    let mut scores: HashMap<SpecieRepr<C>, _> = HashMap::new();
+    let mut born: HashMap<SpecieRepr<C>, usize> = HashMap::new();
+    let mut innovations = 0;
+    let mut evaluations = 0;
+    // the specie that each slab of offspring was reproduced by, to hint speciation with
+    let mut parents: Vec<(SpecieRepr<C>, Range<usize>)> = Vec::new();
+    let mut previous = Vec::new();
+    // unchanged elites needn't be compared against every repr again each generation
+    let mut deltas = DeltaCache::new();
+    let reproduction = scenario.reproduction().unwrap_or_default();
+    let compatibility = scenario.compatibility().unwrap_or_default();
     let mut gen_idx = 0;
     loop {
-        let species = {
-            #[cfg(not(feature = "parallel"))]
-            let genomes = pop_flat.into_iter().map(|genome| {
-                let fitness = scenario.eval(&genome, &σ);
-                (genome, fitness)
-            });
-            #[cfg(feature = "parallel")]
-            let genomes = thread_pool.install(|| {
-                pop_flat
-                    .into_par_iter()
+        evaluations += pop_flat.len();
+        let reprs = scores.keys().cloned().collect::<Vec<_>>();
+        let hints = {
+            let position = reprs
+                .iter()
+                .enumerate()
+                .map(|(idx, repr)| (repr, idx))
+                .collect::<HashMap<_, _>>();
+            let mut hints = vec![None; pop_flat.len()];
+            for (repr, slab) in parents.iter() {
+                if let Some(idx) = position.get(repr) {
+                    hints[slab.clone()].fill(Some(*idx));
+                }
+            }
+            hints
+        };
+        let timer = Instant::now();
+        let genomes: Vec<(G, Fit)> = {
+            let view = PopulationView { species: &previous };
+            let eval = |genome: &G| Fit::scored(eval_genome(&scenario, genome, &σ, &view));
+            match scenario.eval_population(&pop_flat, &σ, &mut rng) {
+                Some(fits) => {
+                    assert_eq!(fits.len(), pop_flat.len(), "fitness count mismatch");
+                    pop_flat
+                        .into_iter()
+                        .zip(fits.into_iter().map(Fit::scored))
+                        .collect::<Vec<_>>()
+                }
+                #[cfg(not(feature = "parallel"))]
+                None => pop_flat
+                    .into_iter()
                     .map(|genome| {
-                        let fitness = scenario.eval(&genome, &σ);
+                        let fitness = eval(&genome);
                         (genome, fitness)
                     })
-                    .collect::<Vec<_>>()
-                    .into_iter()
-            });
-            let reprs = scores.keys().cloned();
+                    .collect(),
+                #[cfg(feature = "parallel")]
+                None => thread_pool.install(|| {
+                    pop_flat
+                        .into_par_iter()
+                        .map(|genome| {
+                            let fitness = eval(&genome);
+                            (genome, fitness)
+                        })
+                        .collect()
+                }),
+            }
+        };
+        report.eval += timer.elapsed();
+
+        let timer = Instant::now();
+        let mut species = {
+            let genomes = genomes
+                .into_iter()
+                .zip(hints)
+                .map(|((genome, fit), hint)| (genome, fit, hint));
 
             #[cfg(not(feature = "smol_bench"))]
-            let species = speciate(genomes, reprs);
+            let species = speciate_cached(genomes, reprs.into_iter(), &compatibility, &mut deltas);
             #[cfg(feature = "smol_bench")]
-            let species = speciate(
+            let species = speciate_cached(
                 genomes.collect::<Vec<_>>().into_iter(),
-                reprs.collect::<Vec<_>>().into_iter(),
+                reprs.into_iter(),
+                &compatibility,
+                &mut deltas,
             );
             species
         };
+        report.speciate += timer.elapsed();
 
-        if hooks
-            .fire(Stats {
-                generation: gen_idx,
-                species: &species,
+        born = species
+            .iter_mut()
+            .map(|specie| {
+                let specie_born = *born.get(&specie.repr).unwrap_or(&gen_idx);
+                specie.age = gen_idx - specie_born;
+                (specie.repr.clone(), specie_born)
             })
-            .is_break()
+            .collect();
+
+        let timer = Instant::now();
+        if let Some(es) = scenario
+            .es()
+            .filter(|es| gen_idx > 0 && gen_idx.is_multiple_of(es.every))
         {
-            break (species, inno_head);
+            for Specie { members, .. } in species.iter_mut() {
+                if let Some(champion) = members.iter_mut().max_by_key(|(_, fit)| *fit) {
+                    if !champion.0.weights().is_empty() {
+                        evaluations += es.evaluations();
+                    }
+                    let view = PopulationView { species: &previous };
+                    *champion =
+                        es.optimize(&champion.0, champion.1, &scenario, &σ, &view, &mut rng);
+                }
+            }
+        }
+
+        report.eval += timer.elapsed();
+
+        let memory = species.iter().map(Specie::memory).sum();
+        report.peak_memory = usize::max(report.peak_memory, memory);
+        // as many genomes as this generation's bytes per genome fit beneath the limit, so that the
+        // population grows back toward its configured size once genomes shrink or the limit lifts
+        let population_lim = match scenario.memory_limit() {
+            Some(limit) if memory > 0 => {
+                let size = species.iter().map(Specie::len).sum::<usize>();
+                (limit.saturating_mul(size) / memory).clamp(1, population)
+            }
+            _ => population,
+        };
+        // at least one offspring, so that the population doesn't drift away from its species
+        let immigration = scenario.immigration().map(|i| Immigration {
+            count: usize::min(i.count, population_lim - 1),
+            ..i
+        });
+
+        let generation = Generation {
+            index: gen_idx,
+            seed: rng.next_u64(),
+            inno_head,
+            population: population_lim - immigration.map_or(0, |i| i.count),
+            scores: scores.into_iter().collect(),
+        };
+        let timer = Instant::now();
+        let flow = hooks.fire(Stats {
+            generation: gen_idx,
+            species: &species,
+            memory,
+            innovations,
+            evaluations,
+            replay: &generation,
+            rng: &mut rng,
+        });
+        report.hooks += timer.elapsed();
+        if flow.is_break() {
+            report.generations = gen_idx + 1;
+            report.evaluations = evaluations;
+            report.rng_draws += rng.draws();
+            report.wall = started.elapsed();
+            break (species, inno_head, report);
         }
 
-        let scores_prev = scores;
+        let timer = Instant::now();
+
+        let past_scores = generation.scores_by_repr();
         scores = species
             .iter()
             .filter_map(|Specie { repr, members, .. }| {
-                let gen_max = members.iter().max_by(|(_, l), (_, r)| {
-                    l.partial_cmp(r)
-                        .unwrap_or_else(|| panic!("cannot partial_cmp {l} and {r}"))
-                });
-                let past_max = scores_prev.get(repr);
+                let gen_max = members.iter().max_by_key(|(_, fit)| *fit);
+                let past_max = past_scores.get(repr);
 
                 match (gen_max, past_max) {
                     (Some((_, gen_max)), Some((past_max, past_idx))) => {
@@ -181,36 +727,913 @@ pub fn evolve<
             })
             .collect();
 
-        let p_scored = species
+        if scenario.sees_population() {
+            previous = species.clone();
+        }
+        let head = inno_head;
+        let mut species_reprs = species
+            .iter()
+            .map(|specie| Some(specie.repr.clone()))
+            .collect::<Vec<_>>();
+        let (slabs, mut innogen);
+        let mut replay_rng = Counted::new(WyRng::seeded(generation.seed));
+        (pop_flat, slabs, innogen) = replay_slabs(
+            species,
+            &generation,
+            |age| {
+                scenario
+                    .annealing()
+                    .map_or(G::PROBABILITIES, |annealing| annealing.probabilities(age))
+            },
+            &reproduction,
+            &mut replay_rng,
+        );
+        report.rng_draws += replay_rng.draws();
+        parents = slabs
             .into_iter()
-            .map(|s| {
-                let (min_fit, gen_achieved) =
-                    *scores_prev.get(&s.repr).unwrap_or(&(f64::MIN, gen_idx));
-
-                if gen_achieved + NO_IMPROVEMENT_TRUNCATE <= gen_idx && s.members.len() > 2 {
-                    (
-                        Specie {
-                            repr: s.repr,
-                            members: {
-                                let mut trunc = s.members;
-                                trunc.sort_by(|(_, l), (_, r)| {
-                                    r.partial_cmp(l)
-                                        .unwrap_or_else(|| panic!("cannot partial_cmp {l} and {r}"))
-                                });
-                                trunc[..2].to_vec()
-                            },
-                        },
-                        f64::MIN,
-                    )
+            .filter_map(|(idx, slab)| Some((species_reprs[idx].take()?, slab)))
+            .collect();
+        if let (Some(immigration), Some(template)) = (immigration, template.as_ref()) {
+            pop_flat.extend(immigration.genomes(
+                template,
+                &reproduction.mutation::<C, G>(),
+                &mut innogen,
+                &mut rng,
+            ));
+        }
+        inno_head = innogen.head;
+        innovations = inno_head - head;
+        report.reproduce += timer.elapsed();
+        debug_assert!(!pop_flat.is_empty(), "nobody past {gen_idx}");
+        gen_idx += 1
+    }
+}
+
+/// How [evolve_hedged] spreads its bets before committing to a single run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hedge {
+    /// Seed of each short run
+    pub seeds: Vec<u64>,
+    /// Generations that each short run lasts for
+    pub generations: usize,
+    /// Species kept from each short run, the fittest by their best member
+    pub top: usize,
+}
+
+/// Evolve several short runs from the same initial population, one for each of `hedge`'s seeds,
+/// then [merge](population_merge) the fittest species of each into a single population and
+/// [evolve] it with `rng` and `hooks`. This hedges against a single run converging early on
+/// something poor, at the cost of the generations spent on short runs.
+///
+/// `init` is called once for each short run, and should give the same population each time.
+/// The merged population is about as large as that, with each kept specie truncated to its
+/// fittest share of it.
+pub fn evolve_hedged<
+    #[cfg(not(feature = "parallel"))] C: Connection,
+    #[cfg(feature = "parallel")] C: Connection + Sync,
+    #[cfg(not(feature = "parallel"))] G: Genome<C>,
+    #[cfg(feature = "parallel")] G: Genome<C> + Send + Sync,
+    I: Fn((usize, usize)) -> (Vec<Specie<C, G>>, usize),
+    #[cfg(not(feature = "parallel"))] A: Fn(f64) -> f64 + Clone,
+    #[cfg(feature = "parallel")] A: Fn(f64) -> f64 + Clone + Sync,
+    #[cfg(not(feature = "parallel"))] S: Scenario<C, G, A> + Clone,
+    #[cfg(feature = "parallel")] S: Scenario<C, G, A> + Clone + Sync,
+>(
+    scenario: S,
+    init: I,
+    σ: A,
+    hedge: &Hedge,
+    rng: impl RngCore,
+    hooks: EvolutionHooks<C, G>,
+) -> (Vec<Specie<C, G>>, usize) {
+    assert!(!hedge.seeds.is_empty(), "no seeds to hedge over");
+    let (population, base) = {
+        let (species, base) = init(scenario.io());
+        (species.iter().map(Specie::len).sum::<usize>(), base)
+    };
+
+    let generations = hedge.generations;
+    let mut runs = hedge
+        .seeds
+        .iter()
+        .map(|seed| {
+            let stop: Hook<C, G> = Box::new(move |stats| {
+                if stats.generation + 1 >= generations {
+                    ControlFlow::Break(())
                 } else {
-                    (s, min_fit)
+                    ControlFlow::Continue(())
                 }
+            });
+            let (mut species, _) = evolve(
+                scenario.clone(),
+                |io| {
+                    let (species, inno_head) = init(io);
+                    assert_eq!(inno_head, base, "init gave a different population");
+                    (species, inno_head)
+                },
+                σ.clone(),
+                WyRng::seeded(*seed),
+                EvolutionHooks::new(vec![stop]),
+            );
+            species.sort_by_key(|specie| Reverse(specie.members.iter().map(|(_, fit)| *fit).max()));
+            species.truncate(hedge.top);
+            species
+        })
+        .collect::<Vec<_>>();
+
+    let share = population.div_ceil(runs.iter().map(Vec::len).sum::<usize>().max(1));
+    for specie in runs.iter_mut().flatten() {
+        specie.members.sort_by_key(|(_, fit)| Reverse(*fit));
+        specie.members.truncate(share);
+    }
+
+    let merged = population_merge(runs, base);
+    evolve(scenario, |_| merged, σ, rng, hooks)
+}
+
+/// Reproduce a generation's `species` into the genomes of the next one, mutating offspring with
+/// the `probabilities` for their specie's age, as configured by `reproduction`. Species who
+/// haven't improved on their best in some time are truncated down to their 2 fittest members.
+/// [evolve] reproduces every generation with this, so a generation may be replayed exactly given
+/// its [Stats::species] and [Stats::replay].
+pub fn replay_generation<C: Connection, G: Genome<C>>(
+    species: Vec<Specie<C, G>>,
+    generation: &Generation<C>,
+    probabilities: impl Fn(usize) -> GenomeProbabilities,
+    reproduction: &Reproduction,
+) -> (Vec<G>, usize) {
+    let (pop, _, innogen) = replay_slabs(
+        species,
+        generation,
+        probabilities,
+        reproduction,
+        &mut WyRng::seeded(generation.seed),
+    );
+    (pop, innogen.head)
+}
+
+/// [replay_generation] as [evolve] would have for `scenario`, then evaluate the offspring as the
+/// next generation was evaluated, giving each alongside its fitness. Fitnesses are the same as
+/// that generation's so long as `scenario` evaluates deterministically, though they may differ
+/// when it [evaluates the population](Scenario::eval_population) together, which draws from
+/// `rng`, and that generation had immigrants, who aren't replayed.
+pub fn replay_evaluation<C: Connection, G: Genome<C>, A: Fn(f64) -> f64>(
+    scenario: &impl Scenario<C, G, A>,
+    species: Vec<Specie<C, G>>,
+    generation: &Generation<C>,
+    σ: &A,
+    rng: &mut impl RngCore,
+) -> Vec<(G, Fit)> {
+    let previous = if scenario.sees_population() {
+        species.clone()
+    } else {
+        Vec::new()
+    };
+    let (offspring, _) = replay_generation(
+        species,
+        generation,
+        |age| {
+            scenario
+                .annealing()
+                .map_or(G::PROBABILITIES, |annealing| annealing.probabilities(age))
+        },
+        &scenario.reproduction().unwrap_or_default(),
+    );
+
+    if let Some(fits) = scenario.eval_population(&offspring, σ, rng) {
+        assert_eq!(fits.len(), offspring.len(), "fitness count mismatch");
+        return offspring
+            .into_iter()
+            .zip(fits.into_iter().map(Fit::scored))
+            .collect();
+    }
+
+    let view = PopulationView { species: &previous };
+    offspring
+        .into_iter()
+        .map(|genome| {
+            let fitness = eval_genome(scenario, &genome, σ, &view);
+            (genome, Fit::scored(fitness))
+        })
+        .collect()
+}
+
+/// [replay_generation], also returning the slab of offspring reproduced by each specie, by its
+/// index in `species`, and the InnoGen that they were reproduced with. `rng` must be seeded with
+/// the generation's seed for the replay to be exact
+fn replay_slabs<C: Connection, G: Genome<C>>(
+    species: Vec<Specie<C, G>>,
+    generation: &Generation<C>,
+    probabilities: impl Fn(usize) -> GenomeProbabilities,
+    reproduction: &Reproduction,
+    rng: &mut impl RngCore,
+) -> (Vec<G>, Slabs, InnoGen) {
+    let scores = generation.scores_by_repr();
+    let p_scored = species
+        .into_iter()
+        .map(|s| {
+            let (min_fit, gen_achieved) =
+                *scores.get(&s.repr).unwrap_or(&(Fit::MIN, generation.index));
+
+            if gen_achieved + NO_IMPROVEMENT_TRUNCATE <= generation.index && s.members.len() > 2 {
+                (
+                    Specie {
+                        repr: s.repr,
+                        members: {
+                            let mut trunc = s.members;
+                            trunc.sort_by_key(|(_, fit)| Reverse(*fit));
+                            trunc[..2].to_vec()
+                        },
+                        age: s.age,
+                    },
+                    Fit::MIN,
+                )
+            } else {
+                (s, min_fit)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut innogen = InnoGen::new(generation.inno_head);
+    let (pop, slabs) = population_reproduce_slabs(
+        &p_scored,
+        generation.population,
+        &mut innogen,
+        probabilities,
+        reproduction,
+        rng,
+    );
+    (pop, slabs, innogen)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        activate, assert_f64_approx,
+        genome::{Constraints, MutationConfig, Recurrent, WConnection, WeightInit},
+        population::population_init,
+        random::{default_rng, percent},
+    };
+    use std::{cell::RefCell, rc::Rc};
+
+    type C = WConnection;
+    type G = Recurrent<C>;
+    type A = fn(f64) -> f64;
+
+    #[derive(Clone)]
+    struct Fixed(f64);
+
+    impl Scenario<C, G, A> for Fixed {
+        fn io(&self) -> (usize, usize) {
+            (1, 1)
+        }
+
+        fn eval(&self, _: &G, _: &A) -> f64 {
+            self.0
+        }
+    }
+
+    fn battery(aggregate: Aggregate) -> Battery<C, G, A> {
+        Battery::new(
+            vec![(Box::new(Fixed(2.)), 1.), (Box::new(Fixed(-1.)), 3.)],
+            aggregate,
+        )
+    }
+
+    #[test]
+    fn test_battery_aggregate() {
+        let (genome, _) = G::new(1, 1);
+        let σ: A = |x| x;
+        assert_f64_approx!(battery(Aggregate::WeightedSum).eval(&genome, &σ), -1.);
+        assert_f64_approx!(battery(Aggregate::Min).eval(&genome, &σ), -3.);
+    }
+
+    #[test]
+    fn test_battery_rank_sum() {
+        let mut inno = InnoGen::new(0);
+        let genomes = (0..3)
+            .map(|connections| {
+                let (mut genome, _) = G::new(2, 1);
+                for from in 0..connections {
+                    genome.push_connection(C::new(from, 2, &mut inno));
+                }
+                genome
             })
             .collect::<Vec<_>>();
+        let σ: A = |x| x;
 
-        (pop_flat, inno_head) =
-            population_reproduce(&p_scored, population_lim, inno_head, &mut rng);
-        debug_assert!(!pop_flat.is_empty(), "nobody past {gen_idx}");
-        gen_idx += 1
+        // both tasks agree, so each genome beats those with fewer connections twice
+        let scenarios: Vec<(DynScenario<C, G, A>, f64)> =
+            vec![(Box::new(Immigrants), 1.), (Box::new(Capped), 1.)];
+        let ranked = Battery::new(scenarios, Aggregate::RankSum);
+        let fits = ranked.eval_population(&genomes, &σ, &mut default_rng());
+        assert_eq!(fits, Some(vec![0., 2., 4.]));
+        assert_f64_approx!(ranked.eval(&genomes[0], &σ), 6.);
+        let summed = battery(Aggregate::WeightedSum);
+        assert!(summed
+            .eval_population(&genomes, &σ, &mut default_rng())
+            .is_none());
+    }
+
+    #[test]
+    fn test_rank_sum() {
+        let ranked = rank_sum(&[vec![1., 9.], vec![2., 3.], vec![3., 1.]]);
+        assert_eq!(ranked, vec![2., 2., 2.]);
+
+        let ranked = rank_sum(&[vec![1., 1.], vec![2., 2.], vec![3., 3.]]);
+        assert_eq!(ranked, vec![0., 2., 4.]);
+        assert!(rank_sum(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_replay_generation() {
+        let captured = Rc::new(RefCell::new(Vec::new()));
+        let hook_captured = captured.clone();
+        let hook: Hook<C, G> = Box::new(move |stats| {
+            hook_captured
+                .borrow_mut()
+                .push((stats.species.to_vec(), stats.replay.clone()));
+            if stats.generation == 3 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        evolve(
+            Fixed(1.),
+            |(sensory, action)| population_init::<C, G>(sensory, action, 16),
+            activate::relu as A,
+            default_rng(),
+            EvolutionHooks::new(vec![hook]),
+        );
+
+        let captured = captured.borrow();
+        // serde_json may parse a float back an ulp away, which would change the weights that
+        // offspring inherit, so only the generation is round-tripped
+        let species = captured[2].0.clone();
+        let generation =
+            serde_json::from_str::<Generation<C>>(&serde_json::to_string(&captured[2].1).unwrap())
+                .unwrap();
+        let (replayed, _) = replay_generation(
+            species.clone(),
+            &generation,
+            |_| G::PROBABILITIES,
+            &Reproduction::default(),
+        );
+
+        let mut want = captured[3]
+            .0
+            .iter()
+            .flat_map(|specie| specie.members.iter())
+            .map(|(genome, fit)| (genome.to_string().unwrap(), *fit))
+            .collect::<Vec<_>>();
+        let mut have = replayed
+            .iter()
+            .map(|genome| genome.to_string().unwrap())
+            .collect::<Vec<_>>();
+        want.sort_by(|l, r| l.0.cmp(&r.0));
+        have.sort();
+        assert!(want.iter().map(|(genome, _)| genome).eq(have.iter()));
+
+        let mut evaluated = replay_evaluation(
+            &Fixed(1.),
+            species,
+            &generation,
+            &(activate::relu as A),
+            &mut default_rng(),
+        )
+        .into_iter()
+        .map(|(genome, fit)| (genome.to_string().unwrap(), fit))
+        .collect::<Vec<_>>();
+        evaluated.sort_by(|l, r| l.0.cmp(&r.0));
+        assert_eq!(want, evaluated);
+    }
+
+    struct Immigrants;
+
+    impl Scenario<C, G, A> for Immigrants {
+        fn io(&self) -> (usize, usize) {
+            (2, 1)
+        }
+
+        fn eval(&self, genome: &G, _: &A) -> f64 {
+            1. + genome.connections().len() as f64
+        }
+
+        fn immigration(&self) -> Option<Immigration> {
+            Some(Immigration {
+                count: 4,
+                connections: 2,
+            })
+        }
+    }
+
+    #[test]
+    fn test_immigration() {
+        let captured = Rc::new(RefCell::new(Vec::new()));
+        let hook_captured = captured.clone();
+        let hook: Hook<C, G> = Box::new(move |stats| {
+            hook_captured.borrow_mut().push((
+                stats.species.iter().map(Specie::len).sum::<usize>(),
+                stats.replay.population,
+            ));
+            if stats.generation == 2 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        evolve(
+            Immigrants,
+            |(sensory, action)| population_init::<C, G>(sensory, action, 16),
+            activate::relu as A,
+            default_rng(),
+            EvolutionHooks::new(vec![hook]),
+        );
+
+        // every generation is about as large as the first, 4 of which are immigrants. Species
+        // allocations are rounded, so offspring may be off by one for each specie
+        for (len, reproduced) in captured.borrow().iter() {
+            assert!((12..=20).contains(len), "{len}");
+            assert_eq!(*reproduced, 12);
+        }
+    }
+
+    /// Fitter the larger the previous generation was
+    struct Crowd;
+
+    impl Scenario<C, G, A> for Crowd {
+        fn io(&self) -> (usize, usize) {
+            (2, 2)
+        }
+
+        fn eval(&self, _: &G, _: &A) -> f64 {
+            unreachable!("evaluated without the population")
+        }
+
+        fn eval_with_population(&self, _: &G, _: &A, population: &PopulationView<C, G>) -> f64 {
+            1. + population.len() as f64
+        }
+
+        fn sees_population(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_eval_with_population() {
+        let captured = Rc::new(RefCell::new(Vec::new()));
+        let hook_captured = captured.clone();
+        let hook: Hook<C, G> = Box::new(move |stats| {
+            let size = stats.species.iter().map(|s| s.members.len()).sum::<usize>();
+            let fit = stats.fittest().unwrap().1;
+            hook_captured.borrow_mut().push((size, fit));
+            if stats.generation == 2 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        evolve(
+            Crowd,
+            |(sensory, action)| population_init::<C, G>(sensory, action, 16),
+            activate::relu as A,
+            default_rng(),
+            EvolutionHooks::new(vec![hook]),
+        );
+
+        let captured = captured.borrow();
+        assert_eq!(captured[0].1, Fit(1.));
+        for window in captured.windows(2) {
+            assert_eq!(window[1].1, Fit(1. + window[0].0 as f64));
+        }
+    }
+
+    #[test]
+    fn test_evolve_hedged() {
+        let captured = Rc::new(RefCell::new(Vec::new()));
+        let hook_captured = captured.clone();
+        let merged = Rc::new(RefCell::new(Vec::new()));
+        let hook_merged = merged.clone();
+        let hook: Hook<C, G> = Box::new(move |stats| {
+            hook_captured
+                .borrow_mut()
+                .push(stats.species.iter().map(Specie::len).sum::<usize>());
+            if stats.generation == 0 {
+                hook_merged.borrow_mut().extend(
+                    stats
+                        .species
+                        .iter()
+                        .flat_map(|specie| specie.members.iter().map(|(genome, _)| genome.clone())),
+                );
+            }
+            if stats.generation == 1 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        let hedge = Hedge {
+            seeds: vec![1, 2, 3],
+            generations: 3,
+            top: 1,
+        };
+        let (species, inno_head) = evolve_hedged(
+            Fixed(1.),
+            |(sensory, action)| population_init::<C, G>(sensory, action, 8),
+            activate::relu as A,
+            &hedge,
+            default_rng(),
+            EvolutionHooks::new(vec![hook]),
+        );
+
+        let captured = captured.borrow();
+        assert_eq!(captured.len(), 2);
+        // 3 species, each of at most its share of 8
+        assert!((1..=9).contains(&captured[0]), "{}", captured[0]);
+        assert!(!species.is_empty());
+        assert!(inno_head >= population_init::<C, G>(1, 1, 1).1);
+
+        // every run's ids were renumbered by path, so that one id means one path throughout
+        let merged = merged.borrow();
+        assert_eq!(merged.len(), captured[0]);
+        let mut paths = HashMap::new();
+        for genome in merged.iter() {
+            assert!(genome.connections().is_sorted_by_key(|c| c.inno()));
+            for c in genome.connections() {
+                assert!(c.inno() < inno_head);
+                assert_eq!(*paths.entry(c.inno()).or_insert(c.path()), c.path());
+            }
+        }
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut species = population_init::<C, G>(2, 2, 4).0;
+        for (idx, (_, fit)) in species[0].members.iter_mut().enumerate() {
+            *fit = Fit(idx as f64);
+        }
+        let replay = Generation {
+            index: 3,
+            seed: 0,
+            inno_head: 0,
+            population: 4,
+            scores: Vec::new(),
+        };
+        let stats = Stats {
+            generation: 3,
+            species: &species,
+            memory: 0,
+            innovations: 2,
+            evaluations: 4,
+            replay: &replay,
+            rng: &mut default_rng(),
+        };
+
+        assert_eq!(
+            stats.snapshot(),
+            StatsSnapshot {
+                generation: 3,
+                best: Fit(3.),
+                mean: Fit(1.5),
+                species: vec![4],
+                innovations: 2,
+                evaluations: 4,
+            }
+        );
+    }
+
+    struct Diverging;
+
+    impl Scenario<C, G, A> for Diverging {
+        fn io(&self) -> (usize, usize) {
+            (2, 1)
+        }
+
+        // genomes with an odd count of connections diverge
+        fn eval(&self, genome: &G, _: &A) -> f64 {
+            match genome.connections().len() {
+                odd if odd % 2 == 1 => f64::NAN,
+                even => 1. + even as f64,
+            }
+        }
+    }
+
+    #[test]
+    fn test_nan_fitness() {
+        let diverged = Rc::new(RefCell::new(0));
+        let hook_diverged = diverged.clone();
+        let hook: Hook<C, G> = Box::new(move |stats| {
+            for specie in stats.species {
+                let (champion, fit) = specie.members.iter().max_by_key(|(_, fit)| *fit).unwrap();
+                assert!(fit.0.is_finite());
+                assert_eq!(champion.connections().len() % 2, 0);
+                assert!(!specie.fit_adjusted().0.is_nan());
+            }
+            *hook_diverged.borrow_mut() += stats
+                .species
+                .iter()
+                .flat_map(|specie| specie.members.iter())
+                .filter(|(_, fit)| *fit == Fit::MIN)
+                .count();
+            ControlFlow::Continue(())
+        });
+        evolve(
+            Diverging,
+            |(sensory, action)| population_init::<C, G>(sensory, action, 32),
+            activate::relu as A,
+            default_rng(),
+            EvolutionHooks::new(vec![hook, max_evaluations(320)]),
+        );
+        assert!(*diverged.borrow() > 0);
+    }
+
+    struct Capped;
+
+    impl Scenario<C, G, A> for Capped {
+        fn io(&self) -> (usize, usize) {
+            (2, 1)
+        }
+
+        // growth is always rewarded, so only the cap holds it back
+        fn eval(&self, genome: &G, _: &A) -> f64 {
+            1. + genome.connections().len() as f64 + genome.nodes().len() as f64
+        }
+
+        fn reproduction(&self) -> Option<Reproduction> {
+            let constraints = Constraints::NONE.with_max_connections(6).with_max_nodes(5);
+            Some(
+                Reproduction::default().with_mutation(
+                    MutationConfig::NONE
+                        .with_constraints(constraints)
+                        .with_weight_bounds(-0.5, 0.5),
+                ),
+            )
+        }
+    }
+
+    #[test]
+    fn test_evolve_reproduction() {
+        let hook: Hook<C, G> = Box::new(move |stats| {
+            for (genome, _) in stats
+                .species
+                .iter()
+                .flat_map(|specie| specie.members.iter())
+            {
+                assert!(genome.connections().len() <= 6);
+                assert!(genome.nodes().len() <= 5);
+                assert!(genome.connections().iter().all(|c| c.weight.abs() <= 0.5));
+            }
+            ControlFlow::Continue(())
+        });
+        evolve(
+            Capped,
+            |(sensory, action)| population_init::<C, G>(sensory, action, 16),
+            activate::relu as A,
+            default_rng(),
+            EvolutionHooks::new(vec![hook, max_evaluations(320)]),
+        );
+    }
+
+    // starved of memory for its first generations, and unbounded after
+    struct Starved(std::sync::atomic::AtomicUsize);
+
+    impl Scenario<C, G, A> for Starved {
+        fn io(&self) -> (usize, usize) {
+            (1, 1)
+        }
+
+        fn eval(&self, _: &G, _: &A) -> f64 {
+            1.
+        }
+
+        fn memory_limit(&self) -> Option<usize> {
+            let calls = self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            (calls < 2).then_some(1)
+        }
+    }
+
+    #[test]
+    fn test_memory_limit_recovers() {
+        let captured = Rc::new(RefCell::new(Vec::new()));
+        let hook_captured = captured.clone();
+        let hook: Hook<C, G> = Box::new(move |stats| {
+            hook_captured.borrow_mut().push(stats.replay.population);
+            if stats.generation == 3 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        evolve(
+            Starved(Default::default()),
+            |(sensory, action)| population_init::<C, G>(sensory, action, 16),
+            activate::relu as A,
+            default_rng(),
+            EvolutionHooks::new(vec![hook]),
+        );
+
+        assert_eq!(*captured.borrow(), vec![1, 1, 16, 16]);
+    }
+
+    struct Grow;
+
+    impl Scenario<C, G, A> for Grow {
+        fn io(&self) -> (usize, usize) {
+            (3, 2)
+        }
+
+        fn eval(&self, genome: &G, _: &A) -> f64 {
+            1. + genome.connections().len() as f64
+        }
+
+        // only ever new connections, so that every weight is as it was drawn
+        fn annealing(&self) -> Option<Annealing> {
+            let grow = [percent(100), 0, 0, 0, 0];
+            Some(Annealing {
+                young: grow,
+                old: grow,
+                maturity: 1,
+            })
+        }
+
+        fn reproduction(&self) -> Option<Reproduction> {
+            let init = WeightInit::Constant(0.25);
+            Some(Reproduction::default().with_mutation(MutationConfig::NONE.with_weight_init(init)))
+        }
+    }
+
+    #[test]
+    fn test_evolve_weight_init() {
+        let grown = Rc::new(RefCell::new(0));
+        let hook_grown = grown.clone();
+        let hook: Hook<C, G> = Box::new(move |stats| {
+            for (genome, _) in stats
+                .species
+                .iter()
+                .flat_map(|specie| specie.members.iter())
+            {
+                assert!(genome.connections().iter().all(|c| c.weight == 0.25));
+                *hook_grown.borrow_mut() += genome.connections().len();
+            }
+            ControlFlow::Continue(())
+        });
+        evolve(
+            Grow,
+            |(sensory, action)| population_init::<C, G>(sensory, action, 16),
+            activate::relu as A,
+            default_rng(),
+            EvolutionHooks::new(vec![hook, max_evaluations(64)]),
+        );
+        assert!(*grown.borrow() > 0);
+    }
+
+    #[test]
+    fn test_max_evaluations() {
+        let captured = Rc::new(RefCell::new(Vec::new()));
+        let hook_captured = captured.clone();
+        let hook: Hook<C, G> = Box::new(move |stats| {
+            hook_captured.borrow_mut().push(stats.evaluations);
+            ControlFlow::Continue(())
+        });
+        evolve(
+            Fixed(1.),
+            |(sensory, action)| population_init::<C, G>(sensory, action, 16),
+            activate::relu as A,
+            default_rng(),
+            EvolutionHooks::new(vec![hook, max_evaluations(60)]),
+        );
+
+        // a population of about 16 spends about 16 evaluations every generation
+        let captured = captured.borrow();
+        assert_eq!(captured[0], 16);
+        assert!(captured.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(*captured.last().unwrap() >= 60);
+        assert!(captured[captured.len() - 2] < 60);
+    }
+
+    #[test]
+    fn test_evolve_report() {
+        let captured = Rc::new(RefCell::new(Vec::new()));
+        let hook_captured = captured.clone();
+        let hook: Hook<C, G> = Box::new(move |stats| {
+            hook_captured
+                .borrow_mut()
+                .push((stats.memory, stats.evaluations));
+            if stats.generation == 4 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        let (_, _, report) = evolve_report(
+            Fixed(1.),
+            |(sensory, action)| population_init::<C, G>(sensory, action, 16),
+            activate::relu as A,
+            default_rng(),
+            EvolutionHooks::new(vec![hook]),
+        );
+
+        let captured = captured.borrow();
+        assert_eq!(report.generations, 5);
+        assert_eq!(report.evaluations, captured.last().unwrap().1);
+        assert_eq!(
+            report.peak_memory,
+            captured.iter().map(|(memory, _)| *memory).max().unwrap()
+        );
+        assert!(report.rng_draws > 0);
+        assert!(report.eval + report.speciate + report.reproduce + report.hooks <= report.wall);
+        assert!(report.to_string().starts_with("5 generations"));
+    }
+
+    #[test]
+    fn test_innovations() {
+        let captured = Rc::new(RefCell::new(Vec::new()));
+        let hook_captured = captured.clone();
+        let hook: Hook<C, G> = Box::new(move |stats| {
+            hook_captured
+                .borrow_mut()
+                .push((stats.innovations, stats.replay.inno_head));
+            if stats.generation == 4 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        evolve(
+            Fixed(1.),
+            |(sensory, action)| population_init::<C, G>(sensory, action, 16),
+            activate::relu as A,
+            default_rng(),
+            EvolutionHooks::new(vec![hook]),
+        );
+
+        let captured = captured.borrow();
+        assert_eq!(captured[0].0, 0);
+        for pair in captured.windows(2) {
+            assert_eq!(pair[1].0, pair[1].1 - pair[0].1);
+        }
+
+        let species = population_init::<C, G>(2, 2, 4).0;
+        let replay = Generation {
+            index: 0,
+            seed: 0,
+            inno_head: 0,
+            population: 4,
+            scores: Vec::new(),
+        };
+        let flagged = Rc::new(Cell::new(Vec::new()));
+        let hook_flagged = flagged.clone();
+        let stall = innovation_stall::<C, G>(2, 1, move |stats| {
+            let mut flagged = hook_flagged.take();
+            flagged.push(stats.generation);
+            hook_flagged.set(flagged);
+            ControlFlow::Continue(())
+        });
+        for (generation, innovations) in [0, 1, 0, 5, 1, 0, 0].into_iter().enumerate() {
+            let mut stats = Stats {
+                generation,
+                species: &species,
+                memory: 0,
+                innovations,
+                evaluations: 0,
+                replay: &replay,
+                rng: &mut default_rng(),
+            };
+            assert!(stall(&mut stats).is_continue());
+        }
+        assert_eq!(flagged.take(), vec![2, 5, 6]);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_par_members() {
+        let species = population_init::<C, G>(2, 2, 8).0;
+        let replay = Generation {
+            index: 0,
+            seed: 0,
+            inno_head: 0,
+            population: 8,
+            scores: Vec::new(),
+        };
+        let stats = Stats {
+            generation: 0,
+            species: &species,
+            memory: 0,
+            innovations: 0,
+            evaluations: 8,
+            replay: &replay,
+            rng: &mut default_rng(),
+        };
+
+        assert_eq!(stats.par_species().count(), 1);
+        assert_eq!(
+            stats
+                .par_members()
+                .map(|(genome, _)| genome.connections().len())
+                .sum::<usize>(),
+            species[0].members[0].0.connections().len() * 8
+        );
     }
 }
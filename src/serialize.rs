@@ -1,8 +1,15 @@
 //! Helpers for de/serializing NeuroEvoluiton components
 
-use crate::{genome::NodeKind, Connection};
+use crate::{genome::NodeKind, Connection, Genome};
+use core::error::Error;
 use rulinalg::matrix::Matrix;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// JSON schema of a [Recurrent](crate::genome::Recurrent) genome of
+/// [WConnection](crate::genome::WConnection)s, as saved by [to_file](crate::Genome::to_file).
+/// Also published as `schema/genome.schema.json`.
+pub const GENOME_SCHEMA: &str = include_str!("../schema/genome.schema.json");
 
 pub fn serialize_matrix<S: Serializer>(
     matrix: &Matrix<f64>,
@@ -49,3 +56,297 @@ pub fn deserialize_connections<'de, C: Connection, D: Deserializer<'de>>(
 ) -> Result<Vec<C>, D::Error> {
     Vec::<C>::deserialize(deserializer)
 }
+
+/// Rewrite a genome saved in the older `CTRGenome` layout into the current one, returning whether
+/// it was. That layout gave its bias node a value of its own, as `{"Bias": b}`, where the current
+/// one has a `"Static"` node always of 1, so connections from it are scaled by `b` instead.
+/// Genomes already in the current layout are left as they are.
+pub fn upgrade_legacy(genome: &mut Value) -> bool {
+    let Some(nodes) = genome.get_mut("nodes").and_then(Value::as_array_mut) else {
+        return false;
+    };
+
+    let mut biases = Vec::new();
+    for (idx, node) in nodes.iter_mut().enumerate() {
+        if let Some(bias) = node.get("Bias").and_then(Value::as_f64) {
+            biases.push((idx, bias));
+            *node = Value::from("Static");
+        }
+    }
+
+    let connections = genome
+        .get_mut("connections")
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten();
+    for c in connections {
+        let from = c.get("from").and_then(Value::as_u64);
+        let Some((_, bias)) = biases.iter().find(|(idx, _)| Some(*idx as u64) == from) else {
+            continue;
+        };
+        if let Some(weight) = c.get_mut("weight").filter(|_| *bias != 1.) {
+            *weight = Value::from(weight.as_f64().unwrap_or(0.) * bias);
+        }
+    }
+
+    !biases.is_empty()
+}
+
+/// Deserialize a genome from a JSON string in either the current layout or the older `CTRGenome`
+/// one, [upgrading](upgrade_legacy) it if need be. [Genome::from_str] falls back to this.
+pub fn from_legacy_str<C: Connection, G: Genome<C>>(s: &str) -> Result<G, Box<dyn Error>> {
+    let mut genome = serde_json::from_str::<Value>(s)?;
+    upgrade_legacy(&mut genome);
+    Ok(serde_json::from_value(genome)?)
+}
+
+/// Check `json` against `schema`, such as [GENOME_SCHEMA], so that a hand-edited genome fails
+/// with where it's wrong rather than with whatever serde makes of it. Errors name the offending
+/// value by its path, like `$.connections[3].weight`.
+///
+/// Only the subset of JSON schema that [GENOME_SCHEMA] uses is understood: `type`, `enum`,
+/// `minimum`, `maximum`, `properties`, `required`, `additionalProperties: false` and `items`.
+/// Anything else in `schema` is ignored.
+pub fn validate(schema: &str, json: &str) -> Result<(), Box<dyn Error>> {
+    let schema = serde_json::from_str::<Value>(schema)?;
+    let value = serde_json::from_str::<Value>(json)?;
+    validate_value(&schema, &value, "$").map_err(Into::into)
+}
+
+fn type_of(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_u64() || n.is_i64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn validate_value(schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+    let allowed = match schema.get("type") {
+        Some(Value::String(ty)) => vec![ty.as_str()],
+        Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+        _ => vec![],
+    };
+    let found = type_of(value);
+    if !allowed.is_empty()
+        && !allowed
+            .iter()
+            .any(|ty| *ty == found || (*ty == "number" && found == "integer"))
+    {
+        return Err(format!(
+            "{path}: expected {}, found {found}",
+            allowed.join(" or ")
+        ));
+    }
+
+    if let Some(Value::Array(options)) = schema.get("enum") {
+        if !options.contains(value) {
+            let options = options.iter().map(Value::to_string).collect::<Vec<_>>();
+            return Err(format!(
+                "{path}: expected one of {}, found {value}",
+                options.join(", ")
+            ));
+        }
+    }
+
+    if let Some(n) = value.as_f64() {
+        if let Some(min) = schema
+            .get("minimum")
+            .and_then(Value::as_f64)
+            .filter(|min| n < *min)
+        {
+            return Err(format!("{path}: {value} is less than {min}"));
+        }
+        if let Some(max) = schema
+            .get("maximum")
+            .and_then(Value::as_f64)
+            .filter(|max| n > *max)
+        {
+            return Err(format!("{path}: {value} is greater than {max}"));
+        }
+        if let Some(min) = schema
+            .get("exclusiveMinimum")
+            .and_then(Value::as_f64)
+            .filter(|min| n <= *min)
+        {
+            return Err(format!("{path}: {value} is not greater than {min}"));
+        }
+        if let Some(max) = schema
+            .get("exclusiveMaximum")
+            .and_then(Value::as_f64)
+            .filter(|max| n >= *max)
+        {
+            return Err(format!("{path}: {value} is not less than {max}"));
+        }
+    }
+
+    if let Value::Object(fields) = value {
+        for name in schema
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+        {
+            if !fields.contains_key(name) {
+                return Err(format!("{path}: missing field `{name}`"));
+            }
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (name, field) in fields {
+            match properties.and_then(|properties| properties.get(name)) {
+                Some(schema) => validate_value(schema, field, &format!("{path}.{name}"))?,
+                None if schema.get("additionalProperties") == Some(&Value::Bool(false)) => {
+                    return Err(format!("{path}: unknown field `{name}`"));
+                }
+                None => {}
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(schema)) = (value, schema.get("items")) {
+        for (idx, item) in items.iter().enumerate() {
+            validate_value(schema, item, &format!("{path}[{idx}]"))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        genome::{InnoGen, IoLabels, NodeId, Recurrence, Recurrent, WConnection},
+        Connection, Genome,
+    };
+
+    type C = WConnection;
+
+    fn saved() -> String {
+        let mut inno = InnoGen::new(0);
+        let (genome, _) = Recurrent::<C>::new(2, 1);
+        let mut genome = genome.with_output_affine().with_node_activations();
+        genome.push_connection(C::new(0, 2, &mut inno));
+        genome.push_connection(C::new(1, 2, &mut inno));
+        genome.to_string_pretty().unwrap()
+    }
+
+    #[test]
+    fn test_legacy() {
+        let legacy = r#"{
+            "sensory": 1,
+            "action": 1,
+            "nodes": ["Sensory", "Action", {"Bias": 0.5}, "Internal"],
+            "connections": [
+                {"inno": 0, "from": 2, "to": 3, "weight": 3.0, "enabled": true},
+                {"inno": 1, "from": 3, "to": 1, "weight": 3.0, "enabled": true}
+            ]
+        }"#;
+        let genome = Recurrent::<C>::from_str(legacy).unwrap();
+        assert!(matches!(genome.nodes()[2], NodeKind::Static));
+        assert_eq!(genome.weights(), vec![1.5, 3.]);
+
+        let mut current = serde_json::from_str::<Value>(&genome.to_string().unwrap()).unwrap();
+        assert!(!upgrade_legacy(&mut current));
+
+        let champion =
+            Recurrent::<C>::from_str(include_str!("../benches/data/ctr-genome-rand-100.json"));
+        assert_eq!(champion.unwrap().nodes().len(), 113);
+    }
+
+    #[test]
+    fn test_validate() {
+        let saved = saved();
+        assert!(
+            saved.contains("\n  \"nodes\": [\n    \"Sensory\","),
+            "{saved}"
+        );
+        validate(GENOME_SCHEMA, &saved).unwrap();
+        assert!(Recurrent::<C>::from_str(&saved).is_ok());
+
+        for (edit, want) in [
+            (
+                saved.replacen("\"weight\": 1.0", "\"weight\": \"0\"", 1),
+                "$.connections[0].weight: expected number, found string",
+            ),
+            (
+                saved.replacen("\"weight\"", "\"wieght\"", 1),
+                "$.connections[0]: missing field `weight`",
+            ),
+            (
+                saved.replacen("\"Static\"", "\"Bias\"", 1),
+                "$.nodes[3]: expected one of \"Sensory\", \"Action\", \"Internal\", \"Static\", found \"Bias\"",
+            ),
+            (
+                saved.replacen("\"to\": 2", "\"to\": -2", 1),
+                "$.connections[0].to: -2 is less than 0",
+            ),
+            (
+                saved.replacen("\"sensory\"", "\"inputs\": 2, \"sensory\"", 1),
+                "$: unknown field `inputs`",
+            ),
+        ] {
+            assert_eq!(validate(GENOME_SCHEMA, &edit).unwrap_err().to_string(), want);
+        }
+    }
+
+    #[test]
+    fn test_validate_every_gene() {
+        let mut inno = InnoGen::new(0);
+        let (genome, _) = Recurrent::<C>::new(2, 1);
+        let mut genome = genome
+            .with_output_affine()
+            .with_node_activations()
+            .with_node_time_constants()
+            .with_node_biases()
+            .with_sensory_gains()
+            .with_io_labels(IoLabels::new(["x", "y"], ["out"]))
+            .with_mutation_sigma(0.5)
+            .with_recurrence(Recurrence::NoSelfLoops);
+        genome.push_node(NodeKind::Internal);
+        genome.push_connection(C::new(0, 4, &mut inno));
+        genome.push_connection(C::new(4, 2, &mut inno));
+        genome.connections_mut()[0].set_frozen(true);
+        genome.define_module("hidden", &[NodeId(4)]).unwrap();
+        genome.extend_io(1, 1, &mut inno).unwrap();
+
+        let saved = genome.to_string_pretty().unwrap();
+        for field in [
+            "output",
+            "activation",
+            "time_constant",
+            "bias",
+            "gain",
+            "labels",
+            "recurrence",
+            "sigma",
+            "modules",
+            "frozen",
+            "sensory_appended",
+            "action_appended",
+        ] {
+            assert!(
+                saved.contains(&format!("\"{field}\"")),
+                "{field} in {saved}"
+            );
+        }
+        validate(GENOME_SCHEMA, &saved).unwrap();
+        assert!(Recurrent::<C>::from_str(&saved).is_ok());
+
+        assert_eq!(
+            validate(
+                GENOME_SCHEMA,
+                &saved.replacen("\"sigma\": 0.5", "\"sigma\": 0.0", 1)
+            )
+            .unwrap_err()
+            .to_string(),
+            "$.sigma: 0.0 is not greater than 0"
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! Indirect encoding of large networks with HyperNEAT.
+//!
+//! Rather than encoding every connection of a network, a genome is decoded into a CPPN: a small
+//! network who, given the coordinates of two nodes placed on some [Substrate], answers with the
+//! weight of the connection between them. A genome of a few dozen genes may so describe a network
+//! of thousands of connections, whose weights vary regularly over its geometry.
+//!
+//! Any [Genome] of io `(4, 1)` may serve as a CPPN. Its network is queried with `[x1, y1, x2, y2]`
+//! for a connection from `(x1, y1)` to `(x2, y2)`. A network of the substrate described by some
+//! [Geometry] `S` is built from a genome as a [`Hyper<S>`], and so with
+//! [ToNetwork](crate::network::ToNetwork) like any other network. A
+//! [Cppn](crate::genome::Cppn), whose nodes evolve their own activations, may instead
+//! [decode](crate::genome::Cppn::decode) a substrate itself.
+use crate::{
+    activate,
+    network::{Continuous, Footprint, FromGenome, Recurrent, Stateful},
+    Connection, Genome, Network,
+};
+use core::marker::PhantomData;
+use rulinalg::matrix::Matrix;
+use serde::{Deserialize, Serialize};
+
+/// Coordinates of a single substrate node
+pub type Point = (f64, f64);
+
+/// The placement of a network's nodes in space. Nodes are laid out in the decoded network as
+/// sensory, then action, then hidden.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Substrate {
+    pub sensory: Vec<Point>,
+    pub hidden: Vec<Point>,
+    pub action: Vec<Point>,
+    /// Connections whose queried weight is of a smaller magnitude are left unexpressed
+    pub threshold: f64,
+    /// Queried weights are clamped to `[-max_weight, max_weight]`
+    pub max_weight: f64,
+}
+
+impl Substrate {
+    /// `n` points spread evenly over `x` in `[-1, 1]` along `y`
+    fn row(n: usize, y: f64) -> Vec<Point> {
+        (0..n)
+            .map(|i| match n {
+                1 => (0., y),
+                _ => (2. * i as f64 / (n - 1) as f64 - 1., y),
+            })
+            .collect()
+    }
+
+    /// A substrate with no hidden nodes, whose sensory nodes are spread evenly along `y = -1` and
+    /// whose action nodes are spread evenly along `y = 1`
+    pub fn layered(sensory: usize, action: usize) -> Self {
+        Self {
+            sensory: Self::row(sensory, -1.),
+            hidden: Vec::new(),
+            action: Self::row(action, 1.),
+            threshold: 0.2,
+            max_weight: 3.,
+        }
+    }
+
+    /// Add a row of `n` hidden nodes spread evenly along `y`
+    pub fn with_hidden_row(mut self, n: usize, y: f64) -> Self {
+        self.hidden.extend(Self::row(n, y));
+        self
+    }
+
+    /// Every node, in the order that they're laid out in the decoded network
+    fn points(&self) -> impl Iterator<Item = &Point> {
+        self.sensory
+            .iter()
+            .chain(self.action.iter())
+            .chain(self.hidden.iter())
+    }
+
+    /// Query `cppn` for the weight of every connection of this substrate, and build a network of
+    /// it. Connections run from every sensory or hidden node to every hidden or action node.
+    /// `cppn` is flushed before each query and stepped `prec` times with σ.
+    pub fn query<NN: Network, F: Fn(f64) -> f64>(
+        &self,
+        cppn: &mut NN,
+        prec: usize,
+        σ: F,
+    ) -> Continuous {
+        self.query_with(|coordinates| {
+            cppn.flush();
+            cppn.step(prec, coordinates, &σ);
+            cppn.output()[0]
+        })
+    }
+
+    /// Like [query](Substrate::query), but asking `weight` for the weight of each connection,
+    /// given `[x1, y1, x2, y2]`, rather than some network.
+    pub fn query_with<F: FnMut(&[f64; 4]) -> f64>(&self, mut weight: F) -> Continuous {
+        let cols = self.sensory.len() + self.action.len() + self.hidden.len();
+        let action = self.sensory.len()..self.sensory.len() + self.action.len();
+        let mut w = vec![0.; cols * cols];
+        for (from, &(x1, y1)) in self.points().enumerate() {
+            if action.contains(&from) {
+                continue;
+            }
+
+            for (to, &(x2, y2)) in self.points().enumerate() {
+                if to < self.sensory.len() {
+                    continue;
+                }
+
+                let weight = weight(&[x1, y1, x2, y2]);
+                if weight.abs() >= self.threshold {
+                    w[from * cols + to] = weight.clamp(-self.max_weight, self.max_weight);
+                }
+            }
+        }
+
+        Continuous {
+            y: Matrix::zeros(1, cols),
+            θ: Matrix::zeros(1, cols),
+            τ: Matrix::new(1, cols, vec![0.1; cols]),
+            w: Matrix::new(cols, cols, w),
+            sensory: (0, self.sensory.len()),
+            action: (action.start, action.end),
+            appended: Default::default(),
+            activation: None,
+            dt: 1.,
+            solver: Default::default(),
+        }
+    }
+}
+
+/// A substrate known ahead of time, so that a network of it may be decoded from a genome
+/// without any more context.
+pub trait Geometry {
+    /// Steps taken by the CPPN for each query
+    const PREC: usize = 3;
+
+    fn substrate() -> Substrate;
+
+    /// Activation of the CPPN
+    fn σ(x: f64) -> f64 {
+        activate::steep_sigmoid(x)
+    }
+}
+
+/// A network over the substrate of `S`, whose weights are given by a CPPN genome
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Hyper<S: Geometry> {
+    pub inner: Continuous,
+    #[serde(skip)]
+    geometry: PhantomData<S>,
+}
+
+impl<S: Geometry> Network for Hyper<S> {
+    fn step<F: Fn(f64) -> f64>(&mut self, prec: usize, input: &[f64], σ: F) {
+        self.inner.step(prec, input, σ);
+    }
+
+    fn flush(&mut self) {
+        self.inner.flush();
+    }
+
+    fn output(&self) -> &[f64] {
+        self.inner.output()
+    }
+
+    fn activations(&self) -> &[f64] {
+        self.inner.activations()
+    }
+
+    fn state(&self) -> Vec<f64> {
+        self.inner.state()
+    }
+
+    fn set_state(&mut self, state: &[f64]) {
+        self.inner.set_state(state);
+    }
+
+    fn footprint(&self) -> Footprint {
+        self.inner.footprint()
+    }
+}
+
+impl<S: Geometry> Recurrent for Hyper<S> {}
+
+impl<S: Geometry> Stateful for Hyper<S> {}
+
+impl<C: Connection, G: Genome<C>, S: Geometry> FromGenome<C, G> for Hyper<S> {
+    fn from_genome(genome: &G) -> Self {
+        assert_eq!(
+            (genome.inputs(), genome.outputs()),
+            (4, 1),
+            "a CPPN genome must have io (4, 1)"
+        );
+        let mut cppn = Continuous::from_genome(genome);
+        Self {
+            inner: S::substrate().query(&mut cppn, S::PREC, S::σ),
+            geometry: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        genome::{InnoGen, Recurrent, WConnection},
+        network::ToNetwork,
+    };
+    use rulinalg::matrix::BaseMatrix;
+
+    type C = WConnection;
+
+    struct Grid;
+
+    impl Geometry for Grid {
+        fn substrate() -> Substrate {
+            Substrate::layered(3, 2).with_hidden_row(2, 0.)
+        }
+    }
+
+    #[test]
+    fn test_substrate_layered() {
+        let substrate = Substrate::layered(3, 1).with_hidden_row(2, 0.);
+        assert_eq!(substrate.sensory, vec![(-1., -1.), (0., -1.), (1., -1.)]);
+        assert_eq!(substrate.action, vec![(0., 1.)]);
+        assert_eq!(substrate.hidden, vec![(-1., 0.), (1., 0.)]);
+    }
+
+    #[test]
+    fn test_hyper() {
+        // weight grows with the target's x
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = Recurrent::<C>::new(4, 1);
+        genome.push_connection(C::new(2, 4, &mut inno));
+        genome.connections_mut()[0].weight = 20.;
+
+        let nn: Hyper<Grid> = genome.network();
+        let cols = 3 + 2 + 2;
+        assert_eq!(nn.inner.w.cols(), cols);
+        assert_eq!(nn.output().len(), 2);
+
+        let w = nn.inner.w.data();
+        // nothing into sensory nodes, nothing out of action nodes
+        for node in 0..cols {
+            for sensory in 0..3 {
+                assert_eq!(w[node * cols + sensory], 0.);
+            }
+            for action in 3..5 {
+                assert_eq!(w[action * cols + node], 0.);
+            }
+        }
+        // from sensory 0 to action 3 at x = -1, and to action 4 at x = 1
+        assert!(w[4] > w[3], "{} !> {}", w[4], w[3]);
+        assert!(w.iter().all(|w| w.abs() <= 3.));
+    }
+
+    #[test]
+    #[should_panic(expected = "a CPPN genome must have io (4, 1)")]
+    fn test_hyper_io() {
+        let (genome, _) = Recurrent::<C>::new(2, 1);
+        let _: Hyper<Grid> = genome.network();
+    }
+}
@@ -0,0 +1,146 @@
+//! Utilities for testing the behavior of evolved networks.
+//!
+//! Evolved genomes are artifacts in their own right, and are often saved and built on long after
+//! evolution. [assert_behavior] pins a network's response to known inputs, and [regressions]
+//! checks that saved champions still score as well as they did, so that changes to this crate
+//! ( or to a scenario ) that silently change what a genome means are caught in tests.
+pub use crate::assert_behavior;
+use crate::{Connection, Fit, Genome, Scenario};
+use std::{error::Error, fs::read_dir, path::PathBuf};
+
+/// Assert that a network responds to each input with the expected output, within `tol`. The
+/// network is flushed before each case and stepped `prec` times with σ.
+///
+/// # Examples
+///
+/// ```
+/// use eevee::{
+///     activate, assert_behavior,
+///     genome::{Genome, Recurrent, WConnection},
+///     network::{Continuous, ToNetwork},
+/// };
+///
+/// let (genome, _) = Recurrent::<WConnection>::new(2, 1);
+/// let mut nn: Continuous = genome.network();
+/// assert_behavior!(nn, activate::relu, 1; [0., 0.] => [0.], [1., 1.] => [0.]; 1e-9);
+/// ```
+#[macro_export]
+macro_rules! assert_behavior {
+    ($nn:expr, $σ:expr, $prec:expr; $($input:expr => $expected:expr),+ $(,)?; $tol:expr) => {{
+        let nn = &mut $nn;
+        $(
+            $crate::network::Network::flush(nn);
+            $crate::network::Network::step(nn, $prec, &$input, &$σ);
+            let expected: &[f64] = &$expected;
+            let output = $crate::network::Network::output(nn);
+            assert_eq!(
+                output.len(),
+                expected.len(),
+                "network has {} outputs, expected {}",
+                output.len(),
+                expected.len()
+            );
+            for (idx, (have, want)) in output.iter().zip(expected).enumerate() {
+                assert!(
+                    (have - want).abs() <= $tol,
+                    "behavior differs for input {:?} at [{idx}]: {have} !~ {want}",
+                    $input,
+                );
+            }
+        )+
+    }};
+}
+
+/// Evaluate every genome saved in `dir` ( such as with
+/// [population_to_files](crate::population::population_to_files) ) against `scenario`, and
+/// return the path and fitness of those who no longer reach `threshold`. Fails if any file isn't
+/// a valid genome, or if `dir` holds none.
+pub fn regressions<C: Connection, G: Genome<C>, A: Fn(f64) -> f64, S: Scenario<C, G, A>>(
+    dir: impl Into<PathBuf>,
+    scenario: &S,
+    σ: &A,
+    threshold: f64,
+) -> Result<Vec<(PathBuf, Fit)>, Box<dyn Error>> {
+    let mut checked = 0;
+    let mut failed = Vec::new();
+    for entry in read_dir(dir.into())? {
+        let path = entry?.path();
+        let fit = Fit(scenario.eval(&G::from_file(&path)?, σ));
+        if fit < Fit(threshold) {
+            failed.push((path, fit));
+        }
+        checked += 1;
+    }
+
+    if checked == 0 {
+        return Err("no genomes".into());
+    }
+
+    failed.sort();
+    Ok(failed)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        activate,
+        genome::{InnoGen, Recurrent, WConnection},
+        network::{Continuous, FromGenome},
+    };
+    use std::{env, fs};
+
+    type C = WConnection;
+    type G = Recurrent<C>;
+    type A = fn(f64) -> f64;
+
+    struct Size;
+
+    impl Scenario<C, G, A> for Size {
+        fn io(&self) -> (usize, usize) {
+            (1, 1)
+        }
+
+        fn eval(&self, genome: &G, _: &A) -> f64 {
+            genome.connections().len() as f64
+        }
+    }
+
+    #[test]
+    fn test_assert_behavior() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = G::new(1, 1);
+        genome.push_connection(C::new(0, 1, &mut inno));
+        genome.connections_mut()[0].weight = 2.;
+
+        let mut nn = Continuous::from_genome(&genome);
+        assert_behavior!(nn, activate::relu, 1; [0.] => [0.], [1.] => [0.]; 1e-9);
+        assert_behavior!(nn, activate::relu, 2; [1.] => [0.005]; 1e-9);
+    }
+
+    #[test]
+    #[should_panic(expected = "behavior differs for input [0.0] at [0]")]
+    fn test_assert_behavior_differs() {
+        let (genome, _) = G::new(1, 1);
+        let mut nn = Continuous::from_genome(&genome);
+        assert_behavior!(nn, activate::relu, 1; [0.] => [1.]; 1e-9);
+    }
+
+    #[test]
+    fn test_regressions() {
+        let dir = env::temp_dir().join(format!("eevee-regressions-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut inno = InnoGen::new(0);
+        let (small, _) = G::new(1, 1);
+        let mut big = small.clone();
+        big.push_connection(C::new(0, 1, &mut inno));
+        small.to_file(dir.join("small.json")).unwrap();
+        big.to_file(dir.join("big.json")).unwrap();
+
+        let σ: A = activate::relu;
+        let failed = regressions(&dir, &Size, &σ, 1.).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(failed, vec![(dir.join("small.json"), Fit(0.))]);
+    }
+}